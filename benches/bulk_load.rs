@@ -0,0 +1,49 @@
+use criterion::*;
+use permutation_iterator::Permutor;
+use rand::{RngCore, thread_rng};
+use bijective_map::BiMap;
+
+const BULK_SIZE: usize = 1 << 20;
+
+fn bench_bulk_load(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("bulk_load");
+    group.sample_size(10);
+
+    let mut permutor_left = Permutor::new_with_u64_key(u64::MAX, rng.next_u64());
+    let mut permutor_right = Permutor::new_with_u64_key(u64::MAX, rng.next_u64());
+    let pairs: Vec<(u64, u64)> = (0..BULK_SIZE)
+        .map(|_| (permutor_left.next().unwrap(), permutor_right.next().unwrap()))
+        .collect();
+
+    group.bench_function("insert", |b| {
+        b.iter_batched(|| BiMap::with_capacity(BULK_SIZE), |mut map| {
+            for &(left, right) in &pairs {
+                map.insert(left, right);
+            }
+            map
+        }, BatchSize::LargeInput);
+    });
+
+    group.bench_function("insert_unique_unchecked", |b| {
+        b.iter_batched(|| BiMap::with_capacity(BULK_SIZE), |mut map| {
+            for &(left, right) in &pairs {
+                map.insert_unique_unchecked(left, right);
+            }
+            map
+        }, BatchSize::LargeInput);
+    });
+
+    group.bench_function("bulk_insert", |b| {
+        b.iter_batched(BiMap::new, |mut map| {
+            map.bulk_insert(pairs.iter().copied());
+            map
+        }, BatchSize::LargeInput);
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_bulk_load);
+criterion_main!(benches);
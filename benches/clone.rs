@@ -0,0 +1,37 @@
+use criterion::*;
+use rand::{RngCore, thread_rng};
+use bijective_map::BiMap;
+
+const CLONE_SIZES: [usize; 3] = [1 << 12, 1 << 16, 1 << 19];
+
+fn bench_clone(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("clone");
+    group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
+
+    for length in CLONE_SIZES {
+        let mut map = BiMap::with_capacity(length);
+        for _ in 0..length {
+            map.insert(rng.next_u64(), rng.next_u64());
+        }
+
+        group.bench_with_input(BenchmarkId::new("clone", length), &length, |b, _| {
+            b.iter(|| black_box(map.clone()));
+        });
+
+        // the scratch map keeps its allocations between the iterations
+        let mut scratch = map.clone();
+        group.bench_with_input(BenchmarkId::new("clone_from", length), &length, |b, _| {
+            b.iter(|| {
+                scratch.clone_from(&map);
+                black_box(&scratch);
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_clone);
+criterion_main!(benches);
@@ -1 +1,12 @@
-pub const SIZES: [usize; 8] = [1 << 10, 1 << 12, 1 << 14, 1 << 16, 1 << 18, 1 << 20, 1 << 22, 1 << 24];
\ No newline at end of file
+use rand::Rng;
+use rand::distributions::Alphanumeric;
+
+pub const SIZES: [usize; 8] = [1 << 10, 1 << 12, 1 << 14, 1 << 16, 1 << 18, 1 << 20, 1 << 22, 1 << 24];
+
+/// Generate a random alphanumeric string between 8 and 64 bytes long.
+// not every benchmark uses strings
+#[allow(dead_code)]
+pub fn random_string(rng: &mut impl Rng) -> String {
+    let length = rng.gen_range(8..=64);
+    rng.sample_iter(&Alphanumeric).take(length).map(char::from).collect()
+}
@@ -0,0 +1,62 @@
+use criterion::*;
+use permutation_iterator::Permutor;
+use rand::{RngCore, thread_rng};
+use bijective_map::BiMap;
+
+const COMPACT_SIZES: [usize; 3] = [1 << 12, 1 << 16, 1 << 20];
+
+fn bench_compact(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("compact");
+    group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
+
+    for length in COMPACT_SIZES {
+        let mut map = BiMap::with_capacity(length);
+        let mut permutor_left = Permutor::new_with_u64_key(u64::MAX, rng.next_u64());
+        let mut permutor_right = Permutor::new_with_u64_key(u64::MAX, rng.next_u64());
+
+        let mut lefts = Vec::with_capacity(length);
+        for _ in 0..length {
+            let left = permutor_left.next().unwrap();
+            map.insert(left, permutor_right.next().unwrap());
+            lefts.push(left);
+        }
+
+        // churn the map by replacing random pairs, which scatters the buckets across the data vec
+        for _ in 0..length * 4 {
+            let index = (rng.next_u64() % length as u64) as usize;
+            map.remove_left(&lefts[index]);
+            let left = permutor_left.next().unwrap();
+            map.insert(left, permutor_right.next().unwrap());
+            lefts[index] = left;
+        }
+
+        // look up the left values in index order, which is the access pattern compaction helps.
+        // A compacted copy of the map iterates in that order.
+        let mut compacted = map.clone();
+        compacted.compact();
+        let ordered_lefts = compacted.left_values().copied().collect::<Vec<_>>();
+
+        group.bench_with_input(BenchmarkId::new("get_right_churned", length), &length, |b, _| {
+            b.iter(|| {
+                for left in &ordered_lefts {
+                    black_box(map.get_right(left));
+                }
+            });
+        });
+
+        group.bench_with_input(BenchmarkId::new("get_right_compacted", length), &length, |b, _| {
+            b.iter(|| {
+                for left in &ordered_lefts {
+                    black_box(compacted.get_right(left));
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_compact);
+criterion_main!(benches);
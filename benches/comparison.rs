@@ -0,0 +1,243 @@
+mod common;
+
+use std::collections::HashMap;
+use std::hash::RandomState;
+use std::mem;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use bimap::BiHashMap;
+use criterion::*;
+use criterion::measurement::WallTime;
+use permutation_iterator::Permutor;
+use rand::{RngCore, thread_rng};
+use bijective_map::BiMap;
+use crate::common::*;
+
+/// The operations shared by all compared implementations of a bijection.
+trait Bijection {
+    /// The name of the implementation in the benchmark ids.
+    const NAME: &'static str;
+
+    fn with_capacity_and_hasher(capacity: usize, hasher: &RandomState) -> Self;
+
+    fn insert(&mut self, left: u64, right: u64);
+
+    fn get_right(&self, left: &u64) -> Option<&u64>;
+
+    fn get_left(&self, right: &u64) -> Option<&u64>;
+
+    fn remove_left(&mut self, left: &u64) -> Option<u64>;
+
+    /// The heap bytes held by the map. Implementations without an exact count estimate it from
+    /// their capacity and the layout of their tables.
+    fn allocated_bytes(&self) -> usize;
+}
+
+/// The number of heap bytes of a `HashMap` with the given capacity and entry size, modelled after
+/// the table layout of hashbrown: a power of two of buckets, one control byte per bucket, and one
+/// trailing group of control bytes.
+fn hash_map_bytes(capacity: usize, entry_size: usize) -> usize {
+    if capacity == 0 {
+        return 0;
+    }
+    let buckets = if capacity < 8 { (capacity + 1).next_power_of_two() } else { (capacity / 7 * 8).next_power_of_two() };
+    buckets * (entry_size + 1) + 16
+}
+
+impl Bijection for BiMap<u64, u64> {
+    const NAME: &'static str = "bijective_map";
+
+    fn with_capacity_and_hasher(capacity: usize, hasher: &RandomState) -> Self {
+        BiMap::with_hashers(capacity, hasher.clone(), hasher.clone())
+    }
+
+    fn insert(&mut self, left: u64, right: u64) {
+        BiMap::insert(self, left, right);
+    }
+
+    fn get_right(&self, left: &u64) -> Option<&u64> {
+        BiMap::get_right(self, left)
+    }
+
+    fn get_left(&self, right: &u64) -> Option<&u64> {
+        BiMap::get_left(self, right)
+    }
+
+    fn remove_left(&mut self, left: &u64) -> Option<u64> {
+        BiMap::remove_left(self, left)
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        BiMap::allocated_bytes(self)
+    }
+}
+
+impl Bijection for BiHashMap<u64, u64> {
+    const NAME: &'static str = "bimap";
+
+    fn with_capacity_and_hasher(capacity: usize, hasher: &RandomState) -> Self {
+        BiHashMap::with_capacity_and_hashers(capacity, hasher.clone(), hasher.clone())
+    }
+
+    fn insert(&mut self, left: u64, right: u64) {
+        BiHashMap::insert(self, left, right);
+    }
+
+    fn get_right(&self, left: &u64) -> Option<&u64> {
+        self.get_by_left(left)
+    }
+
+    fn get_left(&self, right: &u64) -> Option<&u64> {
+        self.get_by_right(right)
+    }
+
+    fn remove_left(&mut self, left: &u64) -> Option<u64> {
+        self.remove_by_left(left).map(|(_, right)| right)
+    }
+
+    /// Both tables store a pair of `Rc`s per entry, and each value lives in its own `Rc`
+    /// allocation.
+    fn allocated_bytes(&self) -> usize {
+        let entry_size = 2 * mem::size_of::<Rc<u64>>();
+        let rc_size = 2 * mem::size_of::<usize>() + mem::size_of::<u64>();
+        2 * hash_map_bytes(self.capacity(), entry_size) + 2 * self.len() * rc_size
+    }
+}
+
+/// The naive bijection of two hash maps, which store each pair once per direction.
+struct DualHashMap {
+    right_of: HashMap<u64, u64, RandomState>,
+    left_of: HashMap<u64, u64, RandomState>,
+}
+
+impl Bijection for DualHashMap {
+    const NAME: &'static str = "dual_hash_map";
+
+    fn with_capacity_and_hasher(capacity: usize, hasher: &RandomState) -> Self {
+        DualHashMap {
+            right_of: HashMap::with_capacity_and_hasher(capacity, hasher.clone()),
+            left_of: HashMap::with_capacity_and_hasher(capacity, hasher.clone()),
+        }
+    }
+
+    fn insert(&mut self, left: u64, right: u64) {
+        if let Some(old_right) = self.right_of.insert(left, right) {
+            self.left_of.remove(&old_right);
+        }
+        if let Some(old_left) = self.left_of.insert(right, left) {
+            if old_left != left {
+                self.right_of.remove(&old_left);
+            }
+        }
+    }
+
+    fn get_right(&self, left: &u64) -> Option<&u64> {
+        self.right_of.get(left)
+    }
+
+    fn get_left(&self, right: &u64) -> Option<&u64> {
+        self.left_of.get(right)
+    }
+
+    fn remove_left(&mut self, left: &u64) -> Option<u64> {
+        let right = self.right_of.remove(left)?;
+        self.left_of.remove(&right);
+        Some(right)
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        let entry_size = 2 * mem::size_of::<u64>();
+        hash_map_bytes(self.right_of.capacity(), entry_size) + hash_map_bytes(self.left_of.capacity(), entry_size)
+    }
+}
+
+/// Benchmark one implementation on the given pairs. The lookups of absent values use `misses`,
+/// which are disjoint from the left values of `pairs`.
+fn bench_implementation<M: Bijection>(group: &mut BenchmarkGroup<WallTime>, hasher: &RandomState, pairs: &[(u64, u64)], misses: &[u64]) {
+    let mut rng = thread_rng();
+    let length = pairs.len();
+
+    // filling a map is measured as a whole, so report the time per inserted pair
+    group.throughput(Throughput::Elements(length as u64));
+    group.bench_with_input(BenchmarkId::new(format!("insert_{}", M::NAME), length), &length, |b, _| {
+        b.iter_batched(|| M::with_capacity_and_hasher(length, hasher), |mut map| {
+            for &(left, right) in pairs {
+                map.insert(left, right);
+            }
+            map
+        }, BatchSize::LargeInput);
+    });
+    group.throughput(Throughput::Elements(1));
+
+    let mut map = M::with_capacity_and_hasher(length, hasher);
+    for &(left, right) in pairs {
+        map.insert(left, right);
+    }
+    println!("{}/{}: {:.1} bytes per pair", M::NAME, length, map.allocated_bytes() as f64 / length as f64);
+
+    group.bench_with_input(BenchmarkId::new(format!("get_right_{}", M::NAME), length), &length, |b, _| {
+        b.iter_batched(|| pairs[(rng.next_u64() % length as u64) as usize].0, |left| map.get_right(&left).copied(), BatchSize::SmallInput);
+    });
+
+    group.bench_with_input(BenchmarkId::new(format!("get_left_{}", M::NAME), length), &length, |b, _| {
+        b.iter_batched(|| pairs[(rng.next_u64() % length as u64) as usize].1, |right| map.get_left(&right).copied(), BatchSize::SmallInput);
+    });
+
+    group.bench_with_input(BenchmarkId::new(format!("get_right_miss_{}", M::NAME), length), &length, |b, _| {
+        b.iter_batched(|| misses[(rng.next_u64() % misses.len() as u64) as usize], |left| map.get_right(&left).copied(), BatchSize::SmallInput);
+    });
+
+    // only the removals are timed, the removed pairs are reinserted afterwards to keep the length
+    // of the map stable
+    group.bench_with_input(BenchmarkId::new(format!("remove_left_{}", M::NAME), length), &length, |b, _| {
+        b.iter_custom(|iters| {
+            let mut elapsed = Duration::ZERO;
+            let mut remaining = iters as usize;
+            while remaining > 0 {
+                let batch = remaining.min(length);
+                let offset = (rng.next_u64() % length as u64) as usize;
+                let start = Instant::now();
+                for i in 0..batch {
+                    black_box(map.remove_left(&pairs[(offset + i) % length].0));
+                }
+                elapsed += start.elapsed();
+                for i in 0..batch {
+                    let (left, right) = pairs[(offset + i) % length];
+                    map.insert(left, right);
+                }
+                remaining -= batch;
+            }
+            elapsed
+        });
+    });
+}
+
+fn bench_comparison(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("comparison");
+    group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
+
+    // all implementations share the hasher, so they see the same hashes for the same values
+    let hasher = RandomState::new();
+
+    for length in SIZES {
+        let mut permutor_left = Permutor::new_with_u64_key(u64::MAX, rng.next_u64());
+        let mut permutor_right = Permutor::new_with_u64_key(u64::MAX, rng.next_u64());
+
+        let pairs = (0..length)
+            .map(|_| (permutor_left.next().unwrap(), permutor_right.next().unwrap()))
+            .collect::<Vec<_>>();
+        // the permutor never repeats a value, so values drawn after the pairs are absent
+        let misses = (0..length).map(|_| permutor_left.next().unwrap()).collect::<Vec<_>>();
+
+        bench_implementation::<BiMap<u64, u64>>(&mut group, &hasher, &pairs, &misses);
+        bench_implementation::<BiHashMap<u64, u64>>(&mut group, &hasher, &pairs, &misses);
+        bench_implementation::<DualHashMap>(&mut group, &hasher, &pairs, &misses);
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_comparison);
+criterion_main!(benches);
\ No newline at end of file
@@ -0,0 +1,53 @@
+use criterion::*;
+use rand::{RngCore, thread_rng};
+use bijective_map::BiMap;
+use bijective_map::dense::DenseBiMap;
+
+const DENSE_SIZES: [usize; 3] = [1 << 12, 1 << 16, 1 << 20];
+
+fn bench_dense(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("dense");
+    group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
+
+    for length in DENSE_SIZES {
+        // interned ids: random left values mapped to the ids 0..length
+        let lefts = (0..length).map(|_| rng.next_u64()).collect::<Vec<_>>();
+
+        let mut general = BiMap::with_capacity(length);
+        let mut dense = DenseBiMap::new(length);
+        for (id, &left) in lefts.iter().enumerate() {
+            general.insert(left, id as u32);
+            dense.insert(left, id as u32);
+        }
+
+        group.bench_with_input(BenchmarkId::new("get_left_general", length), &length, |b, _| {
+            b.iter_batched(|| (rng.next_u64() % length as u64) as u32, |id| general.get_left(&id).copied(), BatchSize::SmallInput);
+        });
+
+        group.bench_with_input(BenchmarkId::new("get_left_dense", length), &length, |b, _| {
+            b.iter_batched(|| (rng.next_u64() % length as u64) as u32, |id| dense.get_left(&id).copied(), BatchSize::SmallInput);
+        });
+
+        // removed pairs are reinserted right away to keep the maps full
+        group.bench_with_input(BenchmarkId::new("remove_right_general", length), &length, |b, _| {
+            b.iter_batched(|| (rng.next_u64() % length as u64) as u32, |id| {
+                let left = general.remove_right(&id).unwrap();
+                general.insert(left, id);
+            }, BatchSize::SmallInput);
+        });
+
+        group.bench_with_input(BenchmarkId::new("remove_right_dense", length), &length, |b, _| {
+            b.iter_batched(|| (rng.next_u64() % length as u64) as u32, |id| {
+                let left = dense.remove_right(&id).unwrap();
+                dense.insert(left, id);
+            }, BatchSize::SmallInput);
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_dense);
+criterion_main!(benches);
@@ -0,0 +1,62 @@
+use criterion::*;
+use permutation_iterator::Permutor;
+use rand::{RngCore, thread_rng};
+use bijective_map::BiMap;
+
+const FILTER_SIZES: [usize; 3] = [1 << 12, 1 << 16, 1 << 20];
+
+/// The share of lookups that hit a value in the map, in percent.
+const HIT_PERCENTAGE: u64 = 20;
+
+fn bench_filter(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("negative_filter");
+    group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
+
+    for length in FILTER_SIZES {
+        let mut map = BiMap::with_capacity(length);
+        let mut permutor_left = Permutor::new_with_u64_key(u64::MAX, rng.next_u64());
+        let mut permutor_right = Permutor::new_with_u64_key(u64::MAX, rng.next_u64());
+
+        let mut lefts = Vec::with_capacity(length);
+        for _ in 0..length {
+            let left = permutor_left.next().unwrap();
+            map.insert(left, permutor_right.next().unwrap());
+            lefts.push(left);
+        }
+
+        // the permutor never repeats a value, so values drawn after filling the map are misses
+        let lookups = (0..length)
+            .map(|_| if rng.next_u64() % 100 < HIT_PERCENTAGE {
+                lefts[(rng.next_u64() % length as u64) as usize]
+            } else {
+                permutor_left.next().unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let mut filtered = map.clone();
+        filtered.enable_negative_filter();
+
+        group.bench_with_input(BenchmarkId::new("contains_left_unfiltered", length), &length, |b, _| {
+            b.iter(|| {
+                for left in &lookups {
+                    black_box(map.contains_left(left));
+                }
+            });
+        });
+
+        group.bench_with_input(BenchmarkId::new("contains_left_filtered", length), &length, |b, _| {
+            b.iter(|| {
+                for left in &lookups {
+                    black_box(filtered.contains_left(left));
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_filter);
+criterion_main!(benches);
@@ -3,6 +3,7 @@ mod common;
 use criterion::*;
 use permutation_iterator::Permutor;
 use rand::{RngCore, thread_rng};
+use rand::rngs::ThreadRng;
 use bijective_map::BiMap;
 use crate::common::*;
 
@@ -15,8 +16,8 @@ fn bench_get(c: &mut Criterion) {
     for load_factor in [0.5, 0.75, 0.8, 0.9] {
         for length in SIZES {
             let mut map = BiMap::with_capacity(length);
-            let mut permutor_left = Permutor::new_with_u64_key(u64::MAX, rng.next_u64()).into_iter();
-            let mut permutor_right = Permutor::new_with_u64_key(u64::MAX, rng.next_u64()).into_iter();
+            let mut permutor_left = Permutor::new_with_u64_key(u64::MAX, rng.next_u64());
+            let mut permutor_right = Permutor::new_with_u64_key(u64::MAX, rng.next_u64());
 
             let entire_length = (length as f64 * (load_factor / 0.9)) as usize;
             for _ in 0..entire_length {
@@ -34,5 +35,136 @@ fn bench_get(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_get);
+/// The share of lookups that miss the map, in percent.
+const MISS_PERCENTAGES: [u64; 3] = [0, 50, 100];
+
+/// The number of lookups per iteration of the miss benchmarks.
+const LOOKUPS: usize = 1024;
+
+/// Draw lookups of which the given share misses the map. Hits are drawn from the values in the
+/// map, misses are generated with `fresh`, which must not return values in the map.
+fn draw_lookups<T: Clone, R: RngCore>(values: &[T], miss_percentage: u64, rng: &mut R, mut fresh: impl FnMut(&mut R) -> T) -> Vec<T> {
+    (0..LOOKUPS)
+        .map(|_| if rng.next_u64() % 100 < miss_percentage {
+            fresh(rng)
+        } else {
+            values[(rng.next_u64() % values.len() as u64) as usize].clone()
+        })
+        .collect()
+}
+
+fn bench_get_miss(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("get_miss");
+    group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
+    group.throughput(Throughput::Elements(LOOKUPS as u64));
+
+    for load_factor in [0.5, 0.75, 0.8, 0.9] {
+        for length in SIZES {
+            let mut map = BiMap::with_capacity(length);
+            let mut permutor_left = Permutor::new_with_u64_key(u64::MAX, rng.next_u64());
+            let mut permutor_right = Permutor::new_with_u64_key(u64::MAX, rng.next_u64());
+
+            let entire_length = (length as f64 * (load_factor / 0.9)) as usize;
+            let mut lefts = Vec::with_capacity(entire_length);
+            let mut rights = Vec::with_capacity(entire_length);
+            for _ in 0..entire_length {
+                let left = permutor_left.next().unwrap();
+                let right = permutor_right.next().unwrap();
+                map.insert(left, right);
+                lefts.push(left);
+                rights.push(right);
+            }
+
+            for miss_percentage in MISS_PERCENTAGES {
+                // the permutors never repeat a value, so values drawn after filling the map are misses
+                let left_lookups = draw_lookups(&lefts, miss_percentage, &mut rng, |_| permutor_left.next().unwrap());
+                let right_lookups = draw_lookups(&rights, miss_percentage, &mut rng, |_| permutor_right.next().unwrap());
+
+                group.bench_with_input(BenchmarkId::new(format!("get_right_miss{}_{}", miss_percentage, load_factor), length), &length, |b, _| {
+                    b.iter(|| {
+                        for left in &left_lookups {
+                            black_box(map.get_right(left));
+                        }
+                    });
+                });
+
+                group.bench_with_input(BenchmarkId::new(format!("get_left_miss{}_{}", miss_percentage, load_factor), length), &length, |b, _| {
+                    b.iter(|| {
+                        for right in &right_lookups {
+                            black_box(map.get_left(right));
+                        }
+                    });
+                });
+            }
+        }
+    }
+
+    group.finish();
+}
+
+fn bench_get_string(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("get_string");
+    group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
+    group.throughput(Throughput::Elements(LOOKUPS as u64));
+
+    // the largest sizes would need several gigabytes of strings
+    for load_factor in [0.5, 0.75, 0.8, 0.9] {
+        for length in SIZES.into_iter().filter(|&length| length <= 1 << 20) {
+            let mut map = BiMap::with_capacity(length);
+
+            let entire_length = (length as f64 * (load_factor / 0.9)) as usize;
+            let mut lefts = Vec::with_capacity(entire_length);
+            let mut rights = Vec::with_capacity(entire_length);
+            while map.len() < entire_length {
+                let left = random_string(&mut rng);
+                let right = random_string(&mut rng);
+                if map.try_insert(left.clone(), right.clone()).is_ok() {
+                    lefts.push(left);
+                    rights.push(right);
+                }
+            }
+
+            for miss_percentage in MISS_PERCENTAGES {
+                let fresh_left = |rng: &mut ThreadRng| loop {
+                    let left = random_string(rng);
+                    if !map.contains_left(&left) {
+                        break left;
+                    }
+                };
+                let fresh_right = |rng: &mut ThreadRng| loop {
+                    let right = random_string(rng);
+                    if !map.contains_right(&right) {
+                        break right;
+                    }
+                };
+                let left_lookups = draw_lookups(&lefts, miss_percentage, &mut rng, fresh_left);
+                let right_lookups = draw_lookups(&rights, miss_percentage, &mut rng, fresh_right);
+
+                group.bench_with_input(BenchmarkId::new(format!("get_right_miss{}_{}", miss_percentage, load_factor), length), &length, |b, _| {
+                    b.iter(|| {
+                        for left in &left_lookups {
+                            black_box(map.get_right(left));
+                        }
+                    });
+                });
+
+                group.bench_with_input(BenchmarkId::new(format!("get_left_miss{}_{}", miss_percentage, load_factor), length), &length, |b, _| {
+                    b.iter(|| {
+                        for right in &right_lookups {
+                            black_box(map.get_left(right));
+                        }
+                    });
+                });
+            }
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_get, bench_get_miss, bench_get_string);
 criterion_main!(benches);
\ No newline at end of file
@@ -0,0 +1,85 @@
+//! Reports the heap usage of maps through dhat, for each phase of a workload: building a map,
+//! churning its pairs, and shrinking it after removing half of them. Each scenario runs under its
+//! own profiler, which also writes a `dhat-heap-<scenario>.json` file for the dhat viewer.
+
+use std::hash::Hash;
+use rand::{Rng, thread_rng};
+use bijective_map::BiMap;
+
+#[global_allocator]
+static ALLOC: dhat::Alloc = dhat::Alloc;
+
+const HEAP_SIZES: [usize; 3] = [1 << 10, 1 << 16, 1 << 20];
+
+/// A value type of the profiled maps, which is created from a distinct integer.
+trait Value: Hash + Eq {
+    const NAME: &'static str;
+
+    fn from_index(index: u64) -> Self;
+}
+
+impl Value for u64 {
+    const NAME: &'static str = "u64";
+
+    fn from_index(index: u64) -> Self {
+        index
+    }
+}
+
+impl Value for String {
+    const NAME: &'static str = "string";
+
+    fn from_index(index: u64) -> Self {
+        format!("value-{:016x}", index)
+    }
+}
+
+/// The heap statistics at the end of a phase, relative to the start of the scenario.
+fn report_phase(phase: &str, start: &dhat::HeapStats) {
+    let stats = dhat::HeapStats::get();
+    println!("  {:<16} {:>14} {:>14} {:>12} {:>16}",
+             phase, stats.curr_bytes - start.curr_bytes, stats.max_bytes - start.curr_bytes, stats.total_blocks - start.total_blocks, stats.total_bytes - start.total_bytes);
+}
+
+fn profile<T: Value, U: Value>(length: usize) {
+    let mut rng = thread_rng();
+    let scenario = format!("{}-{}-{}", T::NAME, U::NAME, length);
+    let _profiler = dhat::Profiler::builder()
+        .file_name(format!("dhat-heap-{}.json", scenario))
+        .build();
+
+    println!("{}:", scenario);
+    println!("  {:<16} {:>14} {:>14} {:>12} {:>16}", "phase", "live bytes", "peak bytes", "allocations", "allocated bytes");
+    let mut live = (0..length as u64).collect::<Vec<_>>();
+    let start = dhat::HeapStats::get();
+
+    let mut map = BiMap::new();
+    for index in 0..length as u64 {
+        map.insert(T::from_index(index), U::from_index(index));
+    }
+    report_phase("construction", &start);
+
+    // replace random pairs by fresh ones, which keeps the length of the map. The strings that are
+    // created to look up pairs count towards the allocations of the phase
+    for fresh in length as u64..2 * length as u64 {
+        let slot = rng.gen_range(0..live.len());
+        map.remove_left(&T::from_index(live[slot]));
+        map.insert(T::from_index(fresh), U::from_index(fresh));
+        live[slot] = fresh;
+    }
+    report_phase("churn", &start);
+
+    for index in live.drain(..length / 2) {
+        map.remove_left(&T::from_index(index));
+    }
+    map.shrink_to_fit();
+    report_phase("shrink_to_fit", &start);
+}
+
+fn main() {
+    for length in HEAP_SIZES {
+        profile::<u64, u64>(length);
+        profile::<String, u64>(length);
+        profile::<String, String>(length);
+    }
+}
\ No newline at end of file
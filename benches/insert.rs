@@ -0,0 +1,101 @@
+mod common;
+
+use std::time::{Duration, Instant};
+use criterion::*;
+use permutation_iterator::Permutor;
+use rand::{RngCore, thread_rng};
+use bijective_map::BiMap;
+use crate::common::*;
+
+fn bench_insert(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("insert");
+    group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
+
+    for load_factor in [0.5, 0.75, 0.8, 0.9] {
+        for length in SIZES {
+            let mut permutor_left = Permutor::new_with_u64_key(u64::MAX, rng.next_u64());
+            let mut permutor_right = Permutor::new_with_u64_key(u64::MAX, rng.next_u64());
+
+            let entire_length = (length as f64 * (load_factor / 0.9)) as usize;
+            let pairs = (0..entire_length)
+                .map(|_| (permutor_left.next().unwrap(), permutor_right.next().unwrap()))
+                .collect::<Vec<_>>();
+
+            // filling a map is measured as a whole, so report the time per inserted pair
+            group.throughput(Throughput::Elements(entire_length as u64));
+
+            group.bench_with_input(BenchmarkId::new(format!("insert_reserved_{}", load_factor), length), &length, |b, _| {
+                b.iter_batched(|| BiMap::with_capacity(length), |mut map| {
+                    for &(left, right) in &pairs {
+                        map.insert(left, right);
+                    }
+                    map
+                }, BatchSize::LargeInput);
+            });
+
+            group.bench_with_input(BenchmarkId::new(format!("insert_growing_{}", load_factor), length), &length, |b, _| {
+                b.iter_batched(BiMap::new, |mut map| {
+                    for &(left, right) in &pairs {
+                        map.insert(left, right);
+                    }
+                    map
+                }, BatchSize::LargeInput);
+            });
+
+            group.throughput(Throughput::Elements(1));
+
+            let mut map = BiMap::with_capacity(length);
+            for &(left, right) in &pairs {
+                map.insert(left, right);
+            }
+            let mut pairs = pairs;
+
+            // assign a fresh right value to an existing left value, which replaces one pair
+            group.bench_with_input(BenchmarkId::new(format!("insert_replace_{}", load_factor), length), &length, |b, _| {
+                b.iter_batched(|| {
+                    let index = (rng.next_u64() % entire_length as u64) as usize;
+                    pairs[index].1 = permutor_right.next().unwrap();
+                    pairs[index]
+                }, |(left, right)| map.insert(left, right), BatchSize::SmallInput);
+            });
+
+            // connect the left value of one pair with the right value of another pair, which
+            // replaces both pairs. Only the collapsing inserts are timed, the remaining values are
+            // connected afterwards to restore the length of the map
+            group.bench_with_input(BenchmarkId::new(format!("insert_collapse_{}", load_factor), length), &length, |b, _| {
+                b.iter_custom(|iters| {
+                    let mut elapsed = Duration::ZERO;
+                    let mut remaining = iters as usize;
+                    while remaining > 0 {
+                        let batch = remaining.min(entire_length / 2);
+                        let offset = (rng.next_u64() % entire_length as u64) as usize;
+                        let start = Instant::now();
+                        for i in 0..batch {
+                            let first = (offset + 2 * i) % entire_length;
+                            let second = (offset + 2 * i + 1) % entire_length;
+                            black_box(map.insert(pairs[first].0, pairs[second].1));
+                        }
+                        elapsed += start.elapsed();
+                        for i in 0..batch {
+                            let first = (offset + 2 * i) % entire_length;
+                            let second = (offset + 2 * i + 1) % entire_length;
+                            map.insert(pairs[second].0, pairs[first].1);
+                            let right = pairs[first].1;
+                            pairs[first].1 = pairs[second].1;
+                            pairs[second].1 = right;
+                        }
+                        remaining -= batch;
+                    }
+                    elapsed
+                });
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_insert);
+criterion_main!(benches);
\ No newline at end of file
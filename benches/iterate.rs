@@ -0,0 +1,52 @@
+use criterion::*;
+use permutation_iterator::Permutor;
+use rand::{RngCore, thread_rng};
+use bijective_map::BiMap;
+
+/// A right value that is much larger than the left values, so that interleaved storage drags a lot
+/// of unused bytes through the cache when only the left values are scanned.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct Payload([u64; 8]);
+
+const ITERATE_SIZES: [usize; 3] = [1 << 12, 1 << 16, 1 << 20];
+
+// compare the results with and without the `columnar` feature to see the trade-off between the
+// storage modes
+fn bench_iterate(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("iterate");
+    group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
+
+    for length in ITERATE_SIZES {
+        let mut map = BiMap::with_capacity(length);
+        let mut permutor_left = Permutor::new_with_u64_key(u64::MAX, rng.next_u64());
+        let mut permutor_right = Permutor::new_with_u64_key(u64::MAX, rng.next_u64());
+
+        let mut lefts = Vec::with_capacity(length);
+        for _ in 0..length {
+            let left = permutor_left.next().unwrap();
+            map.insert(left, Payload([permutor_right.next().unwrap(); 8]));
+            lefts.push(left);
+        }
+
+        group.throughput(Throughput::Elements(length as u64));
+
+        group.bench_with_input(BenchmarkId::new("left_values", length), &length, |b, _| {
+            b.iter(|| map.left_values().fold(0u64, |sum, left| sum.wrapping_add(*left)));
+        });
+
+        group.bench_with_input(BenchmarkId::new("iter", length), &length, |b, _| {
+            b.iter(|| map.iter().fold(0u64, |sum, (left, right)| sum.wrapping_add(*left ^ right.0[0])));
+        });
+
+        group.bench_with_input(BenchmarkId::new("get_right", length), &length, |b, _| {
+            b.iter_batched(|| lefts[(rng.next_u64() % length as u64) as usize], |key| map.get_right(&key).copied(), BatchSize::SmallInput);
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_iterate);
+criterion_main!(benches);
@@ -0,0 +1,78 @@
+use criterion::*;
+use rand::{RngCore, thread_rng};
+use bijective_map::BiMap;
+
+const PARALLEL_SIZES: [usize; 2] = [1 << 16, 1 << 20];
+
+/// A predicate that is expensive enough to dominate the removal of the pairs, and keeps about half
+/// of them.
+fn expensive_predicate(left: &u64, right: &u64) -> bool {
+    let mut hash = *left ^ *right;
+    for _ in 0..200 {
+        hash = hash.wrapping_mul(0x9e37_79b9_7f4a_7c15).rotate_left(17);
+    }
+    hash & 1 == 0
+}
+
+fn bench_retain(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("retain");
+    group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
+    group.sample_size(10);
+
+    for length in PARALLEL_SIZES {
+        let mut map = BiMap::with_capacity(length);
+        for _ in 0..length {
+            map.insert(rng.next_u64(), rng.next_u64());
+        }
+
+        group.bench_with_input(BenchmarkId::new("retain", length), &length, |b, _| {
+            b.iter_batched(|| map.clone(), |mut map| {
+                map.retain(expensive_predicate);
+                map
+            }, BatchSize::LargeInput);
+        });
+
+        group.bench_with_input(BenchmarkId::new("par_retain", length), &length, |b, _| {
+            b.iter_batched(|| map.clone(), |mut map| {
+                map.par_retain(expensive_predicate);
+                map
+            }, BatchSize::LargeInput);
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_sort(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("sort");
+    group.sample_size(10);
+
+    let length = 10_000_000;
+    let mut map = BiMap::with_capacity(length);
+    for _ in 0..length {
+        map.insert(rng.next_u64(), rng.next_u64());
+    }
+
+    group.bench_function(BenchmarkId::new("sort_by_left", length), |b| {
+        b.iter_batched(|| map.clone(), |mut map| {
+            map.sort_by_left();
+            map
+        }, BatchSize::LargeInput);
+    });
+
+    group.bench_function(BenchmarkId::new("par_sort_by_left", length), |b| {
+        b.iter_batched(|| map.clone(), |mut map| {
+            map.par_sort_by_left();
+            map
+        }, BatchSize::LargeInput);
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_retain, bench_sort);
+criterion_main!(benches);
@@ -0,0 +1,212 @@
+mod common;
+
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, Instant};
+use criterion::*;
+use permutation_iterator::Permutor;
+use rand::{RngCore, thread_rng};
+use bijective_map::BiMap;
+use crate::common::*;
+
+fn bench_remove(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("remove");
+    group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
+
+    for load_factor in [0.5, 0.75, 0.8, 0.9] {
+        for length in SIZES {
+            let mut map = BiMap::with_capacity(length);
+            let mut permutor_left = Permutor::new_with_u64_key(u64::MAX, rng.next_u64());
+            let mut permutor_right = Permutor::new_with_u64_key(u64::MAX, rng.next_u64());
+
+            let entire_length = (length as f64 * (load_factor / 0.9)) as usize;
+            let mut pairs = Vec::with_capacity(entire_length);
+            for _ in 0..entire_length {
+                let left = permutor_left.next().unwrap();
+                let right = permutor_right.next().unwrap();
+                map.insert(left, right);
+                pairs.push((left, right));
+            }
+
+            // only the removals are timed, the removed pairs are reinserted afterwards to keep the
+            // load factor stable
+            group.bench_with_input(BenchmarkId::new(format!("remove_left_{}", load_factor), length), &length, |b, _| {
+                b.iter_custom(|iters| {
+                    let mut elapsed = Duration::ZERO;
+                    let mut remaining = iters as usize;
+                    while remaining > 0 {
+                        let batch = remaining.min(entire_length);
+                        let offset = (rng.next_u64() % entire_length as u64) as usize;
+                        let start = Instant::now();
+                        for i in 0..batch {
+                            black_box(map.remove_left(&pairs[(offset + i) % entire_length].0));
+                        }
+                        elapsed += start.elapsed();
+                        for i in 0..batch {
+                            let (left, right) = pairs[(offset + i) % entire_length];
+                            map.insert(left, right);
+                        }
+                        remaining -= batch;
+                    }
+                    elapsed
+                });
+            });
+
+            group.bench_with_input(BenchmarkId::new(format!("remove_right_{}", load_factor), length), &length, |b, _| {
+                b.iter_custom(|iters| {
+                    let mut elapsed = Duration::ZERO;
+                    let mut remaining = iters as usize;
+                    while remaining > 0 {
+                        let batch = remaining.min(entire_length);
+                        let offset = (rng.next_u64() % entire_length as u64) as usize;
+                        let start = Instant::now();
+                        for i in 0..batch {
+                            black_box(map.remove_right(&pairs[(offset + i) % entire_length].1));
+                        }
+                        elapsed += start.elapsed();
+                        for i in 0..batch {
+                            let (left, right) = pairs[(offset + i) % entire_length];
+                            map.insert(left, right);
+                        }
+                        remaining -= batch;
+                    }
+                    elapsed
+                });
+            });
+
+            // the permutor never repeats a value, so values drawn after filling the map are absent
+            group.bench_with_input(BenchmarkId::new(format!("remove_left_absent_{}", load_factor), length), &length, |b, _| {
+                b.iter_batched(|| permutor_left.next().unwrap(), |left| map.remove_left(&left), BatchSize::SmallInput);
+            });
+        }
+    }
+
+    group.finish();
+}
+
+/// A hasher that hashes an integer to itself, rounded down to a multiple of [`CHAIN_LENGTH`], so
+/// that runs of consecutive integers collide.
+#[derive(Clone, Copy, Default)]
+struct IdentityHasher {
+    state: u64,
+}
+
+/// The number of consecutive integers that share a hash under the [`IdentityHasher`].
+const CHAIN_LENGTH: u64 = 32;
+
+impl Hasher for IdentityHasher {
+    fn finish(&self) -> u64 {
+        self.state - self.state % CHAIN_LENGTH
+    }
+
+    fn write(&mut self, _: &[u8]) {
+        unimplemented!("the identity hasher only hashes integers")
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.state = value;
+    }
+}
+
+impl BuildHasher for IdentityHasher {
+    type Hasher = IdentityHasher;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        IdentityHasher::default()
+    }
+}
+
+fn bench_remove_chain(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("remove_chain");
+    group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
+
+    for load_factor in [0.5, 0.75, 0.8, 0.9] {
+        for length in SIZES {
+            let mut map = BiMap::with_hashers(length, IdentityHasher::default(), IdentityHasher::default());
+
+            // every run of consecutive values forms one collision chain in each index
+            let entire_length = (length as f64 * (load_factor / 0.9)) as u64;
+            for value in 0..entire_length {
+                map.insert(value, value);
+            }
+
+            group.bench_with_input(BenchmarkId::new(format!("remove_left_{}", load_factor), length), &length, |b, _| {
+                b.iter_custom(|iters| {
+                    let mut elapsed = Duration::ZERO;
+                    let mut remaining = iters;
+                    while remaining > 0 {
+                        let batch = remaining.min(entire_length);
+                        let offset = rng.next_u64() % entire_length;
+                        let start = Instant::now();
+                        for i in 0..batch {
+                            black_box(map.remove_left(&((offset + i) % entire_length)));
+                        }
+                        elapsed += start.elapsed();
+                        for i in 0..batch {
+                            let value = (offset + i) % entire_length;
+                            map.insert(value, value);
+                        }
+                        remaining -= batch;
+                    }
+                    elapsed
+                });
+            });
+        }
+    }
+
+    group.finish();
+}
+
+fn bench_remove_string(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("remove_string");
+    group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
+
+    // the largest sizes would need several gigabytes of strings
+    for load_factor in [0.5, 0.75, 0.8, 0.9] {
+        for length in SIZES.into_iter().filter(|&length| length <= 1 << 20) {
+            let mut map = BiMap::with_capacity(length);
+
+            let entire_length = (length as f64 * (load_factor / 0.9)) as usize;
+            let mut pairs = Vec::with_capacity(entire_length);
+            while map.len() < entire_length {
+                let left = random_string(&mut rng);
+                let right = random_string(&mut rng);
+                if map.try_insert(left.clone(), right.clone()).is_ok() {
+                    pairs.push((left, right));
+                }
+            }
+
+            group.bench_with_input(BenchmarkId::new(format!("remove_left_{}", load_factor), length), &length, |b, _| {
+                b.iter_custom(|iters| {
+                    let mut elapsed = Duration::ZERO;
+                    let mut remaining = iters as usize;
+                    while remaining > 0 {
+                        let batch = remaining.min(entire_length);
+                        let offset = (rng.next_u64() % entire_length as u64) as usize;
+                        let start = Instant::now();
+                        for i in 0..batch {
+                            black_box(map.remove_left(&pairs[(offset + i) % entire_length].0));
+                        }
+                        elapsed += start.elapsed();
+                        for i in 0..batch {
+                            let (left, right) = &pairs[(offset + i) % entire_length];
+                            map.insert(left.clone(), right.clone());
+                        }
+                        remaining -= batch;
+                    }
+                    elapsed
+                });
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_remove, bench_remove_chain, bench_remove_string);
+criterion_main!(benches);
@@ -0,0 +1,43 @@
+use criterion::*;
+use rand::{RngCore, thread_rng};
+use bijective_map::BiMap;
+use bijective_map::shared::SharedBiMap;
+
+const SHARED_SIZES: [usize; 3] = [1 << 12, 1 << 16, 1 << 20];
+
+fn bench_shared(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("shared");
+    group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
+
+    for length in SHARED_SIZES {
+        let mut map = BiMap::with_capacity(length);
+        for _ in 0..length {
+            map.insert(rng.next_u64(), rng.next_u64());
+        }
+        let mut shared = SharedBiMap::from(map.clone());
+
+        group.bench_with_input(BenchmarkId::new("clone_deep", length), &length, |b, _| {
+            b.iter(|| map.clone());
+        });
+
+        group.bench_with_input(BenchmarkId::new("clone_shared", length), &length, |b, _| {
+            b.iter(|| shared.clone());
+        });
+
+        // a snapshot per frame, after which the original is mutated and has to copy its contents
+        group.bench_with_input(BenchmarkId::new("clone_shared_then_insert", length), &length, |b, _| {
+            b.iter_batched(|| (rng.next_u64(), rng.next_u64()), |(left, right)| {
+                let snapshot = shared.clone();
+                shared.insert(left, right);
+                snapshot
+            }, BatchSize::SmallInput);
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_shared);
+criterion_main!(benches);
@@ -0,0 +1,40 @@
+use criterion::*;
+use rand::{RngCore, thread_rng};
+use bijective_map::BiMap;
+
+const SMALL_SIZES: [usize; 4] = [1, 2, 4, 8];
+
+fn bench_small(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("small");
+
+    for length in SMALL_SIZES {
+        let pairs: Vec<(u64, u64)> = (0..length).map(|_| (rng.next_u64(), rng.next_u64())).collect();
+
+        // build a fresh map, which includes its allocations
+        group.bench_with_input(BenchmarkId::new("insert", length), &length, |b, _| {
+            b.iter(|| {
+                let mut map = BiMap::new();
+                for &(left, right) in &pairs {
+                    map.insert(left, right);
+                }
+                map
+            });
+        });
+
+        let mut map = BiMap::new();
+        for &(left, right) in &pairs {
+            map.insert(left, right);
+        }
+
+        group.bench_with_input(BenchmarkId::new("get", length), &length, |b, _| {
+            b.iter_batched(|| pairs[(rng.next_u64() % length as u64) as usize].1, |key| map.get_left(&key), BatchSize::SmallInput);
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_small);
+criterion_main!(benches);
@@ -0,0 +1,58 @@
+mod common;
+
+use criterion::*;
+use permutation_iterator::Permutor;
+use rand::{RngCore, thread_rng};
+use bijective_map::BiMap;
+use crate::common::*;
+
+fn bench_update(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("update");
+    group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
+
+    for load_factor in [0.5, 0.75, 0.8, 0.9] {
+        for length in SIZES {
+            let mut map = BiMap::with_capacity(length);
+            let mut permutor_left = Permutor::new_with_u64_key(u64::MAX, rng.next_u64());
+            let mut permutor_right = Permutor::new_with_u64_key(u64::MAX, rng.next_u64());
+
+            let entire_length = (length as f64 * (load_factor / 0.9)) as usize;
+            let mut lefts = Vec::with_capacity(entire_length);
+            let mut rights = Vec::with_capacity(entire_length);
+            for _ in 0..entire_length {
+                let left = permutor_left.next().unwrap();
+                let right = permutor_right.next().unwrap();
+                map.insert(left, right);
+                lefts.push(left);
+                rights.push(right);
+            }
+
+            // assign a fresh right value to an existing left value
+            group.bench_with_input(BenchmarkId::new(format!("update_right_{}", load_factor), length), &length, |b, _| {
+                b.iter_batched(|| {
+                    let index = (rng.next_u64() % entire_length as u64) as usize;
+                    let right = permutor_right.next().unwrap();
+                    rights[index] = right;
+                    (lefts[index], right)
+                }, |(left, right)| map.insert(left, right), BatchSize::SmallInput);
+            });
+
+            // assign a fresh left value to an existing right value
+            group.bench_with_input(BenchmarkId::new(format!("update_left_{}", load_factor), length), &length, |b, _| {
+                b.iter_batched(|| {
+                    let index = (rng.next_u64() % entire_length as u64) as usize;
+                    let left = permutor_left.next().unwrap();
+                    lefts[index] = left;
+                    (left, rights[index])
+                }, |(left, right)| map.insert(left, right), BatchSize::SmallInput);
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_update);
+criterion_main!(benches);
@@ -0,0 +1,140 @@
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use criterion::*;
+use permutation_iterator::Permutor;
+use rand::{RngCore, thread_rng};
+use bijective_map::BiMap;
+
+/// The lengths of the collision chains. Each chain holds all values of a map.
+const CHAIN_LENGTHS: [usize; 5] = [8, 32, 128, 512, 1024];
+
+/// The number of pairs after which the growth latency is measured. The measured insert is the
+/// first one at or after this length that grows the indices. The largest length needs about 8 GiB
+/// of memory, because the map is cloned for every measurement.
+const GROWTH_LENGTHS: [usize; 3] = [1_000_000, 10_000_000, 50_000_000];
+
+/// A hasher that maps all values to the same hash, so that all values share their ideal index.
+#[derive(Clone, Copy, Default)]
+struct CollidingHasher;
+
+impl Hasher for CollidingHasher {
+    fn finish(&self) -> u64 {
+        0
+    }
+
+    fn write(&mut self, _: &[u8]) {}
+}
+
+impl BuildHasher for CollidingHasher {
+    type Hasher = CollidingHasher;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        CollidingHasher
+    }
+}
+
+fn bench_collision_chain(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("collision_chain");
+    group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
+
+    for length in CHAIN_LENGTHS {
+        // twice the capacity keeps even the shortest chain out of the small map mode
+        let mut map = BiMap::with_hashers(2 * length, CollidingHasher, CollidingHasher);
+        for value in 0..length as u64 {
+            map.insert(value, value);
+        }
+
+        group.bench_with_input(BenchmarkId::new("get_right", length), &length, |b, _| {
+            b.iter_batched(|| rng.next_u64() % length as u64, |left| map.get_right(&left).copied(), BatchSize::SmallInput);
+        });
+
+        // a miss probes the entire chain
+        group.bench_with_input(BenchmarkId::new("get_right_miss", length), &length, |b, _| {
+            b.iter(|| map.get_right(&(length as u64)).copied());
+        });
+
+        // only the removals are timed, the removed pairs are reinserted afterwards to keep the
+        // chain length stable
+        group.bench_with_input(BenchmarkId::new("remove_left", length), &length, |b, _| {
+            b.iter_custom(|iters| {
+                let mut elapsed = Duration::ZERO;
+                let mut remaining = iters;
+                while remaining > 0 {
+                    let batch = remaining.min(length as u64);
+                    let offset = rng.next_u64() % length as u64;
+                    let start = Instant::now();
+                    for i in 0..batch {
+                        black_box(map.remove_left(&((offset + i) % length as u64)));
+                    }
+                    elapsed += start.elapsed();
+                    for i in 0..batch {
+                        let value = (offset + i) % length as u64;
+                        map.insert(value, value);
+                    }
+                    remaining -= batch;
+                }
+                elapsed
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_growth_latency(c: &mut Criterion) {
+    let mut rng = thread_rng();
+
+    let mut group = c.benchmark_group("growth_latency");
+    group.sample_size(10);
+    group.sampling_mode(SamplingMode::Flat);
+
+    for length in GROWTH_LENGTHS {
+        let mut permutor_left = Permutor::new_with_u64_key(u64::MAX, rng.next_u64());
+        let mut permutor_right = Permutor::new_with_u64_key(u64::MAX, rng.next_u64());
+
+        // fill a map up to the target length, then continue until an insert grows the indices
+        let mut map = BiMap::new();
+        let mut pairs = Vec::with_capacity(length);
+        let mut next_pair = || (permutor_left.next().unwrap(), permutor_right.next().unwrap());
+        for _ in 0..length {
+            let (left, right) = next_pair();
+            map.insert(left, right);
+            pairs.push((left, right));
+        }
+
+        let grown = Arc::new(AtomicBool::new(false));
+        let hook_grown = grown.clone();
+        map.set_resize_hook(Some(Box::new(move |_| hook_grown.store(true, Ordering::Relaxed))));
+        while !grown.load(Ordering::Relaxed) {
+            let (left, right) = next_pair();
+            map.insert(left, right);
+            pairs.push((left, right));
+        }
+        drop(map);
+
+        // the capacities of a map only depend on its insertions, so repeating all inserts but the
+        // last one leads to the map right before the growth
+        let (left, right) = pairs.pop().unwrap();
+        let mut brink = BiMap::new();
+        for &(left, right) in &pairs {
+            brink.insert(left, right);
+        }
+        drop(pairs);
+
+        group.bench_with_input(BenchmarkId::new("insert", length), &length, |b, _| {
+            b.iter_batched(|| brink.clone(), |mut map| {
+                map.insert(left, right);
+                map
+            }, BatchSize::PerIteration);
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_collision_chain, bench_growth_latency);
+criterion_main!(benches);
\ No newline at end of file
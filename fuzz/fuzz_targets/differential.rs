@@ -0,0 +1,190 @@
+//! Applies a sequence of operations decoded from the input to a map and to an oracle of two hash
+//! maps, and compares them after every operation.
+//!
+//! Each operation is encoded as an opcode byte, followed by the operands of the operation. Values
+//! are little endian `u16`s, and capacities and the lengths of bulk insertions are single bytes. The
+//! map uses a hasher that only keeps the lowest bits of a value, so that values collide and probe
+//! chains wrap around the indices. The invariants of the indices are checked after every operation,
+//! so a corruption fails at the operation that introduced it.
+
+#![no_main]
+
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use libfuzzer_sys::fuzz_target;
+use bijective_map::BiMap;
+
+/// A hasher that keeps only the lowest six bits of the value.
+#[derive(Clone, Copy, Default)]
+struct LowBitsHasher {
+    state: u64,
+}
+
+impl Hasher for LowBitsHasher {
+    fn finish(&self) -> u64 {
+        self.state & 0x3F
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes.iter().rev() {
+            self.state = (self.state << 8) | byte as u64;
+        }
+    }
+}
+
+impl BuildHasher for LowBitsHasher {
+    type Hasher = LowBitsHasher;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        LowBitsHasher::default()
+    }
+}
+
+#[derive(Debug)]
+enum Operation {
+    Insert(u16, u16),
+    TryInsert(u16, u16),
+    RemoveLeft(u16),
+    RemoveRight(u16),
+    Clear,
+    Reserve(usize),
+    ShrinkToFit,
+    ShrinkTo(usize),
+    Compact,
+    /// Bulk insertion of consecutive pairs from the given starting values, which can grow the
+    /// indices several times at once.
+    BulkInsert(u16, u16, u8),
+}
+
+/// Decodes operations from the input. Trailing bytes that do not form a complete operation are
+/// ignored.
+struct Decoder<'a> {
+    input: &'a [u8],
+}
+
+impl Decoder<'_> {
+    fn byte(&mut self) -> Option<u8> {
+        let (&byte, rest) = self.input.split_first()?;
+        self.input = rest;
+        Some(byte)
+    }
+
+    fn value(&mut self) -> Option<u16> {
+        Some(u16::from_le_bytes([self.byte()?, self.byte()?]))
+    }
+}
+
+impl Iterator for Decoder<'_> {
+    type Item = Operation;
+
+    fn next(&mut self) -> Option<Operation> {
+        Some(match self.byte()? % 10 {
+            0 => Operation::Insert(self.value()?, self.value()?),
+            1 => Operation::TryInsert(self.value()?, self.value()?),
+            2 => Operation::RemoveLeft(self.value()?),
+            3 => Operation::RemoveRight(self.value()?),
+            4 => Operation::Clear,
+            5 => Operation::Reserve(self.byte()? as usize),
+            6 => Operation::ShrinkToFit,
+            7 => Operation::ShrinkTo(self.byte()? as usize),
+            8 => Operation::Compact,
+            _ => Operation::BulkInsert(self.value()?, self.value()?, self.byte()?),
+        })
+    }
+}
+
+/// The reference implementation of a bijection.
+#[derive(Default)]
+struct Oracle {
+    right_of: HashMap<u16, u16>,
+    left_of: HashMap<u16, u16>,
+}
+
+impl Oracle {
+    fn insert(&mut self, left: u16, right: u16) -> (Option<u16>, Option<u16>) {
+        // reinserting an existing pair reports both values as replaced
+        if self.right_of.get(&left) == Some(&right) {
+            return (Some(right), Some(left));
+        }
+
+        let old_right = self.remove_left(left);
+        let old_left = self.remove_right(right);
+        self.right_of.insert(left, right);
+        self.left_of.insert(right, left);
+        (old_right, old_left)
+    }
+
+    /// Inserts the pairs whose values are both absent, in order, and returns the rejected pairs.
+    fn bulk_insert(&mut self, pairs: &[(u16, u16)]) -> Vec<(u16, u16)> {
+        let mut rejected = Vec::new();
+        for &(left, right) in pairs {
+            if self.right_of.contains_key(&left) || self.left_of.contains_key(&right) {
+                rejected.push((left, right));
+            } else {
+                self.insert(left, right);
+            }
+        }
+        rejected
+    }
+
+    fn remove_left(&mut self, left: u16) -> Option<u16> {
+        let right = self.right_of.remove(&left)?;
+        self.left_of.remove(&right);
+        Some(right)
+    }
+
+    fn remove_right(&mut self, right: u16) -> Option<u16> {
+        let left = self.left_of.remove(&right)?;
+        self.right_of.remove(&left);
+        Some(left)
+    }
+}
+
+fuzz_target!(|input: &[u8]| {
+    let mut map = BiMap::with_hashers(0, LowBitsHasher::default(), LowBitsHasher::default());
+    let mut oracle = Oracle::default();
+
+    for operation in (Decoder { input }) {
+        match operation {
+            Operation::Insert(left, right) => {
+                assert_eq!(map.insert(left, right), oracle.insert(left, right), "{:?}", operation);
+            }
+            Operation::TryInsert(left, right) => {
+                let left_occupied = oracle.right_of.contains_key(&left);
+                let right_occupied = oracle.left_of.contains_key(&right);
+                let result = map.try_insert(left, right).map_err(|error| (error.left_occupied, error.right_occupied));
+                if !left_occupied && !right_occupied {
+                    oracle.insert(left, right);
+                    assert_eq!(result, Ok(()), "{:?}", operation);
+                } else {
+                    assert_eq!(result, Err((left_occupied, right_occupied)), "{:?}", operation);
+                }
+            }
+            Operation::RemoveLeft(left) => assert_eq!(map.remove_left(&left), oracle.remove_left(left), "{:?}", operation),
+            Operation::RemoveRight(right) => assert_eq!(map.remove_right(&right), oracle.remove_right(right), "{:?}", operation),
+            Operation::Clear => {
+                map.clear();
+                oracle = Oracle::default();
+            }
+            Operation::Reserve(additional) => map.reserve(additional),
+            Operation::ShrinkToFit => map.shrink_to_fit(),
+            Operation::ShrinkTo(min_capacity) => map.shrink_to(min_capacity),
+            Operation::Compact => map.compact(),
+            Operation::BulkInsert(left, right, count) => {
+                let pairs = (0..count as u16).map(|i| (left.wrapping_add(i), right.wrapping_add(i))).collect::<Vec<_>>();
+                assert_eq!(map.bulk_insert(pairs.iter().copied()), oracle.bulk_insert(&pairs), "{:?}", operation);
+            }
+        }
+
+        assert_eq!(map.len(), oracle.right_of.len());
+        for (left, right) in &oracle.right_of {
+            assert_eq!(map.get_right(left), Some(right));
+            assert_eq!(map.get_left(right), Some(left));
+        }
+        if let Err(violation) = map.check_invariants() {
+            panic!("{:?} broke the invariants: {:?}", operation, violation);
+        }
+    }
+
+    assert_eq!(map.iter().count(), oracle.right_of.len());
+});
@@ -0,0 +1,188 @@
+//! A bi-directional map with a payload for every pair.
+
+use std::hash::{BuildHasher, Hash, RandomState};
+use crate::BiMap;
+
+/// The pairs replaced by [`AnnotatedBiMap::insert_with`]: the right value that the left value was
+/// mapped to and the left value that the right value was mapped to, each with its payload.
+pub type ReplacedPairs<T, U, M> = (Option<(U, M)>, Option<(T, M)>);
+
+/// A bi-directional map that stores a payload of type `M` with every pair, such as the time a pair
+/// was created. The payload is neither hashed nor compared, so it can be mutated in place, and it
+/// follows its pair until the pair is replaced or removed.
+///
+/// The payloads are stored next to the buckets of the map, indexed by bucket, and move along when
+/// a removal moves the last bucket into the gap.
+#[derive(Clone, Debug)]
+pub struct AnnotatedBiMap<T, U, M, H = RandomState, RH = RandomState>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    map: BiMap<T, U, H, RH>,
+    /// The payloads of the buckets, indexed like the buckets.
+    metas: Vec<M>,
+}
+
+impl<T, U, M> AnnotatedBiMap<T, U, M>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    /// Create a new empty map.
+    pub fn new() -> Self {
+        Self::with_hashers(0, RandomState::default(), RandomState::default())
+    }
+}
+
+impl<T, U, M> Default for AnnotatedBiMap<T, U, M>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, U, M, H, RH> AnnotatedBiMap<T, U, M, H, RH>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    /// Create a new empty map with the given capacity and hashers.
+    pub fn with_hashers(capacity: usize, hasher: H, reverse_hasher: RH) -> Self {
+        AnnotatedBiMap {
+            map: BiMap::with_hashers(capacity, hasher, reverse_hasher),
+            metas: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Remove a bucket from the map together with its payload. The last bucket was moved into its
+    /// position, so its payload is moved as well.
+    fn remove_bucket(&mut self, bucket_index: usize) -> (T, U, M) {
+        let bucket = self.map.remove_bucket(bucket_index);
+        self.map.rebuild_filter_if_stale();
+        let meta = self.metas.swap_remove(bucket_index);
+        (bucket.left, bucket.right, meta)
+    }
+
+    /// Inserts a value pair with its payload. Like [`BiMap::insert`], the pairs that either value
+    /// was mapped to are removed, and the replaced values are returned together with the payloads
+    /// of their pairs.
+    pub fn insert_with(&mut self, left: T, right: U, meta: M) -> ReplacedPairs<T, U, M> {
+        // removing the pair of the left value moves the last bucket, so the right value is looked
+        // up afterwards
+        let replaced_right = self.map.find_bucket_left(&left).map(|bucket_index| {
+            let (_, right, meta) = self.remove_bucket(bucket_index);
+            (right, meta)
+        });
+        let replaced_left = self.map.find_bucket_right(&right).map(|bucket_index| {
+            let (left, _, meta) = self.remove_bucket(bucket_index);
+            (left, meta)
+        });
+
+        self.map.insert_unique_unchecked(left, right);
+        self.metas.push(meta);
+        (replaced_right, replaced_left)
+    }
+
+    /// Get the right value for the given left value. If the left value is not in the map, None is
+    /// returned.
+    #[must_use]
+    pub fn get_right(&self, left: &T) -> Option<&U> {
+        self.map.get_right(left)
+    }
+
+    /// Get the left value for the given right value. If the right value is not in the map, None is
+    /// returned.
+    #[must_use]
+    pub fn get_left(&self, right: &U) -> Option<&T> {
+        self.map.get_left(right)
+    }
+
+    /// Check if the map contains a mapping for the given left value.
+    #[must_use]
+    pub fn contains_left(&self, left: &T) -> bool {
+        self.map.contains_left(left)
+    }
+
+    /// Check if the map contains a mapping for the given right value.
+    #[must_use]
+    pub fn contains_right(&self, right: &U) -> bool {
+        self.map.contains_right(right)
+    }
+
+    /// Get the payload of the pair of the given left value. If the left value is not in the map,
+    /// None is returned.
+    #[must_use]
+    pub fn get_meta_by_left(&self, left: &T) -> Option<&M> {
+        Some(&self.metas[self.map.find_bucket_left(left)?])
+    }
+
+    /// Get the payload of the pair of the given right value. If the right value is not in the map,
+    /// None is returned.
+    #[must_use]
+    pub fn get_meta_by_right(&self, right: &U) -> Option<&M> {
+        Some(&self.metas[self.map.find_bucket_right(right)?])
+    }
+
+    /// Get the payload of the pair of the given left value for mutation. If the left value is not in
+    /// the map, None is returned.
+    pub fn get_meta_mut_by_left(&mut self, left: &T) -> Option<&mut M> {
+        Some(&mut self.metas[self.map.find_bucket_left(left)?])
+    }
+
+    /// Get the payload of the pair of the given right value for mutation. If the right value is not
+    /// in the map, None is returned.
+    pub fn get_meta_mut_by_right(&mut self, right: &U) -> Option<&mut M> {
+        Some(&mut self.metas[self.map.find_bucket_right(right)?])
+    }
+
+    /// Deletes the mapping for the given left value and returns the right value that was mapped to
+    /// it, together with the payload of the pair. If the left value is not in the map, None is
+    /// returned.
+    pub fn remove_left(&mut self, left: &T) -> Option<(U, M)> {
+        let bucket_index = self.map.find_bucket_left(left)?;
+        let (_, right, meta) = self.remove_bucket(bucket_index);
+        Some((right, meta))
+    }
+
+    /// Deletes the mapping for the given right value and returns the left value that was mapped to
+    /// it, together with the payload of the pair. If the right value is not in the map, None is
+    /// returned.
+    pub fn remove_right(&mut self, right: &U) -> Option<(T, M)> {
+        let bucket_index = self.map.find_bucket_right(right)?;
+        let (left, _, meta) = self.remove_bucket(bucket_index);
+        Some((left, meta))
+    }
+
+    /// Clears the map, removing all mappings and their payloads. Keeps the allocated memory for
+    /// reuse.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.metas.clear();
+    }
+
+    /// Returns an iterator over the mappings and their payloads in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item=(&T, &U, &M)> {
+        self.map.iter()
+            .zip(&self.metas)
+            .map(|((left, right), meta)| (left, right, meta))
+    }
+
+    /// Returns an iterator over the mappings in arbitrary order, with their payloads for mutation.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item=(&T, &U, &mut M)> {
+        self.map.iter()
+            .zip(&mut self.metas)
+            .map(|((left, right), meta)| (left, right, meta))
+    }
+
+    /// Returns the number of pairs in the map.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the map contains no pairs.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the underlying map without the payloads, which provides the remaining read-only
+    /// operations.
+    pub fn as_bimap(&self) -> &BiMap<T, U, H, RH> {
+        &self.map
+    }
+}
@@ -0,0 +1,137 @@
+//! Loading many pairs at once, building the indices in one pass at the final size.
+//!
+//! Inserting pairs one by one maintains both indices after every insertion and grows them several
+//! times on the way. A bulk insertion appends all pairs to the pair storage first and then places
+//! all pairs into indices of the final capacity, like a resize does. Duplicates are only found
+//! afterwards: equal values have the same ideal index, and the index finds the entries of each ideal
+//! index together, e.g. as a run of neighboring slots in a Robin Hood index, so they are compared
+//! pairwise. The rejected pairs are then removed, and the indices are placed once more.
+
+use std::cmp::max;
+use std::hash::{BuildHasher, Hash};
+use allocator_api2::alloc::Allocator;
+use crate::{BiMap, ResizeReason, SMALL_MAP_CAPACITY};
+use crate::side;
+use crate::storage::{Bucket, Storage};
+
+impl<T, U, H, RH, A: Allocator + Clone> BiMap<T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    /// Inserts many pairs at once, and returns the pairs that were not inserted, in their order in
+    /// the input. This is faster than inserting the pairs one by one when the pairs are mostly
+    /// unique, because the indices are built once at their final size.
+    ///
+    /// Unlike [`insert`], a pair never replaces another pair: a pair is rejected if its left or
+    /// right value is already in the map, or belongs to an earlier pair of the input that was
+    /// inserted. This is the result of calling [`try_insert`] for every pair in order.
+    ///
+    /// All values are hashed before the map is changed, so a panicking `Hash` implementation leaves
+    /// the map intact. The inserted pairs notify the insert hook in their order in the input.
+    ///
+    /// [`insert`]: #method.insert
+    /// [`try_insert`]: #method.try_insert
+    pub fn bulk_insert<I>(&mut self, pairs: I) -> Vec<(T, U)>
+        where I: IntoIterator<Item=(T, U)>
+    {
+        let start = self.len();
+        let pairs = pairs.into_iter().map(|(left, right)| Bucket { left, right }).collect::<Vec<_>>();
+        if pairs.is_empty() {
+            return Vec::new();
+        }
+
+        let rejected = if self.is_small() && start + pairs.len() <= SMALL_MAP_CAPACITY {
+            for bucket in pairs {
+                self.data.push(bucket);
+            }
+            let marks = self.small_duplicate_marks(start);
+            self.data.extract_marked(&marks)
+        } else {
+            self.place_bulk(pairs)
+        };
+
+        for bucket_index in start..self.len() {
+            self.notify_insert(bucket_index);
+        }
+        rejected.into_iter().map(|bucket| (bucket.left, bucket.right)).collect()
+    }
+
+    /// Mark the pairs of a small map from `start` on that share a value with an earlier pair that
+    /// is not marked.
+    fn small_duplicate_marks(&self, start: usize) -> Vec<bool> {
+        let mut marks = vec![false; self.len()];
+        for later in start..self.len() {
+            marks[later] = (0..later).any(|earlier| {
+                !marks[earlier] && (self.data.left(earlier) == self.data.left(later) || self.data.right(earlier) == self.data.right(later))
+            });
+        }
+        marks
+    }
+
+    /// Append the pairs to the map and place all pairs into indices of the final capacity. The pairs
+    /// that share a value with an earlier pair that was not rejected are removed again, and returned
+    /// in order.
+    fn place_bulk(&mut self, pairs: Vec<Bucket<T, U>>) -> Vec<Bucket<T, U>> {
+        let old_capacity = self.current_capacity();
+        let capacity = if self.can_fit(pairs.len()) {
+            old_capacity
+        } else {
+            max(old_capacity, Self::apply_load_factor(self.len() + pairs.len()))
+        };
+
+        // the indices of the existing pairs change with the capacity, so all pairs are hashed
+        let left_ideal_indices = self.data.lefts()
+            .chain(pairs.iter().map(|bucket| &bucket.left))
+            .map(|left| side::hash_to_index(&self.left.hasher, left, capacity))
+            .collect::<Vec<_>>();
+        let right_ideal_indices = self.data.rights()
+            .chain(pairs.iter().map(|bucket| &bucket.right))
+            .map(|right| side::hash_to_index(&self.right.hasher, right, capacity))
+            .collect::<Vec<_>>();
+
+        for bucket in pairs {
+            self.data.push(bucket);
+        }
+        self.left.back_references.clear();
+        self.left.back_references.extend_from_slice(&left_ideal_indices);
+        self.right.back_references.clear();
+        self.right.back_references.extend_from_slice(&right_ideal_indices);
+        self.place_indices(capacity);
+        #[cfg(feature = "stats")]
+        {
+            self.counters.rehashes += 1;
+        }
+        if capacity != old_capacity {
+            self.notify_resize(old_capacity, capacity, ResizeReason::Growth);
+        }
+
+        // each edge connects a pair to an earlier pair with an equal value
+        let mut edges = self.left.equal_value_edges(&left_ideal_indices, &self.data, Storage::left);
+        edges.extend(self.right.equal_value_edges(&right_ideal_indices, &self.data, Storage::right));
+        if edges.is_empty() {
+            return Vec::new();
+        }
+
+        // edges are resolved in the order of the later pair, so the earlier pair is already decided
+        edges.sort_unstable();
+        let mut marks = vec![false; self.len()];
+        for (later, earlier) in edges {
+            if !marks[earlier] {
+                marks[later] = true;
+            }
+        }
+
+        let rejected = self.data.extract_marked(&marks);
+        let mut left_marks = marks.iter();
+        self.left.back_references.clear();
+        self.left.back_references.extend(left_ideal_indices.into_iter().filter(|_| left_marks.next() == Some(&false)));
+        let mut right_marks = marks.iter();
+        self.right.back_references.clear();
+        self.right.back_references.extend(right_ideal_indices.into_iter().filter(|_| right_marks.next() == Some(&false)));
+        self.place_indices(capacity);
+        #[cfg(feature = "stats")]
+        {
+            self.counters.rehashes += 1;
+        }
+        rejected
+    }
+}
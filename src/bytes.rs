@@ -0,0 +1,112 @@
+//! A raw snapshot of the pairs of a map, for values that are plain old data.
+
+use std::error::Error;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::mem;
+use bytemuck::{Pod, PodCastError};
+use crate::BiMap;
+use crate::storage::Bucket;
+
+/// An error of [`BiMap::from_bytes`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FromBytesError {
+    /// The bytes are not aligned for the pairs, or their length is not a multiple of the size of a
+    /// pair.
+    Cast(PodCastError),
+    /// The left value of pair `duplicate` equals the left value of the earlier pair `first`.
+    DuplicateLeft { first: usize, duplicate: usize },
+    /// The right value of pair `duplicate` equals the right value of the earlier pair `first`.
+    DuplicateRight { first: usize, duplicate: usize },
+}
+
+impl fmt::Display for FromBytesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            FromBytesError::Cast(error) =>
+                write!(f, "the bytes cannot be cast to pairs: {}", error),
+            FromBytesError::DuplicateLeft { first, duplicate } =>
+                write!(f, "the left value of pair {} equals the left value of pair {}", duplicate, first),
+            FromBytesError::DuplicateRight { first, duplicate } =>
+                write!(f, "the right value of pair {} equals the right value of pair {}", duplicate, first),
+        }
+    }
+}
+
+impl Error for FromBytesError {}
+
+impl<T, U, H, RH> BiMap<T, U, H, RH>
+    where T: Hash + Eq + Pod, U: Hash + Eq + Pod, H: BuildHasher, RH: BuildHasher
+{
+    /// The size of a pair, and the offset of the right value within the pair. Pairs with padding
+    /// between or after the values are rejected at compile time, since the padding bytes are not
+    /// initialized.
+    const PAIR_LAYOUT: (usize, usize) = {
+        assert!(
+            mem::size_of::<Bucket<T, U>>() == mem::size_of::<T>() + mem::size_of::<U>(),
+            "pairs with padding cannot be exposed as bytes, use values of the same alignment",
+        );
+        (mem::size_of::<Bucket<T, U>>(), mem::offset_of!(Bucket<T, U>, right))
+    };
+
+    /// Returns the pairs of the map as bytes, in iteration order, as they are laid out in memory.
+    /// Each pair takes `size_of::<T>() + size_of::<U>()` bytes, the left value followed by the right
+    /// value, in the native byte order. The bytes are therefore only portable between platforms of
+    /// the same endianness, and they do not record the types, so reading them as other types than
+    /// they were written with yields garbage pairs or fails on duplicates. The method is not
+    /// available with the `columnar` feature, which stores the values in two separate vectors.
+    ///
+    /// The values must not leave padding in their pair, e.g. `(u64, u64)` and `(u32, u32)` can be
+    /// exposed, while `(u64, u32)`, which is padded to 16 bytes, fails to compile.
+    pub fn as_bytes(&self) -> &[u8] {
+        let _ = Self::PAIR_LAYOUT;
+        let buckets = self.data.buckets();
+        // SAFETY: `Pod` values have no uninitialized bytes, and the pair has no padding either, as
+        // checked by `PAIR_LAYOUT`, so every byte of the pairs is initialized. The bytes borrow the
+        // pairs, which cannot be mutated while the slice lives.
+        unsafe { std::slice::from_raw_parts(buckets.as_ptr().cast::<u8>(), mem::size_of_val(buckets)) }
+    }
+
+    /// Create a map from bytes returned by [`as_bytes`], on a platform of the same endianness.
+    /// The bytes must be aligned like the pairs, and hold a whole number of pairs. All left values
+    /// and all right values must be distinct, otherwise the pairs of the first duplicate value
+    /// are returned in the error.
+    ///
+    /// [`as_bytes`]: #method.as_bytes
+    pub fn from_bytes_with_hashers(bytes: &[u8], hasher: H, reverse_hasher: RH) -> Result<Self, FromBytesError> {
+        let (pair_size, right_offset) = Self::PAIR_LAYOUT;
+        if bytes.as_ptr().align_offset(mem::align_of::<Bucket<T, U>>()) != 0 {
+            return Err(FromBytesError::Cast(PodCastError::TargetAlignmentGreaterAndInputNotAligned));
+        }
+        if pair_size == 0 || !bytes.len().is_multiple_of(pair_size) {
+            return Err(FromBytesError::Cast(PodCastError::OutputSliceWouldHaveSlop));
+        }
+
+        let mut map = Self::with_capacity_and_hashers(bytes.len() / pair_size, hasher, reverse_hasher);
+        for (pair, chunk) in bytes.chunks_exact(pair_size).enumerate() {
+            let left: T = bytemuck::pod_read_unaligned(&chunk[..mem::size_of::<T>()]);
+            let right: U = bytemuck::pod_read_unaligned(&chunk[right_offset..]);
+            if let Some(first) = map.find_bucket_left(&left) {
+                return Err(FromBytesError::DuplicateLeft { first, duplicate: pair });
+            }
+            if let Some(first) = map.find_bucket_right(&right) {
+                return Err(FromBytesError::DuplicateRight { first, duplicate: pair });
+            }
+            map.insert_unique_unchecked(left, right);
+        }
+        Ok(map)
+    }
+}
+
+impl<T, U> BiMap<T, U>
+    where T: Hash + Eq + Pod, U: Hash + Eq + Pod
+{
+    /// Create a map from bytes returned by [`as_bytes`], see [`from_bytes_with_hashers`].
+    ///
+    /// [`as_bytes`]: #method.as_bytes
+    /// [`from_bytes_with_hashers`]: #method.from_bytes_with_hashers
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FromBytesError> {
+        Self::from_bytes_with_hashers(bytes, Default::default(), Default::default())
+    }
+}
@@ -0,0 +1,53 @@
+//! The differences between two maps, which can be applied to one map to turn it into the other.
+
+use std::hash::{BuildHasher, Hash};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use allocator_api2::alloc::Allocator;
+use crate::BiMap;
+
+/// The changes that turn one map into another, see [`BiMap::diff`] and [`BiMap::apply`].
+///
+/// The changes are keyed by the left values: a pair whose left value is in both maps, but mapped to
+/// different right values, is a changed pair, even if its right value moved to another left value.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ChangeSet<T, U> {
+    /// The pairs of the old map whose left values are not in the new map.
+    pub removed: Vec<(T, U)>,
+    /// The pairs of the new map whose left values are not in the old map.
+    pub added: Vec<(T, U)>,
+    /// The pairs of the new map whose left values are in the old map, but mapped to a different
+    /// right value there.
+    pub changed: Vec<(T, U)>,
+}
+
+impl<T, U> ChangeSet<T, U>
+    where T: Hash + Eq + Clone, U: Hash + Eq + Clone
+{
+    pub(crate) fn between<H, RH, A, H2, RH2, A2>(old: &BiMap<T, U, H, RH, A>, new: &BiMap<T, U, H2, RH2, A2>) -> Self
+        where H: BuildHasher, RH: BuildHasher, A: Allocator + Clone, H2: BuildHasher, RH2: BuildHasher, A2: Allocator + Clone
+    {
+        let mut changes = ChangeSet { removed: Vec::new(), added: Vec::new(), changed: Vec::new() };
+        for (left, right) in old.iter() {
+            match new.get_right(left) {
+                None => changes.removed.push((left.clone(), right.clone())),
+                Some(new_right) if new_right != right => changes.changed.push((left.clone(), new_right.clone())),
+                Some(_) => {}
+            }
+        }
+        for (left, right) in new.iter() {
+            if !old.contains_left(left) {
+                changes.added.push((left.clone(), right.clone()));
+            }
+        }
+        changes
+    }
+}
+
+impl<T, U> ChangeSet<T, U> {
+    /// Returns true if the change set does not change a map.
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty() && self.changed.is_empty()
+    }
+}
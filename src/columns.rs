@@ -0,0 +1,33 @@
+//! The errors of building a map from two parallel columns of values.
+
+use std::error::Error;
+use std::fmt;
+
+/// An error of [`BiMap::from_columns`](crate::BiMap::from_columns) and
+/// [`BiMap::from_columns_last_wins`](crate::BiMap::from_columns_last_wins). Rows are numbered by
+/// their position in the columns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FromColumnsError {
+    /// The columns have different lengths.
+    LengthMismatch { lefts: usize, rights: usize },
+    /// The left value of row `duplicate` equals the left value of the earlier row `first`.
+    DuplicateLeft { first: usize, duplicate: usize },
+    /// The right value of row `duplicate` equals the right value of the earlier row `first`.
+    DuplicateRight { first: usize, duplicate: usize },
+}
+
+impl fmt::Display for FromColumnsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            FromColumnsError::LengthMismatch { lefts, rights } =>
+                write!(f, "the left column has {} values, but the right column has {}", lefts, rights),
+            FromColumnsError::DuplicateLeft { first, duplicate } =>
+                write!(f, "the left value of row {} equals the left value of row {}", duplicate, first),
+            FromColumnsError::DuplicateRight { first, duplicate } =>
+                write!(f, "the right value of row {} equals the right value of row {}", duplicate, first),
+        }
+    }
+}
+
+impl Error for FromColumnsError {}
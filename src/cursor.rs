@@ -0,0 +1,79 @@
+//! A cursor over the pairs of a map, which can remove and replace pairs while walking the map.
+
+use std::hash::{BuildHasher, Hash, RandomState};
+use std::mem;
+use allocator_api2::alloc::{Allocator, Global};
+use crate::{BiMap, RightMut};
+
+/// A cursor over the pairs of a map, returned by [`BiMap::cursor_mut`].
+///
+/// The cursor walks the pairs in the same order as [`BiMap::iter`]. Each call to
+/// [`next`](CursorMut::next) moves it to the next pair, which becomes the current pair until the
+/// cursor moves again or the pair is removed. Removing the current pair moves the last pair of the
+/// map into its position, and the cursor visits that pair next, so every pair is visited exactly
+/// once.
+#[derive(Debug)]
+pub struct CursorMut<'a, T, U, H = RandomState, RH = RandomState, A: Allocator + Clone = Global>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    map: &'a mut BiMap<T, U, H, RH, A>,
+    /// The bucket of the current pair, if the cursor points at a pair.
+    current: Option<usize>,
+    /// The bucket of the next pair to visit.
+    position: usize,
+}
+
+impl<'a, T, U, H, RH, A: Allocator + Clone> CursorMut<'a, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    pub(crate) fn new(map: &'a mut BiMap<T, U, H, RH, A>) -> Self {
+        CursorMut { map, current: None, position: 0 }
+    }
+
+    /// Move the cursor to the next pair and return it, or return None if all pairs were visited.
+    // the returned pair borrows from the cursor, so the cursor cannot implement `Iterator`
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<(&T, &U)> {
+        if self.position >= self.map.len() {
+            self.current = None;
+            return None;
+        }
+
+        self.current = Some(self.position);
+        self.position += 1;
+        Some((self.map.data.left(self.position - 1), self.map.data.right(self.position - 1)))
+    }
+
+    /// Remove the current pair from the map and return it. The cursor has no current pair
+    /// afterwards, until [`next`](CursorMut::next) moves it to the pair that took the position of
+    /// the removed pair. Returns None if the cursor has no current pair.
+    pub fn remove_current(&mut self) -> Option<(T, U)> {
+        let bucket_index = self.current.take()?;
+        let bucket = self.map.remove_bucket(bucket_index);
+        self.map.rebuild_filter_if_stale();
+
+        // the last pair moved into the position of the removed pair and has not been visited yet
+        self.position = bucket_index;
+        Some((bucket.left, bucket.right))
+    }
+
+    /// Replace the right value of the current pair and return the old right value. The pair stays
+    /// at its position, so the cursor does not visit it again.
+    ///
+    /// # Panics
+    /// Panics if the cursor has no current pair, or if the new right value is mapped to another left
+    /// value already. The map is not modified in either case.
+    pub fn replace_right_current(&mut self, right: U) -> U {
+        let bucket_index = self.current.expect("the cursor has no current pair");
+        if let Some(other) = self.map.find_bucket_right(&right) {
+            assert_eq!(other, bucket_index, "the right value is mapped to another left value already");
+        }
+
+        let mut current = RightMut::new(self.map, bucket_index);
+        let replaced = mem::replace(&mut *current, right);
+        if current.commit().is_err() {
+            unreachable!("the right value was checked not to collide");
+        }
+        replaced
+    }
+}
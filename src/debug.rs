@@ -0,0 +1,105 @@
+//! Formatting maps for debugging: the `Debug` implementation of [`BiMap`], which prints the pairs,
+//! and a dump of the slot layout of the hash indices, for debugging hashers and collisions. The
+//! tables of the `hashbrown-backend` feature do not expose their layout, so they cannot be dumped.
+
+use std::fmt;
+#[cfg(not(feature = "hashbrown-backend"))]
+use std::hash::{BuildHasher, Hash};
+use allocator_api2::alloc::Allocator;
+use crate::BiMap;
+#[cfg(not(feature = "hashbrown-backend"))]
+use crate::SlotIndex;
+
+/// Formats the pairs of the map in iteration order, as in `{1 <-> "a", 2 <-> "b"}`. The indices and
+/// the hashers are not printed.
+impl<T: fmt::Debug, U: fmt::Debug, H, RH, A: Allocator + Clone> fmt::Debug for BiMap<T, U, H, RH, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // the layout of the indices is printed by `debug_table` instead
+        f.debug_set().entries(self.iter().map(|(left, right)| Mapping(left, right))).finish()
+    }
+}
+
+/// A pair of a map, formatted as `left <-> right`.
+struct Mapping<'a, T, U>(&'a T, &'a U);
+
+impl<T: fmt::Debug, U: fmt::Debug> fmt::Debug for Mapping<'_, T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)?;
+        f.write_str(" <-> ")?;
+        self.1.fmt(f)
+    }
+}
+
+/// An occupied slot of a hash index.
+#[cfg(not(feature = "hashbrown-backend"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct SlotDump {
+    bucket: usize,
+    ideal: usize,
+    distance: usize,
+}
+
+/// The slot layout of both hash indices of a map, see [`BiMap::debug_table`]. It is printed with
+/// one line per slot, or as a one-line occupancy string with [`occupancy`](TableDump::occupancy).
+#[cfg(not(feature = "hashbrown-backend"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableDump {
+    left: Vec<Option<SlotDump>>,
+    right: Vec<Option<SlotDump>>,
+    len: usize,
+}
+
+#[cfg(not(feature = "hashbrown-backend"))]
+impl TableDump {
+    pub(crate) fn new<T, U, H, RH, A>(map: &BiMap<T, U, H, RH, A>) -> Self
+        where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher, A: Allocator + Clone
+    {
+        let capacity = map.current_capacity();
+        TableDump {
+            left: Self::dump_slots(&map.left.slots, capacity, |bucket| map.left.ideal_index(map.data.left(bucket))),
+            right: Self::dump_slots(&map.right.slots, capacity, |bucket| map.right.ideal_index(map.data.right(bucket))),
+            len: map.len(),
+        }
+    }
+
+    /// Dump the slots of one index, using `ideal_index` to compute the ideal index of a bucket's value.
+    fn dump_slots(slots: &[SlotIndex], capacity: usize, ideal_index: impl Fn(usize) -> usize) -> Vec<Option<SlotDump>> {
+        slots.iter()
+            .enumerate()
+            .map(|(index, slot)| slot.bucket().map(|bucket| {
+                let ideal = ideal_index(bucket);
+                let distance = if index >= ideal { index - ideal } else { index + capacity - ideal };
+                SlotDump { bucket, ideal, distance }
+            }))
+            .collect()
+    }
+
+    /// Render the occupancy of both indices in one line, with `#` for occupied and `.` for empty
+    /// slots.
+    pub fn occupancy(&self) -> String {
+        let render = |slots: &[Option<SlotDump>]| slots.iter()
+            .map(|slot| if slot.is_some() { '#' } else { '.' })
+            .collect::<String>();
+        format!("left {} right {}", render(&self.left), render(&self.right))
+    }
+}
+
+#[cfg(not(feature = "hashbrown-backend"))]
+impl fmt::Display for TableDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.left.is_empty() {
+            return write!(f, "small map of length {}", self.len);
+        }
+
+        for (name, slots) in [("left", &self.left), ("right", &self.right)] {
+            writeln!(f, "{} index:", name)?;
+            for (index, slot) in slots.iter().enumerate() {
+                match slot {
+                    Some(slot) => writeln!(f, "  slot {} -> bucket {} (ideal {}, dist {})", index, slot.bucket, slot.ideal, slot.distance)?,
+                    None => writeln!(f, "  slot {} empty", index)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
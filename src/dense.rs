@@ -0,0 +1,244 @@
+//! A bi-directional map for right values that are dense small integers, such as interned ids.
+//!
+//! The right index of a [`DenseBiMap`] is kept at least as large as the bound of the right values,
+//! and right values are hashed to themselves. Every right value below the bound therefore owns the
+//! slot with its own number, and `get_left` and `remove_right` read that slot directly instead of
+//! hashing and probing. Values at or above the bound are still supported, they are hashed into the
+//! index like in any other map and fall back to probing. With the `hashbrown-backend` feature, the
+//! entries of the index cannot be read by their position, so all right values are looked up in the
+//! index like in any other map.
+
+use std::hash::{BuildHasher, Hash, Hasher, RandomState};
+use crate::BiMap;
+
+/// Unsigned integers that can be used as the right values of a [`DenseBiMap`].
+pub trait DenseKey: Copy + Hash + Eq {
+    /// The slot of the value in the right index.
+    fn to_index(self) -> usize;
+}
+
+macro_rules! impl_dense_key {
+    ($($ty:ty),*) => {
+        $(
+            impl DenseKey for $ty {
+                #[inline(always)]
+                fn to_index(self) -> usize {
+                    self as usize
+                }
+            }
+        )*
+    };
+}
+
+impl_dense_key!(u8, u16, u32, u64, usize);
+
+/// Hashes unsigned integers to their own value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DenseHasher;
+
+/// The hasher built by [`DenseHasher`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DenseHasherState {
+    value: u64,
+}
+
+impl Hasher for DenseHasherState {
+    fn finish(&self) -> u64 {
+        self.value
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes.iter().rev() {
+            self.value = (self.value << 8) | byte as u64;
+        }
+    }
+
+    fn write_u8(&mut self, value: u8) {
+        self.value = value as u64;
+    }
+
+    fn write_u16(&mut self, value: u16) {
+        self.value = value as u64;
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.value = value as u64;
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.value = value;
+    }
+
+    fn write_usize(&mut self, value: usize) {
+        self.value = value as u64;
+    }
+}
+
+impl BuildHasher for DenseHasher {
+    type Hasher = DenseHasherState;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        DenseHasherState::default()
+    }
+}
+
+/// A bi-directional map whose right values are dense unsigned integers below a bound. See the
+/// [module documentation](self) for how it differs from [`BiMap`].
+#[derive(Clone, Debug)]
+pub struct DenseBiMap<T, U, H = RandomState>
+    where T: Hash + Eq, U: DenseKey
+{
+    map: BiMap<T, U, H, DenseHasher>,
+    bound: usize,
+}
+
+/// Two maps are equal if they have the same bound and contain the same pairs, like [`BiMap`]s.
+impl<T, U, H> PartialEq for DenseBiMap<T, U, H>
+    where T: Hash + Eq, U: DenseKey, H: BuildHasher
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.bound == other.bound && self.map == other.map
+    }
+}
+
+impl<T, U, H> Eq for DenseBiMap<T, U, H>
+    where T: Hash + Eq, U: DenseKey, H: BuildHasher
+{}
+
+impl<T, U> DenseBiMap<T, U>
+    where T: Hash + Eq, U: DenseKey
+{
+    /// Create a new empty map for right values below `bound`.
+    pub fn new(bound: usize) -> Self {
+        Self::with_hasher(bound, RandomState::default())
+    }
+}
+
+impl<T, U, H> DenseBiMap<T, U, H>
+    where T: Hash + Eq, U: DenseKey, H: BuildHasher
+{
+    /// Create a new empty map for right values below `bound`, using the given hasher for the left
+    /// values.
+    pub fn with_hasher(bound: usize, hasher: H) -> Self {
+        // maps below the small-map capacity would not allocate indices at all
+        let capacity = BiMap::<T, U, H, DenseHasher>::apply_load_factor(bound.max(crate::SMALL_MAP_CAPACITY));
+        DenseBiMap { map: BiMap::with_hashers(capacity, hasher, DenseHasher), bound }
+    }
+
+    /// The bound below which right values are looked up directly.
+    pub fn bound(&self) -> usize {
+        self.bound
+    }
+
+    /// Find the bucket of a right value by reading its own slot. Returns `None` if the value is not
+    /// below the bound, or if it was displaced by a value above the bound, in which case the
+    /// general lookup has to be used.
+    #[cfg(not(feature = "hashbrown-backend"))]
+    #[inline(always)]
+    pub(crate) fn find_bucket_direct(&self, right: &U) -> Option<Option<usize>> {
+        let slot = right.to_index();
+        if slot >= self.bound {
+            return None;
+        }
+
+        match self.map.right.slots[slot].bucket() {
+            None => Some(None),
+            Some(bucket) if self.map.data.right(bucket) == right => Some(Some(bucket)),
+            Some(_) => None,
+        }
+    }
+
+    /// Find the bucket of a right value by reading its own slot, which tables do not support, so the
+    /// general lookup always has to be used.
+    #[cfg(feature = "hashbrown-backend")]
+    #[inline(always)]
+    pub(crate) fn find_bucket_direct(&self, _right: &U) -> Option<Option<usize>> {
+        None
+    }
+
+    /// Find the bucket that holds the given right value.
+    #[inline(always)]
+    fn find_bucket_right(&self, right: &U) -> Option<usize> {
+        match self.find_bucket_direct(right) {
+            Some(bucket) => bucket,
+            None => self.map.find_bucket_right(right),
+        }
+    }
+
+    /// Get the right value for the given left value. If the left value is not in the map, None is
+    /// returned.
+    #[must_use]
+    pub fn get_right(&self, left: &T) -> Option<&U> {
+        self.map.get_right(left)
+    }
+
+    /// Get the left value for the given right value. If the right value is not in the map, None is
+    /// returned.
+    #[must_use]
+    pub fn get_left(&self, right: &U) -> Option<&T> {
+        self.find_bucket_right(right).map(|bucket| self.map.data.left(bucket))
+    }
+
+    /// Check if the map contains a mapping for the given left value.
+    #[must_use]
+    pub fn contains_left(&self, left: &T) -> bool {
+        self.map.contains_left(left)
+    }
+
+    /// Check if the map contains a mapping for the given right value.
+    #[must_use]
+    pub fn contains_right(&self, right: &U) -> bool {
+        self.find_bucket_right(right).is_some()
+    }
+
+    /// Inserts a value pair into the map. See [`BiMap::insert`].
+    pub fn insert(&mut self, left: T, right: U) -> (Option<U>, Option<T>) {
+        self.map.insert(left, right)
+    }
+
+    /// Deletes the mappings for the given left value and returns the right value that was mapped to it.
+    /// If the left value is not in the map, None is returned.
+    pub fn remove_left(&mut self, left: &T) -> Option<U> {
+        self.map.remove_left(left)
+    }
+
+    /// Deletes the mappings for the given right value and returns the left value that was mapped to it.
+    /// If the right value is not in the map, None is returned.
+    pub fn remove_right(&mut self, right: &U) -> Option<T> {
+        let bucket = self.find_bucket_right(right)?;
+        let left = self.map.remove_bucket(bucket).left;
+        self.map.rebuild_filter_if_stale();
+        Some(left)
+    }
+
+    /// Clears the map, removing all mappings. Keeps the allocated memory for reuse.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Returns an iterator over the mappings in the map in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item=(&T, &U)> {
+        self.map.iter()
+    }
+
+    /// Returns the number of bijections stored in the map.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the underlying map, which provides the remaining read-only operations.
+    pub fn as_bimap(&self) -> &BiMap<T, U, H, DenseHasher> {
+        &self.map
+    }
+
+    /// Returns the underlying map mutably, which provides the hooks and the journal. The map keeps
+    /// the dense hasher, so pairs it inserts or removes are found directly as well.
+    pub fn as_bimap_mut(&mut self) -> &mut BiMap<T, U, H, DenseHasher> {
+        &mut self.map
+    }
+}
@@ -0,0 +1,228 @@
+//! The errors of the fallible operations of a map.
+//!
+//! Errors of operations that reject values own the rejected values, so callers can recover them
+//! with the public fields or the `into_` methods of the error instead of cloning them beforehand.
+//! All errors implement [`Error`], which is the same trait as `core::error::Error`, so they can be
+//! boxed into a `Box<dyn Error>` and downcast again.
+
+use std::alloc::Layout;
+use std::error::Error;
+use std::fmt;
+use allocator_api2::collections::{self, TryReserveErrorKind};
+
+/// The error of [`BiMap::try_insert`](crate::BiMap::try_insert), if at least one value of the pair
+/// is in the map already. The map is left unchanged, and the rejected pair is returned in the
+/// error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct OccupiedError<T, U> {
+    /// The rejected left value.
+    pub left: T,
+    /// The rejected right value.
+    pub right: U,
+    /// Whether the left value is in the map already.
+    pub left_occupied: bool,
+    /// Whether the right value is in the map already.
+    pub right_occupied: bool,
+}
+
+impl<T, U> OccupiedError<T, U> {
+    /// Returns the rejected pair.
+    pub fn into_pair(self) -> (T, U) {
+        (self.left, self.right)
+    }
+}
+
+impl<T: fmt::Debug, U: fmt::Debug> fmt::Display for OccupiedError<T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.left_occupied, self.right_occupied) {
+            (true, true) =>
+                write!(f, "the left value {:?} and the right value {:?} are already in the map", self.left, self.right),
+            (true, false) => write!(f, "the left value {:?} is already in the map", self.left),
+            _ => write!(f, "the right value {:?} is already in the map", self.right),
+        }
+    }
+}
+
+impl<T: fmt::Debug, U: fmt::Debug> Error for OccupiedError<T, U> {}
+
+/// The error of [`BiMap::try_reserve`](crate::BiMap::try_reserve). The map is left unchanged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TryReserveError {
+    /// The required capacity exceeds the maximum capacity of the map.
+    CapacityOverflow,
+    /// The allocator of the map failed to provide the memory, which reports the layout of the
+    /// failed allocation.
+    AllocError(Layout),
+}
+
+impl From<collections::TryReserveError> for TryReserveError {
+    fn from(error: collections::TryReserveError) -> Self {
+        match error.kind() {
+            TryReserveErrorKind::CapacityOverflow => TryReserveError::CapacityOverflow,
+            TryReserveErrorKind::AllocError { layout, .. } => TryReserveError::AllocError(layout),
+        }
+    }
+}
+
+#[cfg(feature = "hashbrown-backend")]
+impl From<hashbrown::TryReserveError> for TryReserveError {
+    fn from(error: hashbrown::TryReserveError) -> Self {
+        match error {
+            hashbrown::TryReserveError::CapacityOverflow => TryReserveError::CapacityOverflow,
+            hashbrown::TryReserveError::AllocError { layout } => TryReserveError::AllocError(layout),
+        }
+    }
+}
+
+impl fmt::Display for TryReserveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            TryReserveError::CapacityOverflow => write!(f, "the required capacity exceeds the maximum capacity of the map"),
+            TryReserveError::AllocError(_) => write!(f, "the memory allocation of the map failed"),
+        }
+    }
+}
+
+impl Error for TryReserveError {}
+
+/// The error of [`BiMap::try_extend`](crate::BiMap::try_extend), if a pair shares a value with a
+/// pair of the map, including the pairs that were inserted before it. The rejected pair is
+/// returned in the error, together with its position in the input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DuplicatePairError<T, U> {
+    /// The rejected left value.
+    pub left: T,
+    /// The rejected right value.
+    pub right: U,
+    /// The position of the rejected pair in the input.
+    pub position: usize,
+    /// Whether the left value was inserted already.
+    pub left_duplicate: bool,
+    /// Whether the right value was inserted already.
+    pub right_duplicate: bool,
+}
+
+impl<T, U> DuplicatePairError<T, U> {
+    /// Returns the rejected pair.
+    pub fn into_pair(self) -> (T, U) {
+        (self.left, self.right)
+    }
+}
+
+impl<T: fmt::Debug, U: fmt::Debug> fmt::Display for DuplicatePairError<T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.left_duplicate, self.right_duplicate) {
+            (true, true) => write!(f, "both values of pair {} ({:?}, {:?}) are duplicates", self.position, self.left, self.right),
+            (true, false) => write!(f, "the left value {:?} of pair {} is a duplicate", self.left, self.position),
+            _ => write!(f, "the right value {:?} of pair {} is a duplicate", self.right, self.position),
+        }
+    }
+}
+
+impl<T: fmt::Debug, U: fmt::Debug> Error for DuplicatePairError<T, U> {}
+
+/// The error of [`BiMap::rekey_left`](crate::BiMap::rekey_left) and
+/// [`BiMap::rekey_right`](crate::BiMap::rekey_right). The map is left unchanged, and the rejected
+/// new value is returned in the error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RekeyError<T> {
+    /// The value to replace is not in the map.
+    NotFound(T),
+    /// The new value is in the map already, in another pair.
+    Occupied(T),
+}
+
+impl<T> RekeyError<T> {
+    /// Returns the rejected new value.
+    pub fn into_value(self) -> T {
+        match self {
+            RekeyError::NotFound(value) | RekeyError::Occupied(value) => value,
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Display for RekeyError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            RekeyError::NotFound(ref value) => write!(f, "the value to replace with {:?} is not in the map", value),
+            RekeyError::Occupied(ref value) => write!(f, "the new value {:?} is already in the map", value),
+        }
+    }
+}
+
+impl<T: fmt::Debug> Error for RekeyError<T> {}
+
+/// The error of [`BiMap::extend_with_policy`](crate::BiMap::extend_with_policy), if a pair shares
+/// a value with a pair of the map on a side whose policy is [`DupPolicy::Error`]. The rejected pair
+/// is returned in the error, together with its position in the input.
+///
+/// [`DupPolicy::Error`]: crate::DupPolicy::Error
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PolicyViolation<T, U> {
+    /// The rejected left value.
+    pub left: T,
+    /// The rejected right value.
+    pub right: U,
+    /// The position of the rejected pair in the input.
+    pub position: usize,
+    /// Whether the left value is a duplicate whose policy is [`DupPolicy::Error`].
+    ///
+    /// [`DupPolicy::Error`]: crate::DupPolicy::Error
+    pub left_violated: bool,
+    /// Whether the right value is a duplicate whose policy is [`DupPolicy::Error`].
+    ///
+    /// [`DupPolicy::Error`]: crate::DupPolicy::Error
+    pub right_violated: bool,
+}
+
+impl<T, U> PolicyViolation<T, U> {
+    /// Returns the rejected pair.
+    pub fn into_pair(self) -> (T, U) {
+        (self.left, self.right)
+    }
+}
+
+impl<T: fmt::Debug, U: fmt::Debug> fmt::Display for PolicyViolation<T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.left_violated, self.right_violated) {
+            (true, true) => write!(f, "both values of pair {} ({:?}, {:?}) are rejected duplicates", self.position, self.left, self.right),
+            (true, false) => write!(f, "the left value {:?} of pair {} is a rejected duplicate", self.left, self.position),
+            _ => write!(f, "the right value {:?} of pair {} is a rejected duplicate", self.right, self.position),
+        }
+    }
+}
+
+impl<T: fmt::Debug, U: fmt::Debug> Error for PolicyViolation<T, U> {}
+
+/// The error of the conversion of a `HashMap` into a [`BiMap`](crate::BiMap) with `TryFrom`, if
+/// some right values of the `HashMap` belong to more than one left value. The colliding entries are
+/// returned in the error, grouped by their right value, while the other entries are dropped.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NonInjectiveError<T, U> {
+    /// The right values with more than one left value, in arbitrary order.
+    pub collisions: Vec<ValueCollision<T, U>>,
+}
+
+/// A right value of a [`NonInjectiveError`] and the left values that map to it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ValueCollision<T, U> {
+    /// The right value that more than one left value maps to.
+    pub right: U,
+    /// The left values that map to the right value, at least two, in arbitrary order.
+    pub lefts: Vec<T>,
+}
+
+impl<T: fmt::Debug, U: fmt::Debug> fmt::Display for NonInjectiveError<T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ValueCollision { right, lefts } = &self.collisions[0];
+        write!(f, "the right value {:?} belongs to the left values {:?}", right, lefts)?;
+        if self.collisions.len() > 1 {
+            write!(f, ", and {} more right values belong to several left values", self.collisions.len() - 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: fmt::Debug, U: fmt::Debug> Error for NonInjectiveError<T, U> {}
@@ -0,0 +1,237 @@
+//! A bi-directional map whose pairs expire after a time to live.
+//!
+//! An [`ExpiringBiMap`] stores a deadline next to every bucket, indexed by bucket like the links of
+//! the [`LruBiMap`](crate::lru::LruBiMap). Expired pairs are treated as absent by all lookups, but
+//! stay in the map until they are replaced, removed, or purged with
+//! [`purge_expired`](ExpiringBiMap::purge_expired). The map reads the current time from a clock
+//! function, which defaults to [`Instant::now`] and can be replaced to make tests deterministic.
+
+use std::hash::{BuildHasher, Hash, RandomState};
+use std::time::{Duration, Instant};
+use crate::BiMap;
+
+/// A bi-directional map whose pairs can expire. See the [module documentation](self) for when
+/// expired pairs are removed.
+///
+/// Pairs inserted with [`insert_with_ttl`](ExpiringBiMap::insert_with_ttl) expire once the clock
+/// reaches their deadline, while pairs inserted with [`insert`](ExpiringBiMap::insert) never
+/// expire. Since expired pairs are only removed on demand, [`len`](ExpiringBiMap::len) counts them
+/// until they are purged.
+#[derive(Clone, Debug)]
+pub struct ExpiringBiMap<T, U, H = RandomState, RH = RandomState>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    map: BiMap<T, U, H, RH>,
+    /// The deadlines of the buckets, indexed like the buckets. Pairs without a deadline never expire.
+    deadlines: Vec<Option<Instant>>,
+    clock: fn() -> Instant,
+}
+
+impl<T, U> ExpiringBiMap<T, U>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    /// Create a new empty map that reads the time from [`Instant::now`].
+    pub fn new() -> Self {
+        Self::with_hashers(0, RandomState::default(), RandomState::default())
+    }
+}
+
+impl<T, U> Default for ExpiringBiMap<T, U>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, U, H, RH> ExpiringBiMap<T, U, H, RH>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    /// Create a new empty map with the given capacity and hashers, which reads the time from
+    /// [`Instant::now`].
+    pub fn with_hashers(capacity: usize, hasher: H, reverse_hasher: RH) -> Self {
+        ExpiringBiMap {
+            map: BiMap::with_hashers(capacity, hasher, reverse_hasher),
+            deadlines: Vec::with_capacity(capacity),
+            clock: Instant::now,
+        }
+    }
+
+    /// Replace the clock that the map reads the current time from. The deadlines of the pairs in
+    /// the map are kept, so the new clock should be monotonic with the old one.
+    pub fn set_clock(&mut self, clock: fn() -> Instant) {
+        self.clock = clock;
+    }
+
+    /// Check if the bucket has a deadline that is not after `now`.
+    fn is_expired(&self, bucket_index: usize, now: Instant) -> bool {
+        self.deadlines[bucket_index].is_some_and(|deadline| deadline <= now)
+    }
+
+    /// Find the bucket of a left value, unless its pair has expired.
+    fn find_live_left(&self, left: &T) -> Option<usize> {
+        let bucket_index = self.map.find_bucket_left(left)?;
+        (!self.is_expired(bucket_index, (self.clock)())).then_some(bucket_index)
+    }
+
+    /// Find the bucket of a right value, unless its pair has expired.
+    fn find_live_right(&self, right: &U) -> Option<usize> {
+        let bucket_index = self.map.find_bucket_right(right)?;
+        (!self.is_expired(bucket_index, (self.clock)())).then_some(bucket_index)
+    }
+
+    /// Remove a bucket from the map together with its deadline. The last bucket was moved into its
+    /// position, so its deadline is moved as well.
+    fn remove_bucket(&mut self, bucket_index: usize) -> (T, U, Option<Instant>) {
+        let bucket = self.map.remove_bucket(bucket_index);
+        self.map.rebuild_filter_if_stale();
+        let deadline = self.deadlines.swap_remove(bucket_index);
+        (bucket.left, bucket.right, deadline)
+    }
+
+    /// Get the right value for the given left value. If the left value is not in the map or its
+    /// pair has expired, None is returned.
+    #[must_use]
+    pub fn get_right(&self, left: &T) -> Option<&U> {
+        let bucket_index = self.find_live_left(left)?;
+        Some(self.map.data.right(bucket_index))
+    }
+
+    /// Get the left value for the given right value. If the right value is not in the map or its
+    /// pair has expired, None is returned.
+    #[must_use]
+    pub fn get_left(&self, right: &U) -> Option<&T> {
+        let bucket_index = self.find_live_right(right)?;
+        Some(self.map.data.left(bucket_index))
+    }
+
+    /// Check if the map contains an unexpired mapping for the given left value.
+    #[must_use]
+    pub fn contains_left(&self, left: &T) -> bool {
+        self.find_live_left(left).is_some()
+    }
+
+    /// Check if the map contains an unexpired mapping for the given right value.
+    #[must_use]
+    pub fn contains_right(&self, right: &U) -> bool {
+        self.find_live_right(right).is_some()
+    }
+
+    /// Get the deadline of the pair of the given left value. If the left value is not in the map,
+    /// its pair has expired, or its pair never expires, None is returned.
+    #[must_use]
+    pub fn deadline_left(&self, left: &T) -> Option<Instant> {
+        self.deadlines[self.find_live_left(left)?]
+    }
+
+    /// Get the deadline of the pair of the given right value. See [`deadline_left`].
+    ///
+    /// [`deadline_left`]: ExpiringBiMap::deadline_left
+    #[must_use]
+    pub fn deadline_right(&self, right: &U) -> Option<Instant> {
+        self.deadlines[self.find_live_right(right)?]
+    }
+
+    /// Inserts a value pair that never expires. Like [`BiMap::insert`], the pairs that either value
+    /// was mapped to are removed, and the replaced values are returned, except for those of expired
+    /// pairs.
+    pub fn insert(&mut self, left: T, right: U) -> (Option<U>, Option<T>) {
+        self.insert_with_deadline(left, right, None)
+    }
+
+    /// Inserts a value pair that expires after the given time to live, measured from the current
+    /// time of the clock. See [`insert`](ExpiringBiMap::insert) for the pairs that are replaced.
+    /// A time to live too long to be represented never expires.
+    pub fn insert_with_ttl(&mut self, left: T, right: U, ttl: Duration) -> (Option<U>, Option<T>) {
+        let deadline = (self.clock)().checked_add(ttl);
+        self.insert_with_deadline(left, right, deadline)
+    }
+
+    /// Inserts a value pair that expires at the given deadline, or never if the deadline is None.
+    /// See [`insert`](ExpiringBiMap::insert) for the pairs that are replaced.
+    pub fn insert_with_deadline(&mut self, left: T, right: U, deadline: Option<Instant>) -> (Option<U>, Option<T>) {
+        let now = (self.clock)();
+
+        // removing the pair of the left value moves the last bucket, so the right value is looked
+        // up afterwards
+        let mut replaced_right = None;
+        if let Some(bucket_index) = self.map.find_bucket_left(&left) {
+            let expired = self.is_expired(bucket_index, now);
+            let (_, right, _) = self.remove_bucket(bucket_index);
+            replaced_right = (!expired).then_some(right);
+        }
+        let mut replaced_left = None;
+        if let Some(bucket_index) = self.map.find_bucket_right(&right) {
+            let expired = self.is_expired(bucket_index, now);
+            let (left, _, _) = self.remove_bucket(bucket_index);
+            replaced_left = (!expired).then_some(left);
+        }
+
+        self.map.insert_unique_unchecked(left, right);
+        self.deadlines.push(deadline);
+        (replaced_right, replaced_left)
+    }
+
+    /// Deletes the mapping for the given left value and returns the right value that was mapped to
+    /// it. An expired pair is deleted as well, but None is returned for it.
+    pub fn remove_left(&mut self, left: &T) -> Option<U> {
+        let bucket_index = self.map.find_bucket_left(left)?;
+        let expired = self.is_expired(bucket_index, (self.clock)());
+        let (_, right, _) = self.remove_bucket(bucket_index);
+        (!expired).then_some(right)
+    }
+
+    /// Deletes the mapping for the given right value and returns the left value that was mapped to
+    /// it. An expired pair is deleted as well, but None is returned for it.
+    pub fn remove_right(&mut self, right: &U) -> Option<T> {
+        let bucket_index = self.map.find_bucket_right(right)?;
+        let expired = self.is_expired(bucket_index, (self.clock)());
+        let (left, _, _) = self.remove_bucket(bucket_index);
+        (!expired).then_some(left)
+    }
+
+    /// Remove all pairs whose deadline is not after `now`, and return how many were removed. The
+    /// time is passed explicitly, so that purging can be scheduled independently of the clock.
+    pub fn purge_expired(&mut self, now: Instant) -> usize {
+        let len = self.len();
+        // removing a bucket moves the last bucket into its position, which was visited already
+        for bucket_index in (0..len).rev() {
+            if self.is_expired(bucket_index, now) {
+                self.remove_bucket(bucket_index);
+            }
+        }
+        len - self.len()
+    }
+
+    /// Clears the map, removing all mappings. Keeps the allocated memory for reuse.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.deadlines.clear();
+    }
+
+    /// Returns an iterator over the unexpired mappings in the map in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item=(&T, &U)> {
+        let now = (self.clock)();
+        self.map.iter()
+            .zip(&self.deadlines)
+            .filter(move |(_, deadline)| deadline.is_none_or(|deadline| deadline > now))
+            .map(|(pair, _)| pair)
+    }
+
+    /// Returns the number of pairs stored in the map, including expired pairs that have not been
+    /// purged yet.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the map holds no pairs, expired or not.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the underlying map, which provides the remaining read-only operations. It includes
+    /// the expired pairs that have not been purged yet.
+    pub fn as_bimap(&self) -> &BiMap<T, U, H, RH> {
+        &self.map
+    }
+}
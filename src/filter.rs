@@ -0,0 +1,83 @@
+//! A filter that answers most lookups of absent values without probing the hash indices.
+
+use std::mem;
+
+/// A blocked Bloom filter over the hashes of a map's values. Each hash sets three bits within a
+/// single 64-bit word, so testing a hash touches one word only. The filter never reports a false
+/// negative, but values that were removed from the map keep their bits until the filter is rebuilt.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct BloomFilter {
+    /// The words of the filter. Their number is a power of two.
+    words: Box<[u64]>,
+}
+
+impl BloomFilter {
+    /// The number of filter bits per slot of the hash index.
+    const BITS_PER_SLOT: usize = 16;
+
+    /// Create an empty filter for a hash index of the given capacity.
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        let words = (capacity * Self::BITS_PER_SLOT / u64::BITS as usize).next_power_of_two();
+        BloomFilter { words: vec![0; words].into_boxed_slice() }
+    }
+
+    /// Compute the word and the bits within the word that represent the given hash. The hash is
+    /// mixed first, because the index of the value in the hash index is derived from its lower bits.
+    #[inline(always)]
+    fn position(&self, hash: u64) -> (usize, u64) {
+        let mixed = hash.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        let word = (mixed >> 40) as usize & (self.words.len() - 1);
+        let bits = (1 << (mixed & 63)) | (1 << ((mixed >> 6) & 63)) | (1 << ((mixed >> 12) & 63));
+        (word, bits)
+    }
+
+    /// Record a hash in the filter.
+    #[inline(always)]
+    pub(crate) fn insert(&mut self, hash: u64) {
+        let (word, bits) = self.position(hash);
+        self.words[word] |= bits;
+    }
+
+    /// Returns false if no value with the given hash was recorded since the filter was last cleared.
+    #[inline(always)]
+    pub(crate) fn may_contain(&self, hash: u64) -> bool {
+        let (word, bits) = self.position(hash);
+        self.words[word] & bits == bits
+    }
+
+    /// Remove all recorded hashes.
+    pub(crate) fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    /// The number of heap bytes allocated for the filter.
+    pub(crate) fn allocated_bytes(&self) -> usize {
+        self.words.len() * mem::size_of::<u64>()
+    }
+}
+
+/// The filters for the left and right values of a map.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct NegativeFilter {
+    pub(crate) left: BloomFilter,
+    pub(crate) right: BloomFilter,
+    /// An upper bound of the values that were removed from the map since the filters were rebuilt.
+    /// Their bits stay in the filters and only cause false positives.
+    pub(crate) stale: usize,
+}
+
+impl NegativeFilter {
+    /// Create empty filters for hash indices of the given capacity.
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        NegativeFilter {
+            left: BloomFilter::with_capacity(capacity),
+            right: BloomFilter::with_capacity(capacity),
+            stale: 0,
+        }
+    }
+
+    /// The number of heap bytes allocated for both filters.
+    pub(crate) fn allocated_bytes(&self) -> usize {
+        self.left.allocated_bytes() + self.right.allocated_bytes()
+    }
+}
@@ -0,0 +1,17 @@
+//! Aliases of maps with common third-party hashers.
+//!
+//! The hashers of an alias cannot be inferred by [`BiMap::new`] and [`BiMap::with_capacity`], which
+//! only construct maps with the default hashers of the standard library. Maps of an alias are
+//! constructed with [`BiMap::with_default_hashers`] instead, e.g. `FxBiMap::with_default_hashers(0)`.
+
+use crate::BiMap;
+
+/// A map that hashes both sides with the Fx hasher of the Rust compiler. It is much faster than the
+/// default hashers for integer values, but it is not resistant against collision attacks.
+#[cfg(feature = "fxhash")]
+pub type FxBiMap<T, U> = BiMap<T, U, rustc_hash::FxBuildHasher, rustc_hash::FxBuildHasher>;
+
+/// A map that hashes both sides with [aHash](https://docs.rs/ahash), which is faster than the default
+/// hashers while keeping randomly seeded hashers.
+#[cfg(feature = "ahash")]
+pub type ABiMap<T, U> = BiMap<T, U, ahash::RandomState, ahash::RandomState>;
@@ -0,0 +1,152 @@
+//! An allocator of `u32` ids for values, which reuses the ids of released values.
+
+use std::hash::{BuildHasher, Hash, RandomState};
+use crate::BiMap;
+
+/// Assigns `u32` ids to values and reuses the ids of released values, so the ids stay below the
+/// largest number of values that were allocated at the same time. This makes the ids suitable as
+/// indices into external arrays.
+///
+/// Released ids are kept in a free list and reused in last-in, first-out order. Only when the free
+/// list is empty, a value is assigned the next id that was never assigned before, so without
+/// releases the ids are dense and follow the order of allocation.
+#[derive(Clone, Debug)]
+pub struct IdAllocator<T, H = RandomState, RH = RandomState>
+    where T: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    map: BiMap<T, u32, H, RH>,
+    /// The released ids, the most recently released id last.
+    free: Vec<u32>,
+    /// The next id that was never assigned.
+    next_id: u32,
+}
+
+impl<T> IdAllocator<T>
+    where T: Hash + Eq
+{
+    /// Create a new allocator without any ids.
+    pub fn new() -> Self {
+        Self::with_hashers(0, RandomState::default(), RandomState::default())
+    }
+}
+
+impl<T> Default for IdAllocator<T>
+    where T: Hash + Eq
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, H, RH> IdAllocator<T, H, RH>
+    where T: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    /// Create a new allocator with room for `capacity` values, using the given hashers.
+    pub fn with_hashers(capacity: usize, hasher: H, reverse_hasher: RH) -> Self {
+        IdAllocator {
+            map: BiMap::with_hashers(capacity, hasher, reverse_hasher),
+            free: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Returns the id of the value, assigning it an id if it has none. The id is the most recently
+    /// released id, or the next id that was never assigned if no id is free.
+    ///
+    /// # Panics
+    /// Panics, if all `u32` ids are in use.
+    pub fn allocate(&mut self, left: T) -> u32 {
+        if let Some(&id) = self.map.get_right(&left) {
+            return id;
+        }
+
+        let id = match self.free.pop() {
+            Some(id) => id,
+            None => {
+                let id = self.next_id;
+                self.next_id = id.checked_add(1).expect("all ids are in use");
+                id
+            }
+        };
+        self.map.insert_unique_unchecked(left, id);
+        id
+    }
+
+    /// Removes the value and releases its id for reuse. If the value has no id, None is returned.
+    pub fn release_left(&mut self, left: &T) -> Option<u32> {
+        let id = self.map.remove_left(left)?;
+        self.free.push(id);
+        Some(id)
+    }
+
+    /// Releases the id for reuse and returns the value it was assigned to. If the id is not
+    /// assigned, None is returned.
+    pub fn release_id(&mut self, id: u32) -> Option<T> {
+        let left = self.map.remove_right(&id)?;
+        self.free.push(id);
+        Some(left)
+    }
+
+    /// Get the id of the value. If the value has no id, None is returned.
+    #[must_use]
+    pub fn get_id(&self, left: &T) -> Option<u32> {
+        self.map.get_right(left).copied()
+    }
+
+    /// Get the value the id is assigned to. If the id is not assigned, None is returned.
+    #[must_use]
+    pub fn get_left(&self, id: u32) -> Option<&T> {
+        self.map.get_left(&id)
+    }
+
+    /// The exclusive upper bound of the ids assigned so far. All assigned ids are below it, so it
+    /// is the length an external array indexed by the ids needs.
+    #[must_use]
+    pub fn id_bound(&self) -> u32 {
+        self.next_id
+    }
+
+    /// Removes all values and forgets all ids, so the next allocation starts at id zero again.
+    /// Keeps the allocated memory for reuse. See [`release_all`] for clearing the values but
+    /// keeping the ids.
+    ///
+    /// [`release_all`]: IdAllocator::release_all
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.free.clear();
+        self.next_id = 0;
+    }
+
+    /// Removes all values and releases their ids, so that the free list holds every id below
+    /// [`id_bound`]. Unlike [`clear`], the bound does not shrink, which keeps external arrays
+    /// valid. The ids are reused in ascending order.
+    ///
+    /// [`id_bound`]: IdAllocator::id_bound
+    /// [`clear`]: IdAllocator::clear
+    pub fn release_all(&mut self) {
+        self.map.clear();
+        self.free.clear();
+        self.free.extend((0..self.next_id).rev());
+    }
+
+    /// Returns an iterator over the values and their ids in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item=(&T, u32)> {
+        self.map.iter().map(|(left, &id)| (left, id))
+    }
+
+    /// Returns the number of values with an id.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if no value has an id.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the underlying map from values to ids, which provides the remaining read-only
+    /// operations.
+    pub fn as_bimap(&self) -> &BiMap<T, u32, H, RH> {
+        &self.map
+    }
+}
@@ -0,0 +1,43 @@
+//! Wrappers that name the side of a value, and the index operator that takes them.
+
+use std::hash::{BuildHasher, Hash};
+use std::ops::Index;
+use allocator_api2::alloc::Allocator;
+use crate::BiMap;
+
+/// A left value, or a reference to one, for operations that accept values of either side. Since
+/// both sides of a map can have the same type, the wrapper decides which index is searched.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Left<Q>(pub Q);
+
+/// A right value, or a reference to one, for operations that accept values of either side.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Right<Q>(pub Q);
+
+/// Looks up the right value of a left value with `map[Left(&left)]`.
+///
+/// # Panics
+/// Panics if the left value is not in the map. Use [`BiMap::get_right`] for a lookup that may fail.
+impl<T, U, H, RH, A: Allocator + Clone> Index<Left<&T>> for BiMap<T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    type Output = U;
+
+    fn index(&self, left: Left<&T>) -> &U {
+        self.get_right(left.0).expect("the left value is not in the map")
+    }
+}
+
+/// Looks up the left value of a right value with `map[Right(&right)]`.
+///
+/// # Panics
+/// Panics if the right value is not in the map. Use [`BiMap::get_left`] for a lookup that may fail.
+impl<T, U, H, RH, A: Allocator + Clone> Index<Right<&U>> for BiMap<T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    type Output = T;
+
+    fn index(&self, right: Right<&U>) -> &T {
+        self.get_left(right.0).expect("the right value is not in the map")
+    }
+}
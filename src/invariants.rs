@@ -0,0 +1,274 @@
+//! A check of the internal invariants of a map, for fuzzing and for debugging suspected corruption,
+//! and a search for values whose `Hash` and `Eq` implementations disagree.
+
+use std::error::Error;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use allocator_api2::alloc::Allocator;
+use crate::{BiMap, SMALL_MAP_CAPACITY};
+use crate::side::IndexSide;
+#[cfg(not(feature = "hashbrown-backend"))]
+use crate::side::{probe_distance, saturate_distance};
+
+/// A side of a map, which identifies one of its two hash indices.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// A violated invariant of a map, as reported by [`BiMap::check_invariants`]. Each variant names the
+/// bucket or index slot where the violation was found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum InvariantViolation {
+    /// A small map holds more pairs than small maps may hold.
+    SmallMapOverflow { len: usize },
+    /// A small map keeps back-references, although it has no indices to refer to.
+    SmallMapBackReferences { back_references: usize },
+    /// The number of back-references differs from the number of pairs.
+    BackReferenceCount { back_references: usize, len: usize },
+    /// An index holds more pairs than its load factor allows.
+    Overloaded { len: usize, capacity: usize },
+    /// An index slot points at a bucket that does not exist.
+    DanglingSlot { side: Side, slot: usize, bucket: usize },
+    /// The number of occupied slots of an index differs from the number of pairs.
+    OccupiedSlots { side: Side, occupied: usize, len: usize },
+    /// The back-reference of a bucket points at a slot that does not point back at the bucket.
+    BackReference { side: Side, bucket: usize, slot: usize },
+    /// The stored probe distance of a slot differs from the distance of its entry.
+    ProbeDistance { side: Side, slot: usize, stored: u8, expected: u8 },
+    /// The entry of a slot is further from its ideal index than the entry of the preceding slot
+    /// allows, so probing for it would stop before reaching it.
+    RobinHoodOrder { side: Side, slot: usize },
+    /// Looking up the value of a bucket does not find the bucket, because the value is stored twice
+    /// or the negative lookup filter rejects it.
+    Unreachable { side: Side, bucket: usize },
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            InvariantViolation::SmallMapOverflow { len } =>
+                write!(f, "small map holds {} pairs, at most {} are allowed", len, SMALL_MAP_CAPACITY),
+            InvariantViolation::SmallMapBackReferences { back_references } =>
+                write!(f, "small map keeps {} back-references", back_references),
+            InvariantViolation::BackReferenceCount { back_references, len } =>
+                write!(f, "map keeps {} back-references for {} pairs", back_references, len),
+            InvariantViolation::Overloaded { len, capacity } =>
+                write!(f, "indices of capacity {} hold {} pairs", capacity, len),
+            InvariantViolation::DanglingSlot { side, slot, bucket } =>
+                write!(f, "{:?} slot {} points at missing bucket {}", side, slot, bucket),
+            InvariantViolation::OccupiedSlots { side, occupied, len } =>
+                write!(f, "{:?} index has {} occupied slots for {} pairs", side, occupied, len),
+            InvariantViolation::BackReference { side, bucket, slot } =>
+                write!(f, "{:?} back-reference of bucket {} points at slot {}, which does not point back", side, bucket, slot),
+            InvariantViolation::ProbeDistance { side, slot, stored, expected } =>
+                write!(f, "{:?} slot {} stores probe distance {}, expected {}", side, slot, stored, expected),
+            InvariantViolation::RobinHoodOrder { side, slot } =>
+                write!(f, "{:?} slot {} is further from its ideal index than the preceding slot allows", side, slot),
+            InvariantViolation::Unreachable { side, bucket } =>
+                write!(f, "{:?} value of bucket {} cannot be looked up", side, bucket),
+        }
+    }
+}
+
+impl Error for InvariantViolation {}
+
+/// A value of a map whose `Hash` or `Eq` implementation behaves inconsistently, as reported by
+/// [`BiMap::find_inconsistencies`]. Each variant names the bucket of the value, i.e. its position
+/// in the iteration order of the map.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum InconsistencyReport {
+    /// The value is not equal to itself.
+    NotReflexive { side: Side, bucket: usize },
+    /// Hashing the value twice yields different hashes.
+    UnstableHash { side: Side, bucket: usize },
+    /// Looking up the value finds the value of bucket `other` instead, which is equal to it. The map
+    /// holds both values, because they were not equal when they were inserted, or because they had
+    /// different hashes that happened to lead to the same slots.
+    Shadowed { side: Side, bucket: usize, other: usize },
+    /// Looking up the value does not find it, because its hash changed since it was inserted, e.g.
+    /// through interior mutability, or because its hash is not derived from the parts that `Eq`
+    /// compares.
+    Unreachable { side: Side, bucket: usize },
+}
+
+impl fmt::Display for InconsistencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            InconsistencyReport::NotReflexive { side, bucket } =>
+                write!(f, "{:?} value of bucket {} is not equal to itself", side, bucket),
+            InconsistencyReport::UnstableHash { side, bucket } =>
+                write!(f, "{:?} value of bucket {} hashes to different values", side, bucket),
+            InconsistencyReport::Shadowed { side, bucket, other } =>
+                write!(f, "{:?} value of bucket {} equals the value of bucket {}, which is found in its place", side, bucket, other),
+            InconsistencyReport::Unreachable { side, bucket } =>
+                write!(f, "{:?} value of bucket {} cannot be found from its hash", side, bucket),
+        }
+    }
+}
+
+/// Check every value of the map for behavior that violates the contract of `Hash` and `Eq`. See
+/// [`BiMap::find_inconsistencies`].
+pub(crate) fn find_inconsistencies<T, U, H, RH, A>(map: &BiMap<T, U, H, RH, A>) -> Vec<InconsistencyReport>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher, A: Allocator + Clone
+{
+    let mut reports = Vec::new();
+    for bucket in 0..map.len() {
+        let left = map.data.left(bucket);
+        reports.extend(check_value(left, &map.left.hasher, map.find_bucket_left(left), Side::Left, bucket));
+        let right = map.data.right(bucket);
+        reports.extend(check_value(right, &map.right.hasher, map.find_bucket_right(right), Side::Right, bucket));
+    }
+    reports
+}
+
+/// Check a single value, given the bucket it is stored in and the bucket that looking it up found.
+fn check_value<E, G>(value: &E, hasher: &G, found: Option<usize>, side: Side, bucket: usize) -> Option<InconsistencyReport>
+    where E: Hash + Eq, G: BuildHasher
+{
+    #[allow(clippy::eq_op)]
+    if value != value {
+        return Some(InconsistencyReport::NotReflexive { side, bucket });
+    }
+    if hasher.hash_one(value) != hasher.hash_one(value) {
+        return Some(InconsistencyReport::UnstableHash { side, bucket });
+    }
+    match found {
+        Some(found) if found == bucket => None,
+        Some(other) => Some(InconsistencyReport::Shadowed { side, bucket, other }),
+        None => Some(InconsistencyReport::Unreachable { side, bucket }),
+    }
+}
+
+/// Check that the values of a bucket that was just inserted are stored exactly once, and that they
+/// can be looked up from their hashes. The check compares the values with all values of the map.
+///
+/// # Panics
+/// Panics with a description of the inconsistency if the check fails.
+#[cfg(feature = "paranoid")]
+pub(crate) fn verify_inserted<T, U, H, RH, A>(map: &BiMap<T, U, H, RH, A>, bucket: usize)
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher, A: Allocator + Clone
+{
+    let left = map.data.left(bucket);
+    let right = map.data.right(bucket);
+    let lefts = map.data.lefts().filter(|stored| *stored == left).count();
+    let rights = map.data.rights().filter(|stored| *stored == right).count();
+    assert_eq!(lefts, 1, "the inserted left value of bucket {} equals {} stored left values, its Hash and Eq implementations are inconsistent", bucket, lefts);
+    assert_eq!(rights, 1, "the inserted right value of bucket {} equals {} stored right values, its Hash and Eq implementations are inconsistent", bucket, rights);
+    assert_eq!(map.find_bucket_left(left), Some(bucket), "the inserted left value of bucket {} cannot be looked up, its Hash and Eq implementations are inconsistent", bucket);
+    assert_eq!(map.find_bucket_right(right), Some(bucket), "the inserted right value of bucket {} cannot be looked up, its Hash and Eq implementations are inconsistent", bucket);
+}
+
+/// Check all invariants of the map. See [`BiMap::check_invariants`].
+pub(crate) fn check<T, U, H, RH, A>(map: &BiMap<T, U, H, RH, A>) -> Result<(), InvariantViolation>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher, A: Allocator + Clone
+{
+    let len = map.len();
+    if map.is_small() {
+        if len > SMALL_MAP_CAPACITY {
+            return Err(InvariantViolation::SmallMapOverflow { len });
+        }
+        for back_references in [map.left.back_references.len(), map.right.back_references.len()] {
+            if back_references != 0 {
+                return Err(InvariantViolation::SmallMapBackReferences { back_references });
+            }
+        }
+    } else {
+        for back_references in [map.left.back_references.len(), map.right.back_references.len()] {
+            if back_references != len {
+                return Err(InvariantViolation::BackReferenceCount { back_references, len });
+            }
+        }
+        if len > BiMap::<T, U, H, RH>::growth_threshold_for(map.current_capacity()) {
+            return Err(InvariantViolation::Overloaded { len, capacity: map.current_capacity() });
+        }
+
+        check_index(&map.left, Side::Left, len, |bucket| map.left.ideal_index(map.data.left(bucket)))?;
+        check_index(&map.right, Side::Right, len, |bucket| map.right.ideal_index(map.data.right(bucket)))?;
+    }
+
+    for bucket in 0..len {
+        if map.find_bucket_left(map.data.left(bucket)) != Some(bucket) {
+            return Err(InvariantViolation::Unreachable { side: Side::Left, bucket });
+        }
+        if map.find_bucket_right(map.data.right(bucket)) != Some(bucket) {
+            return Err(InvariantViolation::Unreachable { side: Side::Right, bucket });
+        }
+    }
+    Ok(())
+}
+
+/// Check the slots, probe distances and back-references of the index of one side of a map with
+/// `len` pairs, using `ideal_index` to compute the ideal index of a bucket's value.
+#[cfg(not(feature = "hashbrown-backend"))]
+fn check_index<G, A: Allocator + Clone>(index: &IndexSide<G, A>, side: Side, len: usize, ideal_index: impl Fn(usize) -> usize) -> Result<(), InvariantViolation> {
+    let (slots, distances) = (&index.slots, &index.distances);
+    let capacity = slots.len();
+
+    // the actual probe distance of each slot, or None for empty slots
+    let mut slot_distances = Vec::with_capacity(capacity);
+    for (slot, entry) in slots.iter().enumerate() {
+        let Some(bucket) = entry.bucket() else {
+            slot_distances.push(None);
+            continue;
+        };
+        if bucket >= len {
+            return Err(InvariantViolation::DanglingSlot { side, slot, bucket });
+        }
+
+        let distance = probe_distance(ideal_index(bucket), slot, capacity);
+        let expected = saturate_distance(distance);
+        if distances[slot] != expected {
+            return Err(InvariantViolation::ProbeDistance { side, slot, stored: distances[slot], expected });
+        }
+        slot_distances.push(Some(distance));
+    }
+
+    let occupied = slot_distances.iter().filter(|distance| distance.is_some()).count();
+    if occupied != len {
+        return Err(InvariantViolation::OccupiedSlots { side, occupied, len });
+    }
+
+    // an entry may be at most one slot further from its ideal index than its predecessor, otherwise
+    // it would have displaced it. Entries away from their ideal index must not follow an empty slot.
+    for slot in 0..capacity {
+        if let Some(distance) = slot_distances[slot] {
+            let previous = slot_distances[(slot + capacity - 1) % capacity];
+            if distance > 0 && previous.is_none_or(|previous| distance > previous + 1) {
+                return Err(InvariantViolation::RobinHoodOrder { side, slot });
+            }
+        }
+    }
+
+    // together with the slot count, this makes sure that every bucket is referenced by exactly one slot
+    for (bucket, &slot) in index.back_references.iter().enumerate() {
+        if slot >= capacity || slots[slot].bucket() != Some(bucket) {
+            return Err(InvariantViolation::BackReference { side, bucket, slot });
+        }
+    }
+    Ok(())
+}
+
+/// Check the table and back-references of the index of one side of a map with `len` pairs, using
+/// `ideal_index` to compute the hash of a bucket's value. The table has no slot order to check, so
+/// the position of an entry is its position in the iteration order of the table.
+#[cfg(feature = "hashbrown-backend")]
+fn check_index<G, A: Allocator + Clone>(index: &IndexSide<G, A>, side: Side, len: usize, ideal_index: impl Fn(usize) -> usize) -> Result<(), InvariantViolation> {
+    if let Some((slot, &bucket)) = index.table.iter().enumerate().find(|(_, &bucket)| bucket >= len) {
+        return Err(InvariantViolation::DanglingSlot { side, slot, bucket });
+    }
+    if index.table.len() != len {
+        return Err(InvariantViolation::OccupiedSlots { side, occupied: index.table.len(), len });
+    }
+
+    // together with the entry count, this makes sure that every bucket has exactly one entry
+    for (bucket, &hash) in index.back_references.iter().enumerate() {
+        if hash != ideal_index(bucket) || index.table.find(hash as u64, |&entry| entry == bucket).is_none() {
+            return Err(InvariantViolation::BackReference { side, bucket, slot: hash });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,173 @@
+//! A read-only view of a map with the roles of the left and the right values swapped.
+
+use std::fmt;
+use std::hash::{BuildHasher, Hash, RandomState};
+use std::iter::FusedIterator;
+use allocator_api2::alloc::{Allocator, Global};
+use crate::{BiMap, LeftValues, RightValues};
+
+/// A view of a map with its sides swapped, returned by [`BiMap::inverse`]. The right values of the
+/// map are the left values of the view and vice versa, so `map.inverse().get_right(&u)` looks up
+/// the left value that `u` is mapped to.
+///
+/// The view borrows the map, so it always reflects its current contents, and it can be copied
+/// freely to hand the inverse perspective to several consumers.
+pub struct InverseView<'a, T, U, H = RandomState, RH = RandomState, A: Allocator + Clone = Global>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    map: &'a BiMap<T, U, H, RH, A>,
+}
+
+impl<'a, T, U, H, RH, A: Allocator + Clone> InverseView<'a, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    pub(crate) fn new(map: &'a BiMap<T, U, H, RH, A>) -> Self {
+        InverseView { map }
+    }
+
+    /// Get the right value of the view, i.e. the left value of the map, for the given left value
+    /// of the view. See [`BiMap::get_left`].
+    #[must_use]
+    pub fn get_right(&self, left: &U) -> Option<&'a T> {
+        self.map.get_left(left)
+    }
+
+    /// Get the left value of the view, i.e. the right value of the map, for the given right value
+    /// of the view. See [`BiMap::get_right`].
+    #[must_use]
+    pub fn get_left(&self, right: &T) -> Option<&'a U> {
+        self.map.get_right(right)
+    }
+
+    /// Check if the view contains a mapping for the given left value, i.e. if the map contains a
+    /// mapping for it as a right value.
+    #[must_use]
+    pub fn contains_left(&self, left: &U) -> bool {
+        self.map.contains_right(left)
+    }
+
+    /// Check if the view contains a mapping for the given right value, i.e. if the map contains a
+    /// mapping for it as a left value.
+    #[must_use]
+    pub fn contains_right(&self, right: &T) -> bool {
+        self.map.contains_left(right)
+    }
+
+    /// Returns an iterator over the swapped pairs of the map, in the iteration order of the map.
+    pub fn iter(&self) -> InverseIter<'a, T, U, H, RH, A> {
+        InverseIter { map: self.map, position: 0, end: self.map.len() }
+    }
+
+    /// Returns an iterator over the left values of the view, i.e. the right values of the map.
+    pub fn left_values(&self) -> RightValues<'a, T, U> {
+        self.map.right_values()
+    }
+
+    /// Returns an iterator over the right values of the view, i.e. the left values of the map.
+    pub fn right_values(&self) -> LeftValues<'a, T, U> {
+        self.map.left_values()
+    }
+
+    /// Returns the number of pairs in the map.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Check if the map is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+// the view only holds a reference, so it can be copied regardless of the values and hashers
+impl<T, U, H, RH, A: Allocator + Clone> Clone for InverseView<'_, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, U, H, RH, A: Allocator + Clone> Copy for InverseView<'_, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq
+{}
+
+impl<T, U, H, RH, A: Allocator + Clone> fmt::Debug for InverseView<'_, T, U, H, RH, A>
+    where T: Hash + Eq + fmt::Debug, U: Hash + Eq + fmt::Debug, H: BuildHasher, RH: BuildHasher
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T, U, H, RH, A: Allocator + Clone> IntoIterator for InverseView<'a, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    type Item = (&'a U, &'a T);
+    type IntoIter = InverseIter<'a, T, U, H, RH, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// The iterator over the swapped pairs of a map, returned by [`InverseView::iter`].
+pub struct InverseIter<'a, T, U, H = RandomState, RH = RandomState, A: Allocator + Clone = Global>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    map: &'a BiMap<T, U, H, RH, A>,
+    /// The bucket of the next pair.
+    position: usize,
+    /// The bucket after the next pair from the back.
+    end: usize,
+}
+
+impl<'a, T, U, H, RH, A: Allocator + Clone> Iterator for InverseIter<'a, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    type Item = (&'a U, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position >= self.end {
+            return None;
+        }
+        let bucket = self.position;
+        self.position += 1;
+        Some((self.map.data.right(bucket), self.map.data.left(bucket)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.end - self.position;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T, U, H, RH, A: Allocator + Clone> DoubleEndedIterator for InverseIter<'_, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.position >= self.end {
+            return None;
+        }
+        self.end -= 1;
+        Some((self.map.data.right(self.end), self.map.data.left(self.end)))
+    }
+}
+
+impl<T, U, H, RH, A: Allocator + Clone> Clone for InverseIter<'_, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    fn clone(&self) -> Self {
+        InverseIter { map: self.map, position: self.position, end: self.end }
+    }
+}
+
+impl<T, U, H, RH, A: Allocator + Clone> ExactSizeIterator for InverseIter<'_, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq
+{}
+
+impl<T, U, H, RH, A: Allocator + Clone> FusedIterator for InverseIter<'_, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq
+{}
@@ -0,0 +1,248 @@
+//! The iterators over the pairs and values of a map. Each iterator wraps the matching iterator of
+//! the pair storage, so its type does not depend on the storage layout.
+
+use std::fmt;
+use std::iter::FusedIterator;
+use allocator_api2::alloc::{Allocator, Global};
+use crate::{storage, BiMap, PairCallback};
+
+/// The iterator over references to the pairs of a map in arbitrary order, created by
+/// [`BiMap::iter`] and the [`IntoIterator`] implementation of `&BiMap`.
+pub struct Iter<'a, T, U> {
+    pub(crate) pairs: storage::Pairs<'a, T, U>,
+}
+
+impl<'a, T, U> Iterator for Iter<'a, T, U> {
+    type Item = (&'a T, &'a U);
+
+    fn next(&mut self) -> Option<(&'a T, &'a U)> {
+        self.pairs.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.pairs.size_hint()
+    }
+}
+
+impl<T, U> DoubleEndedIterator for Iter<'_, T, U> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.pairs.next_back()
+    }
+}
+
+impl<T, U> ExactSizeIterator for Iter<'_, T, U> {}
+
+impl<T, U> FusedIterator for Iter<'_, T, U> {}
+
+impl<T, U> Clone for Iter<'_, T, U> {
+    fn clone(&self) -> Self {
+        Iter { pairs: self.pairs.clone() }
+    }
+}
+
+impl<T: fmt::Debug, U: fmt::Debug> fmt::Debug for Iter<'_, T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<'a, T, U, H, RH, A: Allocator + Clone> IntoIterator for &'a BiMap<T, U, H, RH, A> {
+    type Item = (&'a T, &'a U);
+    type IntoIter = Iter<'a, T, U>;
+
+    fn into_iter(self) -> Iter<'a, T, U> {
+        self.iter()
+    }
+}
+
+/// The iterator over references to the left values of a map in arbitrary order, created by
+/// [`BiMap::left_values`].
+pub struct LeftValues<'a, T, U> {
+    pub(crate) values: storage::Lefts<'a, T, U>,
+}
+
+impl<'a, T, U> Iterator for LeftValues<'a, T, U> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.values.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+
+impl<T, U> DoubleEndedIterator for LeftValues<'_, T, U> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.values.next_back()
+    }
+}
+
+impl<T, U> ExactSizeIterator for LeftValues<'_, T, U> {}
+
+impl<T, U> FusedIterator for LeftValues<'_, T, U> {}
+
+impl<T, U> Clone for LeftValues<'_, T, U> {
+    fn clone(&self) -> Self {
+        LeftValues { values: self.values.clone() }
+    }
+}
+
+impl<T: fmt::Debug, U> fmt::Debug for LeftValues<'_, T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// The iterator over references to the right values of a map in arbitrary order, created by
+/// [`BiMap::right_values`].
+pub struct RightValues<'a, T, U> {
+    pub(crate) values: storage::Rights<'a, T, U>,
+}
+
+impl<'a, T, U> Iterator for RightValues<'a, T, U> {
+    type Item = &'a U;
+
+    fn next(&mut self) -> Option<&'a U> {
+        self.values.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+
+impl<T, U> DoubleEndedIterator for RightValues<'_, T, U> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.values.next_back()
+    }
+}
+
+impl<T, U> ExactSizeIterator for RightValues<'_, T, U> {}
+
+impl<T, U> FusedIterator for RightValues<'_, T, U> {}
+
+impl<T, U> Clone for RightValues<'_, T, U> {
+    fn clone(&self) -> Self {
+        RightValues { values: self.values.clone() }
+    }
+}
+
+impl<T, U: fmt::Debug> fmt::Debug for RightValues<'_, T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// The iterator that consumes a map and yields its pairs by value in arbitrary order, created by
+/// the [`IntoIterator`] implementation of [`BiMap`]. The indices of the map are dropped when the
+/// iterator is created, and pairs that are not yielded are dropped with the iterator.
+pub struct IntoIter<T, U, A: Allocator = Global> {
+    pub(crate) pairs: storage::IntoPairs<T, U, A>,
+}
+
+impl<T, U, A: Allocator> Iterator for IntoIter<T, U, A> {
+    type Item = (T, U);
+
+    fn next(&mut self) -> Option<(T, U)> {
+        self.pairs.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.pairs.size_hint()
+    }
+}
+
+impl<T, U, A: Allocator> DoubleEndedIterator for IntoIter<T, U, A> {
+    fn next_back(&mut self) -> Option<(T, U)> {
+        self.pairs.next_back()
+    }
+}
+
+impl<T, U, A: Allocator> ExactSizeIterator for IntoIter<T, U, A> {}
+
+impl<T, U, A: Allocator> FusedIterator for IntoIter<T, U, A> {}
+
+impl<T: Clone, U: Clone, A: Allocator + Clone> Clone for IntoIter<T, U, A> {
+    fn clone(&self) -> Self {
+        IntoIter { pairs: self.pairs.clone() }
+    }
+}
+
+impl<T: fmt::Debug, U: fmt::Debug, A: Allocator> fmt::Debug for IntoIter<T, U, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IntoIter").field("remaining", &self.pairs.len()).finish()
+    }
+}
+
+impl<T, U, H, RH, A: Allocator + Clone> IntoIterator for BiMap<T, U, H, RH, A> {
+    type Item = (T, U);
+    type IntoIter = IntoIter<T, U, A>;
+
+    /// Consumes the map and returns an iterator over its pairs in arbitrary order.
+    fn into_iter(self) -> IntoIter<T, U, A> {
+        IntoIter { pairs: self.data.into_pairs() }
+    }
+}
+
+/// The iterator of [`BiMap::drain`], which removes the pairs of a map and yields them in
+/// arbitrary order. It notifies the remove hook of each pair it removes.
+///
+/// The map is empty as soon as the iterator is created. If the iterator is dropped before it is
+/// exhausted, the remaining pairs are removed and dropped as well, like with [`Vec::drain`]. The
+/// allocated memory of the map is kept for reuse.
+pub struct Drain<'a, T, U, A: Allocator = Global> {
+    pub(crate) pairs: storage::DrainPairs<'a, T, U, A>,
+    pub(crate) hook: &'a mut Option<PairCallback<T, U>>,
+}
+
+impl<T, U, A: Allocator> Drain<'_, T, U, A> {
+    /// Notify the remove hook of a pair that is yielded or dropped.
+    fn removed(&mut self, (left, right): (T, U)) -> (T, U) {
+        if let Some(hook) = self.hook {
+            hook(&left, &right);
+        }
+        (left, right)
+    }
+}
+
+impl<T, U, A: Allocator> Iterator for Drain<'_, T, U, A> {
+    type Item = (T, U);
+
+    fn next(&mut self) -> Option<(T, U)> {
+        let pair = self.pairs.next()?;
+        Some(self.removed(pair))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.pairs.size_hint()
+    }
+}
+
+impl<T, U, A: Allocator> DoubleEndedIterator for Drain<'_, T, U, A> {
+    fn next_back(&mut self) -> Option<(T, U)> {
+        let pair = self.pairs.next_back()?;
+        Some(self.removed(pair))
+    }
+}
+
+impl<T, U, A: Allocator> ExactSizeIterator for Drain<'_, T, U, A> {}
+
+impl<T, U, A: Allocator> FusedIterator for Drain<'_, T, U, A> {}
+
+impl<T, U, A: Allocator> Drop for Drain<'_, T, U, A> {
+    fn drop(&mut self) {
+        if let Some(hook) = self.hook {
+            for (left, right) in self.pairs.by_ref() {
+                hook(&left, &right);
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug, U: fmt::Debug, A: Allocator> fmt::Debug for Drain<'_, T, U, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Drain").field("remaining", &self.pairs.len()).finish()
+    }
+}
@@ -0,0 +1,165 @@
+//! Collecting iterators of pairs into maps, with [`FromIterator`], [`Extend`] and an extension
+//! trait, and converting `HashMap`s into maps.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use allocator_api2::alloc::Allocator;
+use crate::BiMap;
+use crate::errors::{NonInjectiveError, ValueCollision};
+
+/// Collects the pairs into a map with default-constructed hashers, inserting them in order with
+/// [`BiMap::insert`], so that later pairs replace the mappings of earlier pairs with equal values.
+/// The map is allocated for the lower bound of the size hint of the iterator.
+impl<T, U, H, RH> FromIterator<(T, U)> for BiMap<T, U, H, RH>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher + Default, RH: BuildHasher + Default
+{
+    fn from_iter<I: IntoIterator<Item=(T, U)>>(pairs: I) -> Self {
+        let pairs = pairs.into_iter();
+        let mut map = Self::with_default_hashers(pairs.size_hint().0);
+        for (left, right) in pairs {
+            map.insert(left, right);
+        }
+        map
+    }
+}
+
+/// Inserts the pairs in order with [`BiMap::insert`], so that the pairs replace the mappings of
+/// earlier pairs and of the pairs of the map with equal values. Capacity for the lower bound of the
+/// size hint of the iterator is reserved first, so the map resizes at most once for that many pairs.
+impl<T, U, H, RH, A> Extend<(T, U)> for BiMap<T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher, A: Allocator + Clone
+{
+    fn extend<I: IntoIterator<Item=(T, U)>>(&mut self, pairs: I) {
+        let pairs = pairs.into_iter();
+        self.reserve(pairs.size_hint().0);
+        for (left, right) in pairs {
+            self.insert(left, right);
+        }
+    }
+}
+
+/// Copies the pairs out of the references and inserts them like [`Extend<(T, U)>`], e.g. to extend
+/// a map with the pairs of another map with `map.extend(other.iter())`.
+impl<'a, T, U, H, RH, A> Extend<(&'a T, &'a U)> for BiMap<T, U, H, RH, A>
+    where T: Hash + Eq + Copy, U: Hash + Eq + Copy, H: BuildHasher, RH: BuildHasher, A: Allocator + Clone
+{
+    fn extend<I: IntoIterator<Item=(&'a T, &'a U)>>(&mut self, pairs: I) {
+        self.extend(pairs.into_iter().map(|(&left, &right)| (left, right)));
+    }
+}
+
+/// Converts a `HashMap` into a map, requiring its right values to be distinct. All right values
+/// with more than one left value are returned in the error, together with their left values.
+///
+/// To keep one of the left values instead, collect the entries with `forward.into_iter().collect()`,
+/// which keeps the left value that comes last in the iteration order of the `HashMap`. A `From`
+/// implementation for that would conflict with this one.
+impl<T, U, S, H, RH> TryFrom<HashMap<T, U, S>> for BiMap<T, U, H, RH>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher + Default, RH: BuildHasher + Default
+{
+    type Error = NonInjectiveError<T, U>;
+
+    fn try_from(forward: HashMap<T, U, S>) -> Result<Self, Self::Error> {
+        // the left values of a HashMap are distinct, so only right values can collide
+        let mut map = Self::with_default_hashers(forward.len());
+        let mut colliding = Vec::new();
+        for (left, right) in forward {
+            if map.contains_right(&right) {
+                colliding.push((left, right));
+            } else {
+                map.insert_unique_unchecked(left, right);
+            }
+        }
+        if colliding.is_empty() {
+            return Ok(map);
+        }
+
+        // the first left value of a colliding right value is still in the map
+        let mut groups: HashMap<U, Vec<T>> = HashMap::new();
+        for (left, right) in colliding {
+            let first = map.remove_right(&right);
+            let lefts = groups.entry(right).or_default();
+            lefts.extend(first);
+            lefts.push(left);
+        }
+        Err(NonInjectiveError {
+            collisions: groups.into_iter().map(|(right, lefts)| ValueCollision { right, lefts }).collect(),
+        })
+    }
+}
+
+/// An error of [`BiMapIteratorExt::try_into_bimap`]. Pairs are numbered by their position in the
+/// iterator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FromPairsError {
+    /// The left value of pair `duplicate` equals the left value of the earlier pair `first`.
+    DuplicateLeft { first: usize, duplicate: usize },
+    /// The right value of pair `duplicate` equals the right value of the earlier pair `first`.
+    DuplicateRight { first: usize, duplicate: usize },
+}
+
+impl fmt::Display for FromPairsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            FromPairsError::DuplicateLeft { first, duplicate } =>
+                write!(f, "the left value of pair {} equals the left value of pair {}", duplicate, first),
+            FromPairsError::DuplicateRight { first, duplicate } =>
+                write!(f, "the right value of pair {} equals the right value of pair {}", duplicate, first),
+        }
+    }
+}
+
+impl Error for FromPairsError {}
+
+/// Methods for collecting an iterator of pairs into a [`BiMap`] at the end of an iterator chain.
+/// The trait is implemented for all iterators of pairs.
+pub trait BiMapIteratorExt<T, U>: Iterator<Item=(T, U)> + Sized
+    where T: Hash + Eq, U: Hash + Eq
+{
+    /// Collect the pairs into a map, inserting them in order with [`BiMap::insert`], so that later
+    /// pairs replace the mappings of earlier pairs with equal values.
+    fn into_bimap(self) -> BiMap<T, U> {
+        let mut map = BiMap::with_capacity(self.size_hint().0);
+        for (left, right) in self {
+            map.insert(left, right);
+        }
+        map
+    }
+
+    /// Collect the pairs into a map with their sides swapped, i.e. each pair `(t, u)` is inserted
+    /// as `(u, t)`. Like [`into_bimap`], later pairs replace the mappings of earlier pairs.
+    ///
+    /// [`into_bimap`]: BiMapIteratorExt::into_bimap
+    fn into_inverse_bimap(self) -> BiMap<U, T> {
+        let mut map = BiMap::with_capacity(self.size_hint().0);
+        for (left, right) in self {
+            map.insert(right, left);
+        }
+        map
+    }
+
+    /// Collect the pairs into a map, requiring all left values and all right values to be distinct.
+    /// The positions of the first duplicate value are returned in the error, and the iterator is
+    /// not consumed any further.
+    fn try_into_bimap(self) -> Result<BiMap<T, U>, FromPairsError> {
+        // without duplicates there are no removals, so every pair is stored in the bucket of its position
+        let mut map = BiMap::with_capacity(self.size_hint().0);
+        for (position, (left, right)) in self.enumerate() {
+            if let Some(first) = map.find_bucket_left(&left) {
+                return Err(FromPairsError::DuplicateLeft { first, duplicate: position });
+            }
+            if let Some(first) = map.find_bucket_right(&right) {
+                return Err(FromPairsError::DuplicateRight { first, duplicate: position });
+            }
+            map.insert_unique_unchecked(left, right);
+        }
+        Ok(map)
+    }
+}
+
+impl<I, T, U> BiMapIteratorExt<T, U> for I
+    where I: Iterator<Item=(T, U)>, T: Hash + Eq, U: Hash + Eq
+{}
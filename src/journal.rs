@@ -0,0 +1,187 @@
+//! A journal of the changes of a map, which can undo and redo them.
+//!
+//! The journal records the pairs that are inserted into and removed from the map through the same
+//! notifications as the insert and remove hooks, so it sees every change of the pairs, including
+//! the pairs that an insertion replaces. The changes of one operation are grouped into one entry,
+//! which is undone and redone as a whole.
+
+use std::collections::VecDeque;
+use std::hash::{BuildHasher, Hash};
+use allocator_api2::alloc::Allocator;
+use crate::BiMap;
+
+/// A change of the pairs of a map, with clones of the values of the pair.
+#[derive(Clone, Debug)]
+enum Change<T, U> {
+    Inserted(T, U),
+    Removed(T, U),
+}
+
+/// The recorded changes of a map, see [`BiMap::enable_journal`].
+#[derive(Clone, Debug)]
+pub(crate) struct Journal<T, U> {
+    /// The entries that can be undone, the most recent entry last.
+    undo: VecDeque<Vec<Change<T, U>>>,
+    /// The entries that were undone and can be redone, the most recently undone entry last.
+    redo: Vec<Vec<Change<T, U>>>,
+    /// The changes of the operation that is in progress.
+    pending: Vec<Change<T, U>>,
+    /// The maximum number of entries that can be undone.
+    limit: usize,
+    /// Clones the values of a pair. The values only need to be `Clone` to enable the journal, so the
+    /// clone implementations are captured when it is enabled.
+    clone_pair: fn(&T, &U) -> (T, U),
+}
+
+// the journal is a history of the map, not part of its contents
+impl<T, U> PartialEq for Journal<T, U> {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl<T, U> Eq for Journal<T, U> {}
+
+impl<T, U> Journal<T, U> {
+    pub(crate) fn record_inserted(&mut self, left: &T, right: &U) {
+        let (left, right) = (self.clone_pair)(left, right);
+        self.pending.push(Change::Inserted(left, right));
+    }
+
+    pub(crate) fn record_removed(&mut self, left: &T, right: &U) {
+        let (left, right) = (self.clone_pair)(left, right);
+        self.pending.push(Change::Removed(left, right));
+    }
+
+    /// Finish the entry of the operation in progress. A new entry discards the entries that were
+    /// undone, since they cannot be redone on top of it.
+    pub(crate) fn seal(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        self.redo.clear();
+        self.undo.push_back(std::mem::take(&mut self.pending));
+        if self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+}
+
+impl<T, U, H, RH, A: Allocator + Clone> BiMap<T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    /// Starts recording the changes of the map, so they can be undone with [`undo`] and redone with
+    /// [`redo`]. Every operation that changes the pairs records one entry: an insertion, including
+    /// the pairs it replaces, a removal, a mutation through [`get_right_mut`] or [`get_left_mut`],
+    /// and [`clear`], [`drain`] or [`retain`] as a whole. Operations of a [`transaction`] record one
+    /// entry each. Undoing restores the pairs, but not their iteration order.
+    ///
+    /// Each entry holds clones of the pairs it inserted and removed, so the journal grows with every
+    /// operation until it is truncated with [`truncate_journal`] or capped with
+    /// [`set_journal_limit`]. If the journal is enabled already, its entries are kept. Like the
+    /// hooks, the journal is not cloned with the map.
+    ///
+    /// [`undo`]: #method.undo
+    /// [`redo`]: #method.redo
+    /// [`get_right_mut`]: #method.get_right_mut
+    /// [`get_left_mut`]: #method.get_left_mut
+    /// [`clear`]: #method.clear
+    /// [`drain`]: #method.drain
+    /// [`retain`]: #method.retain
+    /// [`transaction`]: #method.transaction
+    /// [`truncate_journal`]: #method.truncate_journal
+    /// [`set_journal_limit`]: #method.set_journal_limit
+    pub fn enable_journal(&mut self)
+        where T: Clone, U: Clone
+    {
+        if self.journal.is_none() {
+            self.journal = Some(Journal {
+                undo: VecDeque::new(),
+                redo: Vec::new(),
+                pending: Vec::new(),
+                limit: usize::MAX,
+                clone_pair: |left, right| (left.clone(), right.clone()),
+            });
+        }
+    }
+
+    /// Stops recording changes and discards the journal.
+    pub fn disable_journal(&mut self) {
+        self.journal = None;
+    }
+
+    /// Discards all entries of the journal, so the changes so far can be neither undone nor redone.
+    /// The journal stays enabled.
+    pub fn truncate_journal(&mut self) {
+        if let Some(journal) = &mut self.journal {
+            journal.undo.clear();
+            journal.redo.clear();
+        }
+    }
+
+    /// Limits the number of entries that can be undone. When an operation records an entry beyond
+    /// the limit, the oldest entry is discarded. Entries beyond a new limit are discarded
+    /// immediately. Does nothing if the journal is not enabled.
+    pub fn set_journal_limit(&mut self, max_entries: usize) {
+        if let Some(journal) = &mut self.journal {
+            journal.limit = max_entries;
+            let excess = journal.undo.len().saturating_sub(max_entries);
+            journal.undo.drain(..excess);
+        }
+    }
+
+    /// Undoes the most recent entry of the journal, and returns false if there is none.
+    pub fn undo(&mut self) -> bool {
+        let Some(mut journal) = self.journal.take() else {
+            return false;
+        };
+        let entry = journal.undo.pop_back();
+        if let Some(changes) = &entry {
+            for change in changes.iter().rev() {
+                match change {
+                    Change::Inserted(left, _) => self.remove_recorded(left),
+                    Change::Removed(left, right) => self.insert_recorded(left, right, journal.clone_pair),
+                }
+            }
+        }
+        let undone = entry.is_some();
+        journal.redo.extend(entry);
+        self.journal = Some(journal);
+        undone
+    }
+
+    /// Redoes the most recently undone entry of the journal, and returns false if there is none.
+    /// Entries can only be redone until the map is changed by another operation.
+    pub fn redo(&mut self) -> bool {
+        let Some(mut journal) = self.journal.take() else {
+            return false;
+        };
+        let entry = journal.redo.pop();
+        if let Some(changes) = &entry {
+            for change in changes {
+                match change {
+                    Change::Inserted(left, right) => self.insert_recorded(left, right, journal.clone_pair),
+                    Change::Removed(left, _) => self.remove_recorded(left),
+                }
+            }
+        }
+        let redone = entry.is_some();
+        journal.undo.extend(entry);
+        self.journal = Some(journal);
+        redone
+    }
+
+    /// Remove the pair of a recorded left value while replaying the journal.
+    fn remove_recorded(&mut self, left: &T) {
+        if let Some(bucket_index) = self.find_bucket_left(left) {
+            self.remove_bucket(bucket_index);
+            self.rebuild_filter_if_stale();
+        }
+    }
+
+    /// Insert a recorded pair while replaying the journal.
+    fn insert_recorded(&mut self, left: &T, right: &U, clone_pair: fn(&T, &U) -> (T, U)) {
+        let (left, right) = clone_pair(left, right);
+        self.insert(left, right);
+    }
+}
@@ -0,0 +1,168 @@
+//! Read-only views of the left or the right values of a map as sets.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::{BuildHasher, Hash, RandomState};
+use allocator_api2::alloc::{Allocator, Global};
+use crate::{BiMap, LeftValues, RightValues};
+
+/// A set of values that can be compared with the key-set views of a map. It is implemented by
+/// [`LeftKeySet`], [`RightKeySet`] and [`HashSet`].
+pub trait KeySet<T> {
+    /// The number of values in the set.
+    fn key_count(&self) -> usize;
+
+    /// Check if the set contains the given value.
+    fn contains_key(&self, value: &T) -> bool;
+
+    /// Returns an iterator over the values in the set in arbitrary order.
+    fn keys<'s>(&'s self) -> impl Iterator<Item=&'s T> where T: 's;
+}
+
+impl<T, S> KeySet<T> for HashSet<T, S>
+    where T: Hash + Eq, S: BuildHasher
+{
+    fn key_count(&self) -> usize {
+        self.len()
+    }
+
+    fn contains_key(&self, value: &T) -> bool {
+        self.contains(value)
+    }
+
+    fn keys<'s>(&'s self) -> impl Iterator<Item=&'s T> where T: 's {
+        self.iter()
+    }
+}
+
+/// Check if two sets have no value in common. The smaller set is iterated and its values are
+/// looked up in the larger set.
+fn is_disjoint<T>(set: &impl KeySet<T>, other: &impl KeySet<T>) -> bool {
+    if set.key_count() <= other.key_count() {
+        set.keys().all(|value| !other.contains_key(value))
+    } else {
+        other.keys().all(|value| !set.contains_key(value))
+    }
+}
+
+/// Check if all values of `set` are in `other`.
+fn is_subset<T>(set: &impl KeySet<T>, other: &impl KeySet<T>) -> bool {
+    set.key_count() <= other.key_count() && set.keys().all(|value| other.contains_key(value))
+}
+
+/// Check if two sets hold the same values.
+fn is_equal<T>(set: &impl KeySet<T>, other: &impl KeySet<T>) -> bool {
+    set.key_count() == other.key_count() && set.keys().all(|value| other.contains_key(value))
+}
+
+macro_rules! key_set_view {
+    ($view:ident, $value:ident, $side:literal, $contains:ident, $values:ident, $iter:ident) => {
+        #[doc = concat!("A view of the ", $side, " values of a map as a set, returned by [`BiMap::", $side, "_keys`].")]
+        ///
+        /// Membership tests probe the index of the map, so they are as fast as lookups in the map,
+        /// and the set operations accept other views as well as [`HashSet`]s, see [`KeySet`]. The
+        /// view borrows the map, so it does not allocate.
+        pub struct $view<'a, T, U, H = RandomState, RH = RandomState, A: Allocator + Clone = Global>
+            where T: Hash + Eq, U: Hash + Eq
+        {
+            map: &'a BiMap<T, U, H, RH, A>,
+        }
+
+        impl<'a, T, U, H, RH, A: Allocator + Clone> $view<'a, T, U, H, RH, A>
+            where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+        {
+            pub(crate) fn new(map: &'a BiMap<T, U, H, RH, A>) -> Self {
+                $view { map }
+            }
+
+            #[doc = concat!("Check if the map contains a mapping for the given ", $side, " value.")]
+            #[must_use]
+            pub fn contains(&self, value: &$value) -> bool {
+                self.map.$contains(value)
+            }
+
+            /// The number of values in the set, which is the number of pairs in the map.
+            #[must_use]
+            pub fn len(&self) -> usize {
+                self.map.len()
+            }
+
+            /// Check if the map is empty.
+            #[must_use]
+            pub fn is_empty(&self) -> bool {
+                self.map.is_empty()
+            }
+
+            /// Returns an iterator over the values in the set in arbitrary order.
+            pub fn iter(&self) -> $iter<'a, T, U> {
+                self.map.$values()
+            }
+
+            /// Check if this set and `other` have no value in common.
+            #[must_use]
+            pub fn is_disjoint(&self, other: &impl KeySet<$value>) -> bool {
+                is_disjoint(self, other)
+            }
+
+            /// Check if all values of this set are in `other`.
+            #[must_use]
+            pub fn is_subset(&self, other: &impl KeySet<$value>) -> bool {
+                is_subset(self, other)
+            }
+
+            /// Check if all values of `other` are in this set.
+            #[must_use]
+            pub fn is_superset(&self, other: &impl KeySet<$value>) -> bool {
+                is_subset(other, self)
+            }
+        }
+
+        impl<T, U, H, RH, A: Allocator + Clone> KeySet<$value> for $view<'_, T, U, H, RH, A>
+            where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+        {
+            fn key_count(&self) -> usize {
+                self.len()
+            }
+
+            fn contains_key(&self, value: &$value) -> bool {
+                self.contains(value)
+            }
+
+            fn keys<'s>(&'s self) -> impl Iterator<Item=&'s $value> where $value: 's {
+                self.iter()
+            }
+        }
+
+        // the view only holds a reference, so it can be copied regardless of the values and hashers
+        impl<T, U, H, RH, A: Allocator + Clone> Clone for $view<'_, T, U, H, RH, A>
+            where T: Hash + Eq, U: Hash + Eq
+        {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<T, U, H, RH, A: Allocator + Clone> Copy for $view<'_, T, U, H, RH, A>
+            where T: Hash + Eq, U: Hash + Eq
+        {}
+
+        impl<T, U, H, RH, A: Allocator + Clone> fmt::Debug for $view<'_, T, U, H, RH, A>
+            where T: Hash + Eq + fmt::Debug, U: Hash + Eq + fmt::Debug, H: BuildHasher, RH: BuildHasher
+        {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_set().entries(self.iter()).finish()
+            }
+        }
+
+        impl<T, U, H, RH, A: Allocator + Clone, O> PartialEq<O> for $view<'_, T, U, H, RH, A>
+            where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher, O: KeySet<$value>
+        {
+            fn eq(&self, other: &O) -> bool {
+                is_equal(self, other)
+            }
+        }
+    };
+}
+
+key_set_view!(LeftKeySet, T, "left", contains_left, left_values, LeftValues);
+key_set_view!(RightKeySet, U, "right", contains_right, right_values, RightValues);
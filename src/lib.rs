@@ -1,200 +1,918 @@
 use std::cmp::max;
-use std::hash::{BuildHasher, Hash, Hasher, RandomState};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasher, Hash, RandomState};
 use std::mem;
+#[cfg(not(feature = "hashbrown-backend"))]
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use allocator_api2::alloc::{Allocator, Global};
+use filter::NegativeFilter;
+use journal::Journal;
+use errors::{DuplicatePairError, OccupiedError, RekeyError, TryReserveError};
+use side::{BuiltIndex, EmptyIndex, IndexSide, LeftSide, MapSide, RightSide};
+#[cfg(feature = "stats")]
+use stats::Counters;
+#[cfg(all(feature = "bytemuck", not(feature = "columnar")))]
+pub use bytes::FromBytesError;
+pub use changeset::ChangeSet;
+pub use columns::FromColumnsError;
+pub use cursor::CursorMut;
+#[cfg(not(feature = "hashbrown-backend"))]
+#[doc(hidden)]
+pub use debug::TableDump;
+#[cfg(feature = "ahash")]
+pub use hashers::ABiMap;
+#[cfg(feature = "fxhash")]
+pub use hashers::FxBiMap;
+pub use id_allocator::IdAllocator;
+pub use index::{Left, Right};
+pub use invariants::{InconsistencyReport, InvariantViolation, Side};
+pub use inverse::{InverseIter, InverseView};
+pub use iter::{Drain, IntoIter, Iter, LeftValues, RightValues};
+pub use iter_ext::{BiMapIteratorExt, FromPairsError};
+pub use key_set::{KeySet, LeftKeySet, RightKeySet};
+#[cfg(feature = "named")]
+#[doc(hidden)]
+pub use paste as __paste;
+pub use policy::DupPolicy;
+pub use seeded::FixedState;
+#[cfg(feature = "stats")]
+pub use stats::OpCounters;
+use storage::{Bucket, Storage};
+pub use transaction::{DisplacedPairs, Txn};
+pub use value_mut::{LeftMut, RightMut};
+
+#[cfg(all(feature = "bytemuck", not(feature = "columnar")))]
+mod bytes;
+mod bulk;
+mod changeset;
+mod columns;
+mod cursor;
+mod debug;
+mod filter;
+#[cfg(any(feature = "fxhash", feature = "ahash"))]
+mod hashers;
+mod id_allocator;
+mod index;
+mod invariants;
+mod inverse;
+mod iter;
+mod iter_ext;
+mod journal;
+mod key_set;
+mod macros;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod permutation;
+mod policy;
+#[cfg(feature = "quickcheck")]
+mod quickcheck_impl;
+mod retain;
+#[cfg(feature = "schemars")]
+mod schema;
+mod seeded;
+#[cfg(feature = "serde")]
+mod serialization;
+mod side;
+mod sorted;
+#[cfg(feature = "speedy")]
+mod speedy_impl;
+#[cfg(feature = "stats")]
+mod stats;
+mod storage;
+mod transaction;
+mod value_mut;
+#[cfg(feature = "annotated")]
+pub mod annotated;
+#[cfg(feature = "dense")]
+pub mod dense;
+pub mod errors;
+#[cfg(feature = "ttl")]
+pub mod expiring;
+#[cfg(feature = "lru")]
+pub mod lru;
+#[cfg(feature = "serde")]
+pub mod serde;
+#[cfg(feature = "serde")]
+pub mod serde_object;
+#[cfg(feature = "shared")]
+pub mod shared;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
 
 const DEFAULT_CAPACITY: usize = 32;
 
 const GROWTH_FACTOR: f64 = 2.0;
 
-const MAX_LOAD_FACTOR: f64 = 0.9;
+/// The maximum number of pairs a map holds without hash indices. Such small maps find their pairs
+/// by scanning the data array, and spill into the hashed representation when they grow past it.
+const SMALL_MAP_CAPACITY: usize = 8;
 
-const EMPTY_SLOT: usize = usize::MAX;
+/// The maximum load factor of the indices is `1 - 1 / LOAD_FACTOR_DIVISOR`, i.e. one in ten slots
+/// is always kept free. It is expressed as an integer divisor to avoid float math in the hot path.
+const LOAD_FACTOR_DIVISOR: usize = 10;
+
+/// Probe distances are stored in a byte per slot. Distances that do not fit are stored as this
+/// value, and the actual distance has to be computed by hashing the element.
+const SATURATED_DISTANCE: u8 = u8::MAX;
+
+/// Insertions that create a probe chain longer than this emit a warning with the `tracing` feature.
+#[cfg(feature = "tracing")]
+const PROBE_WARNING_DISTANCE: u8 = 64;
 
 // TODO instead of linear searching, use smart search from https://ieeexplore.ieee.org/stamp/stamp.jsp?tp=&arnumber=4568152
 /// A bi-directional map.
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct BiMap<T, U, H = RandomState, RH = RandomState>
-    where T: Hash + Eq, U: Hash + Eq
-{
-    data: Vec<Bucket<T, U>>,
-    left_index: Box<[usize]>,
-    right_index: Box<[usize]>,
-    hasher: H,
-    reverse_hasher: RH,
+///
+/// Besides the pairs themselves and the two hash indices, the map stores two `usize` back-references
+/// per pair, pointing at the pair's slots in both indices. They make removals constant-time apart
+/// from the backward shift, at the cost of `2 * size_of::<usize>()` additional bytes per pair.
+/// Each index slot additionally stores the probe distance of its entry in one byte, so probing and
+/// deletion rarely need to hash stored elements.
+///
+/// Maps with up to `SMALL_MAP_CAPACITY` pairs do not allocate the indices at all and look up their
+/// pairs with a linear scan instead. They transparently switch to the hashed representation when
+/// they grow past that size, and back when they are shrunk below it.
+///
+/// If a `Hash` or `Eq` implementation of the values panics, the operation unwinds before it
+/// modifies any pair, so the map keeps the pairs it had before the operation. It may have resized
+/// its indices already, and an insertion that reseeds the hashers keeps the inserted pair.
+///
+/// The pairs, the indices and the back-references are allocated in the allocator `A`, which is
+/// the global allocator unless the map is created with [`new_in`], [`with_capacity_in`] or
+/// [`with_hashers_in`]. Allocators implement the `Allocator` trait of the `allocator-api2` crate.
+///
+/// [`new_in`]: #method.new_in
+/// [`with_capacity_in`]: #method.with_capacity_in
+/// [`with_hashers_in`]: #method.with_hashers_in
+pub struct BiMap<T, U, H = RandomState, RH = RandomState, A: Allocator = Global> {
+    data: Storage<T, U, A>,
+    /// The index of the left values and their hasher.
+    left: IndexSide<H, A>,
+    /// The index of the right values and their hasher.
+    right: IndexSide<RH, A>,
+    /// The maximum number of pairs the indices can hold before the map has to grow. It is
+    /// recomputed whenever the capacity of the indices changes.
+    growth_threshold: usize,
+    /// The longest probe distance written into the indices since they were last rebuilt,
+    /// saturating at `SATURATED_DISTANCE`.
+    longest_probe: u8,
+    /// The policy for rebuilding the indices with fresh hashers, if reseeding is enabled.
+    reseed: Option<Reseed<H, RH>>,
+    /// Filters that reject lookups of absent values before probing, if they are enabled.
+    filter: Option<NegativeFilter>,
+    /// How the indices are rebuilt when they are resized.
+    resize_strategy: ResizeStrategy,
+    /// The callback that is notified after the indices were resized, if one is set.
+    resize_hook: Hook<ResizeCallback>,
+    /// The callback that is notified after a pair was inserted, if one is set.
+    insert_hook: Hook<PairCallback<T, U>>,
+    /// The callback that is notified after a pair was removed, if one is set.
+    remove_hook: Hook<PairCallback<T, U>>,
+    /// The recorded changes of the pairs, if the journal is enabled.
+    journal: Option<Journal<T, U>>,
+    /// The number of operations the map performed.
+    #[cfg(feature = "stats")]
+    counters: Counters,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-struct Bucket<T, U> {
-    left: T,
-    right: U,
+/// A policy for replacing the hashers of a map when an insertion creates a pathologically long
+/// probe chain. See [`BiMap::enable_reseeding`].
+#[derive(Clone, Debug)]
+struct Reseed<H, RH> {
+    /// The longest probe distance that is tolerated before the hashers are replaced.
+    max_probe_distance: u8,
+    /// Creates the replacement hashers.
+    hashers: fn() -> (H, RH),
+    /// Whether the indices have been reseeded at their current capacity already. Hashers that
+    /// collide regardless of their seed would otherwise cause a rebuild on every insertion.
+    exhausted: bool,
 }
 
-impl<T, U> Default for BiMap<T, U>
-    where T: Hash + Eq, U: Hash + Eq
+// function pointers cannot be compared meaningfully, so policies are compared by their limit and
+// whether they are exhausted, but not by the function that creates the hashers
+impl<H, RH> PartialEq for Reseed<H, RH> {
+    fn eq(&self, other: &Self) -> bool {
+        self.max_probe_distance == other.max_probe_distance && self.exhausted == other.exhausted
+    }
+}
+
+impl<H, RH> Eq for Reseed<H, RH> {}
+
+/// Why the indices of a map were resized. See [`BiMap::set_resize_hook`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ResizeReason {
+    /// An insertion did not fit into the indices.
+    Growth,
+    /// Capacity was reserved with [`BiMap::reserve`] or [`BiMap::try_reserve`].
+    Reserve,
+    /// The map was shrunk with [`BiMap::shrink_to_fit`] or [`BiMap::shrink_to`].
+    Shrink,
+}
+
+/// How a map rebuilds its indices when it resizes them. See [`BiMap::set_resize_strategy`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ResizeStrategy {
+    /// Build the new indices in fresh allocations, and free the old indices afterwards. While the
+    /// map resizes, it holds the indices at both capacities.
+    Rebuild,
+    /// Compute where the pairs go in the new indices first, then discard the old indices and resize
+    /// their allocations to the new capacity. While the map resizes, it holds at most the indices at
+    /// the larger capacity, at the cost of a second pass over the pairs and their back-references.
+    InPlace,
+}
+
+/// The resize strategy of new maps. The `in-place-resize` feature makes maps resize in place, e.g.
+/// to run the test suite with that strategy.
+const DEFAULT_RESIZE_STRATEGY: ResizeStrategy = if cfg!(feature = "in-place-resize") {
+    ResizeStrategy::InPlace
+} else {
+    ResizeStrategy::Rebuild
+};
+
+/// A completed resize of the indices of a map. See [`BiMap::set_resize_hook`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ResizeEvent {
+    /// The number of slots per index before the resize. Small maps have no index slots.
+    pub old_capacity: usize,
+    /// The number of slots per index after the resize.
+    pub new_capacity: usize,
+    /// The number of pairs in the map.
+    pub len: usize,
+    /// The operation that resized the indices.
+    pub reason: ResizeReason,
+}
+
+/// The callback type of [`BiMap::set_resize_hook`].
+pub type ResizeCallback = Box<dyn FnMut(ResizeEvent) + Send>;
+
+/// The callback type of [`BiMap::set_on_insert`] and [`BiMap::set_on_remove`].
+pub type PairCallback<T, U> = Box<dyn FnMut(&T, &U) + Send>;
+
+/// A hook of a map, which holds a callback if one is set. The hook is only ever accessed through a
+/// mutable reference to the map, so the mutex is never locked. It only makes the map `Sync` for
+/// callbacks that are not.
+struct Hook<C>(Mutex<Option<C>>);
+
+impl<C> Hook<C> {
+    const fn new() -> Self {
+        Hook(Mutex::new(None))
+    }
+
+    #[inline(always)]
+    fn get_mut(&mut self) -> &mut Option<C> {
+        // the mutex cannot be poisoned, since it is never locked
+        self.0.get_mut().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<C> Default for Hook<C> {
+    fn default() -> Self {
+        Hook::new()
+    }
+}
+
+// a callback cannot be cloned, so clones of a map start without hooks
+impl<C> Clone for Hook<C> {
+    fn clone(&self) -> Self {
+        Hook::default()
+    }
+}
+
+// callbacks cannot be compared, so hooks do not affect the equality of maps
+impl<C> PartialEq for Hook<C> {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl<C> Eq for Hook<C> {}
+
+impl<C> fmt::Debug for Hook<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Hook")
+    }
+}
+
+/// A slot of a hash index, which is either empty or points at a bucket in the pair storage. The
+/// bucket is stored incremented by one, so that empty slots are the niche of `NonZeroUsize` and a
+/// slot takes no more space than the bucket index itself.
+#[cfg(not(feature = "hashbrown-backend"))]
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct SlotIndex(Option<NonZeroUsize>);
+
+#[cfg(not(feature = "hashbrown-backend"))]
+const _: () = assert!(mem::size_of::<SlotIndex>() == mem::size_of::<usize>());
+
+#[cfg(not(feature = "hashbrown-backend"))]
+impl SlotIndex {
+    /// A slot that does not point at any bucket.
+    const EMPTY: SlotIndex = SlotIndex(None);
+
+    /// A slot that points at the given bucket.
+    #[inline(always)]
+    fn new(bucket: usize) -> Self {
+        SlotIndex(NonZeroUsize::new(bucket + 1))
+    }
+
+    /// The bucket the slot points at, or `None` if the slot is empty.
+    #[inline(always)]
+    fn bucket(self) -> Option<usize> {
+        self.0.map(|bucket| bucket.get() - 1)
+    }
+
+    /// The bucket the slot points at, for slots that are known to be occupied.
+    ///
+    /// # Panics
+    /// Panics if the slot is empty.
+    #[inline(always)]
+    fn occupied(self) -> usize {
+        self.bucket().expect("slot is empty")
+    }
+}
+
+/// Indices that were built for all pairs of a map, but are not installed in the map yet.
+struct BuiltIndices<A: Allocator> {
+    left: BuiltIndex<A>,
+    right: BuiltIndex<A>,
+}
+
+/// Restores the back-references of both sides of a map from their installed indices when dropped.
+/// It guards the construction of new indices, which overwrites the back-references and may unwind
+/// if hashing panics. Completed constructions forget the guard.
+struct BackReferenceGuard<'a, H, RH, A: Allocator + Clone> {
+    left: &'a mut IndexSide<H, A>,
+    right: &'a mut IndexSide<RH, A>,
+}
+
+impl<H, RH, A: Allocator + Clone> Drop for BackReferenceGuard<'_, H, RH, A> {
+    fn drop(&mut self) {
+        self.left.restore_back_references();
+        self.right.restore_back_references();
+    }
+}
+
+/// Creates an empty map with default-constructed hashers, like [`BiMap::new`] does for the default
+/// hashers.
+impl<T, U, H, RH> Default for BiMap<T, U, H, RH>
+    where H: Default, RH: Default
 {
     fn default() -> Self {
-        Self::new()
+        Self::with_default_hashers(SMALL_MAP_CAPACITY)
     }
 }
 
-impl<T, U> BiMap<T, U>
-    where T: Hash + Eq, U: Hash + Eq {
-    /// Create a new empty BiMap with the default capacity.
+/// Clones the pairs, the indices and the configuration of a map, but not its hooks, which cannot be
+/// cloned, and not its journal, so the clone starts without recording changes. [`clone_from`]
+/// clones into the allocations of the target map, so cloning a map into the same scratch map
+/// repeatedly does not allocate once the scratch map has the capacity.
+///
+/// [`clone_from`]: Clone::clone_from
+impl<T, U, H, RH, A> Clone for BiMap<T, U, H, RH, A>
+    where T: Clone, U: Clone, H: Clone, RH: Clone, A: Allocator + Clone
+{
+    fn clone(&self) -> Self {
+        BiMap {
+            data: self.data.clone(),
+            left: self.left.clone(),
+            right: self.right.clone(),
+            growth_threshold: self.growth_threshold,
+            longest_probe: self.longest_probe,
+            reseed: self.reseed.clone(),
+            filter: self.filter.clone(),
+            resize_strategy: self.resize_strategy,
+            resize_hook: self.resize_hook.clone(),
+            insert_hook: self.insert_hook.clone(),
+            remove_hook: self.remove_hook.clone(),
+            journal: None,
+            #[cfg(feature = "stats")]
+            counters: self.counters.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.data.clone_from(&source.data);
+        self.left.clone_from(&source.left);
+        self.right.clone_from(&source.right);
+        self.growth_threshold = source.growth_threshold;
+        self.longest_probe = source.longest_probe;
+        self.reseed.clone_from(&source.reseed);
+        self.filter.clone_from(&source.filter);
+        self.resize_strategy = source.resize_strategy;
+        self.resize_hook.clone_from(&source.resize_hook);
+        self.insert_hook.clone_from(&source.insert_hook);
+        self.remove_hook.clone_from(&source.remove_hook);
+        self.journal = None;
+        #[cfg(feature = "stats")]
+        self.counters.clone_from(&source.counters);
+    }
+}
+
+/// Two maps are equal if they contain the same pairs, regardless of their iteration order, their
+/// capacity, their hashers and their allocators. Maps with different hashers and allocators can be
+/// compared as well.
+impl<T, U, H, RH, A, H2, RH2, A2> PartialEq<BiMap<T, U, H2, RH2, A2>> for BiMap<T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher, A: Allocator + Clone,
+          H2: BuildHasher, RH2: BuildHasher, A2: Allocator + Clone
+{
+    fn eq(&self, other: &BiMap<T, U, H2, RH2, A2>) -> bool {
+        // the maps are bijections, so the pairs of one map cannot map to fewer pairs of the other
+        self.len() == other.len() && self.iter().all(|(left, right)| other.get_right(left) == Some(right))
+    }
+}
+
+impl<T, U, H, RH, A> Eq for BiMap<T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher, A: Allocator + Clone
+{}
+
+/// A map equals a `HashMap` with the same pairs, which requires the values of the `HashMap` to be
+/// unique.
+impl<T, U, H, RH, A, S> PartialEq<HashMap<T, U, S>> for BiMap<T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher, A: Allocator + Clone
+{
+    fn eq(&self, other: &HashMap<T, U, S>) -> bool {
+        // a HashMap with duplicate values has a value that is missing from the map, or a left value
+        // that is mapped to another right value
+        self.len() == other.len() && other.iter().all(|(left, right)| self.get_right(left) == Some(right))
+    }
+}
+
+impl<T, U, H, RH, A, S> PartialEq<BiMap<T, U, H, RH, A>> for HashMap<T, U, S>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher, A: Allocator + Clone
+{
+    fn eq(&self, other: &BiMap<T, U, H, RH, A>) -> bool {
+        other == self
+    }
+}
+
+/// Consumes the map and returns its pairs, see [`BiMap::into_pairs`].
+impl<T, U, H, RH, A: Allocator + Clone> From<BiMap<T, U, H, RH, A>> for Vec<(T, U)> {
+    fn from(map: BiMap<T, U, H, RH, A>) -> Self {
+        map.into_pairs()
+    }
+}
+
+/// The hash of a map does not depend on the iteration order of its pairs, like its equality: every
+/// pair is hashed on its own with a fixed hasher, and the hashes of the pairs are summed up.
+impl<T, U, H, RH, A> Hash for BiMap<T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, A: Allocator + Clone
+{
+    fn hash<S: std::hash::Hasher>(&self, state: &mut S) {
+        let sum = self.iter().fold(0u64, |sum, pair| {
+            let mut hasher = std::hash::DefaultHasher::new();
+            pair.hash(&mut hasher);
+            sum.wrapping_add(std::hash::Hasher::finish(&hasher))
+        });
+        state.write_usize(self.len());
+        state.write_u64(sum);
+    }
+}
+
+impl<T, U> BiMap<T, U> {
+    /// Create a new empty BiMap. It starts out as a small map and does not allocate its hash indices
+    /// until it grows past a handful of pairs.
     pub fn new() -> Self {
-        Self::with_capacity(DEFAULT_CAPACITY)
+        Self::with_capacity(SMALL_MAP_CAPACITY)
     }
 
     /// Create a new empty BiMap with the given capacity.
     pub fn with_capacity(capacity: usize) -> Self {
-        let capacity_with_load = Self::apply_load_factor(capacity);
-        let left_index = vec![EMPTY_SLOT; capacity_with_load].into_boxed_slice();
-        let right_index = vec![EMPTY_SLOT; capacity_with_load].into_boxed_slice();
-        BiMap {
-            data: Vec::with_capacity(capacity),
-            left_index,
-            right_index,
-            hasher: RandomState::default(),
-            reverse_hasher: RandomState::default(),
+        Self::with_capacity_and_hashers(capacity, RandomState::default(), RandomState::default())
+    }
+}
+
+impl<T, U> BiMap<T, U>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    /// Create a BiMap from two columns of values, pairing the values at the same positions. The map
+    /// is allocated once for all pairs, and the pairs are moved out of the columns without
+    /// collecting them first.
+    ///
+    /// All left values and all right values must be distinct, otherwise the rows of the first
+    /// duplicate value are returned in the error. See [`from_columns_last_wins`] for a variant that
+    /// accepts duplicates, and [`from_columns_with_hashers`] for other hashers.
+    ///
+    /// [`from_columns_last_wins`]: #method.from_columns_last_wins
+    /// [`from_columns_with_hashers`]: #method.from_columns_with_hashers
+    pub fn from_columns(lefts: Vec<T>, rights: Vec<U>) -> Result<Self, FromColumnsError> {
+        Self::from_columns_with_hashers(lefts, rights, RandomState::default(), RandomState::default())
+    }
+
+    /// Create a BiMap from two columns of values like [`from_columns`], but insert the rows in order
+    /// with [`insert`], so that later rows replace the mappings of earlier rows with equal values.
+    /// The map may hold fewer pairs than the columns have rows.
+    ///
+    /// [`from_columns`]: #method.from_columns
+    /// [`insert`]: #method.insert
+    pub fn from_columns_last_wins(lefts: Vec<T>, rights: Vec<U>) -> Result<Self, FromColumnsError> {
+        Self::from_columns_last_wins_with_hashers(lefts, rights, RandomState::default(), RandomState::default())
+    }
+}
+
+impl<T> BiMap<T, usize>
+    where T: Hash + Eq {
+    /// Create a BiMap that assigns dense indices to the given items in order of their first
+    /// occurrence, i.e. the first item is mapped to 0, the next distinct item to 1, and so on.
+    /// Duplicates keep the index of their first occurrence, so the indices are exactly
+    /// `0..map.len()`. The map is allocated once from the lower bound of the iterator's size hint.
+    pub fn from_indexed<I>(items: I) -> Self
+        where I: IntoIterator<Item=T>
+    {
+        let items = items.into_iter();
+        let mut map = Self::with_capacity(items.size_hint().0);
+        for item in items {
+            if !map.contains_left(&item) {
+                let index = map.len();
+                map.insert_unique_unchecked(item, index);
+            }
         }
+        map
     }
 }
 
 impl<T, U, H, RH> BiMap<T, U, H, RH>
-    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+    where H: Default, RH: Default
+{
+    /// Create a new empty BiMap with the given capacity and default-constructed hashers, e.g. an
+    /// `FxBiMap` or an `ABiMap` with the `fxhash` and `ahash` features. Unlike [`new`] and
+    /// [`with_capacity`], this constructor is available for all hashers, but the hashers cannot
+    /// be inferred, so the type of the map must be known.
+    ///
+    /// [`new`]: #method.new
+    /// [`with_capacity`]: #method.with_capacity
+    pub fn with_default_hashers(capacity: usize) -> Self {
+        Self::with_capacity_and_hashers(capacity, H::default(), RH::default())
+    }
+}
+
+impl<T, U, H> BiMap<T, U, H, H>
+    where H: Clone
 {
+    /// Create a new empty BiMap that hashes both sides with the given hasher, like [`new`] does
+    /// with the default hashers.
+    ///
+    /// [`new`]: #method.new
+    pub fn with_hasher(hasher: H) -> Self {
+        Self::with_capacity_and_hasher(SMALL_MAP_CAPACITY, hasher)
+    }
+
+    /// Create a new empty BiMap with the given capacity that hashes both sides with the given
+    /// hasher. The hasher is cloned for the right values. Like [`with_capacity`], the map can hold
+    /// `capacity` pairs without resizing.
+    ///
+    /// [`with_capacity`]: #method.with_capacity
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: H) -> Self {
+        Self::with_capacity_and_hashers(capacity, hasher.clone(), hasher)
+    }
+}
+
+impl<T, U, H, RH> BiMap<T, U, H, RH> {
+    /// Create a new empty BiMap with the given hashers, in a const context. The map does not
+    /// allocate until the first pair is inserted, so it can initialize a `static`, e.g. behind a
+    /// `Mutex` with hashers such as [`BuildHasherDefault`](std::hash::BuildHasherDefault).
+    pub const fn new_with_hashers(hasher: H, reverse_hasher: RH) -> Self {
+        BiMap {
+            data: Storage::new(),
+            left: IndexSide::new(hasher),
+            right: IndexSide::new(reverse_hasher),
+            growth_threshold: Self::growth_threshold_for(0),
+            longest_probe: 0,
+            reseed: None,
+            filter: None,
+            resize_strategy: DEFAULT_RESIZE_STRATEGY,
+            resize_hook: Hook::new(),
+            insert_hook: Hook::new(),
+            remove_hook: Hook::new(),
+            journal: None,
+            #[cfg(feature = "stats")]
+            counters: Counters::new(),
+        }
+    }
+
     /// Create a new empty BiMap with the given capacity and hashers.
     pub fn with_hashers(capacity: usize, hasher: H, reverse_hasher: RH) -> Self {
-        let left_index = vec![EMPTY_SLOT; capacity].into_boxed_slice();
-        let right_index = vec![EMPTY_SLOT; capacity].into_boxed_slice();
+        Self::with_hashers_in(capacity, hasher, reverse_hasher, Global)
+    }
+
+    /// Create a new empty BiMap with room for `capacity` pairs at the maximum load factor of the
+    /// indices. Unlike [`with_hashers`], which allocates exactly `capacity` index slots, this is
+    /// the capacity that [`with_capacity`] reserves.
+    ///
+    /// [`with_hashers`]: #method.with_hashers
+    /// [`with_capacity`]: #method.with_capacity
+    fn with_capacity_and_hashers(capacity: usize, hasher: H, reverse_hasher: RH) -> Self {
+        Self::with_capacity_and_hashers_in(capacity, hasher, reverse_hasher, Global)
+    }
+}
+
+impl<T, U, A: Allocator + Clone> BiMap<T, U, RandomState, RandomState, A> {
+    /// Create a new empty BiMap like [`new`], whose pairs and indices are allocated in the given
+    /// allocator.
+    ///
+    /// [`new`]: #method.new
+    pub fn new_in(alloc: A) -> Self {
+        Self::with_capacity_in(SMALL_MAP_CAPACITY, alloc)
+    }
+
+    /// Create a new empty BiMap with the given capacity like [`with_capacity`], whose pairs and
+    /// indices are allocated in the given allocator. Resizing, growing, shrinking and clearing the
+    /// map allocate and free its memory in that allocator as well. Temporary buffers of bulk
+    /// operations, the negative lookup filters and the journal use the global allocator.
+    ///
+    /// [`with_capacity`]: #method.with_capacity
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        Self::with_capacity_and_hashers_in(capacity, RandomState::default(), RandomState::default(), alloc)
+    }
+}
+
+impl<T, U, H, RH, A: Allocator + Clone> BiMap<T, U, H, RH, A> {
+    /// Create a new empty BiMap with the given capacity and hashers like [`with_hashers`], whose
+    /// pairs and indices are allocated in the given allocator.
+    ///
+    /// [`with_hashers`]: #method.with_hashers
+    pub fn with_hashers_in(capacity: usize, hasher: H, reverse_hasher: RH, alloc: A) -> Self {
+        let index_capacity = if capacity <= SMALL_MAP_CAPACITY { 0 } else { capacity };
         BiMap {
-            data: Vec::with_capacity(capacity),
-            left_index,
-            right_index,
-            hasher,
-            reverse_hasher,
+            data: Storage::with_capacity_in(capacity, alloc.clone()),
+            left: IndexSide::with_capacity_in(index_capacity, index_capacity, hasher, alloc.clone()),
+            right: IndexSide::with_capacity_in(index_capacity, index_capacity, reverse_hasher, alloc),
+            growth_threshold: Self::growth_threshold_for(index_capacity),
+            longest_probe: 0,
+            reseed: None,
+            filter: None,
+            resize_strategy: DEFAULT_RESIZE_STRATEGY,
+            resize_hook: Hook::default(),
+            insert_hook: Hook::default(),
+            remove_hook: Hook::default(),
+            journal: None,
+            #[cfg(feature = "stats")]
+            counters: Counters::new(),
+        }
+    }
+
+    /// Create a new empty BiMap in the given allocator, with room for `capacity` pairs at the
+    /// maximum load factor of the indices, like [`with_capacity_and_hashers`].
+    ///
+    /// [`with_capacity_and_hashers`]: #method.with_capacity_and_hashers
+    fn with_capacity_and_hashers_in(capacity: usize, hasher: H, reverse_hasher: RH, alloc: A) -> Self {
+        let capacity_with_load = Self::index_capacity_for(capacity);
+        // small maps do not keep back-references
+        let buckets = if capacity_with_load == 0 { 0 } else { capacity };
+        BiMap {
+            data: Storage::with_capacity_in(capacity, alloc.clone()),
+            left: IndexSide::with_capacity_in(capacity_with_load, buckets, hasher, alloc.clone()),
+            right: IndexSide::with_capacity_in(capacity_with_load, buckets, reverse_hasher, alloc),
+            growth_threshold: Self::growth_threshold_for(capacity_with_load),
+            longest_probe: 0,
+            reseed: None,
+            filter: None,
+            resize_strategy: DEFAULT_RESIZE_STRATEGY,
+            resize_hook: Hook::default(),
+            insert_hook: Hook::default(),
+            remove_hook: Hook::default(),
+            journal: None,
+            #[cfg(feature = "stats")]
+            counters: Counters::new(),
         }
     }
 
     /// Increase a capacity to make sure no reallocation is required while filling the capacity even
     /// when the maximum load factor is reached.
+    ///
+    /// # Panics
+    /// Panics, if the resulting capacity overflows usize.
     fn apply_load_factor(capacity: usize) -> usize {
-        (capacity as f64 / MAX_LOAD_FACTOR) as usize + 1
+        Self::checked_load_factor(capacity).expect("capacity overflow")
     }
 
-    /// Convert an element into an index by hashing it and mapping the hash to the given capacity
-    fn hash_to_index<E, G>(hasher: &G, element: &E, capacity: usize) -> usize
-        where E: Hash, G: BuildHasher
+    /// Increase a capacity like [`apply_load_factor`], or return None if it overflows usize.
+    ///
+    /// [`apply_load_factor`]: #method.apply_load_factor
+    fn checked_load_factor(capacity: usize) -> Option<usize> {
+        capacity.checked_add(capacity / (LOAD_FACTOR_DIVISOR - 1) + 1)
+    }
+
+    /// Compute the capacity of the indices required to hold the given number of pairs. Pair counts
+    /// that fit into a small map do not need indices at all, so their index capacity is zero.
+    ///
+    /// # Panics
+    /// Panics, if the resulting capacity overflows usize.
+    fn index_capacity_for(len: usize) -> usize {
+        if len <= SMALL_MAP_CAPACITY {
+            0
+        } else {
+            Self::apply_load_factor(len)
+        }
+    }
+
+    /// Compute the maximum number of pairs that fit into indices of the given capacity without
+    /// exceeding the maximum load factor. This is the inverse of [`apply_load_factor`].
+    /// Indices of capacity zero belong to a small map, which holds up to `SMALL_MAP_CAPACITY` pairs.
+    ///
+    /// [`apply_load_factor`]: #method.apply_load_factor
+    const fn growth_threshold_for(capacity: usize) -> usize {
+        if capacity == 0 {
+            return SMALL_MAP_CAPACITY;
+        }
+        (capacity - capacity / LOAD_FACTOR_DIVISOR).saturating_sub(1)
+    }
+
+    /// Returns a reference to the allocator of the pairs and the indices of the map.
+    pub fn allocator(&self) -> &A {
+        self.data.allocator()
+    }
+
+    /// Returns the number of bijections stored in the map, meaning it is half the number of values.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns true if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns a reference to the map’s `BuildHasher` for left values.
+    pub fn hasher_left(&self) -> &H {
+        &self.left.hasher
+    }
+
+    /// Returns a reference to the map’s `BuildHasher` for right values.
+    pub fn hasher_right(&self) -> &RH {
+        &self.right.hasher
+    }
+
+    /// Returns an iterator over the mappings in the map in arbitrary order.
+    pub fn iter(&self) -> Iter<'_, T, U> {
+        Iter { pairs: self.data.iter() }
+    }
+
+    /// Returns an iterator over the left values in the map in arbitrary order.
+    pub fn left_values(&self) -> LeftValues<'_, T, U> {
+        LeftValues { values: self.data.lefts() }
+    }
+
+    /// Returns an iterator over the right values in the map in arbitrary order.
+    pub fn right_values(&self) -> RightValues<'_, T, U> {
+        RightValues { values: self.data.rights() }
+    }
+
+    /// Consumes the map and returns its pairs in iteration order, in a vector of the global
+    /// allocator.
+    pub fn into_pairs(self) -> Vec<(T, U)> {
+        self.data.into_pairs().collect()
+    }
+
+    /// Consumes the map and returns a `HashMap` from its left values to its right values, dropping
+    /// the index of the right values.
+    pub fn into_left_hashmap(self) -> HashMap<T, U>
+        where T: Hash + Eq
     {
-        let mut hasher = hasher.build_hasher();
-        element.hash(&mut hasher);
-        hasher.finish() as usize % capacity
+        self.into_left_hashmap_with_hasher(RandomState::default())
     }
 
-    /// Get the ideal index (i.e. without collisions) for a left value under the current
-    /// container size.
-    #[inline(always)]
-    fn get_ideal_index_left(&self, left: &T) -> usize {
-        Self::hash_to_index(&self.hasher, left, self.current_capacity())
+    /// Consumes the map and returns a `HashMap` from its right values to its left values, dropping
+    /// the index of the left values.
+    pub fn into_right_hashmap(self) -> HashMap<U, T>
+        where U: Hash + Eq
+    {
+        self.into_right_hashmap_with_hasher(RandomState::default())
     }
 
-    /// Get the ideal index (i.e. without collisions) for a right value under the current
-    /// container size.
-    #[inline(always)]
-    fn get_ideal_index_right(&self, right: &U) -> usize {
-        Self::hash_to_index(&self.reverse_hasher, right, self.current_capacity())
+    /// Consumes the map and returns a `HashMap` from its left values to its right values, which
+    /// uses the given hasher. See [`into_left_hashmap`].
+    ///
+    /// [`into_left_hashmap`]: #method.into_left_hashmap
+    pub fn into_left_hashmap_with_hasher<S>(self, hasher: S) -> HashMap<T, U, S>
+        where T: Hash + Eq, S: BuildHasher
+    {
+        let mut forward = HashMap::with_capacity_and_hasher(self.len(), hasher);
+        forward.extend(self.data.into_pairs());
+        forward
     }
 
-    /// Perform the probing algorithm on the given hash index to find the index of the element.
-    /// This method is used for both left and right values, and requires the ideal index function
-    /// and the lookup function to be passed in. It is not intended to be called directly, but rather
-    /// through the lookup_index_left and lookup_index_right methods, or during rehashing.
+    /// Consumes the map and returns a `HashMap` from its right values to its left values, which
+    /// uses the given hasher. See [`into_right_hashmap`].
     ///
-    /// # Parameters
-    /// * `element` - The element for which to find the index.
-    /// * `hash_index` - The hash index to probe.
-    /// * `hasher` - The hasher to use.
-    /// * `lookup` - A function that returns elements of the element type from a bucket.
-    /// * `buckets` - The buckets that contain the elements.
-    /// * `capacity` - The capacity of the hash index.
-    #[inline(always)]
-    fn probe_index<E, G>(element: &E, hash_index: &[usize], hasher: &G, lookup: fn(&Bucket<T, U>) -> &E, buckets: &[Bucket<T, U>], capacity: usize) -> Result<usize, usize>
-        where E: Hash + Eq, G: BuildHasher
+    /// [`into_right_hashmap`]: #method.into_right_hashmap
+    pub fn into_right_hashmap_with_hasher<S>(self, hasher: S) -> HashMap<U, T, S>
+        where U: Hash + Eq, S: BuildHasher
     {
-        let ideal_index = Self::hash_to_index(hasher, element, capacity);
-        let mut index = ideal_index;
-        let mut dist = 0;
-        while hash_index[index] < EMPTY_SLOT {
-            let bucket = &buckets[hash_index[index]];
-            if lookup(bucket) == element {
-                return Ok(index);
-            } else {
-                let target_probe_dist = index.wrapping_sub(Self::hash_to_index(hasher, lookup(bucket), capacity)).rem_euclid(capacity);
-                if dist > target_probe_dist {
-                    return Err(index);
-                }
-            }
+        let mut backward = HashMap::with_capacity_and_hasher(self.len(), hasher);
+        backward.extend(self.data.into_pairs().map(|(left, right)| (right, left)));
+        backward
+    }
+}
 
-            index = (index + 1) % capacity;
-            dist += 1;
+impl<T, U, H, RH> BiMap<T, U, H, RH>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    /// Create a BiMap from two columns of values with the given hashers. See [`from_columns`].
+    ///
+    /// [`from_columns`]: #method.from_columns
+    pub fn from_columns_with_hashers(lefts: Vec<T>, rights: Vec<U>, hasher: H, reverse_hasher: RH) -> Result<Self, FromColumnsError> {
+        if lefts.len() != rights.len() {
+            return Err(FromColumnsError::LengthMismatch { lefts: lefts.len(), rights: rights.len() });
+        }
+
+        // without duplicates there are no removals, so every pair is stored in the bucket of its row
+        let mut map = Self::with_capacity_and_hashers(lefts.len(), hasher, reverse_hasher);
+        for (row, (left, right)) in lefts.into_iter().zip(rights).enumerate() {
+            if let Some(first) = map.find_bucket_left(&left) {
+                return Err(FromColumnsError::DuplicateLeft { first, duplicate: row });
+            }
+            if let Some(first) = map.find_bucket_right(&right) {
+                return Err(FromColumnsError::DuplicateRight { first, duplicate: row });
+            }
+            map.insert_unique_unchecked(left, right);
         }
-        Err(index)
+        Ok(map)
     }
 
-    /// Look up the index of an element in the map. This method is used for both left and right
-    /// values, and requires the ideal index function and the lookup function to be passed in.
-    /// It is not intended to be called directly, but rather through the lookup_index_left and
-    /// lookup_index_right methods.
+    /// Create a BiMap from two columns of values with the given hashers. See
+    /// [`from_columns_last_wins`].
     ///
-    /// # Arguments
-    /// * `element` - The element to look up.
-    /// * `ideal_index_func` - A function that returns the ideal index for the element type.
-    /// * `lookup` - A function that returns elements of the element type from a bucket.
-    ///
-    /// # Returns
-    /// The index where the element is stored or would be stored. If the element is not in the map,
-    /// the index of the first empty bucket is returned.
-    ///
-    /// # Panics
-    /// This method panics if the map is full.
+    /// [`from_columns_last_wins`]: #method.from_columns_last_wins
+    pub fn from_columns_last_wins_with_hashers(lefts: Vec<T>, rights: Vec<U>, hasher: H, reverse_hasher: RH) -> Result<Self, FromColumnsError> {
+        if lefts.len() != rights.len() {
+            return Err(FromColumnsError::LengthMismatch { lefts: lefts.len(), rights: rights.len() });
+        }
+
+        let mut map = Self::with_capacity_and_hashers(lefts.len(), hasher, reverse_hasher);
+        for (left, right) in lefts.into_iter().zip(rights) {
+            map.insert(left, right);
+        }
+        Ok(map)
+    }
+}
+
+impl<T, U, H, RH, A> BiMap<T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher, A: Allocator + Clone
+{
+    /// Find the bucket that holds the given value of a side, in either representation of the map.
     #[inline(always)]
-    fn lookup_index<E, G>(&self, element: &E, hash_index: &[usize], hasher: &G, lookup: fn(&Bucket<T, U>) -> &E) -> Result<usize, usize>
-        where E: Hash + Eq, G: BuildHasher
-    {
-        Self::probe_index(element, hash_index, hasher, lookup, &self.data, self.current_capacity())
+    fn find_bucket<S: MapSide<T, U, H, RH, A>>(&self, value: &S::Value) -> Option<usize> {
+        if self.is_small() {
+            return S::values(&self.data).position(|stored| stored == value);
+        }
+
+        let index = S::index(self);
+        let Some(filter) = &self.filter else {
+            return self.lookup_index::<S>(value).ok().map(|slot| index.bucket(slot));
+        };
+
+        // the hash is computed once for both the filter and the index
+        let hash = index.hasher.hash_one(value);
+        if !S::filter(filter).may_contain(hash) {
+            return None;
+        }
+
+        self.lookup_index_from::<S>(value, side::reduce_hash(hash, self.current_capacity()))
+            .ok()
+            .map(|slot| index.bucket(slot))
     }
 
-    /// Find the index that the left value is stored at or would be stored at. If the left value
-    /// is not in the map, the returned index is either empty or contains a bucket with a lower
-    /// probe distance.
+    /// Find the bucket that holds the given left value, in either representation of the map.
+    #[inline(always)]
+    fn find_bucket_left(&self, left: &T) -> Option<usize> {
+        self.find_bucket::<LeftSide>(left)
+    }
+
+    /// Find the bucket that holds the given right value, in either representation of the map.
+    #[inline(always)]
+    fn find_bucket_right(&self, right: &U) -> Option<usize> {
+        self.find_bucket::<RightSide>(right)
+    }
+
+    /// Find the index that a value is stored at or would be stored at in the index of its side. If
+    /// the value is not in the map, the returned index is either empty or contains a bucket with a
+    /// lower probe distance.
     ///
     /// # Arguments
-    /// * `left` - The left value to look up.
+    /// * `value` - The value to look up.
     ///
     /// # Returns
-    /// The index where the left value is stored or would be stored. If the left value is not in the
-    /// map, the index of the first empty bucket is returned.
+    /// The index where the value is stored or would be stored. If the value is not in the map, the
+    /// index of the first empty bucket is returned.
     ///
     /// # Panics
     /// This method panics if the map is full.
-    fn lookup_index_left(&self, left: &T) -> Result<usize, usize> {
-        self.lookup_index(left, &self.left_index, &self.hasher, |bucket: &Bucket<T, U>| &bucket.left)
+    fn lookup_index<S: MapSide<T, U, H, RH, A>>(&self, value: &S::Value) -> Result<usize, usize> {
+        self.lookup_index_from::<S>(value, S::index(self).ideal_index(value))
     }
 
-    /// Find the index that the right value is stored at or would be stored at. If the right value
-    /// is not in the map, the returned index is either empty or contains a bucket with a lower
-    /// probe distance.
-    ///
-    /// # Arguments
-    /// * `right` - The right value to look up.
-    ///
-    /// # Returns
-    /// The index where the right value is stored or would be stored. If the right value is not in the
-    /// map, the index of the first empty bucket is returned.
+    /// Find the index that a value is stored at or would be stored at, reusing an ideal index that
+    /// has already been computed for it. See [`lookup_index`].
     ///
-    /// # Panics
-    /// This method panics if the map is full.
-    fn lookup_index_right(&self, right: &U) -> Result<usize, usize> {
-        self.lookup_index(right, &self.right_index, &self.reverse_hasher, |bucket: &Bucket<T, U>| &bucket.right)
+    /// [`lookup_index`]: #method.lookup_index
+    #[inline(always)]
+    fn lookup_index_from<S: MapSide<T, U, H, RH, A>>(&self, value: &S::Value, ideal_index: usize) -> Result<usize, usize> {
+        S::index(self).probe(value, ideal_index, &self.data, S::value)
     }
 
     /// Push a new bucket to the tail of the data array. This method is used when both left and right
@@ -205,224 +923,609 @@ impl<T, U, H, RH> BiMap<T, U, H, RH>
     /// # Parameters
     /// * `bucket` - The bucket to push.
     /// * `left_index` - The index in the left index where to insert the mapping.
+    /// * `left_ideal_index` - The ideal index of the left value.
     /// * `right_index` - The index in the right index where to insert the mapping.
-    fn push_new_bucket(&mut self, bucket: Bucket<T, U>, left_index: usize, right_index: usize) {
+    /// * `right_ideal_index` - The ideal index of the right value.
+    fn push_new_bucket(&mut self, bucket: Bucket<T, U>, left_index: usize, left_ideal_index: usize, right_index: usize, right_ideal_index: usize) {
+        self.filter_insert(&bucket.left, &bucket.right);
         self.data.push(bucket);
-        self.insert_mapping_left(left_index, self.len() - 1);
-        self.insert_mapping_right(right_index, self.len() - 1);
+        self.left.push_bucket();
+        self.right.push_bucket();
+        self.insert_mapping::<LeftSide>(left_index, left_ideal_index, self.len() - 1);
+        self.insert_mapping::<RightSide>(right_index, right_ideal_index, self.len() - 1);
+    }
+
+    /// Delete a bucket at the given index. It will update one entry in each index, since
+    /// the last bucket is moved to the deleted bucket's position.
+    /// It will also delete the mappings for the bucket in both left and right indices.
+    ///
+    /// Note: If the bucket to delete is NOT the last bucket, the last bucket will move, meaning
+    /// that the indices of the moved bucket are no longer valid. This method ensures the mappings
+    /// are updated correctly, but any temporary variables that hold the indices of the moved bucket
+    /// will be invalid.
+    ///
+    /// # Parameters
+    /// * `bucket_index` - The index of the bucket to delete.
+    fn delete_bucket(&mut self, bucket_index: usize) -> Bucket<T, U> {
+        assert!(bucket_index < self.len(), "index out of bounds");
+
+        if self.is_small() {
+            return self.data.swap_remove(bucket_index);
+        }
+
+        // hashing may panic, so it is done before either index is modified
+        let left = self.left.slot_of(bucket_index);
+        let right = self.right.slot_of(bucket_index);
+        let shifted_left = self.shifted_saturated::<LeftSide>(left);
+        let shifted_right = self.shifted_saturated::<RightSide>(right);
+        self.left.delete_mapping(left, shifted_left);
+        self.right.delete_mapping(right, shifted_right);
+
+        self.swap_remove_bucket(bucket_index)
+    }
+
+    /// Delete a bucket like [`delete_bucket`], and notify the remove hook.
+    ///
+    /// [`delete_bucket`]: #method.delete_bucket
+    fn remove_bucket(&mut self, bucket_index: usize) -> Bucket<T, U> {
+        let bucket = self.delete_bucket(bucket_index);
+        self.notify_remove(&bucket);
+        self.seal_journal();
+        bucket
+    }
+
+    /// Remove a bucket whose mappings have already been deleted or redirected from both indices.
+    /// The last bucket is moved into its position, and the mappings of the moved bucket are updated.
+    ///
+    /// # Parameters
+    /// * `bucket_index` - The index of the bucket to remove.
+    fn swap_remove_bucket(&mut self, bucket_index: usize) -> Bucket<T, U> {
+        assert!(bucket_index < self.len(), "index out of bounds");
+
+        if self.is_small() {
+            return self.data.swap_remove(bucket_index);
+        }
+
+        if let Some(filter) = &mut self.filter {
+            filter.stale += 1;
+        }
+
+        // update metadata of the last bucket, which is moved into the hole, unless it is the
+        // bucket that is removed
+        self.left.swap_remove(bucket_index);
+        self.right.swap_remove(bucket_index);
+        self.data.swap_remove(bucket_index)
+    }
+
+    /// Replace a bucket at the given index with a new bucket. The old bucket is returned.
+    /// No changes to the indices are made, and the new values must have been recorded in the
+    /// negative lookup filters already.
+    fn replace_bucket(&mut self, bucket_index: usize, bucket: Bucket<T, U>) -> Bucket<T, U> {
+        assert!(bucket_index < self.len(), "index out of bounds");
+        if let Some(filter) = &mut self.filter {
+            filter.stale += 1;
+        }
+        self.data.replace(bucket_index, bucket)
+    }
+
+    /// Notify the insert hook of the pair at `bucket_index`, if a hook is set.
+    #[inline(always)]
+    fn notify_insert(&mut self, bucket_index: usize) {
+        #[cfg(feature = "paranoid")]
+        invariants::verify_inserted(self, bucket_index);
+        #[cfg(feature = "stats")]
+        {
+            self.counters.inserts += 1;
+        }
+        if let Some(hook) = self.insert_hook.get_mut() {
+            hook(self.data.left(bucket_index), self.data.right(bucket_index));
+        }
+        // an insertion is the last change of an operation
+        if let Some(journal) = &mut self.journal {
+            journal.record_inserted(self.data.left(bucket_index), self.data.right(bucket_index));
+            journal.seal();
+        }
+    }
+
+    /// Notify the remove hook of a pair that was removed from the map, if a hook is set.
+    #[inline(always)]
+    fn notify_remove(&mut self, bucket: &Bucket<T, U>) {
+        #[cfg(feature = "stats")]
+        {
+            self.counters.removals += 1;
+        }
+        if let Some(hook) = self.remove_hook.get_mut() {
+            hook(&bucket.left, &bucket.right);
+        }
+        if let Some(journal) = &mut self.journal {
+            journal.record_removed(&bucket.left, &bucket.right);
+        }
+    }
+
+    /// Notify the remove hook of the pair at `bucket_index`, whose values are no longer indexed
+    /// and are about to be mutated in place, if a hook is set.
+    #[inline(always)]
+    fn notify_remove_at(&mut self, bucket_index: usize) {
+        #[cfg(feature = "stats")]
+        {
+            self.counters.removals += 1;
+        }
+        if let Some(hook) = self.remove_hook.get_mut() {
+            hook(self.data.left(bucket_index), self.data.right(bucket_index));
+        }
+        if let Some(journal) = &mut self.journal {
+            journal.record_removed(self.data.left(bucket_index), self.data.right(bucket_index));
+        }
+    }
+
+    /// Finish the journal entry of the operation in progress, if the journal is enabled. Insertions
+    /// finish their entry when they notify the insert hook, so only operations that end with a
+    /// removal need to finish their entry explicitly.
+    fn seal_journal(&mut self) {
+        if let Some(journal) = &mut self.journal {
+            journal.seal();
+        }
+    }
+
+    /// Record the removal of all pairs as one journal entry, if the journal is enabled, and count
+    /// the removals.
+    fn record_clear(&mut self) {
+        #[cfg(feature = "stats")]
+        {
+            self.counters.removals += self.len() as u64;
+        }
+        if let Some(journal) = &mut self.journal {
+            for (left, right) in self.data.iter() {
+                journal.record_removed(left, right);
+            }
+            journal.seal();
+        }
+    }
+
+    /// Swap the positions of two buckets in the data array, and redirect their mappings.
+    fn swap_buckets(&mut self, a: usize, b: usize) {
+        if a == b {
+            return;
+        }
+
+        self.data.swap(a, b);
+        if !self.is_small() {
+            self.left.swap(a, b);
+            self.right.swap(a, b);
+        }
+    }
+
+    /// Move every bucket to its new position, given as a permutation of the bucket positions, and
+    /// redirect the mappings. No value is hashed or compared.
+    fn permute_buckets(&mut self, mut new_positions: Vec<usize>) {
+        debug_assert_eq!(new_positions.len(), self.len());
+
+        // apply the permutation in place, moving each bucket into its final position
+        for bucket in 0..self.len() {
+            while new_positions[bucket] != bucket {
+                let target = new_positions[bucket];
+                self.data.swap(bucket, target);
+                if !self.is_small() {
+                    self.left.swap(bucket, target);
+                    self.right.swap(bucket, target);
+                }
+                new_positions.swap(bucket, target);
+            }
+        }
+    }
+
+    /// Insert a bucket that was removed from `bucket_index` by [`delete_bucket`] again, at its old
+    /// position. The bucket that the deletion moved into that position moves back to the tail, so
+    /// the data array is in the same order as before the deletion. Neither value of the bucket may
+    /// be in the map.
+    ///
+    /// [`delete_bucket`]: #method.delete_bucket
+    fn restore_bucket(&mut self, bucket_index: usize, bucket: Bucket<T, U>) {
+        self.insert_unique_unchecked(bucket.left, bucket.right);
+        self.swap_buckets(bucket_index, self.len() - 1);
+    }
+
+    /// Replace the value of one side of a bucket with a value that is not in the map, and return the
+    /// old value.
+    fn replace_at<S: MapSide<T, U, H, RH, A>>(&mut self, bucket_index: usize, value: S::Value) -> S::Value {
+        self.unindex::<S>(bucket_index);
+        let replaced = mem::replace(S::value_mut(&mut self.data, bucket_index), value);
+        if self.reindex::<S>(bucket_index).is_err() {
+            unreachable!("the value is not in the map");
+        }
+        self.notify_insert(bucket_index);
+        replaced
+    }
+
+    /// Insert metadata into the index of a side for the given bucket, and track the longest probe
+    /// distance. See [`IndexSide::insert_mapping`].
+    ///
+    /// # Parameters
+    /// * `mapping_index` - The index in the index of the side to insert at. It must be the index
+    ///   returned by the `lookup_index` method. The method will move all elements to the right until
+    ///   an empty slot is found.
+    /// * `ideal_index` - The ideal index of the value of the bucket.
+    /// * `bucket_index` - The index of the bucket to insert.
+    fn insert_mapping<S: MapSide<T, U, H, RH, A>>(&mut self, mapping_index: usize, ideal_index: usize, bucket_index: usize) {
+        let longest_distance = S::index_mut(self).insert_mapping(mapping_index, ideal_index, bucket_index);
+        self.record_probe(longest_distance);
+    }
+
+    /// Compute the distances that saturated entries will have after the deletion of the mapping at
+    /// `mapping_index` from the index of a side shifted them back. Only saturated entries have to be
+    /// hashed during a deletion, so computing their distances up front lets a panicking `Hash`
+    /// implementation unwind before any index is modified. If no distance has saturated since the
+    /// indices were built, the scan is skipped. See [`IndexSide::shifted_saturated`].
+    fn shifted_saturated<S: MapSide<T, U, H, RH, A>>(&self, mapping_index: usize) -> Vec<u8> {
+        if self.longest_probe < SATURATED_DISTANCE {
+            return Vec::new();
+        }
+        S::index(self).shifted_saturated(mapping_index, &self.data, S::value)
+    }
+
+    /// Delete the mapping at `mapping_index` from the index of a side, computing the distances of
+    /// the saturated entries that it shifts first. Returns the index that is empty after the
+    /// deletion. See [`IndexSide::delete_mapping`].
+    fn delete_mapping<S: MapSide<T, U, H, RH, A>>(&mut self, mapping_index: usize) -> usize {
+        let shifted = self.shifted_saturated::<S>(mapping_index);
+        S::index_mut(self).delete_mapping(mapping_index, shifted)
+    }
+
+    /// Delete the mapping of a bucket from the index of a side, before the value of that side is
+    /// mutated in place. Until [`reindex`] inserts it again, lookups of the value do not find the
+    /// bucket, and the back-reference of the bucket into the index is stale.
+    ///
+    /// [`reindex`]: #method.reindex
+    fn unindex<S: MapSide<T, U, H, RH, A>>(&mut self, bucket_index: usize) {
+        if !self.is_small() {
+            self.delete_mapping::<S>(S::index(self).slot_of(bucket_index));
+            if let Some(filter) = &mut self.filter {
+                filter.stale += 1;
+            }
+        }
+        self.notify_remove_at(bucket_index);
     }
 
-    /// Delete a bucket at the given index. It will update one entry in each index, since
-    /// the last bucket is moved to the deleted bucket's position.
-    /// It will also delete the mappings for the bucket in both left and right indices.
+    /// Insert the mapping of a bucket whose value of a side was mutated in place into the index of
+    /// that side again. If the mutated value equals the value of another bucket, the mutated bucket
+    /// is removed and returned instead. The insert hook is not notified, since the caller may still
+    /// have to forget its guard against unwinding.
     ///
-    /// Note: If the bucket to delete is NOT the last bucket, the last bucket will move, meaning
-    /// that the indices of the moved bucket are no longer valid. This method ensures the mappings
-    /// are updated correctly, but any temporary variables that hold the indices of the moved bucket
-    /// will be invalid.
+    /// If hashing or comparing the values panics, the bucket is left without its mapping and must
+    /// be removed with [`remove_unindexed`].
     ///
-    /// # Parameters
-    /// * `bucket_index` - The index of the bucket to delete.
-    /// * `left_meta_index` - The entry in the left index that points to the bucket to delete.
-    /// If none, this method will search for the index
-    /// * `right_meta_index` - The entry in the right index that points to the bucket to delete.
-    /// If none, this method will search for the index
-    fn delete_bucket(&mut self, bucket_index: usize, left_meta_index: Option<usize>, right_meta_index: Option<usize>) -> Bucket<T, U> {
-        assert!(bucket_index < self.len(), "index out of bounds");
-
-        if let Some(left_meta_index) = left_meta_index {
-            self.delete_mapping_left(left_meta_index);
-        } else {
-            self.delete_mapping_left(self.lookup_index_left(&self.data[bucket_index].left).unwrap());
+    /// [`remove_unindexed`]: #method.remove_unindexed
+    fn reindex<S: MapSide<T, U, H, RH, A>>(&mut self, bucket_index: usize) -> Result<(), Bucket<T, U>> {
+        if self.is_small() {
+            let value = S::value(&self.data, bucket_index);
+            if S::values(&self.data).enumerate().any(|(bucket, stored)| bucket != bucket_index && stored == value) {
+                return Err(self.remove_unindexed::<S>(bucket_index));
+            }
+            return Ok(());
         }
 
-        if let Some(right_meta_index) = right_meta_index {
-            self.delete_mapping_right(right_meta_index);
-        } else {
-            self.delete_mapping_right(self.lookup_index_right(&self.data[bucket_index].right).unwrap());
+        let hash = S::index(self).hasher.hash_one(S::value(&self.data, bucket_index));
+        let ideal_index = side::reduce_hash(hash, self.current_capacity());
+        match self.lookup_index_from::<S>(S::value(&self.data, bucket_index), ideal_index) {
+            Ok(_) => Err(self.remove_unindexed::<S>(bucket_index)),
+            Err(position) => {
+                if let Some(filter) = &mut self.filter {
+                    S::filter_mut(filter).insert(hash);
+                }
+                self.insert_mapping::<S>(position, ideal_index, bucket_index);
+                Ok(())
+            }
         }
+    }
 
-        // trivial case: delete and return the last bucket
-        if bucket_index == self.len() - 1 {
-            return self.data.pop().unwrap();
+    /// Remove a bucket whose mapping in the index of a side was deleted by [`unindex`], by deleting
+    /// its mapping in the opposite index.
+    ///
+    /// [`unindex`]: #method.unindex
+    fn remove_unindexed<S: MapSide<T, U, H, RH, A>>(&mut self, bucket_index: usize) -> Bucket<T, U> {
+        if !self.is_small() {
+            self.delete_mapping::<S::Opposite>(S::Opposite::index(self).slot_of(bucket_index));
         }
+        self.swap_remove_bucket(bucket_index)
+    }
 
-        // find metadata of the bucket to move
-        let bucket_to_move = &self.data[self.len() - 1];
-        let left_index = self.lookup_index_left(&bucket_to_move.left);
-        let right_index = self.lookup_index_right(&bucket_to_move.right);
-        debug_assert!(left_index.is_ok());
-        debug_assert!(right_index.is_ok());
+    /// Get the current capacity for both indices.
+    fn current_capacity(&self) -> usize {
+        self.left.capacity()
+    }
 
-        let tail = self.len() - 1;
-        let (lower, upper) = self.data.split_at_mut(tail);
+    /// Returns whether the map is a small map without hash indices.
+    #[inline(always)]
+    fn is_small(&self) -> bool {
+        self.left.capacity() == 0
+    }
 
-        // swap with last element
-        mem::swap(&mut lower[bucket_index], &mut upper[0]);
+    /// Returns whether the map can fit additional `num` elements without exceeding the maximum load.
+    fn can_fit(&self, num: usize) -> bool {
+        num <= self.growth_threshold.saturating_sub(self.len())
+    }
 
-        // update metadata of moved bucket
-        self.left_index[left_index.unwrap()] = bucket_index;
-        self.right_index[right_index.unwrap()] = bucket_index;
+    /// Grow the map to the given capacity. A capacity of zero turns the map into a small map.
+    fn resize(&mut self, new_capacity: usize) {
+        #[cfg(feature = "tracing")]
+        let old_capacity = self.current_capacity();
+
+        if new_capacity == 0 {
+            assert!(self.len() <= SMALL_MAP_CAPACITY, "small maps cannot hold more than {} pairs", SMALL_MAP_CAPACITY);
+            self.left.release();
+            self.right.release();
+            self.growth_threshold = Self::growth_threshold_for(0);
+            self.rebuilt_indices(0);
+            #[cfg(feature = "tracing")]
+            self.trace_resize(old_capacity);
+            return;
+        }
 
-        // return the deleted bucket
-        self.data.pop().unwrap()
+        assert!(new_capacity >= self.len(), "new capacity must be at least the current length");
+        match self.resize_strategy {
+            ResizeStrategy::Rebuild => {
+                let built = self.build_indices(new_capacity, None);
+                self.install_indices(built);
+            }
+            ResizeStrategy::InPlace => self.rebuild_indices_in_place(new_capacity),
+        }
+        #[cfg(feature = "stats")]
+        {
+            self.counters.rehashes += 1;
+        }
+        #[cfg(feature = "tracing")]
+        self.trace_resize(old_capacity);
     }
 
-    /// Replace a bucket at the given index with a new bucket. The old bucket is returned.
-    /// No changes to the indices are made.
-    fn replace_bucket(&mut self, bucket_index: usize, bucket: Bucket<T, U>) -> Bucket<T, U> {
-        assert!(bucket_index < self.len(), "index out of bounds");
-        let mut old_bucket = bucket;
-        mem::swap(&mut self.data[bucket_index], &mut old_bucket);
-        old_bucket
+    /// Build indices of the given capacity for all pairs of the map, using the given hashers or
+    /// the hashers of the map. The back-references of the map are overwritten to refer into the
+    /// new indices, everything else is left untouched until [`install_indices`]. If a `Hash`
+    /// implementation panics, the back-references are restored from the installed indices, so the
+    /// map stays intact.
+    ///
+    /// [`install_indices`]: #method.install_indices
+    fn build_indices(&mut self, new_capacity: usize, hashers: Option<(&H, &RH)>) -> BuiltIndices<A> {
+        let left = self.left.allocate(new_capacity);
+        let right = self.right.allocate(new_capacity);
+        self.build_indices_into(left, right, hashers)
     }
 
-    /// Insert metadata into the given index for the given element and bucket index.
-    /// The method will move all elements to the right until an empty slot is found.
-    /// The method is used for both left and right indices.
+    /// Build indices like [`build_indices`], into empty indices that were allocated by the caller.
     ///
-    /// # Parameters
-    /// * `meta_index` - The meta index to insert into.
-    /// * `mapping_index` - The index in the meta index to insert at. It must be the index returned
-    /// by the `lookup_index_left` or `lookup_index_right` method. The method will move all elements
-    /// to the right until an empty slot is found, so it should be the index that already exceeds
-    /// the probe distance.
-    #[inline(always)]
-    fn insert_mapping(meta_index: &mut [usize], mut mapping_index: usize, bucket_index: usize) {
-        let mut current_content = bucket_index;
-        while meta_index[mapping_index] < EMPTY_SLOT {
-            mem::swap(&mut meta_index[mapping_index], &mut current_content);
-            mapping_index = (mapping_index + 1) % meta_index.len();
-        }
-        meta_index[mapping_index] = current_content;
+    /// [`build_indices`]: #method.build_indices
+    fn build_indices_into(&mut self, left: EmptyIndex<A>, right: EmptyIndex<A>, hashers: Option<(&H, &RH)>) -> BuiltIndices<A> {
+        let (hasher, reverse_hasher) = hashers.unzip();
+        let guard = BackReferenceGuard { left: &mut self.left, right: &mut self.right };
+        let left = guard.left.build(left, hasher, &self.data, Storage::left);
+        let right = guard.right.build(right, reverse_hasher, &self.data, Storage::right);
+        mem::forget(guard);
+        BuiltIndices { left, right }
     }
 
-    /// Insert metadata into the left index for the given element and bucket index.
+    /// Rebuild the indices at the given capacity in their current allocations. The ideal indices of
+    /// all pairs at the new capacity are computed first, and stored in the back-references, so that
+    /// a panicking `Hash` implementation leaves the map intact like in [`build_indices`]. The old
+    /// indices are discarded only then, and the pairs are placed at their ideal indices without
+    /// hashing them again. The back-references are restored from the new indices in a last pass.
     ///
-    /// # Parameters
-    /// * `mapping_index` - The index in the left index to insert at. It must be the index returned
-    /// by the `lookup_index_left` method. The method will move all elements to the right until an
-    /// empty slot is found.
-    /// * `bucket_index` - The index of the bucket to insert.
-    fn insert_mapping_left(&mut self, mapping_index: usize, bucket_index: usize) {
-        Self::insert_mapping(&mut self.left_index, mapping_index, bucket_index)
+    /// [`build_indices`]: #method.build_indices
+    fn rebuild_indices_in_place(&mut self, new_capacity: usize) {
+        let guard = BackReferenceGuard { left: &mut self.left, right: &mut self.right };
+        guard.left.store_ideal_indices(new_capacity, &self.data, Storage::left);
+        guard.right.store_ideal_indices(new_capacity, &self.data, Storage::right);
+        mem::forget(guard);
+        self.place_indices(new_capacity);
     }
 
-    /// Insert metadata into the right index for the given element and bucket index.
+    /// Discard the indices and rebuild them at the given capacity from the ideal indices of the
+    /// pairs, which are stored in their back-references. No value is hashed, so this cannot panic in
+    /// a `Hash` implementation. See [`rebuild_indices_in_place`].
     ///
-    /// # Parameters
-    /// * `mapping_index` - The index in the right index to insert at. It must be the index returned
-    /// by the `lookup_index_right` method. The method will move all elements to the right until an
-    /// empty slot is found.
-    /// * `bucket_index` - The index of the bucket to insert.
-    fn insert_mapping_right(&mut self, mapping_index: usize, bucket_index: usize) {
-        Self::insert_mapping(&mut self.right_index, mapping_index, bucket_index)
+    /// [`rebuild_indices_in_place`]: #method.rebuild_indices_in_place
+    fn place_indices(&mut self, capacity: usize) {
+        let left_probe = self.left.rebuild(capacity);
+        let right_probe = self.right.rebuild(capacity);
+
+        self.growth_threshold = Self::growth_threshold_for(capacity);
+        self.rebuilt_indices(left_probe.max(right_probe));
+        self.rebuild_filter();
     }
 
-    /// Delete a mapping in the left index and move following elements to the right if necessary.
-    fn delete_mapping_left(&mut self, mapping_index: usize) {
-        self.left_index[mapping_index] = EMPTY_SLOT;
-        let mut current_mapping_index = (mapping_index + 1) % self.current_capacity();
+    /// Replace the indices of the map with indices that were built for all of its pairs.
+    fn install_indices(&mut self, built: BuiltIndices<A>) {
+        let longest_probe = built.left.longest_probe.max(built.right.longest_probe);
+        self.left.install(built.left);
+        self.right.install(built.right);
+        self.growth_threshold = Self::growth_threshold_for(self.current_capacity());
+        self.rebuilt_indices(longest_probe);
+        self.rebuild_filter();
+    }
 
-        // move elements over until we find a free spot or an element that is already in the right spot
-        let mut current_neighbor = self.left_index[current_mapping_index];
+    /// Resize the indices and notify the resize hook, if one is set and the capacity changed.
+    fn resize_for(&mut self, new_capacity: usize, reason: ResizeReason) {
+        let old_capacity = self.current_capacity();
+        self.resize(new_capacity);
+        if self.current_capacity() != old_capacity {
+            self.notify_resize(old_capacity, self.current_capacity(), reason);
+        }
+    }
 
-        while current_neighbor != EMPTY_SLOT && self.get_ideal_index_left(&self.data[current_neighbor].left).wrapping_sub(current_mapping_index) != 0 {
-            if current_mapping_index == 0 {
-                let (lower, upper) = self.left_index.split_at_mut(self.current_capacity() - 1);
-                mem::swap(&mut lower[0], &mut upper[0]);
-            } else {
-                let (lower, upper) = self.left_index.split_at_mut(current_mapping_index);
-                mem::swap(&mut lower[current_mapping_index - 1], &mut upper[0]);
-            }
-            current_mapping_index = (current_mapping_index + 1) % self.current_capacity();
-            current_neighbor = self.left_index[current_mapping_index];
+    /// Notify the resize hook of a completed resize, if one is set.
+    fn notify_resize(&mut self, old_capacity: usize, new_capacity: usize, reason: ResizeReason) {
+        if let Some(hook) = self.resize_hook.get_mut() {
+            hook(ResizeEvent { old_capacity, new_capacity, len: self.data.len(), reason });
         }
     }
 
-    /// Delete a mapping in the right index and move following elements to the right if necessary.
-    fn delete_mapping_right(&mut self, mapping_index: usize) {
-        self.right_index[mapping_index] = EMPTY_SLOT;
-        let mut current_mapping_index = (mapping_index + 1) % self.current_capacity();
+    /// Emit an event for a completed resize of the indices.
+    #[cfg(feature = "tracing")]
+    fn trace_resize(&self, old_capacity: usize) {
+        tracing::debug!(old_capacity, new_capacity = self.current_capacity(), len = self.len(), "resized the indices");
+    }
 
-        // move elements over until we find a free spot or an element that is already in the right spot
-        let mut current_neighbor = self.right_index[current_mapping_index];
+    /// Rebuild the negative lookup filters from the pairs in the map, if they are enabled. This
+    /// drops the bits of removed values, and resizes the filters to the capacity of the indices.
+    fn rebuild_filter(&mut self) {
+        if self.filter.is_none() {
+            return;
+        }
 
-        while current_neighbor != EMPTY_SLOT && self.get_ideal_index_right(&self.data[current_neighbor].right).wrapping_sub(current_mapping_index) != 0 {
-            if current_mapping_index == 0 {
-                let (lower, upper) = self.right_index.split_at_mut(self.current_capacity() - 1);
-                mem::swap(&mut lower[0], &mut upper[0]);
-            } else {
-                let (lower, upper) = self.right_index.split_at_mut(current_mapping_index);
-                mem::swap(&mut lower[current_mapping_index - 1], &mut upper[0]);
+        let mut filter = NegativeFilter::with_capacity(self.current_capacity());
+        if !self.is_small() {
+            for (left, right) in self.data.iter() {
+                filter.left.insert(self.left.hasher.hash_one(left));
+                filter.right.insert(self.right.hasher.hash_one(right));
             }
-            current_mapping_index = (current_mapping_index + 1) % self.current_capacity();
-            current_neighbor = self.right_index[current_mapping_index];
         }
+        self.filter = Some(filter);
     }
 
-    /// Get the current capacity for both indices.
-    fn current_capacity(&self) -> usize {
-        self.left_index.len()
+    /// Remove all values from the negative lookup filters, if they are enabled.
+    fn clear_filter(&mut self) {
+        if let Some(filter) = &mut self.filter {
+            filter.left.clear();
+            filter.right.clear();
+            filter.stale = 0;
+        }
     }
 
-    /// Returns whether the map can fit additional `num` elements without exceeding the maximum load.
-    fn can_fit(&self, num: usize) -> bool {
-        ((self.len() + num) as f64) < (self.current_capacity() as f64 * MAX_LOAD_FACTOR)
+    /// Rebuild the negative lookup filters once more values have been removed since the last
+    /// rebuild than the map holds, so that false positives do not accumulate.
+    fn rebuild_filter_if_stale(&mut self) {
+        if self.filter.as_ref().is_some_and(|filter| filter.stale > self.len().max(SMALL_MAP_CAPACITY)) {
+            self.rebuild_filter();
+        }
     }
 
-    /// Grow the map to the given capacity.
-    fn resize(&mut self, new_capacity: usize) {
-        assert!(new_capacity >= self.len(), "new capacity must be at least the current length");
+    /// Record the values of a bucket in the negative lookup filters, if they are enabled. Bits of
+    /// values that do not end up in the map only cause false positives, so callers record the
+    /// values before modifying the map.
+    fn filter_insert(&mut self, left: &T, right: &U) {
+        if let Some(filter) = &mut self.filter {
+            filter.left.insert(self.left.hasher.hash_one(left));
+            filter.right.insert(self.right.hasher.hash_one(right));
+        }
+    }
 
-        let mut new_left_index = vec![EMPTY_SLOT; new_capacity].into_boxed_slice();
-        let mut new_right_index = vec![EMPTY_SLOT; new_capacity].into_boxed_slice();
+    /// Track the longest probe distance written by an insertion. With the `tracing` feature, a
+    /// warning is emitted when the longest distance first exceeds `PROBE_WARNING_DISTANCE` since the
+    /// indices were last rebuilt, so that a degenerate hasher does not flood the log.
+    #[inline(always)]
+    fn record_probe(&mut self, distance: u8) {
+        #[cfg(feature = "tracing")]
+        if self.longest_probe <= PROBE_WARNING_DISTANCE && distance > PROBE_WARNING_DISTANCE {
+            tracing::warn!(probe_distance = distance, capacity = self.current_capacity(), len = self.len(), "long probe chain in the indices");
+        }
+        self.longest_probe = self.longest_probe.max(distance);
+    }
 
-        for (bucket_index, bucket) in self.data.iter().enumerate() {
-            let left_element_index = Self::probe_index(&bucket.left, &new_left_index, &self.hasher, |bucket: &Bucket<T, U>| &bucket.left, &self.data[..bucket_index], new_left_index.len()).unwrap_err();
-            let right_element_index = Self::probe_index(&bucket.right, &new_right_index, &self.reverse_hasher, |bucket: &Bucket<T, U>| &bucket.right, &self.data[..bucket_index], new_right_index.len()).unwrap_err();
+    /// Count the probe steps of the lookups of a left and a right value, from their ideal indices to
+    /// the index where they were found or would be inserted.
+    #[cfg(feature = "stats")]
+    fn count_probes(&mut self, left_ideal_index: usize, left_index: Result<usize, usize>, right_ideal_index: usize, right_index: Result<usize, usize>) {
+        let capacity = self.current_capacity();
+        let (Ok(left_index) | Err(left_index)) = left_index;
+        let (Ok(right_index) | Err(right_index)) = right_index;
+        self.counters.probe_steps += (side::probe_distance(left_ideal_index, left_index, capacity)
+            + side::probe_distance(right_ideal_index, right_index, capacity)) as u64;
+    }
 
-            Self::insert_mapping(&mut new_left_index, left_element_index, bucket_index);
-            Self::insert_mapping(&mut new_right_index, right_element_index, bucket_index);
+    /// Reset the probe tracking after the indices were rebuilt from scratch.
+    fn rebuilt_indices(&mut self, longest_probe: u8) {
+        self.longest_probe = longest_probe;
+        if let Some(reseed) = &mut self.reseed {
+            reseed.exhausted = false;
         }
+    }
+
+    /// Rebuild the indices with fresh hashers if an insertion created a probe chain that is longer
+    /// than the reseeding policy tolerates. The pairs and the capacity are unaffected.
+    fn reseed_if_pathological(&mut self) {
+        let Some(reseed) = &self.reseed else {
+            return;
+        };
+
+        if self.longest_probe > reseed.max_probe_distance && !reseed.exhausted {
+            // the indices are built before the hashers are replaced, so that a panicking `Hash`
+            // implementation leaves the map with its old hashers and indices
+            let (hasher, reverse_hasher) = (reseed.hashers)();
+            let built = self.build_indices(self.current_capacity(), Some((&hasher, &reverse_hasher)));
+            self.left.hasher = hasher;
+            self.right.hasher = reverse_hasher;
+            self.install_indices(built);
+            #[cfg(feature = "stats")]
+            {
+                self.counters.rehashes += 1;
+            }
+            #[cfg(feature = "tracing")]
+            self.trace_resize(self.current_capacity());
 
-        self.left_index = new_left_index;
-        self.right_index = new_right_index;
+            // if fresh hashers did not shorten the chains, do not try again until the map resizes
+            let longest_probe = self.longest_probe;
+            if let Some(reseed) = &mut self.reseed {
+                reseed.exhausted = longest_probe > reseed.max_probe_distance;
+            }
+        }
     }
 
     /// Grow the map according to the growth factor.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(len = self.len())))]
     fn grow(&mut self) {
-        self.resize((self.current_capacity() as f64 * GROWTH_FACTOR).ceil() as usize)
+        if self.is_small() {
+            self.resize_for(Self::apply_load_factor(DEFAULT_CAPACITY), ResizeReason::Growth);
+        } else {
+            self.resize_for((self.current_capacity() as f64 * GROWTH_FACTOR).ceil() as usize, ResizeReason::Growth)
+        }
     }
 
     /// Get the right value for the given left value. If the left value is not in the map, None is
     /// returned.
     #[must_use]
     pub fn get_right(&self, left: &T) -> Option<&U> {
-        self.lookup_index_left(left)
-            .ok()
-            .map(|index| &self.data[self.left_index[index]].right)
+        self.find_bucket_left(left)
+            .map(|bucket| self.data.right(bucket))
     }
 
     /// Get the left value for the given right value. If the right value is not in the map, None is
     /// returned.
     #[must_use]
     pub fn get_left(&self, right: &U) -> Option<&T> {
-        self.lookup_index_right(right)
-            .ok()
-            .map(|index| &self.data[self.right_index[index]].left)
+        self.find_bucket_right(right)
+            .map(|bucket| self.data.left(bucket))
+    }
+
+    /// Get mutable access to the right value for the given left value. If the left value is not in
+    /// the map, None is returned.
+    ///
+    /// The returned guard dereferences to the right value, and makes sure that the right value can
+    /// still be looked up after it was mutated. See [`RightMut`] for what happens if the mutated
+    /// value equals the right value of another pair.
+    pub fn get_right_mut(&mut self, left: &T) -> Option<RightMut<'_, T, U, H, RH, A>> {
+        let bucket_index = self.find_bucket_left(left)?;
+        Some(RightMut::new(self, bucket_index))
+    }
+
+    /// Get mutable access to the left value for the given right value. If the right value is not in
+    /// the map, None is returned.
+    ///
+    /// The returned guard dereferences to the left value, and makes sure that the left value can
+    /// still be looked up after it was mutated. See [`LeftMut`] for what happens if the mutated
+    /// value equals the left value of another pair.
+    pub fn get_left_mut(&mut self, right: &U) -> Option<LeftMut<'_, T, U, H, RH, A>> {
+        let bucket_index = self.find_bucket_right(right)?;
+        Some(LeftMut::new(self, bucket_index))
     }
 
     /// Check if the map contains a mapping for the given left value.
     #[must_use]
     pub fn contains_left(&self, left: &T) -> bool {
-        self.lookup_index_left(left).is_ok()
+        self.find_bucket_left(left).is_some()
     }
 
     /// Check if the map contains a mapping for the given right value.
     #[must_use]
     pub fn contains_right(&self, right: &U) -> bool {
-        self.lookup_index_right(right).is_ok()
+        self.find_bucket_right(right).is_some()
     }
 
     /// Inserts a value pair into the map, creating a bijection between the two values.
@@ -433,7 +1536,8 @@ impl<T, U, H, RH> BiMap<T, U, H, RH>
     /// It is a logical error to insert a key into the map that is equal (`==`) to a key that is
     /// already in the map, but not identical.
     ///
-    /// If the map is near full, it will resize itself.
+    /// If the map is near full and the pair is new, it will resize itself. Replacing existing
+    /// mappings never resizes the map.
     /// The map will never shrink itself.
     ///
     /// If both the left and right values already exist in the map, but are not mapped to each other,
@@ -441,124 +1545,405 @@ impl<T, U, H, RH> BiMap<T, U, H, RH>
     ///
     /// [`len`]: #method.len
     pub fn insert(&mut self, left: T, right: U) -> (Option<U>, Option<T>) {
-        if !self.can_fit(1) {
-            self.grow();
+        if self.is_small() {
+            return self.insert_small(left, right);
         }
 
-        let left_index = self.lookup_index_left(&left);
-        let right_index = self.lookup_index_right(&right);
+        let replaced = self.insert_hashed(left, right);
+        self.reseed_if_pathological();
+        self.rebuild_filter_if_stale();
+        replaced
+    }
+
+    /// Insert a value pair into a map that is not small, like [`insert`], but without reseeding the
+    /// hashers or rebuilding the negative lookup filters afterwards.
+    ///
+    /// [`insert`]: #method.insert
+    fn insert_hashed(&mut self, left: T, right: U) -> (Option<U>, Option<T>) {
+        // the ideal indices are kept around so that probe positions can be corrected after
+        // deletions shifted neighboring mappings, instead of probing again
+        let mut left_ideal_index = self.left.ideal_index(&left);
+        let mut right_ideal_index = self.right.ideal_index(&right);
+        let left_index = self.lookup_index_from::<LeftSide>(&left, left_ideal_index);
+        let right_index = self.lookup_index_from::<RightSide>(&right, right_ideal_index);
+        #[cfg(feature = "stats")]
+        self.count_probes(left_ideal_index, left_index, right_ideal_index, right_index);
+
+        match (left_index, right_index) {
+            (Ok(left_meta_index), Ok(right_meta_index)) => {
+                // the bucket where the left element is currently stored, henceforth "the left bucket",
+                // and the bucket where the right element is currently stored, "the right bucket".
+                // Deleting mappings shifts slots but never moves buckets, so only the buckets are
+                // used after the first deletion.
+                let left_bucket = self.left.bucket(left_meta_index);
+                let right_bucket = self.right.bucket(right_meta_index);
+
+                if left_bucket == right_bucket {
+                    // old mapping is equal to the new mapping, do nothing
+                    return (Some(right), Some(left));
+                }
+
+                // delete the mapping of the old right value of the left bucket, and the left mapping
+                // of the right bucket, which will be removed entirely. Everything that hashes is
+                // done before the first modification, so a panicking `Hash` leaves the map intact
+                self.filter_insert(&left, &right);
+                let deleted_right = self.right.slot_of(left_bucket);
+                let deleted_left = self.left.slot_of(right_bucket);
+                let shifted_right = self.shifted_saturated::<RightSide>(deleted_right);
+                let shifted_left = self.shifted_saturated::<LeftSide>(deleted_left);
+                self.right.delete_mapping(deleted_right, shifted_right);
+                self.left.delete_mapping(deleted_left, shifted_left);
+
+                // the right value moves from the right bucket to the left bucket, so its mapping
+                // only needs to be redirected. The deletions may have shifted its slot back, so the
+                // slot is taken from the back-reference instead of the lookup above. The left bucket
+                // is replaced before removing the right bucket, because the left bucket may be moved
+                // into the position of the right bucket
+                self.right.move_mapping(right_bucket, left_bucket);
+                let tail = self.len() - 1;
+                let old_bucket = self.replace_bucket(left_bucket, Bucket { left, right });
+                let removed_bucket = self.swap_remove_bucket(right_bucket);
+                self.notify_remove(&old_bucket);
+                self.notify_remove(&removed_bucket);
+                self.notify_insert(if left_bucket == tail { right_bucket } else { left_bucket });
+                #[cfg(feature = "stats")]
+                {
+                    self.counters.replacements += 1;
+                }
 
-        let mut old_right = None;
-        let mut old_left = None;
+                (Some(old_bucket.right), Some(removed_bucket.left))
+            }
+            (Ok(left_meta_index), Err(right_position)) => {
+                let bucket = self.replace_opposite::<LeftSide>(left_meta_index, right_position, right_ideal_index, Bucket { left, right });
+                (Some(bucket.right), None)
+            }
+            (Err(left_position), Ok(right_meta_index)) => {
+                let bucket = self.replace_opposite::<RightSide>(right_meta_index, left_position, left_ideal_index, Bucket { left, right });
+                (None, Some(bucket.left))
+            }
+            (Err(mut left_position), Err(mut right_position)) => {
+                // only a new pair increases the length, replacements never need to grow the map
+                if !self.can_fit(1) {
+                    self.grow();
+
+                    // the ideal indices and probe positions are invalid after growing
+                    left_ideal_index = self.left.ideal_index(&left);
+                    right_ideal_index = self.right.ideal_index(&right);
+                    left_position = self.lookup_index_from::<LeftSide>(&left, left_ideal_index).unwrap_err();
+                    right_position = self.lookup_index_from::<RightSide>(&right, right_ideal_index).unwrap_err();
+                    #[cfg(feature = "stats")]
+                    self.count_probes(left_ideal_index, Err(left_position), right_ideal_index, Err(right_position));
+                }
 
-        if let Ok(left_meta_index) = left_index {
-            // the bucket where the left element is currently stored, henceforth "the left bucket".
-            let mut left_bucket = self.left_index[left_meta_index];
+                self.push_new_bucket(Bucket { left, right }, left_position, left_ideal_index, right_position, right_ideal_index);
+                self.notify_insert(self.len() - 1);
+                (None, None)
+            }
+        }
+    }
 
-            // delete the right bucket if it exists and store the old left value
-            // unless the right bucket is the same as the left bucket
-            if let Ok(right_meta_index) = right_index {
-                // the bucket where the right index is currently stored, henceforth "the right bucket".
-                let right_bucket = self.right_index[right_meta_index];
+    /// Replace the bucket that holds a value of a side with a new bucket that holds the same value
+    /// and a value of the opposite side that is not in the map. The mapping of the side keeps
+    /// pointing at the bucket, while the mapping of the old opposite value is deleted and the new
+    /// opposite value is inserted at `position`, its probe position before the deletion. Returns
+    /// the replaced bucket.
+    ///
+    /// # Parameters
+    /// * `meta_index` - The slot of the value of the side in its index.
+    /// * `position` - The probe position of the new opposite value in the opposite index.
+    /// * `ideal_index` - The ideal index of the new opposite value.
+    /// * `bucket` - The new bucket.
+    fn replace_opposite<S: MapSide<T, U, H, RH, A>>(&mut self, meta_index: usize, position: usize, ideal_index: usize, bucket: Bucket<T, U>) -> Bucket<T, U> {
+        let bucket_index = S::index(self).bucket(meta_index);
+
+        // delete the opposite mapping of the bucket, since a new opposite value is inserted, and
+        // insert that value at the position corrected for the deletion
+        self.filter_insert(&bucket.left, &bucket.right);
+        let deleted_index = S::Opposite::index(self).slot_of(bucket_index);
+        let vacated_index = self.delete_mapping::<S::Opposite>(deleted_index);
+        let position = side::shift_back_position(position, ideal_index, deleted_index, vacated_index, self.current_capacity());
+        self.insert_mapping::<S::Opposite>(position, ideal_index, bucket_index);
+
+        // replace the bucket with the new bucket, no update to the index of the side is necessary,
+        // since it already points to this bucket.
+        let replaced = self.replace_bucket(bucket_index, bucket);
+        self.notify_remove(&replaced);
+        self.notify_insert(bucket_index);
+        #[cfg(feature = "stats")]
+        {
+            self.counters.replacements += 1;
+        }
+        replaced
+    }
 
-                if left_bucket != right_bucket {
-                    // delete the right bucket
-                    let bucket = self.delete_bucket(right_bucket, None, right_index.ok());
+    /// Inserts a value pair into the map like [`insert`], but never allocates. If the pair is new
+    /// and would not fit into the allocated indices or pair storage, the map is left untouched and
+    /// the pair is returned in `Err`. Replacing existing mappings always succeeds.
+    ///
+    /// Maps created with [`with_capacity`] hold at least the given number of pairs. Reseeding the
+    /// hashers and rebuilding the negative lookup filters allocate, so they are deferred to later
+    /// calls of [`insert`] and the removal methods.
+    ///
+    /// [`insert`]: #method.insert
+    /// [`with_capacity`]: #method.with_capacity
+    pub fn insert_within_capacity(&mut self, left: T, right: U) -> Result<(Option<U>, Option<T>), (T, U)> {
+        let fits = self.can_fit(1)
+            && self.data.capacity() > self.len()
+            && (self.is_small() || self.left.back_references.capacity().min(self.right.back_references.capacity()) > self.len());
+        if !fits && !self.contains_left(&left) && !self.contains_right(&right) {
+            return Err((left, right));
+        }
 
-                    // if the left bucket was moved to the right bucket's position, update the left index
-                    if left_bucket == self.len() {
-                        // the left bucket was moved to the right bucket's position, update the left index
-                        left_bucket = right_bucket;
-                    }
+        if self.is_small() {
+            Ok(self.insert_small(left, right))
+        } else {
+            Ok(self.insert_hashed(left, right))
+        }
+    }
 
-                    old_left = Some(bucket.left);
+    /// Insert a value pair into a small map. The buckets are updated in the same way as in
+    /// [`insert`], so both representations keep the same iteration order. Only inserting a new
+    /// pair into a full small map spills it into the hashed representation.
+    ///
+    /// [`insert`]: #method.insert
+    fn insert_small(&mut self, left: T, right: U) -> (Option<U>, Option<T>) {
+        let left_bucket = self.data.lefts().position(|stored| *stored == left);
+        let right_bucket = self.data.rights().position(|stored| *stored == right);
+
+        match (left_bucket, right_bucket) {
+            (Some(left_bucket), Some(right_bucket)) if left_bucket == right_bucket => (Some(right), Some(left)),
+            (Some(left_bucket), Some(right_bucket)) => {
+                let tail = self.len() - 1;
+                let old_bucket = self.replace_bucket(left_bucket, Bucket { left, right });
+                let removed_bucket = self.swap_remove_bucket(right_bucket);
+                self.notify_remove(&old_bucket);
+                self.notify_remove(&removed_bucket);
+                self.notify_insert(if left_bucket == tail { right_bucket } else { left_bucket });
+                #[cfg(feature = "stats")]
+                {
+                    self.counters.replacements += 1;
+                }
+                (Some(old_bucket.right), Some(removed_bucket.left))
+            }
+            (Some(left_bucket), None) => {
+                let bucket = self.replace_bucket(left_bucket, Bucket { left, right });
+                self.notify_remove(&bucket);
+                self.notify_insert(left_bucket);
+                #[cfg(feature = "stats")]
+                {
+                    self.counters.replacements += 1;
+                }
+                (Some(bucket.right), None)
+            }
+            (None, Some(right_bucket)) => {
+                let bucket = self.replace_bucket(right_bucket, Bucket { left, right });
+                self.notify_remove(&bucket);
+                self.notify_insert(right_bucket);
+                #[cfg(feature = "stats")]
+                {
+                    self.counters.replacements += 1;
+                }
+                (None, Some(bucket.left))
+            }
+            (None, None) => {
+                if self.can_fit(1) {
+                    self.data.push(Bucket { left, right });
+                    self.notify_insert(self.len() - 1);
                 } else {
-                    // old mapping is equal to the new mapping, do nothing
-                    return (Some(right), Some(left));
+                    // notifies the insert hook
+                    self.insert_unique_unchecked(left, right);
                 }
+                (None, None)
             }
-
-            // delete the right mapping for the left bucket, since we will insert a new right value,
-            // and insert that value
-            self.delete_mapping_right(self.lookup_index_right(&self.data[left_bucket].right).unwrap());
-            self.insert_mapping_right(self.lookup_index_right(&right).unwrap_err(), left_bucket);
-
-            // replace left bucket with new bucket, no update to left index necessary, since it
-            // already points to this bucket.
-            let bucket = self.replace_bucket(left_bucket, Bucket { left, right });
-            old_right = Some(bucket.right);
-        } else if let Ok(right_meta_index) = right_index {
-            let right_bucket = self.right_index[right_meta_index];
-
-            // replace the right bucket with the new bucket, and delete the left mapping to it,
-            // since we insert a new left mapping for the new value
-            self.delete_mapping_left(self.lookup_index_left(&self.data[right_bucket].left).unwrap());
-
-            // insert mapping to the left index, no update to right index necessary.
-            self.insert_mapping_left(left_index.unwrap_err(), right_bucket);
-            let bucket = self.replace_bucket(right_bucket, Bucket { left, right });
-            old_left = Some(bucket.left);
-        } else {
-            self.push_new_bucket(Bucket { left, right }, left_index.unwrap_err(), right_index.unwrap_err());
         }
-
-        (old_right, old_left)
     }
 
     /// Tries to insert a value pair into the map, creating a bijection between the two values.
-    /// If the map already had one of the values present, nothing is updated, and an error containing
-    /// the present values is returned. The first value in the tuple is the present right value for the
-    /// left key, and the second value is the present left value for the right key.
+    /// If the map already had one of the values present, nothing is updated, and an
+    /// [`OccupiedError`] is returned, which holds the rejected pair and tells which of its values
+    /// are present.
     /// If the map did not have any of the values present, the values are inserted and Ok is returned.
     ///
     /// If the map is near full, it will resize itself.
     // TODO adjust this method to mirror HashMap::try_insert (when it gets stabilized)
-    //  this includes changing the Err to an occupied error type,
-    //  and changing the name if Rust decides that try_ should be reserved to allocation errors
-    pub fn try_insert(&mut self, left: T, right: U) -> Result<(), (Option<&U>, Option<&T>)> {
-        let left_index = self.lookup_index_left(&left);
-        let right_index = self.lookup_index_right(&right);
-
-        if left_index.is_err() && right_index.is_err() {
-            if !self.can_fit(1) {
-                self.grow();
+    //  this includes changing the name if Rust decides that try_ should be reserved to allocation errors
+    pub fn try_insert(&mut self, left: T, right: U) -> Result<(), OccupiedError<T, U>> {
+        if self.is_small() {
+            let left_occupied = self.find_bucket_left(&left).is_some();
+            let right_occupied = self.find_bucket_right(&right).is_some();
+            if left_occupied || right_occupied {
+                return Err(OccupiedError { left, right, left_occupied, right_occupied });
             }
 
-            self.push_new_bucket(Bucket { left, right }, left_index.unwrap_err(), right_index.unwrap_err());
-            Ok(())
-        } else {
-            Err((left_index.ok().map(|index| &self.data[self.left_index[index]].right), right_index.ok().map(|index| &self.data[self.right_index[index]].left)))
+            if self.can_fit(1) {
+                self.data.push(Bucket { left, right });
+                self.notify_insert(self.len() - 1);
+                return Ok(());
+            }
+
+            // the pair spills the small map into the hashed representation
+            self.grow();
+        }
+
+        let mut left_ideal_index = self.left.ideal_index(&left);
+        let mut right_ideal_index = self.right.ideal_index(&right);
+        let left_index = self.lookup_index_from::<LeftSide>(&left, left_ideal_index);
+        let right_index = self.lookup_index_from::<RightSide>(&right, right_ideal_index);
+        #[cfg(feature = "stats")]
+        self.count_probes(left_ideal_index, left_index, right_ideal_index, right_index);
+
+        match (left_index, right_index) {
+            (Err(mut left_index), Err(mut right_index)) => {
+                if !self.can_fit(1) {
+                    self.grow();
+
+                    // the ideal indices and probe positions are invalid after growing
+                    left_ideal_index = self.left.ideal_index(&left);
+                    right_ideal_index = self.right.ideal_index(&right);
+                    left_index = self.lookup_index_from::<LeftSide>(&left, left_ideal_index).unwrap_err();
+                    right_index = self.lookup_index_from::<RightSide>(&right, right_ideal_index).unwrap_err();
+                    #[cfg(feature = "stats")]
+                    self.count_probes(left_ideal_index, Err(left_index), right_ideal_index, Err(right_index));
+                }
+
+                self.push_new_bucket(Bucket { left, right }, left_index, left_ideal_index, right_index, right_ideal_index);
+                self.notify_insert(self.len() - 1);
+                self.reseed_if_pathological();
+                Ok(())
+            }
+            (left_index, right_index) => {
+                Err(OccupiedError { left, right, left_occupied: left_index.is_ok(), right_occupied: right_index.is_ok() })
+            }
+        }
+    }
+
+    /// Tries to insert all pairs of an iterator into the map in order, like [`try_insert`]. At the
+    /// first pair that shares a value with a pair of the map, including the pairs inserted before
+    /// it, a [`DuplicatePairError`] with the rejected pair and its position is returned. The pairs
+    /// before it stay inserted, and the pairs after it are not consumed from the iterator.
+    ///
+    /// [`try_insert`]: #method.try_insert
+    pub fn try_extend<I: IntoIterator<Item=(T, U)>>(&mut self, pairs: I) -> Result<(), DuplicatePairError<T, U>> {
+        let pairs = pairs.into_iter();
+        self.reserve(pairs.size_hint().0);
+        for (position, (left, right)) in pairs.enumerate() {
+            if let Err(error) = self.try_insert(left, right) {
+                return Err(DuplicatePairError {
+                    left: error.left,
+                    right: error.right,
+                    position,
+                    left_duplicate: error.left_occupied,
+                    right_duplicate: error.right_occupied,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the left value `old` of a pair with `new`, keeping the right value it is mapped to.
+    /// If `old` is not in the map, or `new` is in the map in another pair, a [`RekeyError`] holding
+    /// `new` is returned and the map is left unchanged. Replacing a value with an equal value
+    /// replaces the stored value.
+    pub fn rekey_left(&mut self, old: &T, new: T) -> Result<(), RekeyError<T>> {
+        self.rekey::<LeftSide>(old, new)
+    }
+
+    /// Replaces the right value `old` of a pair with `new`, keeping the left value it is mapped to.
+    /// See [`rekey_left`].
+    ///
+    /// [`rekey_left`]: #method.rekey_left
+    pub fn rekey_right(&mut self, old: &U, new: U) -> Result<(), RekeyError<U>> {
+        self.rekey::<RightSide>(old, new)
+    }
+
+    /// Replace a value of a side in its pair, see [`rekey_left`]. The new value is looked up before
+    /// the map is changed, so a panicking `Hash` implementation leaves the map intact.
+    ///
+    /// [`rekey_left`]: #method.rekey_left
+    fn rekey<S: MapSide<T, U, H, RH, A>>(&mut self, old: &S::Value, new: S::Value) -> Result<(), RekeyError<S::Value>> {
+        let Some(bucket_index) = self.find_bucket::<S>(old) else {
+            return Err(RekeyError::NotFound(new));
+        };
+        if self.find_bucket::<S>(&new).is_some_and(|occupant| occupant != bucket_index) {
+            return Err(RekeyError::Occupied(new));
         }
+
+        // notifies the hooks and the journal of the removal of the old pair and the insertion of
+        // the new pair
+        self.replace_at::<S>(bucket_index, new);
+        self.rebuild_filter_if_stale();
+        Ok(())
+    }
+
+    /// Inserts a value pair into the map without checking whether either value is already present,
+    /// and returns references to the inserted values.
+    ///
+    /// This skips both lookups that [`insert`] and [`try_insert`] perform, which makes it suitable
+    /// for bulk loading data that is known to be a bijection, ideally after reserving enough capacity
+    /// with [`reserve`].
+    ///
+    /// It is a logic error to insert a left or right value that is already in the map. Doing so does
+    /// not cause undefined behavior, but the map will contain both pairs and lookups, replacements
+    /// and removals of the duplicated values may return either pair. In debug builds, this method
+    /// panics if either value is already present.
+    ///
+    /// If the map is near full, it will resize itself.
+    ///
+    /// [`insert`]: #method.insert
+    /// [`try_insert`]: #method.try_insert
+    /// [`reserve`]: #method.reserve
+    pub fn insert_unique_unchecked(&mut self, left: T, right: U) -> (&T, &U) {
+        debug_assert!(!self.contains_left(&left), "the left value is already in the map");
+        debug_assert!(!self.contains_right(&right), "the right value is already in the map");
+
+        if !self.can_fit(1) {
+            self.grow();
+        }
+
+        if self.is_small() {
+            self.data.push(Bucket { left, right });
+            self.notify_insert(self.len() - 1);
+            return (self.data.left(self.len() - 1), self.data.right(self.len() - 1));
+        }
+
+        let left_ideal_index = self.left.ideal_index(&left);
+        let right_ideal_index = self.right.ideal_index(&right);
+        let left_position = self.left.probe_insert_position(left_ideal_index, &self.data, Storage::left);
+        let right_position = self.right.probe_insert_position(right_ideal_index, &self.data, Storage::right);
+        #[cfg(feature = "stats")]
+        self.count_probes(left_ideal_index, Err(left_position), right_ideal_index, Err(right_position));
+
+        self.push_new_bucket(Bucket { left, right }, left_position, left_ideal_index, right_position, right_ideal_index);
+        self.notify_insert(self.len() - 1);
+        self.reseed_if_pathological();
+
+        (self.data.left(self.len() - 1), self.data.right(self.len() - 1))
     }
 
     /// Deletes the mappings for the given left value and returns the right value that was mapped to it.
     /// If the left value is not in the map, None is returned.
     pub fn remove_left(&mut self, left: &T) -> Option<U> {
-        let left_index = self.lookup_index_left(left);
-        if let Ok(left_meta_index) = left_index {
-            let bucket = self.left_index[left_meta_index];
-
-            // delete the bucket
-            let bucket = self.delete_bucket(bucket, left_index.ok(), None);
-            Some(bucket.right)
-        } else {
-            None
+        let bucket = self.find_bucket_left(left);
+        #[cfg(feature = "stats")]
+        if let Some(bucket) = bucket.filter(|_| !self.is_small()) {
+            self.counters.probe_steps += side::probe_distance(self.left.ideal_index(left), self.left.slot_of(bucket), self.current_capacity()) as u64;
         }
+        let removed = bucket.map(|bucket| self.remove_bucket(bucket).right);
+        self.rebuild_filter_if_stale();
+        removed
     }
 
     /// Deletes the mappings for the given right value and returns the left value that was mapped to it.
     /// If the right value is not in the map, None is returned.
     pub fn remove_right(&mut self, right: &U) -> Option<T> {
-        let right_index = self.lookup_index_right(right);
-        if let Ok(right_meta_index) = right_index {
-            let bucket = self.right_index[right_meta_index];
-
-            // delete the bucket
-            let bucket = self.delete_bucket(bucket, None, right_index.ok());
-            Some(bucket.left)
-        } else {
-            None
+        let bucket = self.find_bucket_right(right);
+        #[cfg(feature = "stats")]
+        if let Some(bucket) = bucket.filter(|_| !self.is_small()) {
+            self.counters.probe_steps += side::probe_distance(self.right.ideal_index(right), self.right.slot_of(bucket), self.current_capacity()) as u64;
         }
+        let removed = bucket.map(|bucket| self.remove_bucket(bucket).left);
+        self.rebuild_filter_if_stale();
+        removed
     }
 
     /// Reserves capacity for at least `additional` more elements to be inserted in the map.
@@ -569,25 +1954,63 @@ impl<T, U, H, RH> BiMap<T, U, H, RH>
     /// # Panics
     /// Panics, if the new capacity overflows usize.
     /// Panics, if the allocation fails.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(len = self.len(), additional = additional)))]
     pub fn reserve(&mut self, additional: usize) {
         if !self.can_fit(additional) {
-            if usize::MAX - self.current_capacity() < additional {
-                panic!("capacity overflow");
-            }
+            let required = self.len().checked_add(additional).expect("capacity overflow");
+            let new_capacity = Self::apply_load_factor(required);
+            self.resize_for(new_capacity, ResizeReason::Reserve);
+        }
+    }
 
-            let new_capacity = Self::apply_load_factor(self.len() + additional);
-            self.resize(new_capacity);
+    /// Tries to reserve capacity for at least `additional` more elements to be inserted in the map,
+    /// like [`reserve`]. Unlike [`reserve`], the storage of the pairs is reserved as well, so that
+    /// the insertions do not allocate at all. If the capacity overflows or an allocation fails, an
+    /// error is returned instead of panicking or aborting, and the map is left unchanged, although
+    /// the storage of the pairs may have grown.
+    ///
+    /// The indices are always rebuilt in a new allocation, regardless of the [`ResizeStrategy`].
+    ///
+    /// [`reserve`]: #method.reserve
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(len = self.len(), additional = additional)))]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let required = self.len().checked_add(additional).ok_or(TryReserveError::CapacityOverflow)?;
+        self.data.try_reserve(additional)?;
+        if self.can_fit(additional) {
+            return Ok(());
+        }
+
+        let new_capacity = Self::checked_load_factor(required).ok_or(TryReserveError::CapacityOverflow)?;
+        let left = self.left.try_allocate(new_capacity)?;
+        let right = self.right.try_allocate(new_capacity)?;
+        self.left.back_references.try_reserve(required.saturating_sub(self.left.back_references.len()))?;
+        self.right.back_references.try_reserve(required.saturating_sub(self.right.back_references.len()))?;
+
+        let old_capacity = self.current_capacity();
+        let built = self.build_indices_into(left, right, None);
+        self.install_indices(built);
+        #[cfg(feature = "stats")]
+        {
+            self.counters.rehashes += 1;
         }
+        #[cfg(feature = "tracing")]
+        self.trace_resize(old_capacity);
+        self.notify_resize(old_capacity, new_capacity, ResizeReason::Reserve);
+        Ok(())
     }
 
     /// Shrinks the capacity of the map as much as possible.
     /// It will drop down as much as possible while maintaining the internal rules and possibly
     /// leaving some space in accordance with the resize policy.
+    /// Maps that are small enough release their hash indices entirely.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(len = self.len())))]
     pub fn shrink_to_fit(&mut self) {
         // we want to leave some space to avoid too many collisions
-        let new_capacity = Self::apply_load_factor(self.len());
-        self.resize(new_capacity);
+        let new_capacity = Self::index_capacity_for(self.len());
+        self.resize_for(new_capacity, ResizeReason::Shrink);
         self.data.shrink_to_fit();
+        self.left.back_references.shrink_to_fit();
+        self.right.back_references.shrink_to_fit();
     }
 
     /// Shrinks the capacity of the map with a lower limit.
@@ -596,65 +2019,327 @@ impl<T, U, H, RH> BiMap<T, U, H, RH>
     /// and possibly leaving some space in accordance with the resize policy.
     ///
     /// If the current capacity is less than the lower limit, this is a no-op.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(len = self.len(), min_capacity = min_capacity)))]
     pub fn shrink_to(&mut self, min_capacity: usize) {
         if min_capacity < self.current_capacity() {
-            let new_capacity = Self::apply_load_factor(max(self.len(), min_capacity));
-            self.resize(new_capacity);
+            let new_capacity = Self::index_capacity_for(max(self.len(), min_capacity));
+            self.resize_for(new_capacity, ResizeReason::Shrink);
             self.data.shrink_to(min_capacity);
+            self.left.back_references.shrink_to(min_capacity);
+            self.right.back_references.shrink_to(min_capacity);
+        }
+    }
+
+    /// Reorders the pairs of the map so that they are stored in the order of the left index, and
+    /// releases unused memory of the pair storage. After many insertions and removals, the pairs of
+    /// neighboring index slots are scattered across memory, which slows down lookups that touch many
+    /// of them. Compacting the map restores locality without changing its contents or capacity.
+    ///
+    /// The iteration order of the map changes.
+    pub fn compact(&mut self) {
+        if !self.is_small() {
+            // the new position of each bucket is its rank in the left index
+            let mut new_positions = vec![0; self.len()];
+            for (rank, bucket) in self.left.buckets().enumerate() {
+                new_positions[bucket] = rank;
+            }
+            self.permute_buckets(new_positions);
         }
+
+        self.data.shrink_to_fit();
+        self.left.back_references.shrink_to_fit();
+        self.right.back_references.shrink_to_fit();
     }
 
     /// Clears the map, removing all mappings. Keeps the allocated memory for reuse.
     pub fn clear(&mut self) {
+        if self.remove_hook.get_mut().is_some() {
+            // the pairs are removed one by one, so that the hook sees each of them
+            self.drain().for_each(drop);
+            return;
+        }
+
+        self.record_clear();
         self.data.clear();
-        self.left_index.fill(EMPTY_SLOT);
-        self.right_index.fill(EMPTY_SLOT);
+        self.left.clear();
+        self.right.clear();
+        self.rebuilt_indices(0);
+        self.clear_filter();
     }
 
-    /// Returns an iterator over the mappings in the map in arbitrary order.
-    pub fn iter(&self) -> impl Iterator<Item=(&T, &U)> {
-        self.data.iter().map(|bucket| (&bucket.left, &bucket.right))
+    /// Returns a read-only view of the map with the roles of the left and the right values swapped.
+    /// The view borrows the map, so it reflects the current contents of the map whenever it is
+    /// used. See [`InverseView`].
+    pub fn inverse(&self) -> InverseView<'_, T, U, H, RH, A> {
+        InverseView::new(self)
     }
 
-    /// Returns an iterator over the left values in the map in arbitrary order.
-    pub fn left_values(&self) -> impl Iterator<Item=&T> {
-        self.data.iter().map(|bucket| &bucket.left)
+    /// Returns a view of the left values as a set, which supports lookups and set operations without
+    /// collecting the values. See [`LeftKeySet`].
+    pub fn left_keys(&self) -> LeftKeySet<'_, T, U, H, RH, A> {
+        LeftKeySet::new(self)
     }
 
-    /// Returns an iterator over the right values in the map in arbitrary order.
-    pub fn right_values(&self) -> impl Iterator<Item=&U> {
-        self.data.iter().map(|bucket| &bucket.right)
+    /// Returns a view of the right values as a set. See [`RightKeySet`].
+    pub fn right_keys(&self) -> RightKeySet<'_, T, U, H, RH, A> {
+        RightKeySet::new(self)
     }
 
     /// Clears the map, returning all value pairs as an iterator in arbitrary order.
     /// Keeps the allocated memory for reuse.
-    /// The iterator keeps a mutable reference to the map.
-    pub fn drain<'s>(&'s mut self) -> impl Iterator<Item=(T, U)> + 's {
-        self.left_index.fill(EMPTY_SLOT);
-        self.right_index.fill(EMPTY_SLOT);
-        self.data.drain(..).map(|bucket| (bucket.left, bucket.right))
+    /// The iterator keeps a mutable reference to the map. If it is dropped before it is exhausted,
+    /// the remaining pairs are dropped. See [`Drain`].
+    pub fn drain(&mut self) -> Drain<'_, T, U, A> {
+        self.record_clear();
+        self.left.clear();
+        self.right.clear();
+        self.rebuilt_indices(0);
+        self.clear_filter();
+        Drain { pairs: self.data.drain(), hook: self.remove_hook.get_mut() }
     }
 
-    /// Returns the number of bijections stored in the map, meaning it is half the number of values.
-    pub fn len(&self) -> usize {
-        self.data.len()
+    /// Returns a cursor over the pairs of the map, which can remove pairs and replace their right
+    /// values while walking the map. See [`CursorMut`].
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T, U, H, RH, A> {
+        CursorMut::new(self)
     }
 
-    /// Returns true if the map is empty.
-    pub fn is_empty(&self) -> bool {
-        self.data.is_empty()
+    /// Applies a series of insertions and removals to the map as a whole. The operations are applied
+    /// through the [`Txn`] passed to `f`, which records how to undo them. If `f` returns an error,
+    /// the operations are undone in reverse order, and the map holds the same pairs in the same order
+    /// as before the transaction. If `f` panics, the operations are undone as well.
+    ///
+    /// Only the pairs are restored: capacity that the map grew during the transaction is kept, and
+    /// so are hashers that were replaced because of long probe chains (see [`enable_reseeding`]).
+    ///
+    /// [`enable_reseeding`]: #method.enable_reseeding
+    pub fn transaction<R, E>(&mut self, f: impl FnOnce(&mut Txn<'_, T, U, H, RH, A>) -> Result<R, E>) -> Result<R, E> {
+        let mut transaction = Txn::new(self);
+        let result = f(&mut transaction);
+        if result.is_ok() {
+            transaction.commit();
+        }
+        result
     }
 
-    /// Returns a reference to the map’s `BuildHasher` for left values.
-    pub fn hasher_left(&self) -> &H {
-        &self.hasher
+    /// Computes the changes that turn this map into `other`. Applying them to this map with
+    /// [`apply`] makes it hold the same pairs as `other`.
+    ///
+    /// [`apply`]: #method.apply
+    pub fn diff<H2, RH2, A2>(&self, other: &BiMap<T, U, H2, RH2, A2>) -> ChangeSet<T, U>
+        where T: Clone, U: Clone, H2: BuildHasher, RH2: BuildHasher, A2: Allocator + Clone
+    {
+        ChangeSet::between(self, other)
     }
 
-    /// Returns a reference to the map’s `BuildHasher` for right values.
-    pub fn hasher_right(&self) -> &RH {
-        &self.reverse_hasher
+    /// Applies changes that were computed with [`diff`]. The removed pairs are removed by their left
+    /// values first, then the added and changed pairs are inserted, which removes the pairs that
+    /// their right values were mapped to before.
+    ///
+    /// [`diff`]: #method.diff
+    pub fn apply(&mut self, changes: &ChangeSet<T, U>)
+        where T: Clone, U: Clone
+    {
+        for (left, _) in &changes.removed {
+            self.remove_left(left);
+        }
+        for (left, right) in changes.added.iter().chain(&changes.changed) {
+            self.insert(left.clone(), right.clone());
+        }
+    }
+
+    /// Returns the number of bytes the map has allocated on the heap. This includes the unused
+    /// capacity of the pair storage, the free slots that the load factor keeps in the indices, and
+    /// the negative lookup filters if they are enabled, but not heap memory owned by the values.
+    pub fn allocated_bytes(&self) -> usize {
+        self.data.allocated_bytes()
+            + (self.left.back_references.capacity() + self.right.back_references.capacity()) * mem::size_of::<usize>()
+            + self.left.index_bytes() + self.right.index_bytes()
+            + self.filter.as_ref().map_or(0, NegativeFilter::allocated_bytes)
+    }
+
+    /// Returns the number of bytes a map created with [`with_capacity`] for `len` pairs allocates on
+    /// the heap, so memory can be planned for without building the map. See [`allocated_bytes`].
+    ///
+    /// [`with_capacity`]: #method.with_capacity
+    /// [`allocated_bytes`]: #method.allocated_bytes
+    ///
+    /// # Panics
+    /// Panics, if the capacity of the indices overflows usize.
+    pub fn estimated_bytes_for(len: usize) -> usize {
+        let index_capacity = Self::index_capacity_for(len);
+        let back_references = if index_capacity == 0 { 0 } else { len };
+        Storage::<T, U, A>::bytes_for(len)
+            + back_references * 2 * mem::size_of::<usize>()
+            + IndexSide::<H, A>::bytes_for(index_capacity) + IndexSide::<RH, A>::bytes_for(index_capacity)
+    }
+
+    /// Returns the slot layout of both hash indices, which shows for each slot the bucket it points
+    /// at, the ideal index of the bucket's value and its probe distance. It is meant for debugging
+    /// hashers, and does not require the values to implement `Debug`. The tables of the
+    /// `hashbrown-backend` feature do not expose their layout, so they have no dump.
+    #[cfg(not(feature = "hashbrown-backend"))]
+    #[doc(hidden)]
+    pub fn debug_table(&self) -> TableDump {
+        TableDump::new(self)
+    }
+
+    /// Checks the internal invariants of the map, and returns the first violation that is found.
+    /// A map that is only modified through its methods always passes the check, unless values were
+    /// duplicated with [`insert_unique_unchecked`] or the hashers are not deterministic. Any other
+    /// violation indicates a bug in the map.
+    ///
+    /// [`insert_unique_unchecked`]: #method.insert_unique_unchecked
+    ///
+    /// The check takes O(n + capacity) time, plus a lookup of every value. It is meant for fuzzing
+    /// and debugging, not for regular use.
+    pub fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        invariants::check(self)
+    }
+
+    /// Checks every value of the map for `Hash` and `Eq` implementations that violate their contract,
+    /// and returns a report for every inconsistent value, in bucket order. Values that are not equal
+    /// to themselves, values whose hash changes between calls, and values that cannot be looked up
+    /// from their hash or find another, equal value in their place are reported. A map of values
+    /// with consistent implementations has no reports.
+    ///
+    /// Equal values are only found if their hashes lead to the same slots. Values that are equal but
+    /// have unrelated hashes, e.g. because `Hash` includes a field that `Eq` ignores, are stored
+    /// twice without a trace in the indices. The `paranoid` feature catches them when they are
+    /// inserted, by comparing every inserted value with all stored values.
+    ///
+    /// The check takes O(n) time and a lookup of every value. It is meant for debugging, not for
+    /// regular use.
+    pub fn find_inconsistencies(&self) -> Vec<InconsistencyReport> {
+        invariants::find_inconsistencies(self)
+    }
+
+    /// Sets how the map rebuilds its indices when it resizes them. By default, the map builds the
+    /// new indices next to the old ones, so that growing the map temporarily needs the memory of
+    /// the indices at both capacities. With [`ResizeStrategy::InPlace`], the indices are rebuilt in
+    /// their own allocations, which bounds the additional memory by the growth of the indices, i.e.
+    /// the size of one index at the new capacity when the map grows. This comes at the cost of an
+    /// additional pass over the pairs, and of the allocator possibly having to copy the allocation
+    /// of the indices when it cannot grow it in place.
+    ///
+    /// The strategy applies to all resizes, including [`reserve`] and [`shrink_to`], but not to
+    /// rebuilding the indices with fresh hashers (see [`enable_reseeding`]). The `in-place-resize`
+    /// feature makes [`ResizeStrategy::InPlace`] the default.
+    ///
+    /// [`reserve`]: #method.reserve
+    /// [`shrink_to`]: #method.shrink_to
+    /// [`enable_reseeding`]: #method.enable_reseeding
+    pub fn set_resize_strategy(&mut self, strategy: ResizeStrategy) {
+        self.resize_strategy = strategy;
+    }
+
+    /// Returns how the map rebuilds its indices when it resizes them. See [`set_resize_strategy`].
+    ///
+    /// [`set_resize_strategy`]: #method.set_resize_strategy
+    pub fn resize_strategy(&self) -> ResizeStrategy {
+        self.resize_strategy
+    }
+
+    /// Enables reseeding of the map's hashers. Whenever an insertion creates a probe chain longer
+    /// than `max_probe_distance` slots, the map replaces both hashers with the ones returned by
+    /// `hashers` and rebuilds its indices, instead of waiting for the load factor to trigger a
+    /// resize. This limits the damage that keys crafted to collide under one seed can do.
+    ///
+    /// Reseeding preserves all pairs and their iteration order. If the indices still contain a
+    /// chain that is too long with the fresh hashers, the map does not reseed again until it resizes.
+    /// Probe distances are tracked up to 254 slots, larger limits are treated as 254.
+    ///
+    /// Maps with the default hashers can be reseeded with `|| (RandomState::new(), RandomState::new())`.
+    ///
+    /// The tables of the `hashbrown-backend` feature do not expose their probe chains, so maps with
+    /// that feature never reseed.
+    pub fn enable_reseeding(&mut self, max_probe_distance: usize, hashers: fn() -> (H, RH)) {
+        let max_probe_distance = side::saturate_distance(max_probe_distance).min(SATURATED_DISTANCE - 1);
+        self.reseed = Some(Reseed { max_probe_distance, hashers, exhausted: false });
+    }
+
+    /// Disables reseeding of the map's hashers. See [`enable_reseeding`].
+    ///
+    /// [`enable_reseeding`]: #method.enable_reseeding
+    pub fn disable_reseeding(&mut self) {
+        self.reseed = None;
+    }
+
+    /// Sets a callback that is called after the indices of the map were resized, because the map grew
+    /// or was reserved or shrunk, or removes it with `None`. Rehashing the indices at the same
+    /// capacity, for example when reseeding, does not call the hook. Maps without a hook do not pay
+    /// for it beyond a check per resize.
+    ///
+    /// The hook is called while the map is borrowed mutably, and it must be `'static`, so it cannot
+    /// access the map it observes. Clones of the map do not inherit the hook.
+    pub fn set_resize_hook(&mut self, hook: Option<ResizeCallback>) {
+        *self.resize_hook.get_mut() = hook;
+    }
+
+    /// Sets a callback that is called after a pair was inserted into the map, or removes it with
+    /// `None`. Together with [`set_on_remove`], the hook sees every change of the pairs, so it can
+    /// mirror the map into another data structure. Maps without a hook do not pay for it beyond a
+    /// check per insertion.
+    ///
+    /// Insertions that replace pairs notify the remove hook of each replaced pair before notifying
+    /// the insert hook of the new pair:
+    /// * A new right value for an existing left value, or the other way around, removes the old pair.
+    /// * A left value and a right value of two different pairs remove the pair of the left value
+    ///   first, then the pair of the right value.
+    /// * Inserting a pair that is in the map already notifies neither hook.
+    ///
+    /// Mutating a value through [`get_right_mut`] or [`get_left_mut`] notifies the remove hook of
+    /// the old pair when the value is first borrowed mutably, and the insert hook of the mutated
+    /// pair when the guard is dropped, unless the mutated pair was removed. Undoing a failed
+    /// [`transaction`] notifies the hooks of the reverse operations.
+    ///
+    /// The hooks are called while the map is borrowed mutably, and they must be `'static`, so they
+    /// cannot access the map they observe. Clones of the map do not inherit the hooks.
+    ///
+    /// [`set_on_remove`]: #method.set_on_remove
+    /// [`get_right_mut`]: #method.get_right_mut
+    /// [`get_left_mut`]: #method.get_left_mut
+    /// [`transaction`]: #method.transaction
+    pub fn set_on_insert(&mut self, hook: Option<PairCallback<T, U>>) {
+        *self.insert_hook.get_mut() = hook;
+    }
+
+    /// Sets a callback that is called after a pair was removed from the map, or removes it with
+    /// `None`. Besides removals, replacing insertions, [`clear`] and [`drain`] notify the hook of
+    /// every pair they remove. See [`set_on_insert`] for the order of the notifications.
+    ///
+    /// [`clear`]: #method.clear
+    /// [`drain`]: #method.drain
+    /// [`set_on_insert`]: #method.set_on_insert
+    pub fn set_on_remove(&mut self, hook: Option<PairCallback<T, U>>) {
+        *self.remove_hook.get_mut() = hook;
+    }
+
+    /// Enables filters that reject most lookups of absent values before the hash indices are
+    /// probed. Each side of the map keeps a small Bloom filter of the hashes of its values, which
+    /// costs two bytes per index slot and one extra hash per inserted pair. Workloads where most
+    /// lookups miss, such as deduplication, benefit most.
+    ///
+    /// Removed values stay in the filters until they are rebuilt, which happens when the map
+    /// resizes or once as many values were removed as the map holds.
+    pub fn enable_negative_filter(&mut self) {
+        if self.filter.is_none() {
+            self.filter = Some(NegativeFilter::with_capacity(0));
+            self.rebuild_filter();
+        }
+    }
+
+    /// Disables the negative lookup filters and releases their memory. See
+    /// [`enable_negative_filter`].
+    ///
+    /// [`enable_negative_filter`]: #method.enable_negative_filter
+    pub fn disable_negative_filter(&mut self) {
+        self.filter = None;
     }
 }
 
 #[cfg(test)]
-mod tests;
\ No newline at end of file
+mod tests;
+
+#[cfg(kani)]
+mod verification;
\ No newline at end of file
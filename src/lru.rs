@@ -0,0 +1,278 @@
+//! A bi-directional map with a bounded number of pairs, which evicts the least recently used pair.
+//!
+//! A [`LruBiMap`] keeps the pairs of its map in a doubly-linked list ordered by recency. The links
+//! are stored next to the buckets and are indexed by bucket, so looking up a value in either
+//! direction finds its links without a second lookup. Removing a bucket moves the last bucket into
+//! its position, and the links of the moved bucket move with it.
+
+use std::hash::{BuildHasher, Hash, RandomState};
+use crate::BiMap;
+
+/// The link of a bucket without a neighbor in that direction.
+const NIL: usize = usize::MAX;
+
+/// The neighbors of a bucket in the recency list.
+#[derive(Clone, Copy, Debug)]
+struct Link {
+    /// The next more recently used bucket.
+    newer: usize,
+    /// The next less recently used bucket.
+    older: usize,
+}
+
+/// A bi-directional map that holds at most a fixed number of pairs. See the
+/// [module documentation](self) for how recency is tracked.
+///
+/// Inserting a new pair into a full map evicts the least recently used pair. Inserting a pair and
+/// looking up a pair with [`get_right`](LruBiMap::get_right) or [`get_left`](LruBiMap::get_left)
+/// count as a use, while the `peek` and `contains` methods leave the recency untouched.
+#[derive(Clone, Debug)]
+pub struct LruBiMap<T, U, H = RandomState, RH = RandomState>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    map: BiMap<T, U, H, RH>,
+    /// The links of the buckets, indexed like the buckets.
+    links: Vec<Link>,
+    /// The most recently used bucket.
+    newest: usize,
+    /// The least recently used bucket.
+    oldest: usize,
+    capacity: usize,
+}
+
+impl<T, U> LruBiMap<T, U>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    /// Create a new empty map that holds at most `capacity` pairs.
+    ///
+    /// # Panics
+    /// Panics if the capacity is zero.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_hashers(capacity, RandomState::default(), RandomState::default())
+    }
+}
+
+impl<T, U, H, RH> LruBiMap<T, U, H, RH>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    /// Create a new empty map that holds at most `capacity` pairs, using the given hashers.
+    ///
+    /// # Panics
+    /// Panics if the capacity is zero.
+    pub fn with_hashers(capacity: usize, hasher: H, reverse_hasher: RH) -> Self {
+        assert!(capacity > 0, "the map must be able to hold at least one pair");
+
+        // the indices are sized for the capacity up front, so the map never grows
+        let index_capacity = BiMap::<T, U, H, RH>::index_capacity_for(capacity).max(capacity);
+        LruBiMap {
+            map: BiMap::with_hashers(index_capacity, hasher, reverse_hasher),
+            links: Vec::with_capacity(capacity),
+            newest: NIL,
+            oldest: NIL,
+            capacity,
+        }
+    }
+
+    /// The maximum number of pairs the map holds.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Unlink a bucket from the recency list. Its own link is left as it is.
+    fn unlink(&mut self, bucket_index: usize) {
+        let Link { newer, older } = self.links[bucket_index];
+        match newer {
+            NIL => self.newest = older,
+            newer => self.links[newer].older = older,
+        }
+        match older {
+            NIL => self.oldest = newer,
+            older => self.links[older].newer = newer,
+        }
+    }
+
+    /// Link an unlinked bucket into the recency list as the most recently used bucket.
+    fn link_newest(&mut self, bucket_index: usize) {
+        self.links[bucket_index] = Link { newer: NIL, older: self.newest };
+        match self.newest {
+            NIL => self.oldest = bucket_index,
+            newest => self.links[newest].newer = bucket_index,
+        }
+        self.newest = bucket_index;
+    }
+
+    /// Mark a bucket as the most recently used bucket.
+    fn promote(&mut self, bucket_index: usize) {
+        if self.newest != bucket_index {
+            self.unlink(bucket_index);
+            self.link_newest(bucket_index);
+        }
+    }
+
+    /// Remove the links of a bucket that was removed from the map. The last bucket was moved into
+    /// its position, so its links are moved as well and its neighbors are redirected.
+    fn remove_links(&mut self, bucket_index: usize) {
+        self.unlink(bucket_index);
+        let tail = self.links.len() - 1;
+        if bucket_index != tail {
+            let Link { newer, older } = self.links[tail];
+            match newer {
+                NIL => self.newest = bucket_index,
+                newer => self.links[newer].older = bucket_index,
+            }
+            match older {
+                NIL => self.oldest = bucket_index,
+                older => self.links[older].newer = bucket_index,
+            }
+        }
+        self.links.swap_remove(bucket_index);
+    }
+
+    /// Remove a bucket from the map and from the recency list.
+    fn remove_bucket(&mut self, bucket_index: usize) -> (T, U) {
+        let bucket = self.map.remove_bucket(bucket_index);
+        self.map.rebuild_filter_if_stale();
+        self.remove_links(bucket_index);
+        (bucket.left, bucket.right)
+    }
+
+    /// Get the right value for the given left value, and mark its pair as the most recently used
+    /// pair. If the left value is not in the map, None is returned.
+    pub fn get_right(&mut self, left: &T) -> Option<&U> {
+        let bucket_index = self.map.find_bucket_left(left)?;
+        self.promote(bucket_index);
+        Some(self.map.data.right(bucket_index))
+    }
+
+    /// Get the left value for the given right value, and mark its pair as the most recently used
+    /// pair. If the right value is not in the map, None is returned.
+    pub fn get_left(&mut self, right: &U) -> Option<&T> {
+        let bucket_index = self.map.find_bucket_right(right)?;
+        self.promote(bucket_index);
+        Some(self.map.data.left(bucket_index))
+    }
+
+    /// Get the right value for the given left value, without marking its pair as used.
+    #[must_use]
+    pub fn peek_right(&self, left: &T) -> Option<&U> {
+        self.map.get_right(left)
+    }
+
+    /// Get the left value for the given right value, without marking its pair as used.
+    #[must_use]
+    pub fn peek_left(&self, right: &U) -> Option<&T> {
+        self.map.get_left(right)
+    }
+
+    /// Check if the map contains a mapping for the given left value, without marking its pair as
+    /// used.
+    #[must_use]
+    pub fn contains_left(&self, left: &T) -> bool {
+        self.map.contains_left(left)
+    }
+
+    /// Check if the map contains a mapping for the given right value, without marking its pair as
+    /// used.
+    #[must_use]
+    pub fn contains_right(&self, right: &U) -> bool {
+        self.map.contains_right(right)
+    }
+
+    /// Inserts a value pair into the map like [`BiMap::insert`], and marks it as the most recently
+    /// used pair. If neither value is in the map and the map is full, the least recently used pair
+    /// is evicted to make room and returned.
+    ///
+    /// Pairs that are replaced because one of the values was mapped already are dropped. They do
+    /// not count as evicted, since the map does not grow in that case.
+    pub fn insert(&mut self, left: T, right: U) -> Option<(T, U)> {
+        let left_bucket = self.map.find_bucket_left(&left);
+        let right_bucket = self.map.find_bucket_right(&right);
+
+        match (left_bucket, right_bucket) {
+            (None, None) => {
+                let evicted = if self.len() == self.capacity { self.pop_oldest() } else { None };
+                self.map.insert(left, right);
+                self.links.push(Link { newer: NIL, older: NIL });
+                self.link_newest(self.links.len() - 1);
+                evicted
+            }
+            (Some(bucket_index), None) | (None, Some(bucket_index)) => {
+                self.map.insert(left, right);
+                self.promote(bucket_index);
+                None
+            }
+            (Some(left_bucket), Some(right_bucket)) if left_bucket == right_bucket => {
+                self.promote(left_bucket);
+                None
+            }
+            (Some(left_bucket), Some(right_bucket)) => {
+                // the pair ends up in the left bucket and the right bucket is removed, which moves
+                // the last bucket into the position of the right bucket
+                let tail = self.len() - 1;
+                self.map.insert(left, right);
+                self.remove_links(right_bucket);
+                self.promote(if left_bucket == tail { right_bucket } else { left_bucket });
+                None
+            }
+        }
+    }
+
+    /// Remove the least recently used pair and return it. If the map is empty, None is returned.
+    pub fn pop_oldest(&mut self) -> Option<(T, U)> {
+        match self.oldest {
+            NIL => None,
+            oldest => Some(self.remove_bucket(oldest)),
+        }
+    }
+
+    /// Deletes the mappings for the given left value and returns the right value that was mapped to it.
+    /// If the left value is not in the map, None is returned.
+    pub fn remove_left(&mut self, left: &T) -> Option<U> {
+        let bucket_index = self.map.find_bucket_left(left)?;
+        Some(self.remove_bucket(bucket_index).1)
+    }
+
+    /// Deletes the mappings for the given right value and returns the left value that was mapped to it.
+    /// If the right value is not in the map, None is returned.
+    pub fn remove_right(&mut self, right: &U) -> Option<T> {
+        let bucket_index = self.map.find_bucket_right(right)?;
+        Some(self.remove_bucket(bucket_index).0)
+    }
+
+    /// Clears the map, removing all mappings. Keeps the allocated memory for reuse.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.links.clear();
+        self.newest = NIL;
+        self.oldest = NIL;
+    }
+
+    /// Returns an iterator over the mappings in the map, from the most recently used to the least
+    /// recently used pair.
+    pub fn iter(&self) -> impl Iterator<Item=(&T, &U)> {
+        let mut bucket_index = self.newest;
+        std::iter::from_fn(move || {
+            if bucket_index == NIL {
+                return None;
+            }
+            let pair = (self.map.data.left(bucket_index), self.map.data.right(bucket_index));
+            bucket_index = self.links[bucket_index].older;
+            Some(pair)
+        })
+    }
+
+    /// Returns the number of bijections stored in the map.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the underlying map, which provides the remaining read-only operations.
+    pub fn as_bimap(&self) -> &BiMap<T, U, H, RH> {
+        &self.map
+    }
+}
@@ -0,0 +1,210 @@
+//! The `bimap!` macro for map literals.
+
+/// Creates a [`BiMap`](crate::BiMap) from a list of pairs, written as `left => right`.
+///
+/// ```
+/// use bijective_map::bimap;
+///
+/// let numbers = bimap! {
+///     1 => "one",
+///     2 => "two",
+/// };
+/// assert_eq!(numbers.get_right(&1), Some(&"one"));
+/// assert_eq!(numbers.get_left(&"two"), Some(&2));
+/// ```
+///
+/// The pairs are inserted in order with [`BiMap::insert`](crate::BiMap::insert), so a pair that
+/// shares a value with an earlier pair replaces the earlier pair. Without pairs, the macro creates
+/// an empty map with [`BiMap::new`](crate::BiMap::new).
+///
+/// ```
+/// # use bijective_map::{bimap, BiMap};
+/// let map = bimap! { 1 => 'a', 2 => 'b', 1 => 'c', 3 => 'b' };
+/// assert_eq!(map.len(), 2);
+/// assert_eq!(map.get_right(&1), Some(&'c'));
+/// assert_eq!(map.get_right(&3), Some(&'b'));
+///
+/// let empty: BiMap<u32, char> = bimap! {};
+/// assert!(empty.is_empty());
+/// ```
+///
+/// The map uses the default hashers, unless the pairs are preceded by `with` and a hasher for both
+/// sides, which is cloned for the right side, or by `with` and the hashers of both sides separated
+/// by a comma.
+///
+/// ```
+/// # use bijective_map::bimap;
+/// use std::hash::{BuildHasherDefault, DefaultHasher, RandomState};
+///
+/// let fixed = bimap![with BuildHasherDefault::<DefaultHasher>::default(); 1 => "one"];
+/// let mixed = bimap![with BuildHasherDefault::<DefaultHasher>::default(), RandomState::new(); 1 => "one"];
+/// assert_eq!(fixed.get_left(&"one"), mixed.get_left(&"one"));
+/// ```
+///
+/// A pair without a right value is rejected:
+///
+/// ```compile_fail
+/// let map = bijective_map::bimap! { 1 => "one", 2 };
+/// ```
+#[macro_export]
+macro_rules! bimap {
+    (with $hasher:expr, $reverse_hasher:expr; $($left:expr => $right:expr),* $(,)?) => {{
+        let pairs = [$(($left, $right)),*];
+        let mut map = $crate::BiMap::with_hashers(pairs.len(), $hasher, $reverse_hasher);
+        for (left, right) in pairs {
+            map.insert(left, right);
+        }
+        map
+    }};
+    (with $hasher:expr; $($left:expr => $right:expr),* $(,)?) => {{
+        let hasher = $hasher;
+        $crate::bimap!(with ::std::clone::Clone::clone(&hasher), hasher; $($left => $right),*)
+    }};
+    () => {
+        $crate::BiMap::new()
+    };
+    ($($left:expr => $right:expr),+ $(,)?) => {{
+        let pairs = [$(($left, $right)),+];
+        let mut map = $crate::BiMap::with_capacity(pairs.len());
+        for (left, right) in pairs {
+            map.insert(left, right);
+        }
+        map
+    }};
+}
+
+/// Generates a newtype of a [`BiMap`](crate::BiMap) whose methods are named after the roles of its
+/// left and right values, available with the `named` feature.
+///
+/// `named_bimap!(HostIpMap, host: Host => ip: IpAddr)` generates a `HostIpMap` that wraps a
+/// `BiMap<Host, IpAddr>` and has these methods besides `new`, `with_capacity`, `len`, `is_empty`,
+/// `iter` and `clear`:
+///
+/// | method                                       | delegates to                                        |
+/// |----------------------------------------------|-----------------------------------------------------|
+/// | `insert(host, ip)`                           | [`BiMap::insert`]                                   |
+/// | `ip_for(&host)`, `host_for(&ip)`             | [`BiMap::get_right`], [`BiMap::get_left`]           |
+/// | `contains_host(&host)`, `contains_ip(&ip)`   | [`BiMap::contains_left`], [`BiMap::contains_right`] |
+/// | `remove_by_host(&host)`, `remove_by_ip(&ip)` | [`BiMap::remove_left`], [`BiMap::remove_right`]     |
+///
+/// The map is accessible with `as_bimap` and `into_bimap` for the remaining operations. Attributes
+/// before the name, such as doc comments and derives, are passed through to the struct, and a
+/// visibility before the name applies to the struct and its methods.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+///
+/// bijective_map::named_bimap!(
+///     /// The addresses of the known hosts.
+///     #[derive(Clone, Debug, Default)]
+///     pub HostIpMap, host: String => ip: IpAddr
+/// );
+///
+/// let mut hosts = HostIpMap::new();
+/// let localhost = IpAddr::V4(Ipv4Addr::LOCALHOST);
+/// hosts.insert("localhost".to_string(), localhost);
+/// assert_eq!(hosts.ip_for(&"localhost".to_string()), Some(&localhost));
+/// assert_eq!(hosts.host_for(&localhost).map(String::as_str), Some("localhost"));
+/// assert!(hosts.contains_ip(&localhost));
+/// ```
+///
+/// [`BiMap::insert`]: crate::BiMap::insert
+/// [`BiMap::get_right`]: crate::BiMap::get_right
+/// [`BiMap::get_left`]: crate::BiMap::get_left
+/// [`BiMap::contains_left`]: crate::BiMap::contains_left
+/// [`BiMap::contains_right`]: crate::BiMap::contains_right
+/// [`BiMap::remove_left`]: crate::BiMap::remove_left
+/// [`BiMap::remove_right`]: crate::BiMap::remove_right
+#[cfg(feature = "named")]
+#[macro_export]
+macro_rules! named_bimap {
+    ($(#[$attribute:meta])* $vis:vis $name:ident, $left:ident: $left_type:ty => $right:ident: $right_type:ty $(,)?) => {
+        $(#[$attribute])*
+        $vis struct $name($crate::BiMap<$left_type, $right_type>);
+
+        $crate::__paste::paste! {
+            #[allow(dead_code)]
+            impl $name {
+                #[doc = concat!("Creates an empty `", stringify!($name), "`.")]
+                $vis fn new() -> Self {
+                    $name($crate::BiMap::new())
+                }
+
+                #[doc = concat!("Creates an empty `", stringify!($name), "` with room for `capacity` pairs.")]
+                $vis fn with_capacity(capacity: usize) -> Self {
+                    $name($crate::BiMap::with_capacity(capacity))
+                }
+
+                #[doc = concat!("Maps the ", stringify!($left), " to the ", stringify!($right), ", replacing the pairs that either was mapped to. ")]
+                #[doc = concat!("Returns the ", stringify!($right), " that the ", stringify!($left), " was mapped to and the ", stringify!($left), " that the ", stringify!($right), " was mapped to.")]
+                $vis fn insert(&mut self, $left: $left_type, $right: $right_type) -> (Option<$right_type>, Option<$left_type>) {
+                    self.0.insert($left, $right)
+                }
+
+                #[doc = concat!("Returns the ", stringify!($right), " of the ", stringify!($left), ", or None if the ", stringify!($left), " is not in the map.")]
+                #[must_use]
+                $vis fn [<$right _for>](&self, $left: &$left_type) -> Option<&$right_type> {
+                    self.0.get_right($left)
+                }
+
+                #[doc = concat!("Returns the ", stringify!($left), " of the ", stringify!($right), ", or None if the ", stringify!($right), " is not in the map.")]
+                #[must_use]
+                $vis fn [<$left _for>](&self, $right: &$right_type) -> Option<&$left_type> {
+                    self.0.get_left($right)
+                }
+
+                #[doc = concat!("Checks if the map contains the ", stringify!($left), ".")]
+                #[must_use]
+                $vis fn [<contains_ $left>](&self, $left: &$left_type) -> bool {
+                    self.0.contains_left($left)
+                }
+
+                #[doc = concat!("Checks if the map contains the ", stringify!($right), ".")]
+                #[must_use]
+                $vis fn [<contains_ $right>](&self, $right: &$right_type) -> bool {
+                    self.0.contains_right($right)
+                }
+
+                #[doc = concat!("Removes the pair of the ", stringify!($left), " and returns its ", stringify!($right), ", or None if the ", stringify!($left), " is not in the map.")]
+                $vis fn [<remove_by_ $left>](&mut self, $left: &$left_type) -> Option<$right_type> {
+                    self.0.remove_left($left)
+                }
+
+                #[doc = concat!("Removes the pair of the ", stringify!($right), " and returns its ", stringify!($left), ", or None if the ", stringify!($right), " is not in the map.")]
+                $vis fn [<remove_by_ $right>](&mut self, $right: &$right_type) -> Option<$left_type> {
+                    self.0.remove_right($right)
+                }
+
+                /// Returns the number of pairs in the map.
+                $vis fn len(&self) -> usize {
+                    self.0.len()
+                }
+
+                /// Returns true if the map contains no pairs.
+                $vis fn is_empty(&self) -> bool {
+                    self.0.is_empty()
+                }
+
+                #[doc = concat!("Returns an iterator over the pairs of ", stringify!($left), " and ", stringify!($right), " in arbitrary order.")]
+                $vis fn iter(&self) -> impl Iterator<Item=(&$left_type, &$right_type)> {
+                    self.0.iter()
+                }
+
+                /// Removes all pairs. Keeps the allocated memory for reuse.
+                $vis fn clear(&mut self) {
+                    self.0.clear()
+                }
+
+                /// Returns the underlying map.
+                $vis fn as_bimap(&self) -> &$crate::BiMap<$left_type, $right_type> {
+                    &self.0
+                }
+
+                /// Returns the underlying map, consuming the newtype.
+                $vis fn into_bimap(self) -> $crate::BiMap<$left_type, $right_type> {
+                    self.0
+                }
+            }
+        }
+    };
+}
@@ -0,0 +1,113 @@
+//! Parallel versions of the operations that evaluate a closure for every pair, and collecting
+//! parallel iterators into maps, available with the `rayon` feature.
+//!
+//! Only the closures and the iterators run in parallel, on the threads of the global rayon pool.
+//! Changing the map stays sequential, like in [`BiMap::retain`] and [`BiMap::sort_by`].
+
+use std::cmp::Ordering;
+use std::hash::{BuildHasher, Hash};
+use rayon::prelude::*;
+use crate::BiMap;
+
+impl<T, U, H, RH> BiMap<T, U, H, RH>
+    where T: Hash + Eq + Sync, U: Hash + Eq + Sync, H: BuildHasher, RH: BuildHasher
+{
+    /// Retains only the pairs for which the predicate returns true, like [`retain`], but evaluates
+    /// the predicate for the pairs in parallel. The rejected pairs are then removed in a single
+    /// sequential pass, which rebuilds the indices once.
+    ///
+    /// This pays off for expensive predicates and large maps, since the removal itself is not
+    /// faster than with [`retain`].
+    ///
+    /// [`retain`]: #method.retain
+    pub fn par_retain<F>(&mut self, predicate: F)
+        where F: Fn(&T, &U) -> bool + Sync
+    {
+        let marks = self.par_marks(|left, right| !predicate(left, right));
+        self.remove_marked(&marks);
+    }
+
+    /// Removes the pairs for which the predicate returns true and returns them in their iteration
+    /// order. The predicate is evaluated for the pairs in parallel, and the pairs are removed like
+    /// in [`par_retain`]. The remaining pairs keep their relative iteration order.
+    ///
+    /// [`par_retain`]: #method.par_retain
+    pub fn par_drain_filter<F>(&mut self, predicate: F) -> Vec<(T, U)>
+        where F: Fn(&T, &U) -> bool + Sync
+    {
+        let marks = self.par_marks(predicate);
+        self.remove_marked(&marks).into_iter().map(|bucket| (bucket.left, bucket.right)).collect()
+    }
+
+    /// Sorts the pairs of the map by their left values like [`sort_by_left`], but compares the pairs
+    /// in parallel.
+    ///
+    /// [`sort_by_left`]: #method.sort_by_left
+    pub fn par_sort_by_left(&mut self)
+        where T: Ord
+    {
+        self.par_sort_by(|left_a, _, left_b, _| left_a.cmp(left_b));
+    }
+
+    /// Sorts the pairs of the map by their right values like [`sort_by_right`], but compares the
+    /// pairs in parallel.
+    ///
+    /// [`sort_by_right`]: #method.sort_by_right
+    pub fn par_sort_by_right(&mut self)
+        where U: Ord
+    {
+        self.par_sort_by(|_, right_a, _, right_b| right_a.cmp(right_b));
+    }
+
+    /// Sorts the pairs of the map with a comparator like [`sort_by`], but compares the pairs in
+    /// parallel. The sort is stable as well, so the result is the same as with [`sort_by`]. Only
+    /// the comparisons run in parallel, the pairs are then moved into their positions and the
+    /// indices are redirected sequentially.
+    ///
+    /// [`sort_by`]: #method.sort_by
+    pub fn par_sort_by<F>(&mut self, compare: F)
+        where F: Fn(&T, &U, &T, &U) -> Ordering + Sync
+    {
+        let mut order = (0..self.len()).collect::<Vec<_>>();
+        order.par_sort_by(|&a, &b| compare(self.data.left(a), self.data.right(a), self.data.left(b), self.data.right(b)));
+        self.apply_order(order);
+    }
+
+    /// Evaluate the predicate for all pairs in parallel, and return the results in bucket order.
+    fn par_marks<F>(&self, predicate: F) -> Vec<bool>
+        where F: Fn(&T, &U) -> bool + Sync
+    {
+        let mut marks = Vec::with_capacity(self.len());
+        self.data.par_iter().map(|(left, right)| predicate(left, right)).collect_into_vec(&mut marks);
+        marks
+    }
+}
+
+/// Collects the pairs of a parallel iterator into a map with default-constructed hashers, like
+/// [`FromIterator`]. See [`ParallelExtend`] for the order of the insertions.
+impl<T, U, H, RH> FromParallelIterator<(T, U)> for BiMap<T, U, H, RH>
+    where T: Hash + Eq + Send, U: Hash + Eq + Send, H: BuildHasher + Default, RH: BuildHasher + Default
+{
+    fn from_par_iter<I: IntoParallelIterator<Item=(T, U)>>(pairs: I) -> Self {
+        let mut map = Self::with_default_hashers(0);
+        map.par_extend(pairs);
+        map
+    }
+}
+
+/// Collects the pairs of a parallel iterator into vectors on the threads of the pool, reserves
+/// capacity for all of them once, and then inserts them sequentially with [`BiMap::insert`]. The
+/// vectors keep the order of the iterator, so the pairs are inserted in the order in which a
+/// sequential iterator would yield them, and later pairs replace the mappings of earlier pairs with
+/// equal values like with [`Extend`].
+impl<T, U, H, RH> ParallelExtend<(T, U)> for BiMap<T, U, H, RH>
+    where T: Hash + Eq + Send, U: Hash + Eq + Send, H: BuildHasher, RH: BuildHasher
+{
+    fn par_extend<I: IntoParallelIterator<Item=(T, U)>>(&mut self, pairs: I) {
+        let chunks = pairs.into_par_iter().collect_vec_list();
+        self.reserve(chunks.iter().map(Vec::len).sum());
+        for (left, right) in chunks.into_iter().flatten() {
+            self.insert(left, right);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Powers of maps from a type to itself, which are partial permutations of their values.
+
+use std::hash::{BuildHasher, Hash};
+use crate::BiMap;
+
+impl<T, H, RH> BiMap<T, T, H, RH>
+    where T: Hash + Eq + Clone, H: BuildHasher + Clone, RH: BuildHasher + Clone
+{
+    /// Returns the map applied `n` times, i.e. the map that sends each value to its `n`-th successor
+    /// along the map. Negative exponents follow the map in the inverse direction, and `pow(0)` maps
+    /// every left value of the map to itself.
+    ///
+    /// The pairs of the map form cycles and, if it is only a partial permutation, paths that start
+    /// at a left value that is no right value and end at a right value that is no left value. Values
+    /// on a cycle are shifted around it, so exponents larger than the cycle wrap around. Values on a
+    /// path are only mapped if the path has an `n`-th successor for them. Each cycle and path is
+    /// walked once, so the power takes O(len) time regardless of `n`. The new map clones the
+    /// hashers of this map.
+    pub fn pow(&self, n: isize) -> Self {
+        let mut power = Self::with_capacity_and_hashers(self.len(), self.left.hasher.clone(), self.right.hasher.clone());
+        let mut visited = vec![false; self.len()];
+        let mut chain = Vec::new();
+        for start in 0..self.len() {
+            if visited[start] {
+                continue;
+            }
+
+            // walk back to the first bucket of a path, or around the cycle back to the start
+            let mut first = start;
+            let mut cycle = false;
+            while let Some(previous) = self.find_bucket_right(self.data.left(first)) {
+                if previous == start {
+                    cycle = true;
+                    break;
+                }
+                first = previous;
+            }
+
+            // the buckets in the order of the chain, the left value of each maps to the next one
+            chain.clear();
+            let mut bucket = first;
+            loop {
+                visited[bucket] = true;
+                chain.push(bucket);
+                match self.find_bucket_left(self.data.right(bucket)) {
+                    Some(next) if next != first => bucket = next,
+                    _ => break,
+                }
+            }
+
+            let len = chain.len();
+            if cycle {
+                let shift = n.rem_euclid(len as isize) as usize;
+                for i in 0..len {
+                    let value = self.data.left(chain[i]).clone();
+                    let successor = self.data.left(chain[(i + shift) % len]).clone();
+                    power.insert_unique_unchecked(value, successor);
+                }
+            } else {
+                // a path of `len` pairs has `len + 1` values, the last one is only a right value
+                let value = |i: usize| if i < len { self.data.left(chain[i]) } else { self.data.right(chain[len - 1]) };
+                let steps = n.unsigned_abs();
+                // every value has a successor at distance `steps` except for the last ones, and the
+                // end of the path is not mapped to itself, since it is no left value
+                for i in 0..(len + 1).saturating_sub(steps.max(1)) {
+                    let (from, to) = if n >= 0 { (i, i + steps) } else { (i + steps, i) };
+                    power.insert_unique_unchecked(value(from).clone(), value(to).clone());
+                }
+            }
+        }
+        power
+    }
+}
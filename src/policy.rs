@@ -0,0 +1,63 @@
+//! Bulk insertion with separate policies for duplicate left and right values.
+
+use std::hash::{BuildHasher, Hash};
+use crate::BiMap;
+use crate::errors::PolicyViolation;
+
+/// What [`BiMap::extend_with_policy`] does with an incoming pair whose value of one side is in the
+/// map already, in another pair.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DupPolicy {
+    /// Insert the incoming pair, removing the pair that holds the duplicate value.
+    Overwrite,
+    /// Drop the incoming pair and keep the pair that holds the duplicate value.
+    Keep,
+    /// Stop and return the incoming pair in a [`PolicyViolation`].
+    Error,
+}
+
+impl<T, U, H, RH> BiMap<T, U, H, RH>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    /// Inserts all pairs of an iterator into the map in order, deciding for each pair that shares a
+    /// value with a pair of the map by the policy of the side of the shared value. The pairs
+    /// inserted before count as pairs of the map.
+    ///
+    /// If a pair is a duplicate on both sides, [`DupPolicy::Error`] on either side takes precedence
+    /// over [`DupPolicy::Keep`], which takes precedence over [`DupPolicy::Overwrite`]. A pair that
+    /// is in the map already is no duplicate, and is inserted as by [`insert`] unless a policy is
+    /// [`DupPolicy::Keep`]. With [`DupPolicy::Overwrite`] on both sides, every pair is inserted as
+    /// by [`insert`].
+    ///
+    /// At the first violation of a [`DupPolicy::Error`], the rejected pair is returned in a
+    /// [`PolicyViolation`] together with its position. The pairs before it stay applied, and the
+    /// pairs after it are not consumed from the iterator.
+    ///
+    /// [`insert`]: #method.insert
+    pub fn extend_with_policy<I>(&mut self, pairs: I, on_dup_left: DupPolicy, on_dup_right: DupPolicy) -> Result<(), PolicyViolation<T, U>>
+        where I: IntoIterator<Item=(T, U)>
+    {
+        let pairs = pairs.into_iter();
+        self.reserve(pairs.size_hint().0);
+        for (position, (left, right)) in pairs.enumerate() {
+            let left_bucket = self.find_bucket_left(&left);
+            let right_bucket = self.find_bucket_right(&right);
+            let present = left_bucket.is_some() && left_bucket == right_bucket;
+            let left_duplicate = left_bucket.is_some() && !present;
+            let right_duplicate = right_bucket.is_some() && !present;
+
+            let left_violated = left_duplicate && on_dup_left == DupPolicy::Error;
+            let right_violated = right_duplicate && on_dup_right == DupPolicy::Error;
+            if left_violated || right_violated {
+                return Err(PolicyViolation { left, right, position, left_violated, right_violated });
+            }
+
+            let keep = (left_bucket.is_some() && on_dup_left == DupPolicy::Keep)
+                || (right_bucket.is_some() && on_dup_right == DupPolicy::Keep);
+            if !keep {
+                self.insert(left, right);
+            }
+        }
+        Ok(())
+    }
+}
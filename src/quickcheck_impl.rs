@@ -0,0 +1,42 @@
+//! The `Arbitrary` implementation of quickcheck, available with the `quickcheck` feature.
+
+use std::hash::Hash;
+use quickcheck::{Arbitrary, Gen};
+use crate::BiMap;
+
+/// Generates maps by inserting arbitrary pairs with [`BiMap::bulk_insert`], so a pair that shares a
+/// value with an earlier pair is dropped and the map can have fewer pairs than the generator size.
+///
+/// Shrinking removes pairs and keeps the remaining pairs unchanged: it first tries the empty map,
+/// then removes half of the pairs, a quarter, and so on down to single pairs, so a failing property
+/// shrinks to a map from which no pair can be removed.
+impl<T, U> Arbitrary for BiMap<T, U>
+    where T: Arbitrary + Hash + Eq, U: Arbitrary + Hash + Eq
+{
+    fn arbitrary(g: &mut Gen) -> Self {
+        let pairs = Vec::<(T, U)>::arbitrary(g);
+        let mut map = BiMap::with_capacity(pairs.len());
+        map.bulk_insert(pairs);
+        map
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item=Self>> {
+        let pairs: Vec<(T, U)> = self.iter().map(|(left, right)| (left.clone(), right.clone())).collect();
+        let len = pairs.len();
+        let chunk_sizes = std::iter::successors(Some(len / 2), |&size| Some(size / 2))
+            .take_while(|&size| size > 0);
+        let subsets = chunk_sizes.flat_map(move |size| (0..len).step_by(size).map(move |start| (start, size)))
+            .map(move |(start, size)| {
+                let mut map = BiMap::with_capacity(len - size.min(len - start));
+                for (index, (left, right)) in pairs.iter().enumerate() {
+                    if index < start || index >= start + size {
+                        map.insert_unique_unchecked(left.clone(), right.clone());
+                    }
+                }
+                map
+            });
+
+        let empty = (len > 0).then(BiMap::new);
+        Box::new(empty.into_iter().chain(subsets))
+    }
+}
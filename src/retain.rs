@@ -0,0 +1,75 @@
+//! Removal of all pairs that a predicate rejects, in a single pass over the pairs.
+//!
+//! The predicate is evaluated for all pairs before the map is changed, so that the parallel
+//! versions with the `rayon` feature only differ in how the marks are computed. The marked pairs
+//! are then removed in one compaction of the pair storage, and the indices are rebuilt once,
+//! instead of removing the pairs one by one.
+
+use std::hash::{BuildHasher, Hash};
+use allocator_api2::alloc::Allocator;
+use crate::BiMap;
+use crate::side;
+use crate::storage::Bucket;
+
+impl<T, U, H, RH, A: Allocator + Clone> BiMap<T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    /// Retains only the pairs for which the predicate returns true, and removes all other pairs.
+    /// The remaining pairs keep their relative iteration order, and the capacity is kept.
+    ///
+    /// The predicate is called once for every pair, before any pair is removed, so a panicking
+    /// predicate leaves the map unchanged. The removals notify the remove hook and are recorded as
+    /// one journal entry.
+    pub fn retain<F>(&mut self, mut predicate: F)
+        where F: FnMut(&T, &U) -> bool
+    {
+        let marks = self.data.iter().map(|(left, right)| !predicate(left, right)).collect::<Vec<_>>();
+        self.remove_marked(&marks);
+    }
+
+    /// Remove the pairs whose mark is set in one pass, and return them in their order in the map.
+    /// The ideal indices of the remaining pairs are computed before the map is changed, so a
+    /// panicking `Hash` implementation leaves the map intact.
+    pub(crate) fn remove_marked(&mut self, marks: &[bool]) -> Vec<Bucket<T, U>> {
+        debug_assert_eq!(marks.len(), self.len());
+        if !marks.contains(&true) {
+            return Vec::new();
+        }
+
+        let removed = if self.is_small() {
+            self.data.extract_marked(marks)
+        } else {
+            let capacity = self.current_capacity();
+            let left_ideal_indices = self.data.lefts()
+                .zip(marks)
+                .filter(|(_, &marked)| !marked)
+                .map(|(left, _)| side::hash_to_index(&self.left.hasher, left, capacity))
+                .collect::<Vec<_>>();
+            let right_ideal_indices = self.data.rights()
+                .zip(marks)
+                .filter(|(_, &marked)| !marked)
+                .map(|(right, _)| side::hash_to_index(&self.right.hasher, right, capacity))
+                .collect::<Vec<_>>();
+
+            let removed = self.data.extract_marked(marks);
+            // the back-references are refilled in place, so they stay in the allocator of the map
+            self.left.back_references.clear();
+            self.left.back_references.extend(left_ideal_indices);
+            self.right.back_references.clear();
+            self.right.back_references.extend(right_ideal_indices);
+            self.place_indices(capacity);
+            #[cfg(feature = "stats")]
+            {
+                self.counters.rehashes += 1;
+            }
+            removed
+        };
+
+        // the map is consistent again before the hook sees the removed pairs
+        for bucket in &removed {
+            self.notify_remove(bucket);
+        }
+        self.seal_journal();
+        removed
+    }
+}
@@ -0,0 +1,28 @@
+//! The JSON schema of the serde representation of a map.
+
+use std::borrow::Cow;
+use std::hash::Hash;
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use crate::BiMap;
+
+/// Describes a map as an array of unique `[left, right]` pairs, the representation of the `serde`
+/// feature. The schema cannot express that the left and right values are unique on their own.
+impl<T, U, H, RH> JsonSchema for BiMap<T, U, H, RH>
+    where T: Hash + Eq + JsonSchema, U: Hash + Eq + JsonSchema
+{
+    fn schema_name() -> Cow<'static, str> {
+        format!("BiMap_of_{}_and_{}", T::schema_name(), U::schema_name()).into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        format!("bijective_map::BiMap<{}, {}>", T::schema_id(), U::schema_id()).into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "array",
+            "items": generator.subschema_for::<(T, U)>(),
+            "uniqueItems": true,
+        })
+    }
+}
@@ -0,0 +1,54 @@
+//! Deterministic hashers, for maps that produce the same layout in every run, see
+//! [`BiMap::with_seed`].
+
+use std::hash::{BuildHasher, DefaultHasher, Hasher};
+use crate::BiMap;
+
+/// A [`BuildHasher`] whose hashers are the SipHash hashers of the standard library with fixed keys,
+/// and the seed written to them before the hashed value. Unlike
+/// [`RandomState`](std::hash::RandomState), it produces the same hashes in every run of a program,
+/// so maps with the same seed and the same operations have the same layout.
+///
+/// The hashes are predictable, so the maps are not resistant against collision attacks: inputs
+/// that an attacker controls can be chosen to collide. The algorithm of the standard library
+/// hashers is not specified, so the hashes can change between Rust versions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FixedState {
+    seed: u64,
+}
+
+impl FixedState {
+    /// Create a hasher with the given seed.
+    pub fn new(seed: u64) -> Self {
+        FixedState { seed }
+    }
+
+    /// The seed of the hasher.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl BuildHasher for FixedState {
+    type Hasher = DefaultHasher;
+
+    fn build_hasher(&self) -> DefaultHasher {
+        let mut hasher = DefaultHasher::new();
+        hasher.write_u64(self.seed);
+        hasher
+    }
+}
+
+impl<T, U> BiMap<T, U, FixedState, FixedState> {
+    /// Create a new empty BiMap with the given capacity whose hashers are derived from the seed, see
+    /// [`FixedState`]. Two maps with the same seed that see the same operations have the same
+    /// iteration order and the same layout of their indices, in every run of the program, which
+    /// makes simulations and golden tests reproducible.
+    ///
+    /// The hashes of a fixed seed are predictable, so the map gives up the protection of the
+    /// default hashers against collision attacks. Do not use it for values that an attacker
+    /// controls.
+    pub fn with_seed(capacity: usize, seed: u64) -> Self {
+        Self::with_capacity_and_hashers(capacity, FixedState::new(seed), FixedState::new(seed))
+    }
+}
@@ -0,0 +1,121 @@
+//! Representations of maps for serde, for use with `#[serde(with = "...")]` on fields that hold a
+//! [`BiMap`]. Each module chooses whether the map is serialized as a sequence of pairs or as a map
+//! from the left values to the right values, and whether duplicate values in the input fail
+//! deserialization or replace the earlier mappings.
+//!
+//! ```
+//! # use bijective_map::BiMap;
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Tables {
+//!     // [[[0, 0], "origin"], ...], since the left values cannot be JSON keys
+//!     #[serde(with = "bijective_map::serde::as_seq_strict")]
+//!     points: BiMap<(i32, i32), String>,
+//!     // {"en": 1, "de": 2}, where a duplicate right value replaces the earlier entry
+//!     #[serde(with = "bijective_map::serde::as_map")]
+//!     languages: BiMap<String, u32>,
+//! }
+//! ```
+//!
+//! The [`Serialize`] and [`Deserialize`] implementations of [`BiMap`] itself are [`as_seq`]. Unlike
+//! [`serde_object`](crate::serde_object), the map representations pass the left values to the
+//! serializer as keys without checking them, so formats with arbitrary keys can represent every
+//! map. Formats such as JSON reject keys that they cannot represent when the map is serialized.
+//!
+//! [`BiMap`]: crate::BiMap
+//! [`Serialize`]: ::serde::Serialize
+//! [`Deserialize`]: ::serde::Deserialize
+
+/// A sequence of pairs, where later pairs replace the mappings of earlier pairs with equal values.
+/// This is the default representation of maps.
+pub mod as_seq {
+    use std::hash::{BuildHasher, Hash};
+    use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use crate::BiMap;
+    use crate::serialization::PairsVisitor;
+
+    /// Serialize a map as a sequence of its pairs in iteration order.
+    pub fn serialize<S, T, U, H, RH>(map: &BiMap<T, U, H, RH>, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer, T: Hash + Eq + Serialize, U: Hash + Eq + Serialize, H: BuildHasher, RH: BuildHasher
+    {
+        serializer.collect_seq(map.iter())
+    }
+
+    /// Deserialize a map from a sequence of pairs, inserting the pairs in order with
+    /// [`BiMap::insert`].
+    pub fn deserialize<'de, D, T, U, H, RH>(deserializer: D) -> Result<BiMap<T, U, H, RH>, D::Error>
+        where D: Deserializer<'de>, T: Hash + Eq + Deserialize<'de>, U: Hash + Eq + Deserialize<'de>, H: BuildHasher + Default, RH: BuildHasher + Default
+    {
+        deserializer.deserialize_seq(PairsVisitor::new(false))
+    }
+}
+
+/// A sequence of pairs, where a duplicate left or right value fails deserialization.
+pub mod as_seq_strict {
+    use std::hash::{BuildHasher, Hash};
+    use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use crate::BiMap;
+    use crate::serialization::PairsVisitor;
+
+    /// Serialize a map as a sequence of its pairs in iteration order.
+    pub fn serialize<S, T, U, H, RH>(map: &BiMap<T, U, H, RH>, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer, T: Hash + Eq + Serialize, U: Hash + Eq + Serialize, H: BuildHasher, RH: BuildHasher
+    {
+        super::as_seq::serialize(map, serializer)
+    }
+
+    /// Deserialize a map from a sequence of pairs. Fails with an error that names the position of
+    /// the first pair whose left or right value occurred before.
+    pub fn deserialize<'de, D, T, U, H, RH>(deserializer: D) -> Result<BiMap<T, U, H, RH>, D::Error>
+        where D: Deserializer<'de>, T: Hash + Eq + Deserialize<'de>, U: Hash + Eq + Deserialize<'de>, H: BuildHasher + Default, RH: BuildHasher + Default
+    {
+        deserializer.deserialize_seq(PairsVisitor::new(true))
+    }
+}
+
+/// A map from the left values to the right values, where later entries replace the mappings of
+/// earlier entries with equal values.
+pub mod as_map {
+    use std::hash::{BuildHasher, Hash};
+    use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use crate::BiMap;
+    use crate::serde_object::ObjectVisitor;
+
+    /// Serialize a map as a map from its left values to its right values, in iteration order.
+    pub fn serialize<S, T, U, H, RH>(map: &BiMap<T, U, H, RH>, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer, T: Hash + Eq + Serialize, U: Hash + Eq + Serialize, H: BuildHasher, RH: BuildHasher
+    {
+        serializer.collect_map(map.iter())
+    }
+
+    /// Deserialize a map from a map of left values to right values, inserting the entries in order
+    /// with [`BiMap::insert`].
+    pub fn deserialize<'de, D, T, U, H, RH>(deserializer: D) -> Result<BiMap<T, U, H, RH>, D::Error>
+        where D: Deserializer<'de>, T: Hash + Eq + Deserialize<'de>, U: Hash + Eq + Deserialize<'de>, H: BuildHasher + Default, RH: BuildHasher + Default
+    {
+        deserializer.deserialize_map(ObjectVisitor::new(false))
+    }
+}
+
+/// A map from the left values to the right values, where a duplicate right value fails
+/// deserialization. Formats that allow duplicate keys also fail on a duplicate left value.
+pub mod as_map_strict {
+    use std::hash::{BuildHasher, Hash};
+    use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use crate::BiMap;
+    use crate::serde_object::ObjectVisitor;
+
+    /// Serialize a map as a map from its left values to its right values, in iteration order.
+    pub fn serialize<S, T, U, H, RH>(map: &BiMap<T, U, H, RH>, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer, T: Hash + Eq + Serialize, U: Hash + Eq + Serialize, H: BuildHasher, RH: BuildHasher
+    {
+        super::as_map::serialize(map, serializer)
+    }
+
+    /// Deserialize a map from a map of left values to right values. Fails with an error that names
+    /// the position of the first entry whose left or right value occurred before.
+    pub fn deserialize<'de, D, T, U, H, RH>(deserializer: D) -> Result<BiMap<T, U, H, RH>, D::Error>
+        where D: Deserializer<'de>, T: Hash + Eq + Deserialize<'de>, U: Hash + Eq + Deserialize<'de>, H: BuildHasher + Default, RH: BuildHasher + Default
+    {
+        deserializer.deserialize_map(ObjectVisitor::new(true))
+    }
+}
@@ -0,0 +1,239 @@
+//! An object representation of maps for serde, keyed by the left values, for use with
+//! `#[serde(with = "bijective_map::serde_object")]` on fields that hold a [`BiMap`].
+//!
+//! ```
+//! # use bijective_map::BiMap;
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Config {
+//!     #[serde(with = "bijective_map::serde_object")]
+//!     languages: BiMap<String, u32>,
+//! }
+//! ```
+//!
+//! The map above serializes as `{"en": 1, "de": 2}` instead of the default `[["en", 1], ["de", 2]]`.
+//! The left values must serialize as strings, such as `String`, newtypes of strings, and unit enum
+//! variants, otherwise serializing fails. Deserializing fails if a left or right value occurs twice.
+//! The [`serde`](crate::serde) module has representations without these restrictions.
+
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+use serde::de::{MapAccess, Visitor};
+use serde::ser::{self, Impossible, SerializeMap};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::BiMap;
+use crate::serialization::{insert_pair, MAX_RESERVED_PAIRS};
+
+/// Serialize a map as an object from the left values to the right values.
+pub fn serialize<S, T, U, H, RH>(map: &BiMap<T, U, H, RH>, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer, T: Hash + Eq + Serialize, U: Hash + Eq + Serialize, H: BuildHasher, RH: BuildHasher
+{
+    let mut object = serializer.serialize_map(Some(map.len()))?;
+    for (left, right) in map.iter() {
+        let key = left.serialize(KeySerializer).map_err(|error| match error {
+            KeyError::NotAString(kind) => ser::Error::custom(format_args!("the left values of a map serialized as an object must serialize as strings, found {}", kind)),
+            KeyError::Custom(message) => ser::Error::custom(message),
+        })?;
+        object.serialize_entry(&key, right)?;
+    }
+    object.end()
+}
+
+/// Deserialize a map from an object from the left values to the right values. Fails if a left or a
+/// right value occurs twice.
+pub fn deserialize<'de, D, T, U, H, RH>(deserializer: D) -> Result<BiMap<T, U, H, RH>, D::Error>
+    where D: Deserializer<'de>, T: Hash + Eq + Deserialize<'de>, U: Hash + Eq + Deserialize<'de>, H: BuildHasher + Default, RH: BuildHasher + Default
+{
+    deserializer.deserialize_map(ObjectVisitor::new(true))
+}
+
+/// Builds a map from an object, see [`deserialize`]. Duplicate values fail if the visitor is
+/// strict.
+pub(crate) struct ObjectVisitor<T, U, H, RH>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    strict: bool,
+    marker: PhantomData<BiMap<T, U, H, RH>>,
+}
+
+impl<T, U, H, RH> ObjectVisitor<T, U, H, RH>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    pub(crate) fn new(strict: bool) -> Self {
+        ObjectVisitor { strict, marker: PhantomData }
+    }
+}
+
+impl<'de, T, U, H, RH> Visitor<'de> for ObjectVisitor<T, U, H, RH>
+    where T: Hash + Eq + Deserialize<'de>, U: Hash + Eq + Deserialize<'de>, H: BuildHasher + Default, RH: BuildHasher + Default
+{
+    type Value = BiMap<T, U, H, RH>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an object from left values to right values")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut object: A) -> Result<Self::Value, A::Error> {
+        let capacity = object.size_hint().unwrap_or(0).min(MAX_RESERVED_PAIRS);
+        let mut map = BiMap::with_default_hashers(capacity);
+        let mut entry = 0;
+        while let Some((left, right)) = object.next_entry()? {
+            insert_pair(&mut map, left, right, self.strict, "entry", entry)?;
+            entry += 1;
+        }
+        Ok(map)
+    }
+}
+
+/// The error of serializing a left value as the key of an object.
+#[derive(Debug)]
+enum KeyError {
+    /// The value did not serialize as a string, but as the named kind of value.
+    NotAString(&'static str),
+    /// The `Serialize` implementation of the value failed.
+    Custom(String),
+}
+
+impl fmt::Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyError::NotAString(kind) => write!(f, "a key serialized as {}", kind),
+            KeyError::Custom(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for KeyError {}
+
+impl ser::Error for KeyError {
+    fn custom<M: fmt::Display>(message: M) -> Self {
+        KeyError::Custom(message.to_string())
+    }
+}
+
+/// A serializer that only accepts strings, which turns a left value into the key of an object.
+struct KeySerializer;
+
+impl Serializer for KeySerializer {
+    type Ok = String;
+    type Error = KeyError;
+    type SerializeSeq = Impossible<String, KeyError>;
+    type SerializeTuple = Impossible<String, KeyError>;
+    type SerializeTupleStruct = Impossible<String, KeyError>;
+    type SerializeTupleVariant = Impossible<String, KeyError>;
+    type SerializeMap = Impossible<String, KeyError>;
+    type SerializeStruct = Impossible<String, KeyError>;
+    type SerializeStructVariant = Impossible<String, KeyError>;
+
+    fn serialize_str(self, value: &str) -> Result<String, KeyError> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_char(self, value: char) -> Result<String, KeyError> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, variant: &'static str) -> Result<String, KeyError> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<V: Serialize + ?Sized>(self, _: &'static str, value: &V) -> Result<String, KeyError> {
+        value.serialize(self)
+    }
+
+    fn serialize_bool(self, _: bool) -> Result<String, KeyError> {
+        Err(KeyError::NotAString("a boolean"))
+    }
+
+    fn serialize_i8(self, _: i8) -> Result<String, KeyError> {
+        Err(KeyError::NotAString("an integer"))
+    }
+
+    fn serialize_i16(self, _: i16) -> Result<String, KeyError> {
+        Err(KeyError::NotAString("an integer"))
+    }
+
+    fn serialize_i32(self, _: i32) -> Result<String, KeyError> {
+        Err(KeyError::NotAString("an integer"))
+    }
+
+    fn serialize_i64(self, _: i64) -> Result<String, KeyError> {
+        Err(KeyError::NotAString("an integer"))
+    }
+
+    fn serialize_u8(self, _: u8) -> Result<String, KeyError> {
+        Err(KeyError::NotAString("an integer"))
+    }
+
+    fn serialize_u16(self, _: u16) -> Result<String, KeyError> {
+        Err(KeyError::NotAString("an integer"))
+    }
+
+    fn serialize_u32(self, _: u32) -> Result<String, KeyError> {
+        Err(KeyError::NotAString("an integer"))
+    }
+
+    fn serialize_u64(self, _: u64) -> Result<String, KeyError> {
+        Err(KeyError::NotAString("an integer"))
+    }
+
+    fn serialize_f32(self, _: f32) -> Result<String, KeyError> {
+        Err(KeyError::NotAString("a float"))
+    }
+
+    fn serialize_f64(self, _: f64) -> Result<String, KeyError> {
+        Err(KeyError::NotAString("a float"))
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<String, KeyError> {
+        Err(KeyError::NotAString("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<String, KeyError> {
+        Err(KeyError::NotAString("an option"))
+    }
+
+    fn serialize_some<V: Serialize + ?Sized>(self, _: &V) -> Result<String, KeyError> {
+        Err(KeyError::NotAString("an option"))
+    }
+
+    fn serialize_unit(self) -> Result<String, KeyError> {
+        Err(KeyError::NotAString("a unit"))
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<String, KeyError> {
+        Err(KeyError::NotAString("a unit struct"))
+    }
+
+    fn serialize_newtype_variant<V: Serialize + ?Sized>(self, _: &'static str, _: u32, _: &'static str, _: &V) -> Result<String, KeyError> {
+        Err(KeyError::NotAString("an enum variant with data"))
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, KeyError> {
+        Err(KeyError::NotAString("a sequence"))
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, KeyError> {
+        Err(KeyError::NotAString("a tuple"))
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeTupleStruct, KeyError> {
+        Err(KeyError::NotAString("a tuple struct"))
+    }
+
+    fn serialize_tuple_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeTupleVariant, KeyError> {
+        Err(KeyError::NotAString("an enum variant with data"))
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, KeyError> {
+        Err(KeyError::NotAString("a map"))
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeStruct, KeyError> {
+        Err(KeyError::NotAString("a struct"))
+    }
+
+    fn serialize_struct_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeStructVariant, KeyError> {
+        Err(KeyError::NotAString("an enum variant with data"))
+    }
+}
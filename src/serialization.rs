@@ -0,0 +1,85 @@
+//! The serde representation of a map, a sequence of its pairs in iteration order.
+
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::BiMap;
+
+/// The largest number of pairs that a size hint reserves space for up front, so that malformed
+/// input cannot make the map allocate more than the input holds.
+pub(crate) const MAX_RESERVED_PAIRS: usize = 1 << 16;
+
+impl<T, U, H, RH> Serialize for BiMap<T, U, H, RH>
+    where T: Hash + Eq + Serialize, U: Hash + Eq + Serialize, H: BuildHasher, RH: BuildHasher
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+/// Insert a deserialized pair into a map. If `strict` is set, a pair that shares a value with a
+/// pair of the map fails with an error that names the position of the pair as the given `item`,
+/// otherwise it replaces the mappings of the values as by [`BiMap::insert`].
+pub(crate) fn insert_pair<T, U, H, RH, E>(map: &mut BiMap<T, U, H, RH>, left: T, right: U, strict: bool, item: &str, position: usize) -> Result<(), E>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher, E: de::Error
+{
+    if !strict {
+        map.insert(left, right);
+        return Ok(());
+    }
+    match map.try_insert(left, right) {
+        Ok(()) => Ok(()),
+        Err(error) if error.left_occupied => Err(E::custom(format_args!("duplicate left value in {} {}", item, position))),
+        Err(_) => Err(E::custom(format_args!("duplicate right value in {} {}", item, position))),
+    }
+}
+
+/// Builds a map from a sequence of pairs, see [`BiMap::deserialize`]. Duplicate values fail if the
+/// visitor is strict.
+pub(crate) struct PairsVisitor<T, U, H, RH>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    strict: bool,
+    marker: PhantomData<BiMap<T, U, H, RH>>,
+}
+
+impl<T, U, H, RH> PairsVisitor<T, U, H, RH>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    pub(crate) fn new(strict: bool) -> Self {
+        PairsVisitor { strict, marker: PhantomData }
+    }
+}
+
+impl<'de, T, U, H, RH> Visitor<'de> for PairsVisitor<T, U, H, RH>
+    where T: Hash + Eq + Deserialize<'de>, U: Hash + Eq + Deserialize<'de>, H: BuildHasher + Default, RH: BuildHasher + Default
+{
+    type Value = BiMap<T, U, H, RH>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence of pairs")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let capacity = seq.size_hint().unwrap_or(0).min(MAX_RESERVED_PAIRS);
+        let mut map = BiMap::with_default_hashers(capacity);
+        let mut position = 0;
+        while let Some((left, right)) = seq.next_element()? {
+            insert_pair(&mut map, left, right, self.strict, "pair", position)?;
+            position += 1;
+        }
+        Ok(map)
+    }
+}
+
+/// Deserializes a map from a sequence of pairs. The pairs are inserted in order with
+/// [`BiMap::insert`], so later pairs replace the mappings of earlier pairs with equal values.
+impl<'de, T, U, H, RH> Deserialize<'de> for BiMap<T, U, H, RH>
+    where T: Hash + Eq + Deserialize<'de>, U: Hash + Eq + Deserialize<'de>, H: BuildHasher + Default, RH: BuildHasher + Default
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(PairsVisitor::new(false))
+    }
+}
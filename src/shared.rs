@@ -0,0 +1,129 @@
+//! A bi-directional map whose clones share their pairs and indices until one of them is mutated.
+//!
+//! Cloning a [`SharedBiMap`] only increments a reference count, so snapshots of large maps are
+//! cheap to take and to hand to other threads. The first mutation of a map that shares its
+//! contents with a clone copies the contents, like [`Arc::make_mut`], so the clones never observe
+//! each other's mutations. Mutating a map that is not shared does not copy anything.
+
+use std::hash::{BuildHasher, Hash, RandomState};
+use std::ops::Deref;
+use std::sync::Arc;
+use crate::BiMap;
+
+/// A bi-directional map with copy-on-write clones. See the [module documentation](self) for when the
+/// contents are copied.
+///
+/// The map dereferences to [`BiMap`] for all read-only operations. Callbacks set on the underlying
+/// map are not copied, so a map that was copied on mutation starts without them.
+#[derive(Debug)]
+pub struct SharedBiMap<T, U, H = RandomState, RH = RandomState>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    map: Arc<BiMap<T, U, H, RH>>,
+}
+
+/// Two maps are equal if they contain the same pairs, like [`BiMap`]s.
+impl<T, U, H, RH> PartialEq for SharedBiMap<T, U, H, RH>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.map, &other.map) || self.map == other.map
+    }
+}
+
+impl<T, U, H, RH> Eq for SharedBiMap<T, U, H, RH>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{}
+
+impl<T, U> SharedBiMap<T, U>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    /// Create a new empty map.
+    pub fn new() -> Self {
+        SharedBiMap { map: Arc::new(BiMap::new()) }
+    }
+}
+
+impl<T, U> Default for SharedBiMap<T, U>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, U, H, RH> SharedBiMap<T, U, H, RH>
+    where T: Hash + Eq + Clone, U: Hash + Eq + Clone, H: BuildHasher + Clone, RH: BuildHasher + Clone
+{
+    /// Returns the underlying map for mutation, and copies its contents first if they are shared
+    /// with a clone of this map.
+    pub fn make_mut(&mut self) -> &mut BiMap<T, U, H, RH> {
+        Arc::make_mut(&mut self.map)
+    }
+
+    /// Inserts a value pair into the map. See [`BiMap::insert`].
+    pub fn insert(&mut self, left: T, right: U) -> (Option<U>, Option<T>) {
+        self.make_mut().insert(left, right)
+    }
+
+    /// Deletes the mappings for the given left value and returns the right value that was mapped to it.
+    /// If the left value is not in the map, None is returned and the contents are not copied.
+    pub fn remove_left(&mut self, left: &T) -> Option<U> {
+        if !self.map.contains_left(left) {
+            return None;
+        }
+        self.make_mut().remove_left(left)
+    }
+
+    /// Deletes the mappings for the given right value and returns the left value that was mapped to it.
+    /// If the right value is not in the map, None is returned and the contents are not copied.
+    pub fn remove_right(&mut self, right: &U) -> Option<T> {
+        if !self.map.contains_right(right) {
+            return None;
+        }
+        self.make_mut().remove_right(right)
+    }
+
+    /// Returns the underlying map, and copies its contents if they are shared with a clone of this
+    /// map.
+    pub fn into_inner(self) -> BiMap<T, U, H, RH> {
+        Arc::unwrap_or_clone(self.map)
+    }
+}
+
+impl<T, U, H, RH> SharedBiMap<T, U, H, RH>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    /// Returns true if the contents of the map are shared with a clone, so that the next mutation
+    /// copies them.
+    pub fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.map) > 1
+    }
+}
+
+// clones share the contents, so neither the values nor the hashers have to be `Clone`
+impl<T, U, H, RH> Clone for SharedBiMap<T, U, H, RH>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    fn clone(&self) -> Self {
+        SharedBiMap { map: Arc::clone(&self.map) }
+    }
+}
+
+impl<T, U, H, RH> Deref for SharedBiMap<T, U, H, RH>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    type Target = BiMap<T, U, H, RH>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+impl<T, U, H, RH> From<BiMap<T, U, H, RH>> for SharedBiMap<T, U, H, RH>
+    where T: Hash + Eq, U: Hash + Eq
+{
+    fn from(map: BiMap<T, U, H, RH>) -> Self {
+        SharedBiMap { map: Arc::new(map) }
+    }
+}
@@ -0,0 +1,1160 @@
+//! The hash indices of the two sides of a map.
+//!
+//! Each side of a map owns an [`IndexSide`], which holds the hash index of that side, the
+//! back-references from the buckets into the index, and the hasher of its values. The algorithms
+//! on an index are its methods, so probing, inserting, deleting and rebuilding are implemented
+//! once for both sides.
+//!
+//! By default, an index is a Robin Hood table of slots and probe distances, and the back-reference
+//! of a bucket is its slot. With the `hashbrown-backend` feature, an index is a hashbrown table of
+//! bucket positions instead, and the back-reference of a bucket is the hash of its value, which
+//! finds its entry in the table without hashing the value again. Both implementations have the
+//! same interface, so the map does not depend on the backend.
+//!
+//! The operations of the map that touch the values of one side, e.g. mutating a value in place,
+//! are generic over a [`MapSide`], which selects the values, the index and the negative lookup
+//! filter of that side from the map, e.g. `map.unindex::<LeftSide>(bucket)`.
+
+use std::hash::{BuildHasher, Hash};
+use allocator_api2::alloc::{Allocator, Global};
+use crate::{BiMap, SATURATED_DISTANCE};
+use crate::filter::{BloomFilter, NegativeFilter};
+use crate::storage::Storage;
+#[cfg(not(feature = "hashbrown-backend"))]
+pub(crate) use robin_hood::{hash_to_index, probe_distance, reduce_hash, shift_back_position, BuiltIndex, EmptyIndex, IndexSide};
+#[cfg(feature = "hashbrown-backend")]
+pub(crate) use table::{hash_to_index, reduce_hash, shift_back_position, BuiltIndex, EmptyIndex, IndexSide};
+#[cfg(all(feature = "hashbrown-backend", feature = "stats"))]
+pub(crate) use table::probe_distance;
+
+/// Convert a probe distance into its stored representation.
+#[inline(always)]
+pub(crate) fn saturate_distance(distance: usize) -> u8 {
+    distance.min(SATURATED_DISTANCE as usize) as u8
+}
+
+#[cfg(not(feature = "hashbrown-backend"))]
+mod robin_hood {
+    use std::hash::{BuildHasher, Hash};
+    use std::mem;
+    use allocator_api2::alloc::{Allocator, Global};
+    use allocator_api2::vec;
+    use crate::{SlotIndex, SATURATED_DISTANCE};
+    use crate::errors::TryReserveError;
+    use crate::storage::Storage;
+    use super::saturate_distance;
+
+    /// The hash index of one side of a map, together with the hasher of the values of that side.
+    #[derive(Debug)]
+    pub(crate) struct IndexSide<H, A: Allocator = Global> {
+        /// The slots of the hash index, which point at the buckets in the pair storage.
+        pub(crate) slots: vec::Vec<SlotIndex, A>,
+        /// The probe distances of the entries in the slots, saturating at `SATURATED_DISTANCE`.
+        pub(crate) distances: vec::Vec<u8, A>,
+        /// The slot of each bucket in the index, stored in parallel to the pairs. Small maps do not
+        /// keep back-references.
+        pub(crate) back_references: vec::Vec<usize, A>,
+        /// The hasher of the values of this side.
+        pub(crate) hasher: H,
+    }
+
+    /// The empty slots and probe distances of an index that is about to be built.
+    pub(crate) struct EmptyIndex<A: Allocator = Global> {
+        slots: vec::Vec<SlotIndex, A>,
+        distances: vec::Vec<u8, A>,
+    }
+
+    /// The slots and probe distances of an index that was built for all pairs of a map, but is not
+    /// installed in its side yet.
+    pub(crate) struct BuiltIndex<A: Allocator = Global> {
+        slots: vec::Vec<SlotIndex, A>,
+        distances: vec::Vec<u8, A>,
+        pub(crate) longest_probe: u8,
+    }
+
+    // the allocators are not compared, like the allocators of vectors
+    impl<H: PartialEq, A: Allocator> PartialEq for IndexSide<H, A> {
+        fn eq(&self, other: &Self) -> bool {
+            self.slots == other.slots && self.distances == other.distances
+                && self.back_references == other.back_references && self.hasher == other.hasher
+        }
+    }
+
+    impl<H: Eq, A: Allocator> Eq for IndexSide<H, A> {}
+
+    // the slots are cloned into the existing allocations, which fit if the capacities are equal
+    impl<H: Clone, A: Allocator + Clone> Clone for IndexSide<H, A> {
+        fn clone(&self) -> Self {
+            IndexSide {
+                slots: self.slots.clone(),
+                distances: self.distances.clone(),
+                back_references: self.back_references.clone(),
+                hasher: self.hasher.clone(),
+            }
+        }
+
+        fn clone_from(&mut self, source: &Self) {
+            self.slots.clone_from(&source.slots);
+            self.distances.clone_from(&source.distances);
+            self.back_references.clone_from(&source.back_references);
+            self.hasher.clone_from(&source.hasher);
+        }
+    }
+
+    impl<H> IndexSide<H> {
+        /// A side without an index, which does not allocate.
+        pub(crate) const fn new(hasher: H) -> Self {
+            IndexSide { slots: vec::Vec::new(), distances: vec::Vec::new(), back_references: vec::Vec::new(), hasher }
+        }
+    }
+
+    impl<H, A: Allocator + Clone> IndexSide<H, A> {
+        /// A side with an empty index of the given capacity, and room for the back-references of
+        /// `buckets` buckets, in the given allocator.
+        pub(crate) fn with_capacity_in(capacity: usize, buckets: usize, hasher: H, alloc: A) -> Self {
+            let EmptyIndex { slots, distances } = Self::allocate_in(capacity, alloc.clone());
+            IndexSide { slots, distances, back_references: vec::Vec::with_capacity_in(buckets, alloc), hasher }
+        }
+
+        /// Allocate the empty slots and distances of an index of the given capacity in the given
+        /// allocator.
+        fn allocate_in(capacity: usize, alloc: A) -> EmptyIndex<A> {
+            EmptyIndex {
+                slots: allocator_api2::vec![in alloc.clone(); SlotIndex::EMPTY; capacity],
+                distances: allocator_api2::vec![in alloc; 0; capacity],
+            }
+        }
+
+        /// The allocator of the slots, the probe distances and the back-references.
+        pub(crate) fn allocator(&self) -> &A {
+            self.back_references.allocator()
+        }
+
+        /// Allocate the empty slots and distances of an index of the given capacity in the
+        /// allocator of the side.
+        pub(crate) fn allocate(&self, capacity: usize) -> EmptyIndex<A> {
+            Self::allocate_in(capacity, self.allocator().clone())
+        }
+
+        /// Allocate the slots and distances of an index like [`allocate`], but return an error
+        /// instead of panicking or aborting if the allocation fails.
+        ///
+        /// [`allocate`]: #method.allocate
+        pub(crate) fn try_allocate(&self, capacity: usize) -> Result<EmptyIndex<A>, TryReserveError> {
+            let mut slots = vec::Vec::new_in(self.allocator().clone());
+            slots.try_reserve_exact(capacity)?;
+            slots.resize(capacity, SlotIndex::EMPTY);
+            let mut distances = vec::Vec::new_in(self.allocator().clone());
+            distances.try_reserve_exact(capacity)?;
+            distances.resize(capacity, 0);
+            Ok(EmptyIndex { slots, distances })
+        }
+
+        /// The number of slots of the index.
+        #[inline(always)]
+        pub(crate) fn capacity(&self) -> usize {
+            self.slots.len()
+        }
+
+        /// The bucket that the occupied slot at `index` points at.
+        #[inline(always)]
+        pub(crate) fn bucket(&self, index: usize) -> usize {
+            self.slots[index].occupied()
+        }
+
+        /// The slot that points at the given bucket.
+        #[inline(always)]
+        pub(crate) fn slot_of(&self, bucket_index: usize) -> usize {
+            self.back_references[bucket_index]
+        }
+
+        /// The buckets of all occupied slots, in the order of the slots.
+        pub(crate) fn buckets(&self) -> impl Iterator<Item=usize> + '_ {
+            self.slots.iter().filter_map(|slot| slot.bucket())
+        }
+
+        /// The number of bytes allocated for the slots and probe distances of the index.
+        pub(crate) fn index_bytes(&self) -> usize {
+            Self::bytes_for(self.capacity())
+        }
+
+        /// The number of bytes allocated for the slots and probe distances of an index of the given
+        /// capacity.
+        pub(crate) fn bytes_for(capacity: usize) -> usize {
+            capacity * (mem::size_of::<SlotIndex>() + mem::size_of::<u8>())
+        }
+
+        /// Make room for the back-reference of a bucket that is pushed to the tail of the pairs,
+        /// before its mapping is inserted with [`insert_mapping`].
+        ///
+        /// [`insert_mapping`]: #method.insert_mapping
+        pub(crate) fn push_bucket(&mut self) {
+            self.back_references.push(usize::MAX);
+        }
+
+        /// Insert a mapping for the given bucket at `mapping_index`, which must be the position
+        /// returned by [`probe`] or [`probe_insert_position`]. All entries up to the next empty
+        /// slot are moved one slot to the right, and the back-references of the inserted and all
+        /// moved buckets are updated. Returns the longest probe distance among the inserted and
+        /// moved entries.
+        ///
+        /// [`probe`]: #method.probe
+        /// [`probe_insert_position`]: #method.probe_insert_position
+        pub(crate) fn insert_mapping(&mut self, mapping_index: usize, ideal_index: usize, bucket_index: usize) -> u8 {
+            insert_into_index(&mut self.slots, &mut self.distances, &mut self.back_references, mapping_index, ideal_index, bucket_index)
+        }
+
+        /// Delete the mapping at `mapping_index` and move the following entries to the left if
+        /// necessary. Returns the index that is empty after the deletion, which is the last index
+        /// that was moved to the left, or `mapping_index` if nothing was moved.
+        ///
+        /// The new distances of saturated entries must have been computed before with
+        /// [`shifted_saturated`].
+        ///
+        /// [`shifted_saturated`]: #method.shifted_saturated
+        pub(crate) fn delete_mapping(&mut self, mapping_index: usize, shifted_saturated: Vec<u8>) -> usize {
+            let mut shifted_saturated = shifted_saturated.into_iter();
+            let capacity = self.capacity();
+            self.slots[mapping_index] = SlotIndex::EMPTY;
+            let mut current_mapping_index = (mapping_index + 1) % capacity;
+
+            // move elements over until we find a free spot or an element that is already in the
+            // right spot
+            while let Some(current_neighbor) = self.slots[current_mapping_index].bucket() {
+                if self.distances[current_mapping_index] == 0 {
+                    break;
+                }
+
+                let previous_mapping_index = (current_mapping_index + capacity - 1) % capacity;
+                let distance = match self.distances[current_mapping_index] {
+                    SATURATED_DISTANCE => shifted_saturated.next().expect("the distances of saturated entries are computed before the deletion"),
+                    distance => distance - 1,
+                };
+
+                self.slots.swap(previous_mapping_index, current_mapping_index);
+                self.distances[previous_mapping_index] = distance;
+                self.back_references[current_neighbor] = previous_mapping_index;
+
+                current_mapping_index = (current_mapping_index + 1) % capacity;
+            }
+
+            (current_mapping_index + capacity - 1) % capacity
+        }
+
+        /// Point the slot of a bucket at the bucket, after the bucket moved to `bucket_index`.
+        #[inline(always)]
+        fn redirect(&mut self, bucket_index: usize) {
+            self.slots[self.back_references[bucket_index]] = SlotIndex::new(bucket_index);
+        }
+
+        /// Point the mapping of the bucket at `from` at the bucket at `to`, whose own mapping was
+        /// deleted, because the value of `from` moves into the bucket at `to`.
+        pub(crate) fn move_mapping(&mut self, from: usize, to: usize) {
+            let slot = self.back_references[from];
+            self.slots[slot] = SlotIndex::new(to);
+            self.back_references[to] = slot;
+        }
+
+        /// Swap the mappings of two buckets, after the buckets swapped their positions.
+        pub(crate) fn swap(&mut self, a: usize, b: usize) {
+            self.back_references.swap(a, b);
+            self.redirect(a);
+            self.redirect(b);
+        }
+
+        /// Remove the back-reference of a bucket whose mapping was deleted, and move the
+        /// back-reference of the last bucket into its position, like the pair storage moves the
+        /// last bucket.
+        pub(crate) fn swap_remove(&mut self, bucket_index: usize) {
+            self.back_references.swap_remove(bucket_index);
+            if bucket_index < self.back_references.len() {
+                self.redirect(bucket_index);
+            }
+        }
+
+        /// Empty all slots of the index and drop the back-references, keeping the allocations.
+        pub(crate) fn clear(&mut self) {
+            self.slots.fill(SlotIndex::EMPTY);
+            self.back_references.clear();
+        }
+
+        /// Free the index and the back-references, for a map that becomes small.
+        pub(crate) fn release(&mut self) {
+            let alloc = self.allocator().clone();
+            self.slots = vec::Vec::new_in(alloc.clone());
+            self.distances = vec::Vec::new_in(alloc.clone());
+            self.back_references = vec::Vec::new_in(alloc);
+        }
+
+        /// Point the back-references at the slots of the installed index, after building another
+        /// index overwrote them.
+        pub(crate) fn restore_back_references(&mut self) {
+            if self.capacity() == 0 {
+                self.back_references.clear();
+                return;
+            }
+
+            for (slot, entry) in self.slots.iter().enumerate() {
+                if let Some(bucket) = entry.bucket() {
+                    self.back_references[bucket] = slot;
+                }
+            }
+        }
+
+        /// Replace the index with an index that was built for all pairs of the map.
+        pub(crate) fn install(&mut self, built: BuiltIndex<A>) {
+            self.slots = built.slots;
+            self.distances = built.distances;
+        }
+
+        /// Discard the index and rebuild it at the given capacity in its current allocations, from
+        /// the ideal indices of the buckets that [`store_ideal_indices`] wrote into the
+        /// back-references. No value is hashed. The back-references are pointed at the new slots
+        /// afterwards. Returns the longest probe distance.
+        ///
+        /// [`store_ideal_indices`]: #method.store_ideal_indices
+        pub(crate) fn rebuild(&mut self, capacity: usize) -> u8 {
+            // the allocations are resized instead of replaced, so the old and the new slots are not
+            // allocated at the same time, unless the allocator has to move them
+            self.slots.clear();
+            self.slots.shrink_to(capacity);
+            self.slots.reserve_exact(capacity);
+            self.slots.resize(capacity, SlotIndex::EMPTY);
+            self.distances.clear();
+            self.distances.shrink_to(capacity);
+            self.distances.reserve_exact(capacity);
+            self.distances.resize(capacity, 0);
+
+            let longest_probe = place_at_ideal_indices(&mut self.slots, &mut self.distances, &self.back_references);
+            self.restore_back_references();
+            longest_probe
+        }
+    }
+
+    impl<H: BuildHasher, A: Allocator + Clone> IndexSide<H, A> {
+        /// Get the ideal index (i.e. without collisions) of a value under the current capacity.
+        #[inline(always)]
+        pub(crate) fn ideal_index<E: Hash>(&self, element: &E) -> usize {
+            hash_to_index(&self.hasher, element, self.capacity())
+        }
+
+        /// Perform the probing algorithm to find the index of an element, starting at its ideal
+        /// index. If the element is not in the index, the error holds the position where it has to
+        /// be inserted, which is either empty or holds an entry with a lower probe distance.
+        ///
+        /// # Parameters
+        /// * `element` - The element for which to find the index.
+        /// * `ideal_index` - The ideal index of the element, as computed by [`ideal_index`].
+        /// * `data` - The buckets that contain the elements.
+        /// * `lookup` - A function that returns elements of the element type from a bucket.
+        ///
+        /// [`ideal_index`]: #method.ideal_index
+        #[inline(always)]
+        pub(crate) fn probe<T, U, E>(&self, element: &E, ideal_index: usize, data: &Storage<T, U, A>, lookup: fn(&Storage<T, U, A>, usize) -> &E) -> Result<usize, usize>
+            where E: Hash + Eq
+        {
+            let capacity = self.capacity();
+            let mut index = ideal_index;
+            let mut dist = 0;
+            while let Some(bucket) = self.slots[index].bucket() {
+                // if the element were in the map, it would have displaced any entry closer to its
+                // ideal index. Only saturated distances require hashing the stored element.
+                let target_probe_dist = self.distances[index];
+                if dist > target_probe_dist as usize && (target_probe_dist < SATURATED_DISTANCE
+                    || dist > probe_distance(self.ideal_index(lookup(data, bucket)), index, capacity)) {
+                    return Err(index);
+                }
+
+                if lookup(data, bucket) == element {
+                    return Ok(index);
+                }
+
+                index = (index + 1) % capacity;
+                dist += 1;
+            }
+            Err(index)
+        }
+
+        /// Find the position where an element has to be inserted, assuming that the element is not
+        /// in the index yet. This is the probing algorithm of [`probe`] without comparing the
+        /// element to the stored elements, so it must not be used for elements that may already be
+        /// in the index.
+        ///
+        /// [`probe`]: #method.probe
+        #[inline(always)]
+        pub(crate) fn probe_insert_position<T, U, E>(&self, ideal_index: usize, data: &Storage<T, U, A>, lookup: fn(&Storage<T, U, A>, usize) -> &E) -> usize
+            where E: Hash
+        {
+            probe_insert_position(&self.slots, &self.distances, &self.hasher, ideal_index, data, lookup)
+        }
+
+        /// Compute the distances that saturated entries will have after the deletion of the mapping
+        /// at `mapping_index` shifted them back, in the order in which [`delete_mapping`] visits
+        /// them.
+        ///
+        /// [`delete_mapping`]: #method.delete_mapping
+        pub(crate) fn shifted_saturated<T, U, E>(&self, mapping_index: usize, data: &Storage<T, U, A>, lookup: fn(&Storage<T, U, A>, usize) -> &E) -> Vec<u8>
+            where E: Hash
+        {
+            let mut shifted = Vec::new();
+            let capacity = self.capacity();
+            let mut index = (mapping_index + 1) % capacity;
+            while index != mapping_index {
+                let Some(bucket) = self.slots[index].bucket() else {
+                    break;
+                };
+                match self.distances[index] {
+                    0 => break,
+                    SATURATED_DISTANCE => {
+                        let previous_index = (index + capacity - 1) % capacity;
+                        let ideal_index = self.ideal_index(lookup(data, bucket));
+                        shifted.push(saturate_distance(probe_distance(ideal_index, previous_index, capacity)));
+                    }
+                    _ => {}
+                }
+                index = (index + 1) % capacity;
+            }
+            shifted
+        }
+
+        /// Insert all buckets into the given empty slots, in bucket order, hashing their elements
+        /// with the given hasher or the hasher of the side. The back-references are overwritten to
+        /// refer into the new slots, but the installed index is left untouched until [`install`].
+        /// If a `Hash` implementation panics, [`restore_back_references`] restores them.
+        ///
+        /// [`install`]: #method.install
+        /// [`restore_back_references`]: #method.restore_back_references
+        pub(crate) fn build<T, U, E>(&mut self, empty: EmptyIndex<A>, hasher: Option<&H>, data: &Storage<T, U, A>, lookup: fn(&Storage<T, U, A>, usize) -> &E) -> BuiltIndex<A>
+            where E: Hash
+        {
+            let EmptyIndex { mut slots, mut distances } = empty;
+            let hasher = hasher.unwrap_or(&self.hasher);
+            let capacity = slots.len();
+
+            // small maps do not keep back-references, they are all overwritten below
+            self.back_references.resize(data.len(), usize::MAX);
+            let mut longest_probe = 0;
+            for bucket_index in 0..data.len() {
+                let ideal_index = hash_to_index(hasher, lookup(data, bucket_index), capacity);
+                let position = probe_insert_position(&slots, &distances, hasher, ideal_index, data, lookup);
+                let distance = insert_into_index(&mut slots, &mut distances, &mut self.back_references, position, ideal_index, bucket_index);
+                longest_probe = longest_probe.max(distance);
+            }
+            BuiltIndex { slots, distances, longest_probe }
+        }
+
+        /// Store the ideal index of every bucket at the given capacity in its back-reference, for
+        /// [`rebuild`]. If a `Hash` implementation panics, [`restore_back_references`] restores
+        /// them.
+        ///
+        /// [`rebuild`]: #method.rebuild
+        /// [`restore_back_references`]: #method.restore_back_references
+        pub(crate) fn store_ideal_indices<T, U, E>(&mut self, capacity: usize, data: &Storage<T, U, A>, lookup: fn(&Storage<T, U, A>, usize) -> &E)
+            where E: Hash
+        {
+            self.back_references.resize(data.len(), usize::MAX);
+            for (bucket_index, back_reference) in self.back_references.iter_mut().enumerate() {
+                *back_reference = hash_to_index(&self.hasher, lookup(data, bucket_index), capacity);
+            }
+        }
+
+        /// Find the buckets with equal elements, by comparing the elements within each run of
+        /// entries with the same ideal index, given the ideal indices of all buckets. Returns the
+        /// buckets as pairs of the later and the earlier bucket.
+        pub(crate) fn equal_value_edges<T, U, E>(&self, ideal_indices: &[usize], data: &Storage<T, U, A>, lookup: fn(&Storage<T, U, A>, usize) -> &E) -> Vec<(usize, usize)>
+            where E: Eq
+        {
+            let capacity = self.capacity();
+            let mut edges = Vec::new();
+
+            // start after an empty slot, so that no run wraps around the start of the scan
+            let empty = self.slots.iter().position(|slot| slot.bucket().is_none()).expect("the load factor keeps a slot empty");
+            let mut run = Vec::new();
+            let mut run_ideal_index = usize::MAX;
+            for offset in 1..=capacity {
+                let bucket = self.slots[(empty + offset) % capacity].bucket();
+                let ideal_index = bucket.map(|bucket| ideal_indices[bucket]);
+                if ideal_index != Some(run_ideal_index) {
+                    for (i, &a) in run.iter().enumerate() {
+                        for &b in &run[i + 1..] {
+                            if lookup(data, a) == lookup(data, b) {
+                                edges.push((a.max(b), a.min(b)));
+                            }
+                        }
+                    }
+                    run.clear();
+                }
+                if let (Some(bucket), Some(ideal_index)) = (bucket, ideal_index) {
+                    run.push(bucket);
+                    run_ideal_index = ideal_index;
+                }
+            }
+            edges
+        }
+    }
+
+    /// Convert an element into an index by hashing it and mapping the hash to the given capacity.
+    /// The reduction is performed on the full 64-bit hash, so that hashers that keep their entropy
+    /// in the upper bits do not degrade on 32-bit platforms.
+    #[inline(always)]
+    pub(crate) fn hash_to_index<E, G>(hasher: &G, element: &E, capacity: usize) -> usize
+        where E: Hash + ?Sized, G: BuildHasher
+    {
+        reduce_hash(hasher.hash_one(element), capacity)
+    }
+
+    /// Map a hash to an index of the given capacity. See [`hash_to_index`].
+    #[inline(always)]
+    pub(crate) fn reduce_hash(hash: u64, capacity: usize) -> usize {
+        (hash % capacity as u64) as usize
+    }
+
+    /// Compute the distance from the ideal index of an element to the index it is stored at,
+    /// taking wrap-around at the end of the index into account.
+    #[inline(always)]
+    pub(crate) fn probe_distance(ideal_index: usize, index: usize, capacity: usize) -> usize {
+        if index >= ideal_index {
+            index - ideal_index
+        } else {
+            index + capacity - ideal_index
+        }
+    }
+
+    /// Correct a probe position of an element that is not in an index, after a backward-shift
+    /// deletion moved entries in that index. Deleting a mapping at `deleted_index` moves all
+    /// entries up to and including `vacated_index` one slot to the left, so an insertion position
+    /// after the deleted mapping moves one slot to the left as well, unless it is the ideal index
+    /// of the element already.
+    ///
+    /// # Parameters
+    /// * `position` - The insertion position returned by the probe before the deletion.
+    /// * `ideal_index` - The ideal index of the element that would be inserted at `position`.
+    /// * `deleted_index` - The index of the deleted mapping.
+    /// * `vacated_index` - The index that was left empty by the deletion, as returned by
+    ///   [`IndexSide::delete_mapping`].
+    /// * `capacity` - The capacity of the index.
+    pub(crate) fn shift_back_position(position: usize, ideal_index: usize, deleted_index: usize, vacated_index: usize, capacity: usize) -> usize {
+        let offset = (position + capacity - deleted_index) % capacity;
+        let shifted = (vacated_index + capacity - deleted_index) % capacity;
+        if offset > 0 && offset <= shifted + 1 && position != ideal_index {
+            (position + capacity - 1) % capacity
+        } else {
+            position
+        }
+    }
+
+    /// Find the position where an element has to be inserted into the given slots. See
+    /// [`IndexSide::probe_insert_position`].
+    #[inline(always)]
+    fn probe_insert_position<T, U, A, E, G>(slots: &[SlotIndex], distances: &[u8], hasher: &G, ideal_index: usize, data: &Storage<T, U, A>, lookup: fn(&Storage<T, U, A>, usize) -> &E) -> usize
+        where A: Allocator, E: Hash, G: BuildHasher
+    {
+        let capacity = slots.len();
+        let mut index = ideal_index;
+        let mut dist = 0;
+        while let Some(bucket) = slots[index].bucket() {
+            let target_probe_dist = distances[index];
+            if dist > target_probe_dist as usize && (target_probe_dist < SATURATED_DISTANCE
+                || dist > probe_distance(hash_to_index(hasher, lookup(data, bucket), capacity), index, capacity)) {
+                return index;
+            }
+
+            index = (index + 1) % capacity;
+            dist += 1;
+        }
+        index
+    }
+
+    /// Insert a mapping into the given slots, moving all entries to the right until an empty slot
+    /// is found. See [`IndexSide::insert_mapping`].
+    #[inline(always)]
+    fn insert_into_index(slots: &mut [SlotIndex], distances: &mut [u8], back_references: &mut [usize], mut mapping_index: usize, ideal_index: usize, bucket_index: usize) -> u8 {
+        let mut current_content = bucket_index;
+        let mut current_distance = saturate_distance(probe_distance(ideal_index, mapping_index, slots.len()));
+        let mut longest_distance = 0;
+        while let Some(displaced) = slots[mapping_index].bucket() {
+            slots[mapping_index] = SlotIndex::new(current_content);
+            mem::swap(&mut distances[mapping_index], &mut current_distance);
+            back_references[current_content] = mapping_index;
+            longest_distance = longest_distance.max(distances[mapping_index]);
+            mapping_index = (mapping_index + 1) % slots.len();
+
+            // the displaced entry moves one slot further away from its ideal index
+            current_content = displaced;
+            current_distance = current_distance.saturating_add(1);
+        }
+        slots[mapping_index] = SlotIndex::new(current_content);
+        distances[mapping_index] = current_distance;
+        back_references[current_content] = mapping_index;
+        longest_distance.max(current_distance)
+    }
+
+    /// Insert all buckets into empty slots, in bucket order, given their ideal indices. Entries are
+    /// placed like [`probe_insert_position`] and [`insert_into_index`] would place them, but the
+    /// distances of saturated entries are computed from their stored ideal index instead of their
+    /// hash, and the ideal indices are left untouched. Returns the longest probe distance.
+    fn place_at_ideal_indices(slots: &mut [SlotIndex], distances: &mut [u8], ideal_indices: &[usize]) -> u8 {
+        let capacity = slots.len();
+        let mut longest_distance = 0;
+        for (bucket_index, &ideal_index) in ideal_indices.iter().enumerate() {
+            // find the first entry that is closer to its ideal index than the new entry would be
+            let mut index = ideal_index;
+            let mut distance = 0;
+            while let Some(resident) = slots[index].bucket() {
+                let resident_distance = match distances[index] {
+                    SATURATED_DISTANCE => probe_distance(ideal_indices[resident], index, capacity),
+                    stored => stored as usize,
+                };
+                if distance > resident_distance {
+                    break;
+                }
+                index = (index + 1) % capacity;
+                distance += 1;
+            }
+
+            // shift the following entries up to the next empty slot back by one
+            let mut current_content = bucket_index;
+            let mut current_distance = saturate_distance(distance);
+            while let Some(displaced) = slots[index].bucket() {
+                slots[index] = SlotIndex::new(current_content);
+                mem::swap(&mut distances[index], &mut current_distance);
+                longest_distance = longest_distance.max(distances[index]);
+                index = (index + 1) % capacity;
+                current_content = displaced;
+                current_distance = current_distance.saturating_add(1);
+            }
+            slots[index] = SlotIndex::new(current_content);
+            distances[index] = current_distance;
+            longest_distance = longest_distance.max(current_distance);
+        }
+        longest_distance
+    }
+
+}
+
+#[cfg(feature = "hashbrown-backend")]
+mod table {
+    use std::hash::{BuildHasher, Hash};
+    use std::mem;
+    use allocator_api2::alloc::{Allocator, Global};
+    use allocator_api2::vec;
+    use hashbrown::HashTable;
+    use crate::errors::TryReserveError;
+    use crate::storage::Storage;
+
+    /// The hash index of one side of a map, together with the hasher of the values of that side.
+    #[derive(Debug)]
+    pub(crate) struct IndexSide<H, A: Allocator = Global> {
+        /// The positions of the buckets in the pair storage, keyed by the hashes of their values.
+        pub(crate) table: HashTable<usize, A>,
+        /// The number of entries the table was allocated for, which the map accounts for as the
+        /// number of slots of the index.
+        capacity: usize,
+        /// The hash of the value of each bucket, stored in parallel to the pairs, which finds the
+        /// entry of the bucket in the table. Small maps do not keep back-references.
+        pub(crate) back_references: vec::Vec<usize, A>,
+        /// The hasher of the values of this side.
+        pub(crate) hasher: H,
+    }
+
+    /// The empty table of an index that is about to be built.
+    pub(crate) struct EmptyIndex<A: Allocator = Global> {
+        table: HashTable<usize, A>,
+        capacity: usize,
+    }
+
+    /// The table of an index that was built for all pairs of a map, but is not installed in its
+    /// side yet. Tables that were built with another hasher carry the hashes of the buckets under
+    /// it.
+    pub(crate) struct BuiltIndex<A: Allocator = Global> {
+        table: HashTable<usize, A>,
+        capacity: usize,
+        hashes: Option<vec::Vec<usize, A>>,
+        pub(crate) longest_probe: u8,
+    }
+
+    // HashTable::clone_from always allocates a new table, so a table that was allocated for the
+    // capacity of the source is refilled from the cloned hashes instead. The capacity that the table
+    // reports shrinks with its tombstones, so it cannot be compared.
+    impl<H: Clone, A: Allocator + Clone> Clone for IndexSide<H, A> {
+        fn clone(&self) -> Self {
+            IndexSide {
+                table: self.table.clone(),
+                capacity: self.capacity,
+                back_references: self.back_references.clone(),
+                hasher: self.hasher.clone(),
+            }
+        }
+
+        fn clone_from(&mut self, source: &Self) {
+            self.back_references.clone_from(&source.back_references);
+            if self.capacity == source.capacity {
+                self.table.clear();
+                insert_all(&mut self.table, &self.back_references);
+            } else {
+                self.table.clone_from(&source.table);
+            }
+            self.capacity = source.capacity;
+            self.hasher.clone_from(&source.hasher);
+        }
+    }
+
+    impl<H> IndexSide<H> {
+        /// A side without an index, which does not allocate.
+        pub(crate) const fn new(hasher: H) -> Self {
+            IndexSide { table: HashTable::new(), capacity: 0, back_references: vec::Vec::new(), hasher }
+        }
+    }
+
+    impl<H, A: Allocator + Clone> IndexSide<H, A> {
+        /// A side with an empty index of the given capacity, and room for the back-references of
+        /// `buckets` buckets, in the given allocator.
+        pub(crate) fn with_capacity_in(capacity: usize, buckets: usize, hasher: H, alloc: A) -> Self {
+            let table = HashTable::with_capacity_in(capacity, alloc.clone());
+            IndexSide { table, capacity, back_references: vec::Vec::with_capacity_in(buckets, alloc), hasher }
+        }
+
+        /// The allocator of the table and the back-references.
+        pub(crate) fn allocator(&self) -> &A {
+            self.back_references.allocator()
+        }
+
+        /// Allocate the empty table of an index of the given capacity in the allocator of the side.
+        pub(crate) fn allocate(&self, capacity: usize) -> EmptyIndex<A> {
+            EmptyIndex { table: HashTable::with_capacity_in(capacity, self.allocator().clone()), capacity }
+        }
+
+        /// Allocate the table of an index like [`allocate`], but return an error instead of
+        /// panicking or aborting if the allocation fails.
+        ///
+        /// [`allocate`]: #method.allocate
+        pub(crate) fn try_allocate(&self, capacity: usize) -> Result<EmptyIndex<A>, TryReserveError> {
+            let mut table = HashTable::new_in(self.allocator().clone());
+            table.try_reserve(capacity, |_| unreachable!("the table is empty"))?;
+            Ok(EmptyIndex { table, capacity })
+        }
+
+        /// The number of entries the index was allocated for.
+        #[inline(always)]
+        pub(crate) fn capacity(&self) -> usize {
+            self.capacity
+        }
+
+        /// The bucket that the mapping at `index` points at. Tables find the entries of buckets by
+        /// their hashes, so the mappings are identified by the buckets themselves.
+        #[inline(always)]
+        pub(crate) fn bucket(&self, index: usize) -> usize {
+            index
+        }
+
+        /// The mapping that points at the given bucket. See [`bucket`].
+        ///
+        /// [`bucket`]: #method.bucket
+        #[inline(always)]
+        pub(crate) fn slot_of(&self, bucket_index: usize) -> usize {
+            bucket_index
+        }
+
+        /// The buckets of all entries, in the order of the table.
+        pub(crate) fn buckets(&self) -> impl Iterator<Item=usize> + '_ {
+            self.table.iter().copied()
+        }
+
+        /// The number of bytes allocated for the table.
+        pub(crate) fn index_bytes(&self) -> usize {
+            self.table.allocation_size()
+        }
+
+        /// The number of bytes allocated for the table of an index of the given capacity, following
+        /// the layout of hashbrown: a power of two of entries that keeps an eighth of them free,
+        /// one control byte per entry, and the control bytes of one group of entries that is probed
+        /// at once, whose width depends on the target.
+        pub(crate) fn bytes_for(capacity: usize) -> usize {
+            if capacity == 0 {
+                return 0;
+            }
+            let group_width = if cfg!(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "sse2")) {
+                16
+            } else {
+                mem::size_of::<usize>()
+            };
+            let entries = match capacity {
+                0..4 => 4,
+                4..8 => 8,
+                _ => (capacity * 8 / 7).next_power_of_two(),
+            };
+            entries * (mem::size_of::<usize>() + 1) + group_width
+        }
+
+        /// Make room for the back-reference of a bucket that is pushed to the tail of the pairs,
+        /// before its mapping is inserted with [`insert_mapping`].
+        ///
+        /// [`insert_mapping`]: #method.insert_mapping
+        pub(crate) fn push_bucket(&mut self) {
+            self.back_references.push(0);
+        }
+
+        /// Insert a mapping for the given bucket, whose ideal index is the hash of its element. The
+        /// table finds a free entry on its own, so `mapping_index` is ignored. Tables do not expose
+        /// how far their entries are from their ideal positions, so the probe distance is zero.
+        pub(crate) fn insert_mapping(&mut self, _mapping_index: usize, ideal_index: usize, bucket_index: usize) -> u8 {
+            self.back_references[bucket_index] = ideal_index;
+            let hashes = &self.back_references;
+            self.table.insert_unique(ideal_index as u64, bucket_index, |&bucket| hashes[bucket] as u64);
+            0
+        }
+
+        /// Delete the mapping at `mapping_index`, which is the bucket it points at. The table does
+        /// not move other entries, so `mapping_index` is returned.
+        pub(crate) fn delete_mapping(&mut self, mapping_index: usize, _shifted_saturated: Vec<u8>) -> usize {
+            let hash = self.back_references[mapping_index] as u64;
+            let Ok(entry) = self.table.find_entry(hash, |&bucket| bucket == mapping_index) else {
+                unreachable!("every bucket has an entry in the table");
+            };
+            entry.remove();
+            mapping_index
+        }
+
+        /// Point the entry of the bucket at `from` at the bucket at `to`, whose own entry was
+        /// deleted, because the value of `from` moves into the bucket at `to`.
+        pub(crate) fn move_mapping(&mut self, from: usize, to: usize) {
+            let hash = self.back_references[from];
+            *self.entry_mut(hash, from) = to;
+            self.back_references[to] = hash;
+        }
+
+        /// Swap the entries of two different buckets, after the buckets swapped their positions.
+        pub(crate) fn swap(&mut self, a: usize, b: usize) {
+            // both entries are found before either changes, since the buckets may have equal hashes
+            let hashes = [self.back_references[a] as u64, self.back_references[b] as u64];
+            let [Some(entry_a), Some(entry_b)] = self.table.get_many_mut(hashes, |i, &bucket| bucket == [a, b][i]) else {
+                unreachable!("every bucket has an entry in the table");
+            };
+            *entry_a = b;
+            *entry_b = a;
+            self.back_references.swap(a, b);
+        }
+
+        /// Remove the back-reference of a bucket whose mapping was deleted, and move the
+        /// back-reference of the last bucket into its position, like the pair storage moves the
+        /// last bucket.
+        pub(crate) fn swap_remove(&mut self, bucket_index: usize) {
+            let tail = self.back_references.len() - 1;
+            if bucket_index < tail {
+                *self.entry_mut(self.back_references[tail], tail) = bucket_index;
+            }
+            self.back_references.swap_remove(bucket_index);
+        }
+
+        /// Empty the table and drop the back-references, keeping the allocations.
+        pub(crate) fn clear(&mut self) {
+            self.table.clear();
+            self.back_references.clear();
+        }
+
+        /// Free the table and the back-references, for a map that becomes small.
+        pub(crate) fn release(&mut self) {
+            let alloc = self.allocator().clone();
+            self.table = HashTable::new_in(alloc.clone());
+            self.capacity = 0;
+            self.back_references = vec::Vec::new_in(alloc);
+        }
+
+        /// Restore the back-references after building another index overwrote them. The hashes do
+        /// not depend on the capacity, so building an index overwrites them with the same hashes,
+        /// unless the map is small and did not keep any.
+        pub(crate) fn restore_back_references(&mut self) {
+            if self.capacity() == 0 {
+                self.back_references.clear();
+            }
+        }
+
+        /// Replace the index with an index that was built for all pairs of the map.
+        pub(crate) fn install(&mut self, built: BuiltIndex<A>) {
+            self.table = built.table;
+            self.capacity = built.capacity;
+            if let Some(hashes) = built.hashes {
+                self.back_references = hashes;
+            }
+        }
+
+        /// Discard the index and rebuild it at the given capacity from the hashes of the buckets,
+        /// which [`store_ideal_indices`] wrote into the back-references. No value is hashed. The
+        /// table is freed before a table of another capacity is allocated. Returns the longest
+        /// probe distance, which is zero for tables.
+        ///
+        /// [`store_ideal_indices`]: #method.store_ideal_indices
+        pub(crate) fn rebuild(&mut self, capacity: usize) -> u8 {
+            self.table.clear();
+            if capacity != self.capacity {
+                self.table = HashTable::new_in(self.allocator().clone());
+                self.table = HashTable::with_capacity_in(capacity, self.allocator().clone());
+                self.capacity = capacity;
+            }
+            insert_all(&mut self.table, &self.back_references);
+            0
+        }
+
+        /// The entry of the bucket at `bucket_index`, whose value has the given hash.
+        fn entry_mut(&mut self, hash: usize, bucket_index: usize) -> &mut usize {
+            self.table.find_mut(hash as u64, |&bucket| bucket == bucket_index).expect("every bucket has an entry in the table")
+        }
+    }
+
+    impl<H: BuildHasher, A: Allocator + Clone> IndexSide<H, A> {
+        /// Get the ideal index of a value, which is its hash.
+        #[inline(always)]
+        pub(crate) fn ideal_index<E: Hash>(&self, element: &E) -> usize {
+            hash_to_index(&self.hasher, element, self.capacity())
+        }
+
+        /// Find the mapping of an element, given its ideal index as computed by [`ideal_index`]. If
+        /// the element is not in the index, the error holds no position, since the table finds a
+        /// free entry on its own when the element is inserted.
+        ///
+        /// [`ideal_index`]: #method.ideal_index
+        #[inline(always)]
+        pub(crate) fn probe<T, U, E>(&self, element: &E, ideal_index: usize, data: &Storage<T, U, A>, lookup: fn(&Storage<T, U, A>, usize) -> &E) -> Result<usize, usize>
+            where E: Hash + Eq
+        {
+            self.table.find(ideal_index as u64, |&bucket| lookup(data, bucket) == element)
+                .copied()
+                .ok_or(0)
+        }
+
+        /// Find the position where an element has to be inserted. The table finds a free entry on
+        /// its own, so there is no position to find.
+        #[inline(always)]
+        pub(crate) fn probe_insert_position<T, U, E>(&self, _ideal_index: usize, _data: &Storage<T, U, A>, _lookup: fn(&Storage<T, U, A>, usize) -> &E) -> usize
+            where E: Hash
+        {
+            0
+        }
+
+        /// Compute the distances that saturated entries will have after a deletion. Tables do not
+        /// keep probe distances, so there are none.
+        pub(crate) fn shifted_saturated<T, U, E>(&self, _mapping_index: usize, _data: &Storage<T, U, A>, _lookup: fn(&Storage<T, U, A>, usize) -> &E) -> Vec<u8>
+            where E: Hash
+        {
+            Vec::new()
+        }
+
+        /// Insert all buckets into the given empty table, in bucket order, hashing their elements
+        /// with the given hasher or the hasher of the side. The hashes under the hasher of the side
+        /// are the back-references already, so they are overwritten with the same hashes, while the
+        /// hashes under another hasher are kept with the built index until [`install`]. If a `Hash`
+        /// implementation panics, [`restore_back_references`] restores the back-references.
+        ///
+        /// [`install`]: #method.install
+        /// [`restore_back_references`]: #method.restore_back_references
+        pub(crate) fn build<T, U, E>(&mut self, empty: EmptyIndex<A>, hasher: Option<&H>, data: &Storage<T, U, A>, lookup: fn(&Storage<T, U, A>, usize) -> &E) -> BuiltIndex<A>
+            where E: Hash
+        {
+            let EmptyIndex { mut table, capacity } = empty;
+            let hashes = hasher.map(|hasher| {
+                let mut hashes = vec::Vec::with_capacity_in(self.back_references.capacity().max(data.len()), self.allocator().clone());
+                hashes.extend((0..data.len()).map(|bucket_index| hash_to_index(hasher, lookup(data, bucket_index), capacity)));
+                hashes
+            });
+            if hashes.is_none() {
+                self.store_ideal_indices(capacity, data, lookup);
+            }
+
+            insert_all(&mut table, hashes.as_deref().unwrap_or(&self.back_references));
+            BuiltIndex { table, capacity, hashes, longest_probe: 0 }
+        }
+
+        /// Store the hash of every bucket in its back-reference, for [`rebuild`]. Unless the map is
+        /// small, the back-references hold these hashes already. If a `Hash` implementation panics,
+        /// [`restore_back_references`] restores them.
+        ///
+        /// [`rebuild`]: #method.rebuild
+        /// [`restore_back_references`]: #method.restore_back_references
+        pub(crate) fn store_ideal_indices<T, U, E>(&mut self, capacity: usize, data: &Storage<T, U, A>, lookup: fn(&Storage<T, U, A>, usize) -> &E)
+            where E: Hash
+        {
+            self.back_references.resize(data.len(), 0);
+            for (bucket_index, back_reference) in self.back_references.iter_mut().enumerate() {
+                *back_reference = hash_to_index(&self.hasher, lookup(data, bucket_index), capacity);
+            }
+        }
+
+        /// Find the buckets with equal elements, by comparing the elements of the buckets with
+        /// equal hashes, given the hashes of all buckets. Returns the buckets as pairs of the later
+        /// and the earlier bucket.
+        pub(crate) fn equal_value_edges<T, U, E>(&self, ideal_indices: &[usize], data: &Storage<T, U, A>, lookup: fn(&Storage<T, U, A>, usize) -> &E) -> Vec<(usize, usize)>
+            where E: Eq
+        {
+            let mut edges = Vec::new();
+            for (bucket, &hash) in ideal_indices.iter().enumerate() {
+                for &earlier in self.table.iter_hash(hash as u64) {
+                    if earlier < bucket && ideal_indices[earlier] == hash && lookup(data, earlier) == lookup(data, bucket) {
+                        edges.push((bucket, earlier));
+                    }
+                }
+            }
+            edges
+        }
+    }
+
+    /// Insert the entries of all buckets into a table, given the hashes of their elements.
+    fn insert_all<A: Allocator>(table: &mut HashTable<usize, A>, hashes: &[usize]) {
+        for (bucket_index, &hash) in hashes.iter().enumerate() {
+            table.insert_unique(hash as u64, bucket_index, |&bucket| hashes[bucket] as u64);
+        }
+    }
+
+    /// Convert an element into the ideal index of its entry, which is its hash. Unlike the slots of
+    /// a Robin Hood index, the table does not reduce the hash to the capacity, but only keeps the
+    /// bits of a `usize`, like hashbrown does on 32-bit platforms.
+    #[inline(always)]
+    pub(crate) fn hash_to_index<E, G>(hasher: &G, element: &E, capacity: usize) -> usize
+        where E: Hash + ?Sized, G: BuildHasher
+    {
+        reduce_hash(hasher.hash_one(element), capacity)
+    }
+
+    /// Map a hash to an ideal index. See [`hash_to_index`].
+    #[inline(always)]
+    pub(crate) fn reduce_hash(hash: u64, _capacity: usize) -> usize {
+        hash as usize
+    }
+
+    /// Compute the distance of an entry from its ideal index. Tables do not expose where they store
+    /// their entries, so the distance is always zero.
+    #[cfg(feature = "stats")]
+    #[inline(always)]
+    pub(crate) fn probe_distance(_ideal_index: usize, _index: usize, _capacity: usize) -> usize {
+        0
+    }
+
+    /// Correct a probe position after a deletion. Tables do not move entries, so the position is
+    /// returned unchanged.
+    pub(crate) fn shift_back_position(position: usize, _ideal_index: usize, _deleted_index: usize, _vacated_index: usize, _capacity: usize) -> usize {
+        position
+    }
+}
+
+/// One side of a map, which selects the values, the index and the negative lookup filter of that
+/// side.
+pub(crate) trait MapSide<T, U, H, RH, A: Allocator + Clone = Global> {
+    /// The type of the values of this side.
+    type Value: Hash + Eq;
+    /// The hasher of the values of this side.
+    type Hasher: BuildHasher;
+    /// The other side of the map.
+    type Opposite: MapSide<T, U, H, RH, A>;
+
+    /// The value of this side of the pair at the given position.
+    fn value(data: &Storage<T, U, A>, bucket: usize) -> &Self::Value;
+
+    /// The value of this side of the pair at the given position, for mutation in place.
+    fn value_mut(data: &mut Storage<T, U, A>, bucket: usize) -> &mut Self::Value;
+
+    /// The values of this side of all pairs, in the order of their positions.
+    fn values<'a>(data: &'a Storage<T, U, A>) -> impl Iterator<Item=&'a Self::Value> where Self::Value: 'a;
+
+    /// The index of this side of a map.
+    fn index(map: &BiMap<T, U, H, RH, A>) -> &IndexSide<Self::Hasher, A>;
+
+    /// The index of this side of a map, for mutation.
+    fn index_mut(map: &mut BiMap<T, U, H, RH, A>) -> &mut IndexSide<Self::Hasher, A>;
+
+    /// The negative lookup filter of this side.
+    fn filter(filter: &NegativeFilter) -> &BloomFilter;
+
+    /// The negative lookup filter of this side, for mutation.
+    fn filter_mut(filter: &mut NegativeFilter) -> &mut BloomFilter;
+}
+
+/// The side of the left values, which are hashed with the hasher of the map.
+pub(crate) struct LeftSide;
+
+/// The side of the right values, which are hashed with the reverse hasher of the map.
+pub(crate) struct RightSide;
+
+impl<T, U, H, RH, A> MapSide<T, U, H, RH, A> for LeftSide
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher, A: Allocator + Clone
+{
+    type Value = T;
+    type Hasher = H;
+    type Opposite = RightSide;
+
+    #[inline(always)]
+    fn value(data: &Storage<T, U, A>, bucket: usize) -> &T {
+        data.left(bucket)
+    }
+
+    #[inline(always)]
+    fn value_mut(data: &mut Storage<T, U, A>, bucket: usize) -> &mut T {
+        data.left_mut(bucket)
+    }
+
+    #[inline(always)]
+    fn values<'a>(data: &'a Storage<T, U, A>) -> impl Iterator<Item=&'a T> where T: 'a {
+        data.lefts()
+    }
+
+    #[inline(always)]
+    fn index(map: &BiMap<T, U, H, RH, A>) -> &IndexSide<H, A> {
+        &map.left
+    }
+
+    #[inline(always)]
+    fn index_mut(map: &mut BiMap<T, U, H, RH, A>) -> &mut IndexSide<H, A> {
+        &mut map.left
+    }
+
+    #[inline(always)]
+    fn filter(filter: &NegativeFilter) -> &BloomFilter {
+        &filter.left
+    }
+
+    #[inline(always)]
+    fn filter_mut(filter: &mut NegativeFilter) -> &mut BloomFilter {
+        &mut filter.left
+    }
+}
+
+impl<T, U, H, RH, A> MapSide<T, U, H, RH, A> for RightSide
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher, A: Allocator + Clone
+{
+    type Value = U;
+    type Hasher = RH;
+    type Opposite = LeftSide;
+
+    #[inline(always)]
+    fn value(data: &Storage<T, U, A>, bucket: usize) -> &U {
+        data.right(bucket)
+    }
+
+    #[inline(always)]
+    fn value_mut(data: &mut Storage<T, U, A>, bucket: usize) -> &mut U {
+        data.right_mut(bucket)
+    }
+
+    #[inline(always)]
+    fn values<'a>(data: &'a Storage<T, U, A>) -> impl Iterator<Item=&'a U> where U: 'a {
+        data.rights()
+    }
+
+    #[inline(always)]
+    fn index(map: &BiMap<T, U, H, RH, A>) -> &IndexSide<RH, A> {
+        &map.right
+    }
+
+    #[inline(always)]
+    fn index_mut(map: &mut BiMap<T, U, H, RH, A>) -> &mut IndexSide<RH, A> {
+        &mut map.right
+    }
+
+    #[inline(always)]
+    fn filter(filter: &NegativeFilter) -> &BloomFilter {
+        &filter.right
+    }
+
+    #[inline(always)]
+    fn filter_mut(filter: &mut NegativeFilter) -> &mut BloomFilter {
+        &mut filter.right
+    }
+}
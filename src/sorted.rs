@@ -0,0 +1,113 @@
+//! Sorting and reordering the pairs of a map, and the positional operations that rely on a sorted
+//! map.
+//!
+//! The pairs are stored in their iteration order, and the indices refer to them by position, so
+//! reordering permutes the pairs and redirects the index entries without hashing any value. The
+//! operations on a sorted map only see the positions of the pairs, which stay sorted until the
+//! caller inserts a pair out of order or removes a pair, which moves the last pair into the gap.
+
+use std::cmp::Ordering;
+use std::hash::{BuildHasher, Hash};
+use std::ops::Range;
+use allocator_api2::alloc::Allocator;
+use crate::BiMap;
+
+impl<T, U, H, RH, A: Allocator + Clone> BiMap<T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    /// Sorts the pairs of the map by their left values, changing the iteration order. The sort is
+    /// stable, but since left values are unique, pairs never compare equal.
+    pub fn sort_by_left(&mut self)
+        where T: Ord
+    {
+        self.sort_by(|left_a, _, left_b, _| left_a.cmp(left_b));
+    }
+
+    /// Sorts the pairs of the map by their right values, changing the iteration order.
+    pub fn sort_by_right(&mut self)
+        where U: Ord
+    {
+        self.sort_by(|_, right_a, _, right_b| right_a.cmp(right_b));
+    }
+
+    /// Sorts the pairs of the map with a comparator that receives the left and right value of two
+    /// pairs, changing the iteration order. The sort is stable, so pairs that compare equal keep
+    /// their relative order.
+    ///
+    /// The pairs stay where they are until the comparator has ordered all of them, so a panicking
+    /// comparator leaves the map unchanged.
+    pub fn sort_by<F>(&mut self, mut compare: F)
+        where F: FnMut(&T, &U, &T, &U) -> Ordering
+    {
+        let mut order = (0..self.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| compare(self.data.left(a), self.data.right(a), self.data.left(b), self.data.right(b)));
+        self.apply_order(order);
+    }
+
+    /// Move the buckets into the given order, which lists the current positions of the buckets in
+    /// their new order.
+    pub(crate) fn apply_order(&mut self, order: Vec<usize>) {
+        let mut new_positions = vec![0; self.len()];
+        for (position, bucket) in order.into_iter().enumerate() {
+            new_positions[bucket] = position;
+        }
+        self.permute_buckets(new_positions);
+    }
+
+    /// Reverses the iteration order of the pairs.
+    pub fn reverse(&mut self) {
+        let len = self.len();
+        self.permute_buckets((0..len).rev().collect());
+    }
+
+    /// Swaps the positions of the pairs at positions `a` and `b` in the iteration order.
+    ///
+    /// # Panics
+    /// Panics if `a` or `b` is out of bounds.
+    pub fn swap_indices(&mut self, a: usize, b: usize) {
+        assert!(a < self.len() && b < self.len(), "index out of bounds");
+        self.swap_buckets(a, b);
+    }
+
+    /// Binary searches the pairs for the given left value, assuming that the pairs are sorted by
+    /// their left values, e.g. by [`sort_by_left`]. Returns the position of the pair of the left
+    /// value, or the position at which a pair with the left value would have to be inserted to keep
+    /// the pairs sorted.
+    ///
+    /// The result is only meaningful while the map is sorted. Inserting a pair appends it at the
+    /// end, and removing a pair moves the last pair into its position, so both can break the order.
+    /// Use [`get_right`] to look up a left value regardless of the order.
+    ///
+    /// [`sort_by_left`]: #method.sort_by_left
+    /// [`get_right`]: #method.get_right
+    pub fn binary_search_by_left(&self, key: &T) -> Result<usize, usize>
+        where T: Ord
+    {
+        let mut low = 0;
+        let mut high = self.len();
+        while low < high {
+            let middle = low + (high - low) / 2;
+            match self.data.left(middle).cmp(key) {
+                Ordering::Less => low = middle + 1,
+                Ordering::Greater => high = middle,
+                Ordering::Equal => return Ok(middle),
+            }
+        }
+        Err(low)
+    }
+
+    /// Returns the pairs at the given range of positions in iteration order, or None if the range
+    /// is out of bounds. Together with [`binary_search_by_left`], this yields contiguous runs of a
+    /// sorted map without allocating.
+    ///
+    /// The positions of the pairs follow the iteration order, which insertions and removals change
+    /// as described for [`binary_search_by_left`].
+    ///
+    /// [`binary_search_by_left`]: #method.binary_search_by_left
+    pub fn get_range(&self, range: Range<usize>) -> Option<impl DoubleEndedIterator<Item=(&T, &U)> + ExactSizeIterator> {
+        if range.start > range.end || range.end > self.len() {
+            return None;
+        }
+        Some(self.data.range(range))
+    }
+}
@@ -0,0 +1,66 @@
+//! The speedy representation of a map, its length followed by its pairs in iteration order.
+
+use std::hash::{BuildHasher, Hash};
+use speedy::{Context, Readable, Reader, Writable, Writer};
+use crate::BiMap;
+
+/// The largest number of pairs that are reserved up front when the reader cannot tell whether
+/// the input holds as many pairs as its length claims.
+const MAX_RESERVED_PAIRS: usize = 1 << 16;
+
+impl<C, T, U, H, RH> Writable<C> for BiMap<T, U, H, RH>
+    where C: Context, T: Hash + Eq + Writable<C>, U: Hash + Eq + Writable<C>, H: BuildHasher, RH: BuildHasher
+{
+    fn write_to<W: ?Sized + Writer<C>>(&self, writer: &mut W) -> Result<(), C::Error> {
+        let len = u32::try_from(self.len()).map_err(|_| speedy::Error::custom("the map holds more than u32::MAX pairs"))?;
+        writer.write_u32(len)?;
+        for (left, right) in self.iter() {
+            left.write_to(writer)?;
+            right.write_to(writer)?;
+        }
+        Ok(())
+    }
+
+    fn bytes_needed(&self) -> Result<usize, C::Error> {
+        let mut bytes = std::mem::size_of::<u32>();
+        for (left, right) in self.iter() {
+            bytes += Writable::<C>::bytes_needed(left)? + Writable::<C>::bytes_needed(right)?;
+        }
+        Ok(bytes)
+    }
+}
+
+/// Reads a map, and fails if a left or a right value occurs twice. The lifetime of the input is
+/// passed on to the values, so values that borrow from the input, such as `Cow<'a, str>`, are read
+/// without copying.
+impl<'a, C, T, U, H, RH> Readable<'a, C> for BiMap<T, U, H, RH>
+    where C: Context, T: Hash + Eq + Readable<'a, C>, U: Hash + Eq + Readable<'a, C>, H: BuildHasher + Default, RH: BuildHasher + Default
+{
+    fn read_from<R: Reader<'a, C>>(reader: &mut R) -> Result<Self, C::Error> {
+        let len = reader.read_u32()? as usize;
+
+        // a corrupt length must neither reserve more than the input can hold, nor read past it
+        let pair_bytes = T::minimum_bytes_needed() + U::minimum_bytes_needed();
+        let capacity = match reader.can_read_at_least(len.saturating_mul(pair_bytes)) {
+            Some(false) => return Err(speedy::Error::custom(format_args!("the input is too short for {} pairs", len)).into()),
+            Some(true) if pair_bytes > 0 => len,
+            _ => len.min(MAX_RESERVED_PAIRS),
+        };
+
+        let mut map = BiMap::with_hashers(capacity, H::default(), RH::default());
+        for pair in 0..len {
+            let left = T::read_from(reader)?;
+            let right = U::read_from(reader)?;
+            match map.try_insert(left, right) {
+                Ok(()) => {}
+                Err(error) if error.left_occupied => return Err(speedy::Error::custom(format_args!("duplicate left value in pair {}", pair)).into()),
+                Err(_) => return Err(speedy::Error::custom(format_args!("duplicate right value in pair {}", pair)).into()),
+            }
+        }
+        Ok(map)
+    }
+
+    fn minimum_bytes_needed() -> usize {
+        std::mem::size_of::<u32>()
+    }
+}
@@ -0,0 +1,91 @@
+//! Counters of the operations of a map, available with the `stats` feature.
+//!
+//! The counters are plain integers that the mutating operations of a map update as they go, so
+//! they are cheap enough to stay enabled in production. Lookups through a shared reference cannot
+//! update them and are not counted. Without the feature, neither the counters nor the code that
+//! updates them is compiled.
+
+use std::ops::{Deref, DerefMut};
+use allocator_api2::alloc::Allocator;
+use crate::BiMap;
+
+/// The number of operations a map performed since it was created or its counters were reset. See
+/// [`BiMap::op_counters`].
+///
+/// The counters describe the changes of the pairs, so `inserts - removals` is the change of the
+/// length of the map over the same period.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct OpCounters {
+    /// The number of pairs that were stored in the map, including pairs that replaced other pairs
+    /// and pairs that were mutated through [`BiMap::get_right_mut`] or [`BiMap::get_left_mut`].
+    pub inserts: u64,
+    /// The number of pairs that were removed from the map, including the pairs that insertions
+    /// replaced and the pairs that [`BiMap::clear`] and [`BiMap::drain`] removed.
+    pub removals: u64,
+    /// The number of insertions that replaced at least one pair of the map.
+    pub replacements: u64,
+    /// The number of times the indices were rebuilt from all pairs of the map, because the map
+    /// resized its indices, reseeded its hashers, or removed pairs with [`BiMap::retain`]. Turning
+    /// into a small map rehashes nothing.
+    pub rehashes: u64,
+    /// The number of index slots that insertions and removals probed past their ideal index to find
+    /// a value or the position of a new value. Removals of absent values are not counted, and small
+    /// maps have no indices and probe nothing.
+    pub probe_steps: u64,
+}
+
+/// The counters of a map. They describe the history of a map rather than its contents, so like
+/// the hooks, they are not cloned with the map and are ignored when maps are compared.
+#[derive(Debug, Default)]
+pub(crate) struct Counters(OpCounters);
+
+impl Counters {
+    pub(crate) const fn new() -> Self {
+        Counters(OpCounters { inserts: 0, removals: 0, replacements: 0, rehashes: 0, probe_steps: 0 })
+    }
+}
+
+impl Deref for Counters {
+    type Target = OpCounters;
+
+    fn deref(&self) -> &OpCounters {
+        &self.0
+    }
+}
+
+impl DerefMut for Counters {
+    fn deref_mut(&mut self) -> &mut OpCounters {
+        &mut self.0
+    }
+}
+
+impl Clone for Counters {
+    fn clone(&self) -> Self {
+        Counters::new()
+    }
+}
+
+impl PartialEq for Counters {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Counters {}
+
+impl<T, U, H, RH, A: Allocator + Clone> BiMap<T, U, H, RH, A> {
+    /// Returns the number of operations the map performed since it was created, cloned, or its
+    /// counters were last reset with [`reset_op_counters`]. See [`OpCounters`] for what is
+    /// counted.
+    ///
+    /// [`reset_op_counters`]: #method.reset_op_counters
+    #[must_use]
+    pub fn op_counters(&self) -> OpCounters {
+        *self.counters
+    }
+
+    /// Resets all operation counters to zero.
+    pub fn reset_op_counters(&mut self) {
+        self.counters = Counters::new();
+    }
+}
@@ -0,0 +1,476 @@
+//! The storage of the value pairs of a map. The hash indices refer to pairs by their position in
+//! the storage, so all operations that move pairs keep both values of a pair at the same position.
+//!
+//! By default, pairs are stored interleaved in a single vector. With the `columnar` feature, the
+//! left and right values are stored in two parallel vectors instead, so that iterating over one
+//! side does not pull the values of the other side into the cache.
+//!
+//! The vectors are allocated in the allocator of the map, so they are the vectors of
+//! `allocator-api2`, which lack some methods of the vectors of the standard library.
+
+#[cfg(not(feature = "columnar"))]
+pub(crate) use interleaved::{DrainPairs, IntoPairs, Lefts, Pairs, Rights, Storage};
+#[cfg(feature = "columnar")]
+pub(crate) use columnar::{DrainPairs, IntoPairs, Lefts, Pairs, Rights, Storage};
+
+use std::ptr;
+use allocator_api2::alloc::Allocator;
+use allocator_api2::vec;
+
+/// Remove the values whose mark is set, keeping the order of the other values, and return the
+/// removed values in order. There must be a mark for every value.
+fn extract_marked<E, A: Allocator>(values: &mut vec::Vec<E, A>, marks: &[bool]) -> Vec<E> {
+    debug_assert_eq!(marks.len(), values.len());
+    let len = values.len();
+    let mut removed = Vec::with_capacity(marks.iter().filter(|&&mark| mark).count());
+    let mut kept = 0;
+    // SAFETY: the length is zero while the values are moved, so the vector never exposes a value
+    // that was moved out or copied over. Every value is either read into the removed values or
+    // copied to the position after the values kept so far, which is at most its own position, so
+    // each value is moved exactly once. The removed values have room for all marked values, so
+    // pushing them does not reallocate, and nothing in between can panic.
+    unsafe {
+        values.set_len(0);
+        let base = values.as_mut_ptr();
+        for (index, &mark) in marks.iter().enumerate().take(len) {
+            if mark {
+                removed.push(ptr::read(base.add(index)));
+            } else {
+                ptr::copy(base.add(index), base.add(kept), 1);
+                kept += 1;
+            }
+        }
+        values.set_len(kept);
+    }
+    removed
+}
+
+/// A value pair that is moved into or out of the storage. The layout is fixed, so that the pairs
+/// of the interleaved storage can be exposed as bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub(crate) struct Bucket<T, U> {
+    pub(crate) left: T,
+    pub(crate) right: U,
+}
+
+#[cfg(not(feature = "columnar"))]
+mod interleaved {
+    use std::{iter, mem, slice};
+    use std::ops::Range;
+    use allocator_api2::alloc::{Allocator, Global};
+    use allocator_api2::collections::TryReserveError;
+    use allocator_api2::vec;
+    #[cfg(feature = "rayon")]
+    use rayon::prelude::*;
+    use super::{extract_marked, Bucket};
+
+    /// The iterator over references to the pairs of the storage.
+    pub(crate) type Pairs<'a, T, U> = iter::Map<slice::Iter<'a, Bucket<T, U>>, fn(&'a Bucket<T, U>) -> (&'a T, &'a U)>;
+
+    /// The iterator over references to the left values of the storage.
+    pub(crate) type Lefts<'a, T, U> = iter::Map<slice::Iter<'a, Bucket<T, U>>, fn(&'a Bucket<T, U>) -> &'a T>;
+
+    /// The iterator over references to the right values of the storage.
+    pub(crate) type Rights<'a, T, U> = iter::Map<slice::Iter<'a, Bucket<T, U>>, fn(&'a Bucket<T, U>) -> &'a U>;
+
+    /// The iterator that drains the pairs of the storage.
+    pub(crate) type DrainPairs<'a, T, U, A = Global> = iter::Map<vec::Drain<'a, Bucket<T, U>, A>, fn(Bucket<T, U>) -> (T, U)>;
+
+    /// The iterator that moves the pairs out of the storage.
+    pub(crate) type IntoPairs<T, U, A = Global> = iter::Map<vec::IntoIter<Bucket<T, U>, A>, fn(Bucket<T, U>) -> (T, U)>;
+
+    /// Stores the pairs as buckets in a single vector.
+    #[derive(Debug, PartialEq, Eq)]
+    pub(crate) struct Storage<T, U, A: Allocator = Global> {
+        buckets: vec::Vec<Bucket<T, U>, A>,
+    }
+
+    impl<T: Clone, U: Clone, A: Allocator + Clone> Clone for Storage<T, U, A> {
+        fn clone(&self) -> Self {
+            Storage { buckets: self.buckets.clone() }
+        }
+
+        fn clone_from(&mut self, source: &Self) {
+            self.buckets.clone_from(&source.buckets);
+        }
+    }
+
+    impl<T, U> Storage<T, U> {
+        pub(crate) const fn new() -> Self {
+            Storage { buckets: vec::Vec::new() }
+        }
+    }
+
+    impl<T, U, A: Allocator + Clone> Storage<T, U, A> {
+        pub(crate) fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+            Storage { buckets: vec::Vec::with_capacity_in(capacity, alloc) }
+        }
+
+        /// The allocator of the pairs.
+        pub(crate) fn allocator(&self) -> &A {
+            self.buckets.allocator()
+        }
+
+        #[inline(always)]
+        pub(crate) fn len(&self) -> usize {
+            self.buckets.len()
+        }
+
+        #[inline(always)]
+        pub(crate) fn is_empty(&self) -> bool {
+            self.buckets.is_empty()
+        }
+
+        /// The left value of the pair at the given position.
+        #[inline(always)]
+        pub(crate) fn left(&self, index: usize) -> &T {
+            &self.buckets[index].left
+        }
+
+        /// The right value of the pair at the given position.
+        #[inline(always)]
+        pub(crate) fn right(&self, index: usize) -> &U {
+            &self.buckets[index].right
+        }
+
+        /// The left value of the pair at the given position, for mutation in place.
+        #[inline(always)]
+        pub(crate) fn left_mut(&mut self, index: usize) -> &mut T {
+            &mut self.buckets[index].left
+        }
+
+        /// The right value of the pair at the given position, for mutation in place.
+        #[inline(always)]
+        pub(crate) fn right_mut(&mut self, index: usize) -> &mut U {
+            &mut self.buckets[index].right
+        }
+
+        /// The pairs in storage order.
+        #[cfg(feature = "bytemuck")]
+        pub(crate) fn buckets(&self) -> &[Bucket<T, U>] {
+            &self.buckets
+        }
+
+        pub(crate) fn push(&mut self, bucket: Bucket<T, U>) {
+            self.buckets.push(bucket);
+        }
+
+        /// Remove the pair at the given position and move the last pair into its place.
+        pub(crate) fn swap_remove(&mut self, index: usize) -> Bucket<T, U> {
+            self.buckets.swap_remove(index)
+        }
+
+        /// Replace the pair at the given position and return the old pair.
+        pub(crate) fn replace(&mut self, index: usize, mut bucket: Bucket<T, U>) -> Bucket<T, U> {
+            mem::swap(&mut self.buckets[index], &mut bucket);
+            bucket
+        }
+
+        pub(crate) fn swap(&mut self, a: usize, b: usize) {
+            self.buckets.swap(a, b);
+        }
+
+        pub(crate) fn iter(&self) -> Pairs<'_, T, U> {
+            self.buckets.iter().map(|bucket| (&bucket.left, &bucket.right))
+        }
+
+        pub(crate) fn range(&self, range: Range<usize>) -> impl DoubleEndedIterator<Item=(&T, &U)> + ExactSizeIterator {
+            self.buckets[range].iter().map(|bucket| (&bucket.left, &bucket.right))
+        }
+
+        pub(crate) fn lefts(&self) -> Lefts<'_, T, U> {
+            self.buckets.iter().map(|bucket| &bucket.left)
+        }
+
+        pub(crate) fn rights(&self) -> Rights<'_, T, U> {
+            self.buckets.iter().map(|bucket| &bucket.right)
+        }
+
+        pub(crate) fn drain(&mut self) -> DrainPairs<'_, T, U, A> {
+            self.buckets.drain(..).map(|bucket| (bucket.left, bucket.right))
+        }
+
+        pub(crate) fn into_pairs(self) -> IntoPairs<T, U, A> {
+            self.buckets.into_iter().map(|bucket| (bucket.left, bucket.right))
+        }
+
+        /// Remove the pairs whose mark is set, keeping the order of the other pairs, and return the
+        /// removed pairs in order.
+        pub(crate) fn extract_marked(&mut self, marks: &[bool]) -> Vec<Bucket<T, U>> {
+            extract_marked(&mut self.buckets, marks)
+        }
+
+        #[cfg(feature = "rayon")]
+        pub(crate) fn par_iter(&self) -> impl IndexedParallelIterator<Item=(&T, &U)>
+            where T: Sync, U: Sync
+        {
+            self.buckets.as_slice().par_iter().map(|bucket| (&bucket.left, &bucket.right))
+        }
+
+        pub(crate) fn clear(&mut self) {
+            self.buckets.clear();
+        }
+
+        /// The number of pairs the storage holds without reallocating.
+        pub(crate) fn capacity(&self) -> usize {
+            self.buckets.capacity()
+        }
+
+        pub(crate) fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+            self.buckets.try_reserve(additional)
+        }
+
+        pub(crate) fn shrink_to_fit(&mut self) {
+            self.buckets.shrink_to_fit();
+        }
+
+        pub(crate) fn shrink_to(&mut self, min_capacity: usize) {
+            self.buckets.shrink_to(min_capacity);
+        }
+
+        /// The number of heap bytes allocated for the pairs.
+        pub(crate) fn allocated_bytes(&self) -> usize {
+            self.buckets.capacity() * mem::size_of::<Bucket<T, U>>()
+        }
+
+        /// The number of heap bytes allocated for the pairs of a storage with the given capacity.
+        pub(crate) fn bytes_for(capacity: usize) -> usize {
+            capacity * mem::size_of::<Bucket<T, U>>()
+        }
+    }
+}
+
+#[cfg(feature = "columnar")]
+mod columnar {
+    use std::{iter, mem, slice};
+    use std::marker::PhantomData;
+    use std::ops::Range;
+    use allocator_api2::alloc::{Allocator, Global};
+    use allocator_api2::collections::TryReserveError;
+    use allocator_api2::vec;
+    #[cfg(feature = "rayon")]
+    use rayon::prelude::*;
+    use super::{extract_marked, Bucket};
+
+    /// The iterator over references to the pairs of the storage.
+    pub(crate) type Pairs<'a, T, U> = iter::Zip<slice::Iter<'a, T>, slice::Iter<'a, U>>;
+
+    /// The iterator over references to the left values of the storage.
+    pub(crate) type Lefts<'a, T, U> = Column<'a, T, U>;
+
+    /// The iterator over references to the right values of the storage.
+    pub(crate) type Rights<'a, T, U> = Column<'a, U, T>;
+
+    /// An iterator over the values of one column. The type of the values of the other column is a
+    /// parameter as well, so that the iterators over the values have the same type parameters in
+    /// both layouts.
+    pub(crate) struct Column<'a, E, O> {
+        values: slice::Iter<'a, E>,
+        other: PhantomData<&'a O>,
+    }
+
+    impl<'a, E, O> Column<'a, E, O> {
+        fn new(values: &'a [E]) -> Self {
+            Column { values: values.iter(), other: PhantomData }
+        }
+    }
+
+    impl<'a, E, O> Iterator for Column<'a, E, O> {
+        type Item = &'a E;
+
+        fn next(&mut self) -> Option<&'a E> {
+            self.values.next()
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            self.values.size_hint()
+        }
+    }
+
+    impl<E, O> DoubleEndedIterator for Column<'_, E, O> {
+        fn next_back(&mut self) -> Option<Self::Item> {
+            self.values.next_back()
+        }
+    }
+
+    impl<E, O> ExactSizeIterator for Column<'_, E, O> {}
+
+    impl<E, O> Clone for Column<'_, E, O> {
+        fn clone(&self) -> Self {
+            Column { values: self.values.clone(), other: PhantomData }
+        }
+    }
+
+    /// The iterator that drains the pairs of the storage.
+    pub(crate) type DrainPairs<'a, T, U, A = Global> = iter::Zip<vec::Drain<'a, T, A>, vec::Drain<'a, U, A>>;
+
+    /// The iterator that moves the pairs out of the storage.
+    pub(crate) type IntoPairs<T, U, A = Global> = iter::Zip<vec::IntoIter<T, A>, vec::IntoIter<U, A>>;
+
+    /// Stores the left and right values in two parallel vectors.
+    #[derive(Debug, PartialEq, Eq)]
+    pub(crate) struct Storage<T, U, A: Allocator = Global> {
+        lefts: vec::Vec<T, A>,
+        rights: vec::Vec<U, A>,
+    }
+
+    impl<T: Clone, U: Clone, A: Allocator + Clone> Clone for Storage<T, U, A> {
+        fn clone(&self) -> Self {
+            Storage { lefts: self.lefts.clone(), rights: self.rights.clone() }
+        }
+
+        fn clone_from(&mut self, source: &Self) {
+            self.lefts.clone_from(&source.lefts);
+            self.rights.clone_from(&source.rights);
+        }
+    }
+
+    impl<T, U> Storage<T, U> {
+        pub(crate) const fn new() -> Self {
+            Storage { lefts: vec::Vec::new(), rights: vec::Vec::new() }
+        }
+    }
+
+    impl<T, U, A: Allocator + Clone> Storage<T, U, A> {
+        pub(crate) fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+            Storage { lefts: vec::Vec::with_capacity_in(capacity, alloc.clone()), rights: vec::Vec::with_capacity_in(capacity, alloc) }
+        }
+
+        /// The allocator of the pairs.
+        pub(crate) fn allocator(&self) -> &A {
+            self.lefts.allocator()
+        }
+
+        #[inline(always)]
+        pub(crate) fn len(&self) -> usize {
+            self.lefts.len()
+        }
+
+        #[inline(always)]
+        pub(crate) fn is_empty(&self) -> bool {
+            self.lefts.is_empty()
+        }
+
+        /// The left value of the pair at the given position.
+        #[inline(always)]
+        pub(crate) fn left(&self, index: usize) -> &T {
+            &self.lefts[index]
+        }
+
+        /// The right value of the pair at the given position.
+        #[inline(always)]
+        pub(crate) fn right(&self, index: usize) -> &U {
+            &self.rights[index]
+        }
+
+        /// The left value of the pair at the given position, for mutation in place.
+        #[inline(always)]
+        pub(crate) fn left_mut(&mut self, index: usize) -> &mut T {
+            &mut self.lefts[index]
+        }
+
+        /// The right value of the pair at the given position, for mutation in place.
+        #[inline(always)]
+        pub(crate) fn right_mut(&mut self, index: usize) -> &mut U {
+            &mut self.rights[index]
+        }
+
+        pub(crate) fn push(&mut self, bucket: Bucket<T, U>) {
+            self.lefts.push(bucket.left);
+            self.rights.push(bucket.right);
+        }
+
+        /// Remove the pair at the given position and move the last pair into its place.
+        pub(crate) fn swap_remove(&mut self, index: usize) -> Bucket<T, U> {
+            Bucket { left: self.lefts.swap_remove(index), right: self.rights.swap_remove(index) }
+        }
+
+        /// Replace the pair at the given position and return the old pair.
+        pub(crate) fn replace(&mut self, index: usize, bucket: Bucket<T, U>) -> Bucket<T, U> {
+            Bucket {
+                left: mem::replace(&mut self.lefts[index], bucket.left),
+                right: mem::replace(&mut self.rights[index], bucket.right),
+            }
+        }
+
+        pub(crate) fn swap(&mut self, a: usize, b: usize) {
+            self.lefts.swap(a, b);
+            self.rights.swap(a, b);
+        }
+
+        pub(crate) fn iter(&self) -> Pairs<'_, T, U> {
+            self.lefts.iter().zip(self.rights.iter())
+        }
+
+        pub(crate) fn range(&self, range: Range<usize>) -> impl DoubleEndedIterator<Item=(&T, &U)> + ExactSizeIterator {
+            self.lefts[range.clone()].iter().zip(self.rights[range].iter())
+        }
+
+        pub(crate) fn lefts(&self) -> Lefts<'_, T, U> {
+            Column::new(&self.lefts)
+        }
+
+        pub(crate) fn rights(&self) -> Rights<'_, T, U> {
+            Column::new(&self.rights)
+        }
+
+        pub(crate) fn drain(&mut self) -> DrainPairs<'_, T, U, A> {
+            self.lefts.drain(..).zip(self.rights.drain(..))
+        }
+
+        pub(crate) fn into_pairs(self) -> IntoPairs<T, U, A> {
+            self.lefts.into_iter().zip(self.rights)
+        }
+
+        /// Remove the pairs whose mark is set, keeping the order of the other pairs, and return the
+        /// removed pairs in order.
+        pub(crate) fn extract_marked(&mut self, marks: &[bool]) -> Vec<Bucket<T, U>> {
+            let lefts = extract_marked(&mut self.lefts, marks);
+            let rights = extract_marked(&mut self.rights, marks);
+            lefts.into_iter().zip(rights).map(|(left, right)| Bucket { left, right }).collect()
+        }
+
+        #[cfg(feature = "rayon")]
+        pub(crate) fn par_iter(&self) -> impl IndexedParallelIterator<Item=(&T, &U)>
+            where T: Sync, U: Sync
+        {
+            self.lefts.as_slice().par_iter().zip(self.rights.as_slice().par_iter())
+        }
+
+        pub(crate) fn clear(&mut self) {
+            self.lefts.clear();
+            self.rights.clear();
+        }
+
+        /// The number of pairs the storage holds without reallocating either vector.
+        pub(crate) fn capacity(&self) -> usize {
+            self.lefts.capacity().min(self.rights.capacity())
+        }
+
+        pub(crate) fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+            self.lefts.try_reserve(additional)?;
+            self.rights.try_reserve(additional)
+        }
+
+        pub(crate) fn shrink_to_fit(&mut self) {
+            self.lefts.shrink_to_fit();
+            self.rights.shrink_to_fit();
+        }
+
+        pub(crate) fn shrink_to(&mut self, min_capacity: usize) {
+            self.lefts.shrink_to(min_capacity);
+            self.rights.shrink_to(min_capacity);
+        }
+
+        /// The number of heap bytes allocated for the pairs.
+        pub(crate) fn allocated_bytes(&self) -> usize {
+            self.lefts.capacity() * mem::size_of::<T>() + self.rights.capacity() * mem::size_of::<U>()
+        }
+
+        /// The number of heap bytes allocated for the pairs of a storage with the given capacity.
+        pub(crate) fn bytes_for(capacity: usize) -> usize {
+            capacity * (mem::size_of::<T>() + mem::size_of::<U>())
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! [proptest](https://docs.rs/proptest) strategies that generate maps, for property tests of code
+//! that embeds maps.
+//!
+//! ```
+//! use proptest::prelude::*;
+//! use bijective_map::strategy::bi_map;
+//!
+//! proptest! {
+//!     // a #[test] in a test module
+//!     fn inverse_lookup(map in bi_map(0..100u32, "[a-z]{1,4}", 0..20)) {
+//!         for (left, right) in map.iter() {
+//!             prop_assert_eq!(map.get_left(right), Some(left));
+//!         }
+//!     }
+//! }
+//! # inverse_lookup();
+//! ```
+
+use std::fmt::Debug;
+use std::hash::Hash;
+use proptest::collection::{vec, SizeRange};
+use proptest::strategy::Strategy;
+use crate::BiMap;
+
+/// Create a strategy that generates maps with left and right values drawn from `left` and `right`,
+/// and a number of pairs within the given range.
+///
+/// The pairs are generated as a vector and inserted with [`BiMap::bulk_insert`], so a pair that
+/// shares a value with an earlier pair is dropped. Like the maps of `proptest::collection::hash_map`,
+/// maps with fewer pairs than the minimum of the range are rejected locally, so the strategies of
+/// the values must be able to produce enough distinct values. Shrinking removes pairs and shrinks the
+/// values of the remaining pairs.
+pub fn bi_map<T, U>(left: T, right: U, size: impl Into<SizeRange>) -> impl Strategy<Value=BiMap<T::Value, U::Value>>
+    where T: Strategy, U: Strategy, T::Value: Hash + Eq + Debug, U::Value: Hash + Eq + Debug
+{
+    let size = size.into();
+    let min_size = size.start();
+    vec((left, right), size)
+        .prop_map(|pairs| {
+            let mut map = BiMap::with_capacity(pairs.len());
+            map.bulk_insert(pairs);
+            map
+        })
+        .prop_filter("BiMap minimum size", move |map| map.len() >= min_size)
+}
@@ -0,0 +1,163 @@
+//! Deterministic hashers for testing code that embeds a [`BiMap`], available with the `test-util`
+//! feature.
+//!
+//! A [`CollidingBuildHasher`] hashes integers to themselves modulo a configurable number of
+//! buckets, so values collide on purpose and the probe chains of the map can be predicted. A
+//! [`SeededBuildHasher`] spreads values well, but produces the same hashes in every run for the
+//! same seed, unlike [`RandomState`](std::hash::RandomState).
+//!
+//! The worst case of a hash map is a single probe chain that holds all values, which a colliding
+//! hasher with one bucket produces:
+//!
+//! ```
+//! use bijective_map::BiMap;
+//!
+//! let mut map = BiMap::with_test_hashers(64, 1);
+//! for value in 0..50u32 {
+//!     map.insert(value, value * 2);
+//! }
+//! assert_eq!(map.get_right(&49), Some(&98));
+//! assert_eq!(map.get_left(&98), Some(&49));
+//! assert!(map.check_invariants().is_ok());
+//! ```
+//!
+//! With more buckets, values that are equal modulo the number of buckets collide, so clusters can
+//! be built at chosen positions of the indices, e.g. at their end to make probe chains wrap around:
+//!
+//! ```
+//! use bijective_map::BiMap;
+//! use bijective_map::testing::{CollidingBuildHasher, SeededBuildHasher};
+//!
+//! // the map allocates exactly 16 index slots, and the left values 15, 31 and 47 all start at slot 15
+//! let mut map = BiMap::with_hashers(16, CollidingBuildHasher::new(16), SeededBuildHasher::new(7));
+//! map.insert(15u32, "a");
+//! map.insert(31, "b");
+//! map.insert(47, "c");
+//! assert_eq!(map.remove_left(&15), Some("a"));
+//! assert_eq!(map.get_right(&47), Some(&"c"));
+//! ```
+
+use std::hash::{BuildHasher, Hasher};
+use crate::BiMap;
+
+/// A [`BuildHasher`] whose hashers map integers to themselves modulo a number of buckets. See the
+/// [module documentation](self) for an example.
+///
+/// Every write is read as a little-endian integer, so integers hash to their value modulo the
+/// number of buckets. Values that write several times, such as strings and tuples, hash to a
+/// combination of their writes, which is still deterministic but harder to predict.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CollidingBuildHasher {
+    buckets: u64,
+}
+
+impl CollidingBuildHasher {
+    /// Create a hasher that maps all values to one of `buckets` hashes.
+    ///
+    /// # Panics
+    /// Panics, if `buckets` is zero.
+    pub fn new(buckets: usize) -> Self {
+        assert!(buckets > 0, "a colliding hasher needs at least one bucket");
+        CollidingBuildHasher { buckets: buckets as u64 }
+    }
+
+    /// The number of distinct hashes the hasher produces.
+    pub fn buckets(&self) -> usize {
+        self.buckets as usize
+    }
+}
+
+impl BuildHasher for CollidingBuildHasher {
+    type Hasher = CollidingHasher;
+
+    fn build_hasher(&self) -> CollidingHasher {
+        CollidingHasher { buckets: self.buckets, state: 0 }
+    }
+}
+
+/// The hasher of a [`CollidingBuildHasher`].
+#[derive(Clone, Debug)]
+pub struct CollidingHasher {
+    buckets: u64,
+    state: u64,
+}
+
+impl Hasher for CollidingHasher {
+    fn finish(&self) -> u64 {
+        self.state % self.buckets
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        // a single write of an integer leaves its value in the state, further writes are mixed in
+        let value = bytes.iter().rev().fold(0u64, |value, &byte| value.rotate_left(8) ^ byte as u64);
+        self.state = self.state.wrapping_mul(31).wrapping_add(value);
+    }
+}
+
+/// A [`BuildHasher`] that spreads values well, but produces the same hashes for the same seed in
+/// every run of a program, so failures that depend on the positions of values can be reproduced.
+/// The hashes are not resistant to collision attacks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SeededBuildHasher {
+    seed: u64,
+}
+
+impl SeededBuildHasher {
+    /// Create a hasher with the given seed.
+    pub fn new(seed: u64) -> Self {
+        SeededBuildHasher { seed }
+    }
+
+    /// The seed of the hasher.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl BuildHasher for SeededBuildHasher {
+    type Hasher = SeededHasher;
+
+    fn build_hasher(&self) -> SeededHasher {
+        SeededHasher { state: self.seed ^ FNV_OFFSET_BASIS }
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// The hasher of a [`SeededBuildHasher`], FNV-1a with the seed mixed into its initial state and a
+/// finalizer that spreads the bits of the result.
+#[derive(Clone, Debug)]
+pub struct SeededHasher {
+    state: u64,
+}
+
+impl Hasher for SeededHasher {
+    fn finish(&self) -> u64 {
+        // the finalizer of splitmix64, so that the low bits depend on all bytes
+        let mut hash = self.state;
+        hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        hash ^ (hash >> 31)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state = (self.state ^ byte as u64).wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+impl<T, U> BiMap<T, U, CollidingBuildHasher, CollidingBuildHasher> {
+    /// Create a new empty BiMap with a [`CollidingBuildHasher`] of `buckets` buckets on both sides.
+    /// Like [`with_hashers`], the indices have exactly `capacity` slots, or none for capacities
+    /// that fit into a small map, so the positions of values can be predicted.
+    ///
+    /// # Panics
+    /// Panics, if `buckets` is zero.
+    ///
+    /// [`with_hashers`]: #method.with_hashers
+    pub fn with_test_hashers(capacity: usize, buckets: usize) -> Self {
+        BiMap::with_hashers(capacity, CollidingBuildHasher::new(buckets), CollidingBuildHasher::new(buckets))
+    }
+}
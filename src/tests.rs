@@ -1,57 +1,166 @@
 use super::*;
-
-/// A hasher that simply returns the first byte of the input as the hash, for testing purposes
-struct IdentityHasher {
-    modulus: u8,
-    state: u8,
+use std::collections::HashSet;
+use std::hash::Hasher;
+use crate::testing::{CollidingBuildHasher, SeededBuildHasher};
+
+/// A hasher whose quality depends on its seed. The seed zero maps all values to the same hash,
+/// other seeds spread consecutive values over consecutive indices.
+#[derive(Clone, Debug)]
+struct SeededHasher {
+    seed: u64,
+    state: u64,
 }
 
-impl Hasher for IdentityHasher {
+impl Hasher for SeededHasher {
     fn finish(&self) -> u64 {
-        self.state as u64
+        self.state.wrapping_mul(self.seed)
     }
 
     fn write(&mut self, bytes: &[u8]) {
-        self.state = bytes.iter().find(|&&b| b != 0).or(Some(&0)).map(|&b| b % self.modulus).unwrap();
+        for &byte in bytes.iter().rev() {
+            self.state = (self.state << 8) | byte as u64;
+        }
+    }
+}
+
+impl BuildHasher for SeededHasher {
+    type Hasher = SeededHasher;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        SeededHasher { seed: self.seed, state: 0 }
     }
 }
 
-impl Default for IdentityHasher {
-    fn default() -> Self {
-        IdentityHasher { modulus: DEFAULT_CAPACITY as u8, state: 0 }
+/// A hasher that only produces entropy in the upper 32 bits of the hash.
+#[derive(Clone, Debug, Default)]
+struct HighBitsHasher {
+    state: u64,
+}
+
+impl Hasher for HighBitsHasher {
+    fn finish(&self) -> u64 {
+        self.state << 32
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes.iter().rev() {
+            self.state = (self.state << 8) | byte as u64;
+        }
     }
 }
 
-impl BuildHasher for IdentityHasher {
-    type Hasher = IdentityHasher;
+impl BuildHasher for HighBitsHasher {
+    type Hasher = HighBitsHasher;
 
     fn build_hasher(&self) -> Self::Hasher {
-        IdentityHasher::default()
+        HighBitsHasher::default()
+    }
+}
+
+/// Assert that the back-references of all buckets point at the slots that refer to the bucket.
+/// Small maps have no indices and therefore must not keep back-references.
+#[cfg(not(feature = "hashbrown-backend"))]
+fn assert_back_references<T, U, H, RH>(map: &BiMap<T, U, H, RH>)
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    if map.is_small() {
+        assert!(map.left.back_references.is_empty());
+        assert!(map.right.back_references.is_empty());
+        return;
+    }
+
+    assert_eq!(map.left.back_references.len(), map.data.len());
+    assert_eq!(map.right.back_references.len(), map.data.len());
+    for bucket_index in 0..map.len() {
+        assert_eq!(map.left.slots[map.left.back_references[bucket_index]].bucket(), Some(bucket_index), "left back-reference of bucket {} is invalid", bucket_index);
+        assert_eq!(map.right.slots[map.right.back_references[bucket_index]].bucket(), Some(bucket_index), "right back-reference of bucket {} is invalid", bucket_index);
+    }
+}
+
+/// Assert that the back-references of all buckets are the hashes of their values, which find the
+/// entries of the buckets in the tables. Small maps have no indices and therefore must not keep
+/// back-references.
+#[cfg(feature = "hashbrown-backend")]
+fn assert_back_references<T, U, H, RH>(map: &BiMap<T, U, H, RH>)
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    if map.is_small() {
+        assert!(map.left.back_references.is_empty());
+        assert!(map.right.back_references.is_empty());
+        return;
+    }
+
+    assert_eq!(map.left.back_references.len(), map.data.len());
+    assert_eq!(map.right.back_references.len(), map.data.len());
+    for bucket_index in 0..map.len() {
+        let (left, right) = (map.left.back_references[bucket_index], map.right.back_references[bucket_index]);
+        assert_eq!(left, map.left.ideal_index(map.data.left(bucket_index)), "left back-reference of bucket {} is invalid", bucket_index);
+        assert_eq!(right, map.right.ideal_index(map.data.right(bucket_index)), "right back-reference of bucket {} is invalid", bucket_index);
+        assert!(map.left.table.find(left as u64, |&bucket| bucket == bucket_index).is_some(), "left entry of bucket {} is missing", bucket_index);
+        assert!(map.right.table.find(right as u64, |&bucket| bucket == bucket_index).is_some(), "right entry of bucket {} is missing", bucket_index);
+    }
+}
+
+/// Assert that the map passes its invariant check, printing the violation otherwise.
+fn assert_invariants<T, U, H, RH>(map: &BiMap<T, U, H, RH>)
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    if let Err(violation) = map.check_invariants() {
+        panic!("invariant violated: {}", violation);
+    }
+}
+
+/// Assert that the stored probe distances of all occupied slots match the distances of their entries.
+#[cfg(not(feature = "hashbrown-backend"))]
+fn assert_probe_distances<T, U, H, RH>(map: &BiMap<T, U, H, RH>)
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    let capacity = map.current_capacity();
+    for index in 0..capacity {
+        if let Some(bucket) = map.left.slots[index].bucket() {
+            let ideal_index = map.left.ideal_index(map.data.left(bucket));
+            let expected = side::saturate_distance(side::probe_distance(ideal_index, index, capacity));
+            assert_eq!(map.left.distances[index], expected, "left distance of slot {} is invalid", index);
+        }
+        if let Some(bucket) = map.right.slots[index].bucket() {
+            let ideal_index = map.right.ideal_index(map.data.right(bucket));
+            let expected = side::saturate_distance(side::probe_distance(ideal_index, index, capacity));
+            assert_eq!(map.right.distances[index], expected, "right distance of slot {} is invalid", index);
+        }
     }
 }
 
+/// Assert that the stored probe distances of all occupied slots match the distances of their entries.
+/// Tables keep no probe distances, so there is nothing to check.
+#[cfg(feature = "hashbrown-backend")]
+fn assert_probe_distances<T, U, H, RH>(_map: &BiMap<T, U, H, RH>) {}
+
 #[test]
 fn test_replacing_inserts() {
     // Test that inserting a key that already exists will replace the old value
 
-    let mut map = BiMap::default();
+    let mut map: BiMap<_, _> = BiMap::default();
 
     let (old_right, old_left) = map.insert(1, 2);
+    assert_invariants(&map);
     assert_eq!(map.len(), 1);
     assert_eq!(old_right, None);
     assert_eq!(old_left, None);
 
     let (old_right, old_left) = map.insert(2, 3);
+    assert_invariants(&map);
     assert_eq!(map.len(), 2);
     assert_eq!(old_right, None);
     assert_eq!(old_left, None);
 
     let (old_right, old_left) = map.insert(2, 4);
+    assert_invariants(&map);
     assert_eq!(map.len(), 2);
     assert_eq!(old_right, Some(3));
     assert_eq!(old_left, None);
 
     let (old_right, old_left) = map.insert(1, 4);
+    assert_invariants(&map);
     assert_eq!(map.len(), 1);
     assert_eq!(old_right, Some(2));
     assert_eq!(old_left, Some(2));
@@ -65,49 +174,365 @@ fn test_replacing_inserts() {
 fn test_try_inserts() {
     // Test that inserting a key that already exists will return an error
 
-    let mut map = BiMap::default();
+    let mut map: BiMap<_, _> = BiMap::default();
 
     let result = map.try_insert(1, 2);
     assert_eq!(result, Ok(()));
     assert_eq!(map.len(), 1);
+    assert_invariants(&map);
 
     let result = map.try_insert(2, 3);
     assert_eq!(result, Ok(()));
     assert_eq!(map.len(), 2);
+    assert_invariants(&map);
 
     let result = map.try_insert(2, 4);
-    assert_eq!(result, Err((Some(&3), None)));
+    assert_eq!(result, Err(OccupiedError { left: 2, right: 4, left_occupied: true, right_occupied: false }));
     assert_eq!(map.len(), 2);
+    assert_invariants(&map);
 
     let result = map.try_insert(1, 4);
-    assert_eq!(result, Err((Some(&2), None)));
+    assert_eq!(result, Err(OccupiedError { left: 1, right: 4, left_occupied: true, right_occupied: false }));
     assert_eq!(map.len(), 2);
+    assert_invariants(&map);
 
     let result = map.try_insert(0, 2);
-    assert_eq!(result, Err((None, Some(&1))));
+    assert_eq!(result, Err(OccupiedError { left: 0, right: 2, left_occupied: false, right_occupied: true }));
     assert_eq!(map.len(), 2);
+    assert_invariants(&map);
 
     let result = map.try_insert(0, 0);
     assert_eq!(result, Ok(()));
     assert_eq!(map.len(), 3);
+    assert_invariants(&map);
 
     let result = map.try_insert(0, 0);
-    assert_eq!(result, Err((Some(&0), Some(&0))));
+    assert_eq!(result, Err(OccupiedError { left: 0, right: 0, left_occupied: true, right_occupied: true }));
     assert_eq!(map.len(), 3);
+    assert_invariants(&map);
 
     let result = map.try_insert(0, 3);
-    assert_eq!(result, Err((Some(&0), Some(&2))));
+    assert_eq!(result, Err(OccupiedError { left: 0, right: 3, left_occupied: true, right_occupied: true }));
     assert_eq!(map.len(), 3);
+    assert_invariants(&map);
+}
+
+#[test]
+fn test_try_extend() {
+    let mut map = BiMap::new();
+    assert_eq!(map.try_extend((0..20).map(|i| (i, i + 100))), Ok(()));
+    assert_eq!(map.len(), 20);
+
+    // the pairs before the duplicate stay inserted, the pairs after it are not consumed
+    let mut pairs = vec![(20, 120), (21, 105), (22, 122)].into_iter();
+    let error = map.try_extend(&mut pairs).unwrap_err();
+    assert_eq!(error, DuplicatePairError { left: 21, right: 105, position: 1, left_duplicate: false, right_duplicate: true });
+    assert_eq!(error.into_pair(), (21, 105));
+    assert_eq!(pairs.next(), Some((22, 122)));
+    assert_eq!(map.len(), 21);
+    assert_eq!(map.get_left(&105), Some(&5));
+    assert_invariants(&map);
+}
+
+type PolicyOutcome = (Result<(), errors::PolicyViolation<i32, i32>>, Vec<(i32, i32)>);
+
+/// Apply a single pair with the given policies to the map {1: 10, 2: 20}, and return the result
+/// together with the sorted pairs of the map afterwards.
+fn extend_one_with_policy(pair: (i32, i32), on_dup_left: DupPolicy, on_dup_right: DupPolicy) -> PolicyOutcome {
+    let mut map = BiMap::new();
+    map.insert(1, 10);
+    map.insert(2, 20);
+    let result = map.extend_with_policy([pair], on_dup_left, on_dup_right);
+    assert_invariants(&map);
+    let mut pairs = map.iter().map(|(&left, &right)| (left, right)).collect::<Vec<_>>();
+    pairs.sort();
+    (result, pairs)
+}
+
+#[test]
+fn test_extend_with_policy() {
+    use DupPolicy::{Error, Keep, Overwrite};
+    let unchanged = vec![(1, 10), (2, 20)];
+    let violation = |left_violated, right_violated| {
+        Err(errors::PolicyViolation { left: 1, right: 20, position: 0, left_violated, right_violated })
+    };
+
+    // a duplicate on one side is decided by the policy of that side only
+    for policy in [Overwrite, Keep, Error] {
+        assert_eq!(extend_one_with_policy((1, 30), Overwrite, policy), (Ok(()), vec![(1, 30), (2, 20)]));
+        assert_eq!(extend_one_with_policy((1, 30), Keep, policy), (Ok(()), unchanged.clone()));
+        assert_eq!(extend_one_with_policy((1, 30), Error, policy).1, unchanged);
+        assert_eq!(extend_one_with_policy((3, 20), policy, Overwrite), (Ok(()), vec![(1, 10), (3, 20)]));
+        assert_eq!(extend_one_with_policy((3, 20), policy, Keep), (Ok(()), unchanged.clone()));
+        assert_eq!(extend_one_with_policy((3, 20), policy, Error).1, unchanged);
+        assert_eq!(extend_one_with_policy((3, 30), policy, policy), (Ok(()), vec![(1, 10), (2, 20), (3, 30)]));
+        // a pair that is in the map already is no duplicate
+        assert_eq!(extend_one_with_policy((1, 10), policy, policy), (Ok(()), unchanged.clone()));
+    }
+
+    // a pair that conflicts on both sides: errors win over keeping, keeping wins over overwriting
+    assert_eq!(extend_one_with_policy((1, 20), Overwrite, Overwrite), (Ok(()), vec![(1, 20)]));
+    assert_eq!(extend_one_with_policy((1, 20), Overwrite, Keep), (Ok(()), unchanged.clone()));
+    assert_eq!(extend_one_with_policy((1, 20), Overwrite, Error), (violation(false, true), unchanged.clone()));
+    assert_eq!(extend_one_with_policy((1, 20), Keep, Overwrite), (Ok(()), unchanged.clone()));
+    assert_eq!(extend_one_with_policy((1, 20), Keep, Keep), (Ok(()), unchanged.clone()));
+    assert_eq!(extend_one_with_policy((1, 20), Keep, Error), (violation(false, true), unchanged.clone()));
+    assert_eq!(extend_one_with_policy((1, 20), Error, Overwrite), (violation(true, false), unchanged.clone()));
+    assert_eq!(extend_one_with_policy((1, 20), Error, Keep), (violation(true, false), unchanged.clone()));
+    assert_eq!(extend_one_with_policy((1, 20), Error, Error), (violation(true, true), unchanged.clone()));
+
+    // pairs are applied one by one, and the pairs after a violation are not consumed
+    let mut map = BiMap::new();
+    let mut pairs = vec![(1, 10), (2, 10), (3, 30), (3, 40), (5, 50)].into_iter();
+    let error = map.extend_with_policy(&mut pairs, Error, Keep).unwrap_err();
+    assert_eq!((error.position, error.into_pair()), (3, (3, 40)));
+    assert_eq!(pairs.next(), Some((5, 50)));
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get_right(&1), Some(&10));
+    assert_eq!(map.get_right(&3), Some(&30));
+}
+
+#[test]
+fn test_try_reserve() {
+    let mut map = BiMap::new();
+    assert_eq!(map.try_reserve(4), Ok(()));
+    assert!(map.is_small());
+
+    for i in 0..5 {
+        map.insert(i, i);
+    }
+    assert_eq!(map.try_reserve(100), Ok(()));
+    assert!(map.growth_threshold >= 105);
+    assert!(map.data.capacity() >= 105);
+    assert_invariants(&map);
+
+    let capacity = map.current_capacity();
+    assert_eq!(map.try_reserve(usize::MAX), Err(errors::TryReserveError::CapacityOverflow));
+    assert!(matches!(map.try_reserve(usize::MAX / 64), Err(errors::TryReserveError::AllocError(_))));
+    assert_eq!(map.current_capacity(), capacity);
+    assert_eq!(map.len(), 5);
+    assert_invariants(&map);
+
+    for i in 5..105 {
+        assert!(map.insert_within_capacity(i, i).is_ok());
+    }
+    assert_eq!(map.current_capacity(), capacity);
+}
+
+#[test]
+fn test_rekey() {
+    for len in [4, 50] {
+        let mut map = BiMap::new();
+        for i in 0..len {
+            map.insert(i, i + 100);
+        }
+
+        assert_eq!(map.rekey_left(&1, 1000), Ok(()));
+        assert_eq!(map.get_right(&1000), Some(&101));
+        assert_eq!(map.get_left(&101), Some(&1000));
+        assert!(!map.contains_left(&1));
+
+        assert_eq!(map.rekey_right(&102, 2000), Ok(()));
+        assert_eq!(map.get_right(&2), Some(&2000));
+        assert!(!map.contains_right(&102));
+
+        // replacing a value with itself is allowed
+        assert_eq!(map.rekey_left(&3, 3), Ok(()));
+        assert_eq!(map.get_right(&3), Some(&103));
+
+        assert_eq!(map.rekey_left(&1, 7), Err(RekeyError::NotFound(7)));
+        assert_eq!(map.rekey_left(&0, 3), Err(RekeyError::Occupied(3)));
+        assert_eq!(map.rekey_right(&100, 103).map_err(RekeyError::into_value), Err(103));
+        assert_eq!(map.len(), len as usize);
+        assert_eq!(map.get_right(&0), Some(&100));
+        assert_invariants(&map);
+    }
+}
+
+#[test]
+fn test_rekey_notifications() {
+    // test that a rekey is reported as one removal and one insertion, in both representations
+    use Notification::{Insert, Remove};
+    for len in [4, 50] {
+        let mut map: BiMap<usize, usize> = (0..len).map(|i| (i, i + 100)).collect();
+        let notifications = record_notifications(&mut map);
+        #[cfg(feature = "stats")]
+        map.reset_op_counters();
+
+        map.rekey_left(&1, 1000).unwrap();
+        map.rekey_right(&102, 2000).unwrap();
+        assert_eq!(notifications.lock().unwrap().as_slice(), &[
+            Remove(1, 101), Insert(1000, 101),
+            Remove(2, 102), Insert(2, 2000),
+        ]);
+        #[cfg(feature = "stats")]
+        {
+            let counters = map.op_counters();
+            assert_eq!((counters.inserts, counters.removals), (2, 2));
+        }
+    }
+}
+
+#[test]
+fn test_rekey_undo() {
+    for len in [4, 50] {
+        let mut map: BiMap<usize, usize> = (0..len).map(|i| (i, i + 100)).collect();
+        map.enable_journal();
+
+        map.rekey_left(&1, 1000).unwrap();
+        map.rekey_right(&102, 2000).unwrap();
+        assert!(map.undo());
+        assert_eq!(map.get_right(&2), Some(&102));
+        assert!(!map.contains_right(&2000));
+        assert!(map.undo());
+        assert_eq!(map.get_right(&1), Some(&101));
+        assert!(!map.contains_left(&1000));
+        assert!(!map.undo());
+        assert_eq!(map.len(), len);
+        assert_invariants(&map);
+
+        assert!(map.redo());
+        assert!(map.redo());
+        assert_eq!(map.get_left(&101), Some(&1000));
+        assert_eq!(map.get_right(&2), Some(&2000));
+        assert!(!map.redo());
+        assert_eq!(map.len(), len);
+        assert_invariants(&map);
+    }
+}
+
+#[test]
+fn test_errors_display_and_downcast() {
+    use std::error::Error;
+
+    let occupied = OccupiedError { left: 1, right: "a", left_occupied: true, right_occupied: false };
+    assert_eq!(occupied.to_string(), "the left value 1 is already in the map");
+    let both = OccupiedError { left: 1, right: "a", left_occupied: true, right_occupied: true };
+    assert_eq!(both.to_string(), "the left value 1 and the right value \"a\" are already in the map");
+    let duplicate = DuplicatePairError { left: 1, right: 2, position: 3, left_duplicate: false, right_duplicate: true };
+    assert_eq!(duplicate.to_string(), "the right value 2 of pair 3 is a duplicate");
+    assert_eq!(RekeyError::NotFound(5).to_string(), "the value to replace with 5 is not in the map");
+    assert_eq!(RekeyError::Occupied(5).to_string(), "the new value 5 is already in the map");
+    let overflow = TryReserveError::CapacityOverflow;
+    assert_eq!(overflow.to_string(), "the required capacity exceeds the maximum capacity of the map");
+    assert!(overflow.source().is_none());
+    let alloc_error = allocator_api2::vec::Vec::<u8>::new().try_reserve(isize::MAX as usize).map_err(TryReserveError::from).unwrap_err();
+    assert_eq!(alloc_error, TryReserveError::AllocError(std::alloc::Layout::array::<u8>(isize::MAX as usize).unwrap()));
+    assert_eq!(alloc_error.to_string(), "the memory allocation of the map failed");
+    assert!(alloc_error.source().is_none());
+
+    let boxed: Box<dyn Error> = Box::new(occupied);
+    assert_eq!(boxed.downcast_ref::<OccupiedError<i32, &str>>(), Some(&occupied));
+    let boxed: Box<dyn Error> = Box::new(duplicate);
+    assert_eq!(*boxed.downcast::<DuplicatePairError<i32, i32>>().unwrap(), duplicate);
+    let boxed: Box<dyn Error> = Box::new(RekeyError::Occupied(5));
+    assert_eq!(boxed.downcast_ref::<RekeyError<i32>>(), Some(&RekeyError::Occupied(5)));
+    let boxed: Box<dyn Error> = Box::new(overflow.clone());
+    assert_eq!(boxed.downcast_ref::<TryReserveError>(), Some(&overflow));
+}
+
+#[test]
+fn test_bimap_macro() {
+    let map = crate::bimap! { 1 => "one", 2 => "two" };
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get_right(&1), Some(&"one"));
+    assert_eq!(map.get_left(&"two"), Some(&2));
+    assert_invariants(&map);
+
+    let trailing_comma = crate::bimap! {
+        1 => "one",
+        2 => "two",
+    };
+    assert_same_pairs(&trailing_comma, &map);
+
+    let empty: BiMap<u32, u32> = crate::bimap! {};
+    assert!(empty.is_empty());
+
+    // duplicates replace the earlier pairs like insert does
+    let duplicates = crate::bimap! { 1 => 10, 2 => 20, 1 => 30, 4 => 20, 5 => 50 };
+    let mut inserted = BiMap::new();
+    for (left, right) in [(1, 10), (2, 20), (1, 30), (4, 20), (5, 50)] {
+        inserted.insert(left, right);
+    }
+    assert_same_pairs(&duplicates, &inserted);
+    assert_eq!(duplicates.len(), 3);
+
+    // enough pairs for the hashed representation
+    let hashed = crate::bimap! { 0 => 0, 1 => 1, 2 => 2, 3 => 3, 4 => 4, 5 => 5, 6 => 6, 7 => 7, 8 => 8, 9 => 9 };
+    assert!(!hashed.is_small());
+    assert_eq!(hashed.get_left(&9), Some(&9));
+    assert_invariants(&hashed);
+
+    let seeded = crate::bimap![with SeededBuildHasher::new(3); 1 => "one", 2 => "two",];
+    assert_eq!(seeded.left.hasher, SeededBuildHasher::new(3));
+    assert_eq!(seeded.right.hasher, SeededBuildHasher::new(3));
+    assert_eq!(seeded.get_right(&2), Some(&"two"));
+
+    let mixed = crate::bimap![with SeededBuildHasher::new(3), CollidingBuildHasher::new(4); 1 => 2];
+    assert_eq!(mixed.right.hasher, CollidingBuildHasher::new(4));
+    assert_eq!(mixed.get_left(&2), Some(&1));
+
+    let empty_seeded: BiMap<u32, u32, SeededBuildHasher, SeededBuildHasher> = crate::bimap![with SeededBuildHasher::new(3);];
+    assert!(empty_seeded.is_empty());
+}
+
+#[cfg(feature = "named")]
+#[test]
+fn test_named_bimap() {
+    crate::named_bimap!(
+        #[derive(Clone, Debug, Default)]
+        HostPortMap, host: String => port: u16,
+    );
+
+    // the generated methods have the expected names and signatures
+    let _: fn() -> HostPortMap = HostPortMap::new;
+    let _: fn(usize) -> HostPortMap = HostPortMap::with_capacity;
+    type Replaced = (Option<u16>, Option<String>);
+    let _: fn(&mut HostPortMap, String, u16) -> Replaced = HostPortMap::insert;
+    let _: for<'a> fn(&'a HostPortMap, &String) -> Option<&'a u16> = HostPortMap::port_for;
+    let _: for<'a> fn(&'a HostPortMap, &u16) -> Option<&'a String> = HostPortMap::host_for;
+    let _: fn(&HostPortMap, &String) -> bool = HostPortMap::contains_host;
+    let _: fn(&HostPortMap, &u16) -> bool = HostPortMap::contains_port;
+    let _: fn(&mut HostPortMap, &String) -> Option<u16> = HostPortMap::remove_by_host;
+    let _: fn(&mut HostPortMap, &u16) -> Option<String> = HostPortMap::remove_by_port;
+    let _: fn(HostPortMap) -> BiMap<String, u16> = HostPortMap::into_bimap;
+
+    let mut ports = HostPortMap::default();
+    assert!(ports.is_empty());
+    assert_eq!(ports.insert("web".to_string(), 80), (None, None));
+    assert_eq!(ports.insert("mail".to_string(), 25), (None, None));
+    assert_eq!(ports.insert("web".to_string(), 443), (Some(80), None));
+    assert_eq!(ports.len(), 2);
+
+    assert_eq!(ports.port_for(&"web".to_string()), Some(&443));
+    assert_eq!(ports.host_for(&25).map(String::as_str), Some("mail"));
+    assert!(ports.contains_host(&"mail".to_string()));
+    assert!(!ports.contains_port(&80));
+    assert_eq!(ports.iter().count(), 2);
+
+    let cloned = ports.clone();
+    assert_eq!(ports.remove_by_port(&25).as_deref(), Some("mail"));
+    assert_eq!(ports.remove_by_host(&"web".to_string()), Some(443));
+    assert!(ports.as_bimap().is_empty());
+    assert_eq!(cloned.len(), 2);
+
+    let mut map = cloned.into_bimap();
+    assert_eq!(map.remove_left(&"web".to_string()), Some(443));
+    let mut ports = HostPortMap::with_capacity(4);
+    ports.insert("ssh".to_string(), 22);
+    ports.clear();
+    assert!(ports.is_empty());
 }
 
 #[test]
 fn test_get() {
     // Test that we get correct values from the map
 
-    let mut map = BiMap::default();
+    let mut map: BiMap<_, _> = BiMap::default();
 
     map.insert(1, 2);
     map.insert(2, 3);
+    assert_invariants(&map);
 
     assert_eq!(map.get_right(&0), None);
     assert_eq!(map.get_left(&0), None);
@@ -118,6 +543,7 @@ fn test_get() {
     assert_eq!(map.get_left(&3), Some(&2));
 
     map.insert(1, 3);
+    assert_invariants(&map);
 
     assert_eq!(map.get_right(&1), Some(&3));
     assert_eq!(map.get_left(&3), Some(&1));
@@ -126,14 +552,155 @@ fn test_get() {
     assert_eq!(map.get_left(&2), None);
 }
 
+/// A value whose hash only depends on its key, so that its count can be mutated without changing
+/// the hash.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Counter {
+    key: u32,
+    count: u32,
+}
+
+impl Hash for Counter {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+    }
+}
+
+#[test]
+fn test_get_mut_keeps_hash() {
+    // test that values can be mutated without changing their hash, in both representations
+    for len in [4, 100] {
+        let mut map = BiMap::new();
+        for key in 0..len {
+            map.insert(key, Counter { key, count: 0 });
+        }
+
+        for key in 0..len {
+            let mut counter = map.get_right_mut(&key).unwrap();
+            counter.count += key;
+        }
+        assert_invariants(&map);
+        assert_back_references(&map);
+
+        for key in 0..len {
+            assert_eq!(map.get_right(&key), Some(&Counter { key, count: key }));
+            assert_eq!(map.get_left(&Counter { key, count: key }), Some(&key));
+        }
+        assert_eq!(map.get_left(&Counter { key: 3, count: 0 }), None);
+    }
+}
+
+#[test]
+fn test_get_mut_changes_hash() {
+    // test that mutated values are found at the position of their new hash, and no longer at the old one
+    for len in [4, 100] {
+        let mut map = BiMap::new();
+        for i in 0..len {
+            map.insert(i, i + 1000);
+        }
+        map.enable_negative_filter();
+
+        for i in (0..len).step_by(2) {
+            *map.get_right_mut(&i).unwrap() += 1000;
+            *map.get_left_mut(&(i + 1 + 1000)).unwrap() += 5000;
+        }
+        assert_invariants(&map);
+        assert_back_references(&map);
+        assert_eq!(map.len(), len);
+
+        for i in (0..len).step_by(2) {
+            assert_eq!(map.get_right(&i), Some(&(i + 2000)));
+            assert_eq!(map.get_left(&(i + 2000)), Some(&i));
+            assert_eq!(map.get_left(&(i + 1000)), None);
+            assert_eq!(map.get_right(&(i + 1 + 5000)), Some(&(i + 1 + 1000)));
+            assert_eq!(map.get_left(&(i + 1 + 1000)), Some(&(i + 1 + 5000)));
+            assert_eq!(map.get_right(&(i + 1)), None);
+        }
+    }
+
+    // reading through the guard leaves the indices untouched
+    let mut map = BiMap::new();
+    map.insert(1, 2);
+    assert_eq!(*map.get_right_mut(&1).unwrap(), 2);
+    assert_eq!(map.get_left(&2), Some(&1));
+    assert!(map.get_right_mut(&2).is_none());
+    assert!(map.get_left_mut(&1).is_none());
+}
+
+#[test]
+fn test_get_mut_saturated_chain() {
+    // test that values can be moved out of and into chains with saturated probe distances
+    let chain_length = SATURATED_DISTANCE as usize + 45;
+    let mut map = BiMap::with_test_hashers(512, DEFAULT_CAPACITY);
+    for i in 0..chain_length {
+        map.insert(i * DEFAULT_CAPACITY + 1, i * DEFAULT_CAPACITY + 2);
+    }
+
+    // move values from the front of the chain to its end, and one out of it
+    for i in [0, 10, SATURATED_DISTANCE as usize] {
+        *map.get_right_mut(&(i * DEFAULT_CAPACITY + 1)).unwrap() += chain_length * DEFAULT_CAPACITY;
+        assert_invariants(&map);
+        assert_probe_distances(&map);
+        assert_back_references(&map);
+    }
+    *map.get_right_mut(&(20 * DEFAULT_CAPACITY + 1)).unwrap() = 5;
+    assert_invariants(&map);
+    assert_probe_distances(&map);
+
+    for i in [0, 10, SATURATED_DISTANCE as usize] {
+        assert_eq!(map.get_left(&((chain_length + i) * DEFAULT_CAPACITY + 2)), Some(&(i * DEFAULT_CAPACITY + 1)));
+        assert_eq!(map.get_left(&(i * DEFAULT_CAPACITY + 2)), None);
+    }
+    assert_eq!(map.get_left(&5), Some(&(20 * DEFAULT_CAPACITY + 1)));
+    assert_eq!(map.get_right(&(30 * DEFAULT_CAPACITY + 1)), Some(&(30 * DEFAULT_CAPACITY + 2)));
+}
+
+#[test]
+fn test_get_mut_collision() {
+    // test that a mutated value that equals the value of another pair removes the mutated pair
+    for len in [6, 100] {
+        let mut map = BiMap::new();
+        for i in 0..len {
+            map.insert(i, i + 1000);
+        }
+
+        let mut right = map.get_right_mut(&0).unwrap();
+        *right = 1001;
+        assert_eq!(right.commit(), Err((0, 1001)));
+        assert_invariants(&map);
+        assert_back_references(&map);
+        assert_eq!(map.len(), len - 1);
+        assert_eq!(map.get_right(&0), None);
+        assert_eq!(map.get_left(&1001), Some(&1));
+
+        let mut left = map.get_left_mut(&1002).unwrap();
+        *left = 3;
+        assert_eq!(left.commit(), Err((3, 1002)));
+        assert_eq!(map.get_left(&1003), Some(&3));
+        assert_eq!(map.get_left(&1002), None);
+
+        // dropping the guard panics instead
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            *map.get_right_mut(&4).unwrap() = 1005;
+        }));
+        assert!(result.is_err());
+        assert_invariants(&map);
+        assert_back_references(&map);
+        assert_eq!(map.len(), len - 3);
+        assert_eq!(map.get_right(&4), None);
+        assert_eq!(map.get_left(&1005), Some(&5));
+    }
+}
+
 #[test]
 fn test_reinsertion() {
     // Test that reinserting a mapping that already exists does not change the map
 
-    let mut map = BiMap::default();
+    let mut map: BiMap<_, _> = BiMap::default();
 
     map.insert(1, 2);
     map.insert(2, 3);
+    assert_invariants(&map);
 
     assert_eq!(map.len(), 2);
     assert_eq!(map.get_right(&1), Some(&2));
@@ -142,6 +709,7 @@ fn test_reinsertion() {
     assert_eq!(map.get_left(&3), Some(&2));
 
     let (old_right, old_left) = map.insert(1, 2);
+    assert_invariants(&map);
     assert_eq!(old_left, Some(1));
     assert_eq!(old_right, Some(2));
 
@@ -156,36 +724,39 @@ fn test_reinsertion() {
 fn test_contains() {
     // Test that contains returns the correct value
 
-    let mut map = BiMap::default();
+    let mut map: BiMap<_, _> = BiMap::default();
 
     map.insert(1, 2);
     map.insert(2, 3);
-
-    assert_eq!(map.contains_left(&1), true);
-    assert_eq!(map.contains_left(&2), true);
-    assert_eq!(map.contains_left(&3), false);
-    assert_eq!(map.contains_left(&0), false);
-    assert_eq!(map.contains_left(&usize::MAX), false);
-
-    assert_eq!(map.contains_right(&1), false);
-    assert_eq!(map.contains_right(&2), true);
-    assert_eq!(map.contains_right(&3), true);
-    assert_eq!(map.contains_right(&0), false);
-    assert_eq!(map.contains_right(&usize::MAX), false);
+    assert_invariants(&map);
+
+    assert!(map.contains_left(&1));
+    assert!(map.contains_left(&2));
+    assert!(!map.contains_left(&3));
+    assert!(!map.contains_left(&0));
+    assert!(!map.contains_left(&usize::MAX));
+
+    assert!(!map.contains_right(&1));
+    assert!(map.contains_right(&2));
+    assert!(map.contains_right(&3));
+    assert!(!map.contains_right(&0));
+    assert!(!map.contains_right(&usize::MAX));
 }
 
 #[test]
 fn test_deletion() {
     // Test that deleting a key removes the mapping
 
-    let mut map = BiMap::default();
+    let mut map: BiMap<_, _> = BiMap::default();
 
     map.insert(1, 2);
     map.insert(2, 3);
+    assert_invariants(&map);
 
     assert_eq!(map.len(), 2);
 
     let right = map.remove_left(&1);
+    assert_invariants(&map);
     assert_eq!(right, Some(2));
     assert_eq!(map.len(), 1);
     assert_eq!(map.get_right(&1), None);
@@ -194,20 +765,24 @@ fn test_deletion() {
     assert_eq!(map.get_left(&3), Some(&2));
 
     let left = map.remove_right(&3);
+    assert_invariants(&map);
     assert_eq!(left, Some(2));
     assert_eq!(map.len(), 0);
     assert_eq!(map.get_right(&2), None);
     assert_eq!(map.get_left(&3), None);
 
     let right = map.remove_left(&1);
+    assert_invariants(&map);
     assert_eq!(right, None);
     assert!(map.is_empty());
 
     let left = map.remove_right(&2);
+    assert_invariants(&map);
     assert_eq!(left, None);
     assert!(map.is_empty());
 
     map.insert(1, 2);
+    assert_invariants(&map);
     assert_eq!(map.remove_left(&0), None);
     assert_eq!(map.remove_right(&0), None);
     assert_eq!(map.get_right(&1), Some(&2));
@@ -218,10 +793,11 @@ fn test_deletion() {
 fn test_insert_after_delete() {
     // Test that inserting a key after deleting it works
 
-    let mut map = BiMap::default();
+    let mut map: BiMap<_, _> = BiMap::default();
 
     map.insert(1, 2);
     map.insert(2, 3);
+    assert_invariants(&map);
 
     assert_eq!(map.len(), 2);
     assert!(map.try_insert(1, 2).is_err());
@@ -241,6 +817,7 @@ fn test_insert_after_delete() {
     assert_eq!(map.get_left(&3), None);
 
     map.insert(1, 3);
+    assert_invariants(&map);
     assert_eq!(map.len(), 1);
     assert_eq!(map.get_right(&1), Some(&3));
     assert_eq!(map.get_left(&3), Some(&1));
@@ -252,15 +829,16 @@ fn test_insert_after_delete() {
 fn test_collisions() {
     // Test that the map works correctly when two values are inserted with the same hash
 
-    let mut map = BiMap::with_hashers(DEFAULT_CAPACITY, IdentityHasher::default(), IdentityHasher::default());
+    let mut map = BiMap::with_test_hashers(DEFAULT_CAPACITY, DEFAULT_CAPACITY);
 
     // verify the test is working as expected
-    assert_eq!(map.get_ideal_index_left(&1), map.get_ideal_index_left(&(DEFAULT_CAPACITY + 1)));
+    assert_eq!(map.left.ideal_index(&1), map.left.ideal_index(&(DEFAULT_CAPACITY + 1)));
 
     // insert colliding values
     map.insert(1, 2);
     map.insert(DEFAULT_CAPACITY + 1, 3);
     map.insert(2 * DEFAULT_CAPACITY + 1, 4);
+    assert_invariants(&map);
 
     assert_eq!(map.get_right(&1), Some(&2));
     assert_eq!(map.get_left(&2), Some(&1));
@@ -271,6 +849,7 @@ fn test_collisions() {
 
     // remove last collision
     map.remove_left(&(2 * DEFAULT_CAPACITY + 1));
+    assert_invariants(&map);
 
     // verify other values are still present
     assert_eq!(map.get_right(&1), Some(&2));
@@ -284,9 +863,11 @@ fn test_collisions() {
 
     // reinsert last collision
     map.insert(2 * DEFAULT_CAPACITY + 1, 4);
+    assert_invariants(&map);
 
     // remove second collision
     map.remove_left(&(DEFAULT_CAPACITY + 1));
+    assert_invariants(&map);
 
     // verify other values are still present
     assert_eq!(map.get_right(&1), Some(&2));
@@ -299,34 +880,38 @@ fn test_collisions() {
     assert_eq!(map.get_left(&3), None);
 }
 
+#[cfg(not(feature = "hashbrown-backend"))]
 #[test]
 fn test_collisions_wrapping() {
     // test that the map works correctly when two values are inserted with the same hash,
     // and the index for linear probing wraps around the end of the array
 
-    let mut map = BiMap::with_hashers(DEFAULT_CAPACITY, IdentityHasher::default(), IdentityHasher::default());
+    let mut map = BiMap::with_test_hashers(DEFAULT_CAPACITY, DEFAULT_CAPACITY);
 
     // verify the test is working as expected
-    assert_eq!(map.get_ideal_index_left(&31), 31);
-    assert_eq!(map.get_ideal_index_left(&(DEFAULT_CAPACITY + 31)), 31);
+    assert_eq!(map.left.ideal_index(&31), 31);
+    assert_eq!(map.left.ideal_index(&(DEFAULT_CAPACITY + 31)), 31);
 
     map.insert(31, 2);
+    assert_invariants(&map);
 
     // verify this hasn't wrapped around
-    assert_eq!(map.left_index[0], EMPTY_SLOT);
+    assert!(map.left.slots[0].bucket().is_none());
 
     // insert colliding values, one of which should end up at index 0 of the mapping
     map.insert(DEFAULT_CAPACITY + 31, 3);
+    assert_invariants(&map);
 
     // verify wrap-around
-    assert_ne!(map.left_index[0], EMPTY_SLOT);
-    assert_eq!(map.left_index[1], EMPTY_SLOT);
+    assert!(map.left.slots[0].bucket().is_some());
+    assert!(map.left.slots[1].bucket().is_none());
 
     // insert second colliding value
     map.insert(2 * DEFAULT_CAPACITY + 31, 4);
+    assert_invariants(&map);
 
     // verify wrap-around
-    assert_ne!(map.left_index[1], EMPTY_SLOT);
+    assert!(map.left.slots[1].bucket().is_some());
 
     // verify the values are recovered correctly
     assert_eq!(map.get_right(&31), Some(&2));
@@ -338,6 +923,7 @@ fn test_collisions_wrapping() {
 
     // remove last collision
     map.remove_left(&(2 * DEFAULT_CAPACITY + 31));
+    assert_invariants(&map);
 
     // verify other values are still present
     assert_eq!(map.get_right(&31), Some(&2));
@@ -351,12 +937,14 @@ fn test_collisions_wrapping() {
 
     // reinsert last collision
     map.insert(2 * DEFAULT_CAPACITY + 31, 4);
+    assert_invariants(&map);
 
-    assert_ne!(map.left_index[0], EMPTY_SLOT);
-    assert_ne!(map.left_index[1], EMPTY_SLOT);
+    assert!(map.left.slots[0].bucket().is_some());
+    assert!(map.left.slots[1].bucket().is_some());
 
     // remove second collision
     map.remove_left(&(DEFAULT_CAPACITY + 31));
+    assert_invariants(&map);
 
     // verify other values are still present
     assert_eq!(map.get_right(&31), Some(&2));
@@ -373,22 +961,24 @@ fn test_collisions_wrapping() {
 fn test_collisions_replacement() {
     // test that the map works correctly when two values are inserted with the same hash,
     // and then some of them are replaced by a new insertion
-    let mut map = BiMap::with_hashers(DEFAULT_CAPACITY, IdentityHasher::default(), IdentityHasher::default());
+    let mut map = BiMap::with_test_hashers(DEFAULT_CAPACITY, DEFAULT_CAPACITY);
 
     map.insert(1, 2);
     map.insert(DEFAULT_CAPACITY + 1, 3);
     map.insert(1, 3);
+    assert_invariants(&map);
 
     assert_eq!(map.get_right(&1), Some(&3));
     assert_eq!(map.get_left(&3), Some(&1));
     assert_eq!(map.get_right(&31), None);
     assert_eq!(map.get_left(&2), None);
 
-    let mut map = BiMap::with_hashers(DEFAULT_CAPACITY, IdentityHasher::default(), IdentityHasher::default());
+    let mut map = BiMap::with_test_hashers(DEFAULT_CAPACITY, DEFAULT_CAPACITY);
 
     map.insert(1, 2);
     map.insert(DEFAULT_CAPACITY + 1, 3);
     map.insert(1, 4);
+    assert_invariants(&map);
 
     assert_eq!(map.get_right(&1), Some(&4));
     assert_eq!(map.get_left(&4), Some(&1));
@@ -397,6 +987,7 @@ fn test_collisions_replacement() {
     assert_eq!(map.get_right(&2), None);
 
     map.insert(DEFAULT_CAPACITY + 1, 5);
+    assert_invariants(&map);
 
     assert_eq!(map.get_right(&1), Some(&4));
     assert_eq!(map.get_left(&4), Some(&1));
@@ -404,31 +995,33 @@ fn test_collisions_replacement() {
     assert_eq!(map.get_left(&5), Some(&(DEFAULT_CAPACITY + 1)));
 }
 
+#[cfg(not(feature = "hashbrown-backend"))]
 #[test]
 fn test_multi_collision() {
     // test whether a lot of collisions are resolved correctly
-    let mut map = BiMap::with_hashers(DEFAULT_CAPACITY, IdentityHasher::default(), IdentityHasher::default());
+    let mut map = BiMap::with_test_hashers(DEFAULT_CAPACITY, DEFAULT_CAPACITY);
 
     for i in 0..10 {
         map.insert(i * DEFAULT_CAPACITY + 1, i + 1);
 
         for j in 0..=i {
             // verify the overflow slots are actually used (otherwise the test is broken)
-            assert_ne!(map.left_index[1 + j], EMPTY_SLOT);
+            assert!(map.left.slots[1 + j].bucket().is_some());
         }
 
         // verify the next slot after all overflow slots is empty
-        assert_eq!(map.left_index[1 + i + 1], EMPTY_SLOT);
+        assert!(map.left.slots[1 + i + 1].bucket().is_none());
 
         for j in 0..=i {
             assert_eq!(map.get_right(&(j * DEFAULT_CAPACITY + 1)), Some(&(j + 1)));
             assert_eq!(map.get_left(&(j + 1)), Some(&(j * DEFAULT_CAPACITY + 1)));
         }
     }
+    assert_invariants(&map);
 
     // test whether a lot of collisions are resolved correctly,
     // some of which wrap around the end of the array
-    let mut map = BiMap::with_hashers(DEFAULT_CAPACITY, IdentityHasher::default(), IdentityHasher::default());
+    let mut map = BiMap::with_test_hashers(DEFAULT_CAPACITY, DEFAULT_CAPACITY);
 
     for i in 0..10 {
         map.insert(i * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 2), i + 1);
@@ -438,11 +1031,13 @@ fn test_multi_collision() {
             assert_eq!(map.get_left(&(j + 1)), Some(&(j * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 2))));
         }
     }
+    assert_invariants(&map);
 
     // test whether a lot of collisions are resolved correctly,
     // some of which wrap around the end of the array, with some deletions
 
     map.remove_left(&(DEFAULT_CAPACITY - 2));
+    assert_invariants(&map);
 
     assert_eq!(map.get_right(&(DEFAULT_CAPACITY - 2)), None);
     assert_eq!(map.get_left(&1), None);
@@ -453,6 +1048,7 @@ fn test_multi_collision() {
     }
 
     map.remove_right(&4);
+    assert_invariants(&map);
 
     for j in 1..10 {
         if j != 3 {
@@ -465,23 +1061,266 @@ fn test_multi_collision() {
     }
 }
 
+#[test]
+fn test_delete_after_collision_chain() {
+    // test that deletions use valid back-references after long collision chains were built on both sides,
+    // which moves the mappings of many buckets during insertions and backward shifts
+
+    for deletion_order in [[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11], [11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0], [5, 6, 4, 7, 3, 8, 2, 9, 1, 10, 0, 11]] {
+        let mut map = BiMap::with_test_hashers(DEFAULT_CAPACITY, DEFAULT_CAPACITY);
+
+        // the chains wrap around the end of the indices
+        for i in 0..12 {
+            map.insert(i * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 3), i * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 5));
+            assert_back_references(&map);
+            assert_invariants(&map);
+        }
+
+        for (deleted, &i) in deletion_order.iter().enumerate() {
+            if deleted % 2 == 0 {
+                assert_eq!(map.remove_left(&(i * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 3))), Some(i * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 5)));
+            } else {
+                assert_eq!(map.remove_right(&(i * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 5))), Some(i * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 3)));
+            }
+            assert_back_references(&map);
+            assert_probe_distances(&map);
+            assert_invariants(&map);
+            assert_eq!(map.len(), 12 - deleted - 1);
+
+            for &j in &deletion_order[deleted + 1..] {
+                assert_eq!(map.get_right(&(j * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 3))), Some(&(j * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 5))));
+                assert_eq!(map.get_left(&(j * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 5))), Some(&(j * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 3))));
+            }
+            for &j in &deletion_order[..=deleted] {
+                assert_eq!(map.get_right(&(j * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 3))), None);
+                assert_eq!(map.get_left(&(j * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 5))), None);
+            }
+        }
+
+        assert!(map.is_empty());
+    }
+}
+
+#[test]
+fn test_back_references_after_replacement() {
+    // test that back-references stay valid when inserts replace and collapse colliding mappings
+    let mut map = BiMap::with_test_hashers(DEFAULT_CAPACITY, DEFAULT_CAPACITY);
+
+    for i in 0..8 {
+        map.insert(i * DEFAULT_CAPACITY + 1, i * DEFAULT_CAPACITY + 2);
+    }
+    assert_invariants(&map);
+    assert_back_references(&map);
+
+    // replace right values with colliding right values
+    for i in 0..8 {
+        map.insert(i * DEFAULT_CAPACITY + 1, (i + 8) * DEFAULT_CAPACITY + 2);
+        assert_back_references(&map);
+        assert_invariants(&map);
+    }
+
+    // collapse pairs of mappings
+    for i in 0..4 {
+        map.insert(i * DEFAULT_CAPACITY + 1, (i + 12) * DEFAULT_CAPACITY + 2);
+        assert_back_references(&map);
+        assert_invariants(&map);
+    }
+    assert_eq!(map.len(), 4);
+
+    for i in 0..4 {
+        assert_eq!(map.get_right(&(i * DEFAULT_CAPACITY + 1)), Some(&((i + 12) * DEFAULT_CAPACITY + 2)));
+        assert_eq!(map.get_left(&((i + 12) * DEFAULT_CAPACITY + 2)), Some(&(i * DEFAULT_CAPACITY + 1)));
+    }
+
+    map.shrink_to_fit();
+    assert_invariants(&map);
+    assert_back_references(&map);
+
+    map.clear();
+    assert_invariants(&map);
+    assert_back_references(&map);
+}
+
+#[cfg(not(feature = "hashbrown-backend"))]
+#[test]
+fn test_probe_distances_wrap_around() {
+    // test that probe distances are maintained when chains wrap around the end of the indices and
+    // are deleted from the front, the middle, and the wrapped region
+    for deleted in [0, 4, 7] {
+        let mut map = BiMap::with_test_hashers(DEFAULT_CAPACITY, DEFAULT_CAPACITY);
+
+        for i in 0..8 {
+            map.insert(i * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 2), i * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 3));
+        }
+        assert_probe_distances(&map);
+
+        // verify the chain actually wraps around
+        assert!(map.left.slots[0].bucket().is_some());
+        assert!(map.right.slots[0].bucket().is_some());
+
+        map.remove_left(&(deleted * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 2)));
+        assert_probe_distances(&map);
+        assert_back_references(&map);
+        assert_invariants(&map);
+
+        for i in (0..8).filter(|&i| i != deleted) {
+            assert_eq!(map.get_right(&(i * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 2))), Some(&(i * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 3))));
+            assert_eq!(map.get_left(&(i * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 3))), Some(&(i * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 2))));
+        }
+        assert_eq!(map.get_right(&(deleted * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 2))), None);
+    }
+}
+
+#[cfg(not(feature = "hashbrown-backend"))]
+#[test]
+fn test_saturated_probe_distances() {
+    // test that chains longer than the largest storable probe distance are probed and deleted correctly
+    let chain_length = SATURATED_DISTANCE as usize + 45;
+    let mut map = BiMap::with_test_hashers(512, DEFAULT_CAPACITY);
+
+    for i in 0..chain_length {
+        map.insert(i * DEFAULT_CAPACITY + 1, i * DEFAULT_CAPACITY + 2);
+    }
+    assert_invariants(&map);
+    assert_probe_distances(&map);
+    assert!(map.left.distances.contains(&SATURATED_DISTANCE));
+
+    // absent values that collide with the chain
+    assert_eq!(map.get_right(&(chain_length * DEFAULT_CAPACITY + 1)), None);
+    assert_eq!(map.get_left(&(chain_length * DEFAULT_CAPACITY + 2)), None);
+    assert_eq!(map.get_right(&3), None);
+
+    // delete from the front, the middle, the saturated region and the end of the chain
+    let mut deleted = Vec::new();
+    for i in [0, 100, SATURATED_DISTANCE as usize + 10, 1, chain_length - 1, SATURATED_DISTANCE as usize] {
+        assert_eq!(map.remove_left(&(i * DEFAULT_CAPACITY + 1)), Some(i * DEFAULT_CAPACITY + 2));
+        deleted.push(i);
+        assert_probe_distances(&map);
+        assert_back_references(&map);
+        assert_invariants(&map);
+
+        for j in 0..chain_length {
+            if deleted.contains(&j) {
+                assert_eq!(map.get_right(&(j * DEFAULT_CAPACITY + 1)), None);
+                assert_eq!(map.get_left(&(j * DEFAULT_CAPACITY + 2)), None);
+            } else {
+                assert_eq!(map.get_right(&(j * DEFAULT_CAPACITY + 1)), Some(&(j * DEFAULT_CAPACITY + 2)));
+                assert_eq!(map.get_left(&(j * DEFAULT_CAPACITY + 2)), Some(&(j * DEFAULT_CAPACITY + 1)));
+            }
+        }
+    }
+    assert_invariants(&map);
+    assert_eq!(map.len(), chain_length - deleted.len());
+}
+
+#[cfg(not(feature = "hashbrown-backend"))]
+#[test]
+fn test_in_place_resize() {
+    // resizing in place must lay out the indices exactly like rebuilding them, also for chains with
+    // saturated probe distances, whose entries cannot be hashed during the placement
+    let chain_length = SATURATED_DISTANCE as usize + 45;
+    let mut maps = [ResizeStrategy::Rebuild, ResizeStrategy::InPlace].map(|strategy| {
+        let mut map = BiMap::with_test_hashers(DEFAULT_CAPACITY, DEFAULT_CAPACITY);
+        map.set_resize_strategy(strategy);
+        for i in 0..chain_length {
+            map.insert(i * DEFAULT_CAPACITY + 1, i * DEFAULT_CAPACITY + 2);
+            map.insert(i + 5000, i + 7000);
+        }
+        map
+    });
+    type Map = BiMap<usize, usize, CollidingBuildHasher, CollidingBuildHasher>;
+    for resize in [|map: &mut Map| map.reserve(3000), Map::shrink_to_fit, |map: &mut Map| map.shrink_to(2000)] {
+        for map in &mut maps {
+            resize(map);
+            assert_invariants(map);
+            assert_probe_distances(map);
+        }
+        let [rebuilt, in_place] = &maps;
+        assert!(in_place.left.distances.contains(&SATURATED_DISTANCE));
+        assert_eq!(in_place.left, rebuilt.left);
+        assert_eq!(in_place.right, rebuilt.right);
+        assert_eq!(in_place.longest_probe, rebuilt.longest_probe);
+    }
+
+    // shrinking into a small map and growing out of it again
+    let [_, in_place] = &mut maps;
+    in_place.clear();
+    in_place.shrink_to_fit();
+    for i in 0..100 {
+        in_place.insert(i, i);
+    }
+    assert_invariants(in_place);
+}
+
+#[cfg(not(feature = "hashbrown-backend"))]
+#[test]
+fn test_probe_distance_non_power_of_two_capacity() {
+    // the circular distance must not depend on the capacity dividing 2^64
+    assert_eq!(side::probe_distance(34, 34, 36), 0);
+    assert_eq!(side::probe_distance(34, 0, 36), 2);
+    assert_eq!(side::probe_distance(34, 20, 36), 22);
+    assert_eq!(side::probe_distance(35, 34, 36), 35);
+    assert_eq!(side::probe_distance(16, 22, 36), 6);
+}
+
+#[test]
+fn test_probe_distances_wrap_around_non_power_of_two_capacity() {
+    // a cluster that wraps around the end of indices of capacity 36 holds entries more than 20 slots
+    // away from their ideal index. A distance that is computed modulo 2^64 before reducing it by the
+    // capacity underestimates them, which makes probes for later entries give up too early
+    const CAPACITY: usize = 36;
+    let identity = SeededHasher { seed: 1, state: 0 };
+    let mut map = BiMap::with_hashers(CAPACITY, identity.clone(), identity);
+    assert_eq!(map.current_capacity(), CAPACITY);
+
+    let cluster = (0..24).map(|i| i * CAPACITY + 34).collect::<Vec<_>>();
+    for &value in &cluster {
+        map.insert(value, value + 1);
+    }
+    // this entry probes past the end of the cluster
+    map.insert(16, 17);
+    assert_eq!(map.current_capacity(), CAPACITY);
+    assert_probe_distances(&map);
+    assert_invariants(&map);
+
+    assert_eq!(map.get_right(&16), Some(&17));
+    assert_eq!(map.get_left(&17), Some(&16));
+    for &value in &cluster {
+        assert_eq!(map.get_right(&value), Some(&(value + 1)));
+        assert_eq!(map.get_left(&(value + 1)), Some(&value));
+    }
+    assert_eq!(map.get_right(&(24 * CAPACITY + 34)), None);
+
+    // shift the wrapped entries back, from the front of the cluster
+    for &value in &cluster[..4] {
+        assert_eq!(map.remove_left(&value), Some(value + 1));
+        assert_probe_distances(&map);
+        assert_back_references(&map);
+        assert_invariants(&map);
+    }
+    assert_eq!(map.get_right(&16), Some(&17));
+    assert_eq!(map.get_left(&17), Some(&16));
+}
+
 #[test]
 fn test_right_collision() {
     // test whether replacing works correctly when the right value has a collision
-    let mut map = BiMap::with_hashers(DEFAULT_CAPACITY, IdentityHasher::default(), IdentityHasher::default());
+    let mut map = BiMap::with_test_hashers(DEFAULT_CAPACITY, DEFAULT_CAPACITY);
 
     map.insert(1, 4);
     map.insert(2, DEFAULT_CAPACITY + 4);
     map.insert(1, DEFAULT_CAPACITY + 4);
+    assert_invariants(&map);
 
     assert_eq!(map.get_right(&1), Some(&(DEFAULT_CAPACITY + 4)));
     assert_eq!(map.get_left(&(DEFAULT_CAPACITY + 4)), Some(&1));
 
-    let mut map = BiMap::with_hashers(DEFAULT_CAPACITY, IdentityHasher::default(), IdentityHasher::default());
+    let mut map = BiMap::with_test_hashers(DEFAULT_CAPACITY, DEFAULT_CAPACITY);
 
     map.insert(1, 4);
     map.insert(2, DEFAULT_CAPACITY + 4);
     map.insert(2, 4);
+    assert_invariants(&map);
 
     assert_eq!(map.get_right(&2), Some(&4));
     assert_eq!(map.get_left(&4), Some(&2));
@@ -494,19 +1333,21 @@ fn test_move_bucket_during_replacement() {
     // mapping in the mapping vector, but the left mapping is. In that case the left mapping will
     // be moved to the place of the right mapping, which can confuse the insert operation.
 
-    let mut map = BiMap::default();
+    let mut map: BiMap<_, _> = BiMap::default();
 
     map.insert(1, 2);
     map.insert(3, 4);
+    assert_invariants(&map);
 
     // verify the data structure looks like the test expects, otherwise the test case is broken
-    assert_eq!(map.data[0], Bucket { left: 1, right: 2 });
-    assert_eq!(map.data[1], Bucket { left: 3, right: 4 });
+    assert_eq!((map.data.left(0), map.data.right(0)), (&1, &2));
+    assert_eq!((map.data.left(1), map.data.right(1)), (&3, &4));
 
     map.insert(3, 2);
+    assert_invariants(&map);
 
     // verify the data structure looks like the test expects, otherwise the test case is broken
-    assert_eq!(map.data[0], Bucket { left: 3, right: 2 });
+    assert_eq!((map.data.left(0), map.data.right(0)), (&3, &2));
 
     assert_eq!(map.get_right(&1), None);
     assert_eq!(map.get_right(&3), Some(&2));
@@ -515,188 +1356,3331 @@ fn test_move_bucket_during_replacement() {
 }
 
 #[test]
-fn test_clear() {
-    // test whether the map is cleared correctly
-    let mut map = BiMap::default();
-
-    map.insert(1, 2);
-    map.insert(3, 4);
-
-    map.clear();
-
-    assert!(map.is_empty());
-    assert_eq!(map.len(), 0);
-    assert_eq!(map.get_right(&1), None);
-    assert_eq!(map.get_left(&2), None);
-    assert_eq!(map.get_right(&3), None);
-    assert_eq!(map.get_left(&4), None);
+fn test_replacement_shifts_other_mapping() {
+    // test that an insert of two existing values stays consistent, if deleting the old mappings
+    // shifts the mappings of the other value. The values of each side form a single cluster, so
+    // every deletion shifts the rest of the cluster back, across the slots of the other bucket.
+    for offset in [1, DEFAULT_CAPACITY - 2] {
+        let lefts = [offset, DEFAULT_CAPACITY + offset, 2 * DEFAULT_CAPACITY + offset];
+        let rights = lefts.map(|left| left + 3 * DEFAULT_CAPACITY);
+
+        for (new_left, new_right) in [(1, 0), (0, 1), (2, 0), (0, 2), (2, 1), (1, 2)] {
+            let mut map = BiMap::with_test_hashers(DEFAULT_CAPACITY, DEFAULT_CAPACITY);
+            for (&left, &right) in lefts.iter().zip(&rights) {
+                map.insert(left, right);
+            }
+            assert_invariants(&map);
+
+            // verify the values collide like the test expects, otherwise the test case is broken
+            assert!(lefts.iter().all(|left| map.left.ideal_index(left) == offset));
+            assert!(rights.iter().all(|right| map.right.ideal_index(right) == offset));
+
+            let replaced = map.insert(lefts[new_left], rights[new_right]);
+            assert_invariants(&map);
+
+            assert_eq!(replaced, (Some(rights[new_left]), Some(lefts[new_right])));
+            assert_eq!(map.len(), 2);
+            assert_eq!(map.get_right(&lefts[new_left]), Some(&rights[new_right]));
+            assert_eq!(map.get_left(&rights[new_right]), Some(&lefts[new_left]));
+            assert_eq!(map.get_right(&lefts[new_right]), None);
+            assert_eq!(map.get_left(&rights[new_left]), None);
+
+            // the untouched pair is still reachable from both sides
+            let untouched = 3 - new_left - new_right;
+            assert_eq!(map.get_right(&lefts[untouched]), Some(&rights[untouched]));
+            assert_eq!(map.get_left(&rights[untouched]), Some(&lefts[untouched]));
+        }
+    }
 }
 
 #[test]
-fn test_growing() {
-    // test whether the map grows correctly
+fn test_insert_unique_unchecked() {
+    // test that unchecked insertions of unique pairs behave like regular insertions
     let mut map = BiMap::with_capacity(10);
 
-    let initial_capacity = map.current_capacity();
+    for i in 0..100 {
+        let (left, right) = map.insert_unique_unchecked(i, i + 1000);
+        assert_eq!((*left, *right), (i, i + 1000));
+    }
+    assert_invariants(&map);
 
-    // insert more than capacity elements
-    for i in 0..40 {
-        map.insert(i, i + 1);
+    assert_eq!(map.len(), 100);
+    for i in 0..100 {
+        assert_eq!(map.get_right(&i), Some(&(i + 1000)));
+        assert_eq!(map.get_left(&(i + 1000)), Some(&i));
     }
 
-    for i in 0..40 {
-        assert_eq!(map.get_right(&i), Some(&(i + 1)));
-        assert_eq!(map.get_left(&(i + 1)), Some(&i));
+    // colliding values must be placed at their correct probe positions
+    let mut map = BiMap::with_test_hashers(DEFAULT_CAPACITY, DEFAULT_CAPACITY);
+    for i in 0..10 {
+        map.insert_unique_unchecked(i * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 2), i * DEFAULT_CAPACITY + 3);
+        map.insert_unique_unchecked(i * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 1), i * DEFAULT_CAPACITY + 2);
     }
+    assert_invariants(&map);
+    assert_back_references(&map);
+    assert_probe_distances(&map);
 
-    assert!(map.current_capacity() > initial_capacity);
+    for i in 0..10 {
+        assert_eq!(map.get_right(&(i * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 2))), Some(&(i * DEFAULT_CAPACITY + 3)));
+        assert_eq!(map.get_right(&(i * DEFAULT_CAPACITY + (DEFAULT_CAPACITY - 1))), Some(&(i * DEFAULT_CAPACITY + 2)));
+    }
+
+    // the inserted pairs can be removed like any other pair
+    assert_eq!(map.remove_left(&(DEFAULT_CAPACITY - 2)), Some(3));
+    assert_eq!(map.remove_right(&2), Some(DEFAULT_CAPACITY - 1));
+    assert_eq!(map.len(), 18);
 }
 
 #[test]
-fn test_capacity() {
-    // test that the load factor is not exceeded when map is allocated with capacity
+#[cfg(debug_assertions)]
+#[should_panic(expected = "the right value is already in the map")]
+fn test_insert_unique_unchecked_duplicate() {
+    // test that duplicates are caught in debug builds
+    let mut map = BiMap::new();
+    map.insert_unique_unchecked(1, 2);
+    map.insert_unique_unchecked(3, 2);
+    assert_invariants(&map);
+}
 
-    for i in 0..1000 {
-        let map = BiMap::<u8, u8>::with_capacity(i);
-        assert!(map.can_fit(i), "Failed for capacity {}", i);
+#[test]
+fn test_small_map() {
+    // test that small maps find their pairs without indices and spill into the hashed
+    // representation and back
+    let mut map = BiMap::new();
+    assert!(map.is_small());
+    assert_eq!(map.current_capacity(), 0);
+
+    for i in 0..SMALL_MAP_CAPACITY {
+        assert_eq!(map.insert(i, i + 100), (None, None));
+    }
+    assert_invariants(&map);
+    assert!(map.is_small());
+    assert_eq!(map.insert(0, 100), (Some(100), Some(0)));
+    assert_eq!(map.try_insert(1, 200).map_err(OccupiedError::into_pair), Err((1, 200)));
+
+    // updates and collapsing replacements behave as in the hashed representation
+    assert_eq!(map.insert(0, 200), (Some(100), None));
+    assert_eq!(map.insert(300, 101), (None, Some(1)));
+    assert_eq!(map.insert(2, 103), (Some(102), Some(3)));
+    assert_eq!(map.len(), SMALL_MAP_CAPACITY - 1);
+    assert_eq!(map.get_right(&0), Some(&200));
+    assert_eq!(map.get_left(&101), Some(&300));
+    assert_eq!(map.get_right(&2), Some(&103));
+    assert!(!map.contains_left(&1));
+    assert!(!map.contains_left(&3));
+    assert!(!map.contains_right(&102));
+    assert!(map.is_small());
+
+    // crossing the threshold spills the map
+    map.insert(1000, 1000);
+    assert_invariants(&map);
+    assert!(map.is_small());
+    map.insert(1001, 1001);
+    assert_invariants(&map);
+    assert!(!map.is_small());
+    assert_back_references(&map);
+    assert_probe_distances(&map);
+
+    let before_spill = map.iter().map(|(left, right)| (*left, *right)).collect::<Vec<_>>();
+    for &(left, right) in &before_spill {
+        assert_eq!(map.get_right(&left), Some(&right));
+        assert_eq!(map.get_left(&right), Some(&left));
+    }
+
+    // removing pairs and shrinking turns the map back into a small map
+    assert_eq!(map.remove_left(&1000), Some(1000));
+    assert_eq!(map.remove_right(&1001), Some(1001));
+    let before_shrink = map.iter().map(|(left, right)| (*left, *right)).collect::<Vec<_>>();
+    map.shrink_to_fit();
+    assert_invariants(&map);
+    assert!(map.is_small());
+    assert_back_references(&map);
+    assert_eq!(map.iter().map(|(left, right)| (*left, *right)).collect::<Vec<_>>(), before_shrink);
+
+    assert_eq!(map.remove_left(&0), Some(200));
+    assert_eq!(map.remove_right(&101), Some(300));
+    assert_eq!(map.remove_left(&0), None);
+    assert_eq!(map.len(), SMALL_MAP_CAPACITY - 3);
+
+    // reserving beyond the small capacity allocates the indices
+    map.reserve(SMALL_MAP_CAPACITY);
+    assert_invariants(&map);
+    assert!(!map.is_small());
+    assert!(map.can_fit(SMALL_MAP_CAPACITY));
+    assert_back_references(&map);
+    assert_probe_distances(&map);
+    for (left, right) in before_shrink.into_iter().filter(|&(left, _)| left != 0 && left != 300) {
+        assert_eq!(map.get_right(&left), Some(&right));
+        assert_eq!(map.get_left(&right), Some(&left));
     }
 }
 
 #[test]
-fn test_reserve() {
-    // test that the map grows correctly when reserving space
-    let mut map = BiMap::<u8, u8>::with_capacity(100);
-    map.reserve(1000);
-    assert!(map.can_fit(1000));
+fn test_small_map_iteration_order() {
+    // test that small and hashed maps keep their pairs in the same order
+    let mut small = BiMap::new();
+    let mut hashed = BiMap::with_capacity(100);
+    assert!(small.is_small());
+    assert!(!hashed.is_small());
+
+    let operations = [(1, 2), (3, 4), (5, 6), (7, 8), (1, 8), (9, 4), (11, 12), (5, 2), (13, 14)];
+    for (left, right) in operations {
+        assert_eq!(small.insert(left, right), hashed.insert(left, right));
+        assert_eq!(small.iter().collect::<Vec<_>>(), hashed.iter().collect::<Vec<_>>());
+    }
 
-    let mut map = BiMap::<u8, u8>::with_capacity(200);
-    map.reserve(5000);
-    assert!(map.can_fit(5000));
+    assert_eq!(small.remove_left(&3), hashed.remove_left(&3));
+    assert_eq!(small.remove_right(&12), hashed.remove_right(&12));
+    assert_eq!(small.iter().collect::<Vec<_>>(), hashed.iter().collect::<Vec<_>>());
+    assert_eq!(small.drain().collect::<Vec<_>>(), hashed.drain().collect::<Vec<_>>());
+}
 
-    let mut map = BiMap::<u8, u8>::with_capacity(10);
-    map.reserve(11);
-    assert!(map.can_fit(11));
+#[cfg(not(feature = "hashbrown-backend"))]
+#[test]
+fn test_reseeding() {
+    // test that a map with a degenerate hasher reseeds itself and keeps answering all lookups
+    let mut map = BiMap::with_hashers(1024, SeededHasher { seed: 0, state: 0 }, SeededHasher { seed: 0, state: 0 });
+    map.enable_reseeding(16, || (SeededHasher { seed: 1, state: 0 }, SeededHasher { seed: 1, state: 0 }));
+    assert_invariants(&map);
+
+    // the last of these is probed 16 slots away from its ideal index, which is still tolerated
+    for i in 0..17u64 {
+        map.insert(i, i + 1000);
+    }
+    assert_invariants(&map);
+    assert_eq!(map.hasher_left().seed, 0);
+    assert_eq!(map.longest_probe, 16);
+
+    // the next insertion exceeds the limit
+    map.insert(17, 1017);
+    assert_invariants(&map);
+    assert_eq!(map.hasher_left().seed, 1);
+    assert_eq!(map.hasher_right().seed, 1);
+    assert_eq!(map.longest_probe, 0);
+    assert_eq!(map.current_capacity(), 1024);
+
+    for i in 18..500u64 {
+        map.insert(i, i + 1000);
+    }
+    map.remove_left(&3);
+    map.insert(3, 2000);
+    map.insert(4, 1005);
+    assert_invariants(&map);
+
+    assert_back_references(&map);
+    assert_probe_distances(&map);
+    assert_eq!(map.len(), 499);
+    for i in (0..500u64).filter(|&i| i != 5) {
+        let right = match i {
+            3 => 2000,
+            4 => 1005,
+            i => i + 1000,
+        };
+        assert_eq!(map.get_right(&i), Some(&right));
+        assert_eq!(map.get_left(&right), Some(&i));
+    }
+}
 
-    let mut map = BiMap::<u8, u8>::with_capacity(10);
-    map.reserve(0);
-    assert!(map.can_fit(10));
+#[cfg(not(feature = "hashbrown-backend"))]
+#[test]
+fn test_reseeding_exhausted() {
+    // test that hashers that collide under every seed do not cause a rebuild on every insertion
+    static RESEEDS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
 
-    let mut map = BiMap::<u8, u8>::with_capacity(1000);
-    for i in 0..200 {
+    let mut map = BiMap::with_hashers(1024, SeededHasher { seed: 0, state: 0 }, SeededHasher { seed: 0, state: 0 });
+    map.enable_reseeding(16, || {
+        RESEEDS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        (SeededHasher { seed: 0, state: 0 }, SeededHasher { seed: 0, state: 0 })
+    });
+
+    for i in 0..200u64 {
         map.insert(i, i);
     }
-    map.reserve(2000);
-    assert!(map.can_fit(2000));
-    for i in 0..200 {
+    assert_invariants(&map);
+    assert_eq!(RESEEDS.load(std::sync::atomic::Ordering::Relaxed), 1);
+
+    for i in 0..200u64 {
         assert_eq!(map.get_right(&i), Some(&i));
         assert_eq!(map.get_left(&i), Some(&i));
     }
+
+    // disabled reseeding leaves the hashers alone
+    map.disable_reseeding();
+    map.shrink_to_fit();
+    map.insert(200, 200);
+    assert_invariants(&map);
+    assert_eq!(RESEEDS.load(std::sync::atomic::Ordering::Relaxed), 1);
+    assert_probe_distances(&map);
 }
 
 #[test]
-fn test_shrink_to_fit() {
-    let mut map = BiMap::with_capacity(1000);
-    assert!(map.current_capacity() >= 1000);
+fn test_high_bits_hash_distribution() {
+    // test that hashes are reduced to an index without truncating them to usize first, which
+    // would map all of these hashes to index zero on 32-bit platforms
+    let capacity = 1009;
+    let ideal_indices = (0..500u64)
+        .map(|value| side::hash_to_index(&HighBitsHasher::default(), &value, capacity))
+        .collect::<std::collections::HashSet<_>>();
+    assert_eq!(ideal_indices.len(), 500);
+
+    let mut map = BiMap::with_hashers(capacity, HighBitsHasher::default(), HighBitsHasher::default());
+    for i in 0..500u64 {
+        map.insert(i, i + 500);
+    }
+    assert_invariants(&map);
+    // none of the hashes collide, so every pair sits at its ideal index
+    assert_eq!(map.longest_probe, 0);
+    assert_probe_distances(&map);
+
+    for i in 0..500u64 {
+        assert_eq!(map.get_right(&i), Some(&(i + 500)));
+        assert_eq!(map.get_left(&(i + 500)), Some(&i));
+    }
+}
 
-    for i in 0..100 {
-        map.insert(i, i);
+#[test]
+fn test_compact() {
+    // test that compacting a churned map keeps all pairs and stores them in left index order
+    let mut map = BiMap::with_test_hashers(DEFAULT_CAPACITY * 4, 100);
+    for i in 0..100u8 {
+        map.insert(i, 99 - i);
+    }
+    assert_invariants(&map);
+    for i in (0..100u8).step_by(3) {
+        map.remove_left(&i);
+    }
+    assert_invariants(&map);
+    for i in (0..100u8).step_by(6) {
+        map.insert(i, 99 - i);
+    }
+    assert_invariants(&map);
+    let mut expected = map.iter().map(|(left, right)| (*left, *right)).collect::<Vec<_>>();
+    let capacity = map.current_capacity();
+
+    map.compact();
+    assert_invariants(&map);
+    assert_back_references(&map);
+    assert_probe_distances(&map);
+    assert_eq!(map.current_capacity(), capacity);
+
+    let in_index_order = map.left.buckets()
+        .map(|bucket| *map.data.left(bucket))
+        .collect::<Vec<_>>();
+    assert_eq!(map.left_values().copied().collect::<Vec<_>>(), in_index_order);
+
+    let mut compacted = map.iter().map(|(left, right)| (*left, *right)).collect::<Vec<_>>();
+    expected.sort();
+    compacted.sort();
+    assert_eq!(compacted, expected);
+    for (left, right) in expected {
+        assert_eq!(map.get_right(&left), Some(&right));
+        assert_eq!(map.get_left(&right), Some(&left));
     }
 
-    map.shrink_to_fit();
+    // compacting a small map only releases memory
+    let mut map = BiMap::new();
+    map.insert(1, 2);
+    map.compact();
+    assert_invariants(&map);
+    assert_eq!(map.get_right(&1), Some(&2));
+}
 
-    for i in 0..100 {
-        assert_eq!(map.get_right(&i), Some(&i));
-        assert_eq!(map.get_left(&i), Some(&i));
+#[test]
+#[cfg(feature = "dense")]
+fn test_dense_right_values() {
+    // test that dense right values are found in their own slots, also after removals left holes
+    use crate::dense::DenseBiMap;
+
+    let bound = 100;
+    let mut map = DenseBiMap::new(bound);
+    for i in 0..bound as u32 {
+        assert_eq!(map.insert(i as u64 * 7, i), (None, None));
+    }
+    assert_invariants(map.as_bimap());
+    for i in 0..bound as u32 {
+        // tables have no slot of their own for each dense value
+        #[cfg(not(feature = "hashbrown-backend"))]
+        assert_eq!(map.find_bucket_direct(&i), Some(map.as_bimap().find_bucket_right(&i)));
+        assert_eq!(map.get_left(&i), Some(&(i as u64 * 7)));
+        assert_eq!(map.get_right(&(i as u64 * 7)), Some(&i));
     }
 
-    assert!(map.current_capacity() < 1000);
+    // punch holes into the dense slots
+    for i in (0..bound as u32).step_by(3) {
+        assert_eq!(map.remove_right(&i), Some(i as u64 * 7));
+        assert_eq!(map.remove_right(&i), None);
+    }
+    assert_invariants(map.as_bimap());
+    for i in 0..bound as u32 {
+        let expected = (i % 3 != 0).then_some(i as u64 * 7);
+        #[cfg(not(feature = "hashbrown-backend"))]
+        assert_eq!(map.find_bucket_direct(&i), Some(map.as_bimap().find_bucket_right(&i)));
+        assert_eq!(map.get_left(&i).copied(), expected);
+        assert_eq!(map.contains_right(&i), expected.is_some());
+    }
+
+    // refill some holes with other left values, and add values above the bound, which are hashed
+    // into the same index and may displace values below the bound
+    for i in (0..bound as u32).step_by(6) {
+        assert_eq!(map.insert(i as u64 + 1000, i), (None, None));
+    }
+    assert_invariants(map.as_bimap());
+    let capacity = map.as_bimap().current_capacity() as u32;
+    for i in 0..20 {
+        assert_eq!(map.insert(i as u64 + 5000, capacity + i * 3), (None, None));
+    }
+    assert_invariants(map.as_bimap());
+    assert_probe_distances(map.as_bimap());
+    assert_back_references(map.as_bimap());
+
+    for i in 0..bound as u32 {
+        let expected = match i {
+            i if i % 6 == 0 => Some(i as u64 + 1000),
+            i if i % 3 == 0 => None,
+            i => Some(i as u64 * 7),
+        };
+        assert_eq!(map.get_left(&i).copied(), expected);
+    }
+    for i in 0..20 {
+        assert_eq!(map.get_left(&(capacity + i * 3)), Some(&(i as u64 + 5000)));
+        assert_eq!(map.get_right(&(i as u64 + 5000)), Some(&(capacity + i * 3)));
+    }
+
+    for i in 0..20 {
+        assert_eq!(map.remove_right(&(capacity + i * 3)), Some(i as u64 + 5000));
+    }
+    assert_invariants(map.as_bimap());
+    for i in (0..bound as u32).filter(|i| i % 3 != 0 || i % 6 == 0) {
+        assert!(map.remove_right(&i).is_some());
+    }
+    assert_invariants(map.as_bimap());
+    assert!(map.is_empty());
 }
 
 #[test]
-fn test_shrink_to() {
+#[cfg(feature = "dense")]
+fn test_dense_remove_right_notifies() {
+    // test that removing a dense right value notifies the remove hook and is recorded in the journal
+    use crate::dense::DenseBiMap;
+
+    let mut map = DenseBiMap::new(100);
+    for i in 0..50u32 {
+        map.insert(i as u64 * 7, i);
+    }
+    let removed = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorded = removed.clone();
+    map.as_bimap_mut().set_on_remove(Some(Box::new(move |&left, &right| recorded.lock().unwrap().push((left, right)))));
+    map.as_bimap_mut().enable_journal();
+
+    assert_eq!(map.remove_right(&3), Some(21));
+    assert_eq!(map.remove_right(&3), None);
+    assert_eq!(*removed.lock().unwrap(), vec![(21, 3)]);
+    assert_invariants(map.as_bimap());
+
+    assert!(map.as_bimap_mut().undo());
+    assert_eq!(map.get_left(&3), Some(&21));
+    assert_eq!(map.len(), 50);
+    assert_invariants(map.as_bimap());
+}
+
+#[test]
+#[cfg(feature = "lru")]
+fn test_lru_eviction_order() {
+    // test that inserts and lookups in both directions count as a use, while peeks do not
+    use crate::lru::LruBiMap;
+
+    let mut map = LruBiMap::new(3);
+    for i in 1..=3 {
+        assert_eq!(map.insert(i, i + 100), None);
+    }
+    assert_eq!(map.get_right(&1), Some(&101));
+    assert_eq!(map.get_left(&103), Some(&3));
+    assert_eq!(map.insert(4, 104), Some((2, 102)));
+    assert_eq!(map.get_right(&1), Some(&101));
+    assert_eq!(map.insert(5, 105), Some((3, 103)));
+    assert_eq!(map.iter().collect::<Vec<_>>(), [(&5, &105), (&1, &101), (&4, &104)]);
+    assert_eq!(map.peek_right(&4), Some(&104));
+    assert_eq!(map.peek_left(&104), Some(&4));
+    assert_eq!(map.insert(6, 106), Some((4, 104)));
+
+    // reinserting or replacing a value does not evict anything, but counts as a use
+    assert_eq!(map.insert(1, 101), None);
+    assert_eq!(map.insert(5, 205), None);
+    assert_eq!(map.insert(7, 106), None);
+    assert_eq!(map.iter().collect::<Vec<_>>(), [(&7, &106), (&5, &205), (&1, &101)]);
+
+    // a hashed map, where evictions move buckets into the holes of evicted pairs
+    let mut map = LruBiMap::new(40);
+    for i in 0..40 {
+        assert_eq!(map.insert(i, i + 1000), None);
+    }
+    for i in (0..40).step_by(2) {
+        assert_eq!(map.get_left(&(i + 1000)), Some(&i));
+    }
+    for i in 40..60 {
+        let oldest = (i - 40) * 2 + 1;
+        assert_eq!(map.insert(i, i + 1000), Some((oldest, oldest + 1000)));
+    }
+    assert_invariants(map.as_bimap());
+    let expected = (40..60).rev().chain((0..40).step_by(2).rev()).collect::<Vec<_>>();
+    assert_eq!(map.iter().map(|(&left, _)| left).collect::<Vec<_>>(), expected);
+    assert_eq!(map.len(), 40);
+}
+
+#[test]
+#[cfg(feature = "lru")]
+fn test_lru_model() {
+    // test random operations against a list of the pairs ordered by recency, with few distinct
+    // values so that insertions often replace and collapse pairs
+    use crate::lru::LruBiMap;
+
+    for capacity in [1, 5, 30] {
+        let mut map = LruBiMap::new(capacity);
+        let mut model: Vec<(u32, u32)> = Vec::new();
+        let mut state = 0x2545_f491_u64;
+        for _ in 0..5000 {
+            state = state.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+            let (operation, left, right) = ((state >> 60) as u32, (state >> 40) as u32 % 50, (state >> 20) as u32 % 50);
+            match operation {
+                0..=7 => {
+                    let replaced = model.iter().any(|&(l, r)| l == left || r == right);
+                    model.retain(|&(l, r)| l != left && r != right);
+                    let evicted = if !replaced && model.len() == capacity { model.pop() } else { None };
+                    model.insert(0, (left, right));
+                    assert_eq!(map.insert(left, right), evicted);
+                }
+                8..=10 => {
+                    let position = model.iter().position(|&(l, _)| l == left);
+                    let pair = position.map(|position| model.remove(position));
+                    model.splice(0..0, pair);
+                    assert_eq!(map.get_right(&left).copied(), pair.map(|(_, r)| r));
+                }
+                11..=13 => {
+                    let position = model.iter().position(|&(_, r)| r == right);
+                    let pair = position.map(|position| model.remove(position));
+                    model.splice(0..0, pair);
+                    assert_eq!(map.get_left(&right).copied(), pair.map(|(l, _)| l));
+                }
+                14 => {
+                    let position = model.iter().position(|&(l, _)| l == left);
+                    let pair = position.map(|position| model.remove(position));
+                    assert_eq!(map.remove_left(&left), pair.map(|(_, r)| r));
+                }
+                _ => assert_eq!(map.pop_oldest(), model.pop()),
+            }
+            assert_eq!(map.iter().map(|(&l, &r)| (l, r)).collect::<Vec<_>>(), model);
+        }
+        assert_invariants(map.as_bimap());
+    }
+}
+
+#[test]
+#[cfg(feature = "shared")]
+fn test_shared_snapshot() {
+    // test that mutating either side of a shared map copies the contents, and leaves the other side
+    // as it was at the time of sharing
+    use crate::shared::SharedBiMap;
+
+    for len in [5, 100] {
+        let mut map = SharedBiMap::new();
+        for i in 0..len {
+            map.insert(i, i + 1000);
+        }
+        let expected = map.make_mut().clone();
+
+        let snapshot = map.clone();
+        assert!(map.is_shared() && snapshot.is_shared());
+        assert!(std::ptr::eq(&*map, &*snapshot));
+
+        // removing missing values does not copy the contents
+        assert_eq!(map.remove_left(&len), None);
+        assert_eq!(map.remove_right(&0), None);
+        assert!(map.is_shared());
+
+        map.insert(0, 2000);
+        map.insert(len, 1001);
+        assert_eq!(map.remove_left(&2), Some(1002));
+        assert!(!map.is_shared() && !snapshot.is_shared());
+        assert_invariants(&map);
+        assert_invariants(&snapshot);
+        assert_same_pairs(&snapshot, &expected);
+        assert_eq!(map.len(), len - 1);
+        assert_eq!(map.get_right(&0), Some(&2000));
+        assert_eq!(map.get_left(&1001), Some(&len));
+
+        // the snapshot can be mutated as well, without affecting the map
+        let mut snapshot = snapshot;
+        let copied = map.clone();
+        snapshot.insert(0, 3000);
+        assert_eq!(snapshot.get_right(&0), Some(&3000));
+        assert_eq!(map.get_right(&0), Some(&2000));
+        assert_same_pairs(&copied.into_inner(), map.make_mut());
+    }
+}
+
+#[test]
+fn test_negative_filter() {
+    // test random operations on a map with the filter against a std map, and check that the filter
+    // never rejects a value that is in the map
+    let mut map = BiMap::new();
+    map.enable_negative_filter();
+    assert_invariants(&map);
+    let mut expected = std::collections::HashMap::new();
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    for _ in 0..20_000 {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let left = (state % 512) as u32;
+        let right = ((state >> 16) % 512) as u32;
+
+        match (state >> 32) % 4 {
+            0 | 1 => {
+                map.insert(left, right);
+                expected.retain(|&key, &mut value| key != left && value != right);
+                expected.insert(left, right);
+            }
+            2 => assert_eq!(map.remove_left(&left), expected.remove(&left)),
+            _ => {
+                let old_left = expected.iter().find(|(_, &value)| value == right).map(|(&key, _)| key);
+                if let Some(old_left) = old_left {
+                    expected.remove(&old_left);
+                }
+                assert_eq!(map.remove_right(&right), old_left);
+            }
+        }
+
+        assert_eq!(map.len(), expected.len());
+        assert_eq!(map.get_right(&left), expected.get(&left));
+        assert_eq!(map.contains_right(&right), expected.values().any(|&value| value == right));
+        // small maps do not consult the filter
+        if let (Some(filter), false) = (&map.filter, map.is_small()) {
+            for (left, right) in map.iter() {
+                assert!(filter.left.may_contain(map.left.hasher.hash_one(left)));
+                assert!(filter.right.may_contain(map.right.hasher.hash_one(right)));
+            }
+        }
+    }
+    assert_invariants(&map);
+
+    for (left, right) in &expected {
+        assert_eq!(map.get_right(left), Some(right));
+        assert_eq!(map.get_left(right), Some(left));
+    }
+}
+
+#[test]
+fn test_negative_filter_rejects_misses() {
+    // test that the filter answers most misses, and that removed values are dropped on a rebuild
     let mut map = BiMap::with_capacity(2000);
-    assert!(map.current_capacity() >= 2000);
+    map.enable_negative_filter();
+    assert_invariants(&map);
+    for i in 0..1000u32 {
+        map.insert(i, i);
+    }
+    assert_invariants(&map);
 
-    for i in 0..100 {
+    let filter = map.filter.as_ref().unwrap();
+    let passed = (1000..11000u32).filter(|i| filter.left.may_contain(map.left.hasher.hash_one(i))).count();
+    assert!(passed < 500, "{} of 10000 misses passed the filter", passed);
+    assert!(!map.contains_left(&1000));
+
+    for i in 0..1000u32 {
+        map.remove_left(&i);
+    }
+    assert_invariants(&map);
+    let filter = map.filter.as_ref().unwrap();
+    assert!(filter.stale <= map.len().max(SMALL_MAP_CAPACITY));
+    assert!((0..1000u32).filter(|i| filter.left.may_contain(map.left.hasher.hash_one(i))).count() < 50);
+
+    // clearing the map clears the filter, and disabling it keeps lookups working
+    map.insert(1, 1);
+    map.clear();
+    assert_invariants(&map);
+    assert!(!map.contains_left(&1));
+    map.insert(2, 2);
+    assert_invariants(&map);
+    map.disable_negative_filter();
+    assert_eq!(map.get_right(&2), Some(&2));
+}
+
+#[cfg(not(feature = "hashbrown-backend"))]
+#[test]
+fn test_slot_index() {
+    // test that slots round-trip bucket indices, including the first bucket
+    assert_eq!(SlotIndex::EMPTY.bucket(), None);
+    for bucket in [0, 1, 1000, usize::MAX - 1] {
+        assert_eq!(SlotIndex::new(bucket).bucket(), Some(bucket));
+        assert_eq!(SlotIndex::new(bucket).occupied(), bucket);
+    }
+}
+
+#[test]
+fn test_const_constructed_static_map() {
+    // test that a map in a static starts out without allocations and grows on the first insertions
+    use std::hash::{BuildHasherDefault, DefaultHasher};
+    use std::sync::Mutex;
+
+    type Hashers = BuildHasherDefault<DefaultHasher>;
+    static LOOKUP: Mutex<BiMap<&'static str, u16, Hashers, Hashers>> = Mutex::new(BiMap::new_with_hashers(BuildHasherDefault::new(), BuildHasherDefault::new()));
+    const NAMES: [&str; 12] = ["alpha", "beta", "gamma", "delta", "epsilon", "zeta", "eta", "theta", "iota", "kappa", "lambda", "mu"];
+
+    assert_eq!(LOOKUP.lock().unwrap().allocated_bytes(), 0);
+    assert_eq!(LOOKUP.lock().unwrap().get_right(&"alpha"), None);
+    for (id, name) in NAMES.into_iter().enumerate() {
+        LOOKUP.lock().unwrap().insert(name, id as u16);
+    }
+
+    let map = LOOKUP.lock().unwrap();
+    assert_invariants(&map);
+    assert_eq!(map.len(), NAMES.len());
+    for (id, name) in NAMES.into_iter().enumerate() {
+        assert_eq!(map.get_right(&name), Some(&(id as u16)));
+        assert_eq!(map.get_left(&(id as u16)), Some(&name));
+    }
+}
+
+#[test]
+fn test_from_columns() {
+    // test that matching columns are paired by position, in both the strict and the last-wins variant
+    for len in [5, 1000] {
+        let lefts = (0..len).collect::<Vec<_>>();
+        let rights = (0..len).map(|i| i.to_string()).collect::<Vec<_>>();
+        let map = BiMap::from_columns(lefts.clone(), rights.clone()).unwrap();
+        assert_invariants(&map);
+        assert_eq!(map.len(), len);
+        assert!(map.iter().eq(lefts.iter().zip(rights.iter())));
+        assert_same_pairs(&BiMap::from_columns_last_wins(lefts, rights).unwrap(), &map);
+    }
+
+    assert_eq!(BiMap::from_columns(vec![1, 2, 3], vec![1, 2]).unwrap_err(), FromColumnsError::LengthMismatch { lefts: 3, rights: 2 });
+    assert_eq!(BiMap::from_columns_last_wins(vec![1], vec![1, 2]).unwrap_err(), FromColumnsError::LengthMismatch { lefts: 1, rights: 2 });
+
+    // duplicates are reported with the rows of both occurrences, or replace the earlier pairs
+    let lefts = vec![1, 2, 3, 2, 5];
+    let rights = vec![10, 20, 30, 40, 50];
+    assert_eq!(BiMap::from_columns(lefts.clone(), rights.clone()).unwrap_err(), FromColumnsError::DuplicateLeft { first: 1, duplicate: 3 });
+    let map = BiMap::from_columns_last_wins(lefts, rights).unwrap();
+    assert_eq!(map.len(), 4);
+    assert_eq!(map.get_right(&2), Some(&40));
+    assert_eq!(map.get_left(&20), None);
+
+    let lefts = vec![1, 2, 3, 4, 5];
+    let rights = vec![10, 20, 30, 40, 30];
+    assert_eq!(BiMap::from_columns(lefts.clone(), rights.clone()).unwrap_err(), FromColumnsError::DuplicateRight { first: 2, duplicate: 4 });
+    let map = BiMap::from_columns_last_wins(lefts, rights).unwrap();
+    assert_eq!(map.len(), 4);
+    assert_eq!(map.get_left(&30), Some(&5));
+    assert_eq!(map.get_right(&3), None);
+}
+
+#[test]
+fn test_insert_within_capacity() {
+    // test that a reserved map is filled to its capacity without reallocating, and then rejects
+    // new pairs while still accepting replacements
+    for capacity in [5, 1000] {
+        let mut map = BiMap::with_capacity(capacity);
+        let allocated = map.allocated_bytes();
+        let mut len = 0;
+        while map.insert_within_capacity(len, len.to_string()).is_ok() {
+            len += 1;
+        }
+        assert!(len >= capacity, "only {} of {} pairs fit", len, capacity);
+        assert_eq!(map.len(), len);
+        assert_eq!(map.insert_within_capacity(len, len.to_string()), Err((len, len.to_string())));
+        assert_eq!(map.insert_within_capacity(0, "zero".to_string()), Ok((Some("0".to_string()), None)));
+        assert_eq!(map.insert_within_capacity(1, "2".to_string()), Ok((Some("1".to_string()), Some(2))));
+        assert_eq!(map.insert_within_capacity(len, len.to_string()), Ok((None, None)));
+        assert_eq!(map.allocated_bytes(), allocated);
+        assert_invariants(&map);
+    }
+}
+
+#[test]
+fn test_allocated_bytes() {
+    // 1000 pairs of 16 bytes, two back-references and 1112 slots with a distance byte per side
+    let word = mem::size_of::<usize>();
+    let expected = 1000 * 16 + 1000 * 2 * word + 2 * 1112 * (word + 1);
+    if word == 8 {
+        assert_eq!(expected, 52016);
+    }
+    // the tables of the hashbrown backend have their own layout
+    #[cfg(feature = "hashbrown-backend")]
+    let expected = 1000 * 16 + 1000 * 2 * word + 2 * IndexSide::<RandomState>::bytes_for(1112);
+
+    let mut map = BiMap::<u64, u64>::with_capacity(1000);
+    assert_eq!(map.current_capacity(), 1112);
+    assert_eq!(map.allocated_bytes(), expected);
+    assert_eq!(BiMap::<u64, u64>::estimated_bytes_for(1000), expected);
+
+    // inserting within the capacity does not allocate. The filters take two bytes per slot and
+    // side, rounded up to a power of two words
+    for i in 0..1000 {
         map.insert(i, i);
     }
+    assert_invariants(&map);
+    assert_eq!(map.allocated_bytes(), expected);
+    map.enable_negative_filter();
+    assert_invariants(&map);
+    assert_eq!(map.allocated_bytes(), expected + 2 * 512 * 8);
+
+    // small maps only allocate their pairs
+    assert_eq!(BiMap::<u64, u64>::new().allocated_bytes(), SMALL_MAP_CAPACITY * 16);
+    assert_eq!(BiMap::<u64, u64>::estimated_bytes_for(SMALL_MAP_CAPACITY), SMALL_MAP_CAPACITY * 16);
+}
 
-    map.shrink_to(500);
+#[cfg(not(feature = "hashbrown-backend"))]
+#[test]
+fn test_debug_table() {
+    // test the dump of a tiny map in which two left values collide
+    let mut map = BiMap::with_test_hashers(10, DEFAULT_CAPACITY);
+    map.insert(1u8, 1u8);
+    map.insert(11, 2);
+    map.insert(3, 3);
+    assert_invariants(&map);
+
+    let capacity = map.current_capacity();
+    let mut expected = String::from("left index:\n");
+    for index in 0..capacity {
+        match index {
+            1 => expected.push_str("  slot 1 -> bucket 0 (ideal 1, dist 0)\n"),
+            2 => expected.push_str("  slot 2 -> bucket 1 (ideal 1, dist 1)\n"),
+            3 => expected.push_str("  slot 3 -> bucket 2 (ideal 3, dist 0)\n"),
+            _ => expected.push_str(&format!("  slot {} empty\n", index)),
+        }
+    }
+    expected.push_str("right index:\n");
+    for index in 0..capacity {
+        match index {
+            1..=3 => expected.push_str(&format!("  slot {} -> bucket {} (ideal {}, dist 0)\n", index, index - 1, index)),
+            _ => expected.push_str(&format!("  slot {} empty\n", index)),
+        }
+    }
+    assert_eq!(map.debug_table().to_string(), expected);
+
+    let side = format!(".###{}", ".".repeat(capacity - 4));
+    assert_eq!(map.debug_table().occupancy(), format!("left {} right {}", side, side));
+
+    // small maps have no indices to dump
+    let mut map = BiMap::new();
+    map.insert(1, 1);
+    assert_invariants(&map);
+    assert_eq!(map.debug_table().to_string(), "small map of length 1");
+    assert_eq!(map.debug_table().occupancy(), "left  right ");
+}
+
+#[test]
+fn test_debug_format() {
+    let mut map = BiMap::new();
+    assert_eq!(format!("{:?}", map), "{}");
+    map.insert(1, "a");
+    map.insert(2, "b");
+    assert_eq!(format!("{:?}", map), r#"{1 <-> "a", 2 <-> "b"}"#);
+    assert_eq!(format!("{:#?}", map), "{\n    1 <-> \"a\",\n    2 <-> \"b\",\n}");
+
+    // the hashers do not need to implement Debug, and are not printed
+    struct OpaqueHasher;
+    impl BuildHasher for OpaqueHasher {
+        type Hasher = std::hash::DefaultHasher;
+
+        fn build_hasher(&self) -> Self::Hasher {
+            std::hash::DefaultHasher::new()
+        }
+    }
+    let mut map = BiMap::with_hashers(0, OpaqueHasher, OpaqueHasher);
+    for i in 0..20 {
+        map.insert(i, i * 2);
+    }
+    let expected = map.iter().map(|(left, right)| format!("{} <-> {}", left, right)).collect::<Vec<_>>().join(", ");
+    assert_eq!(format!("{:?}", map), format!("{{{}}}", expected));
+}
+
+#[test]
+fn test_content_equality() {
+    // the same pairs inserted in different orders compare equal
+    let forward: BiMap<u32, u32> = (0..100).map(|i| (i, i * 2)).collect();
+    let backward: BiMap<u32, u32> = (0..100).rev().map(|i| (i, i * 2)).collect();
+    assert_eq!(forward, backward);
+    assert_ne!(forward.iter().collect::<Vec<_>>(), backward.iter().collect::<Vec<_>>());
+
+    // removing and reinserting pairs changes the order and the capacity, but not the contents
+    let mut cycled = forward.clone();
+    for i in (0..100).step_by(3) {
+        cycled.remove_left(&i);
+    }
+    cycled.shrink_to_fit();
+    for i in (0..100).step_by(3) {
+        cycled.insert(i, i * 2);
+    }
+    assert_eq!(cycled, forward);
 
+    // maps with different hashers are compared by their pairs as well
+    let mut seeded = BiMap::with_hashers(0, SeededBuildHasher::new(7), SeededBuildHasher::new(11));
     for i in 0..100 {
-        assert_eq!(map.get_right(&i), Some(&i));
-        assert_eq!(map.get_left(&i), Some(&i));
+        seeded.insert(i, i * 2);
     }
+    assert!(seeded == forward);
+    assert!(forward == seeded);
+
+    // the same left values with a different right value, and fewer pairs, are not equal
+    let mut changed = forward.clone();
+    changed.insert(5, 1000);
+    assert_ne!(changed, forward);
+    let mut shorter = forward.clone();
+    shorter.remove_left(&5);
+    assert_ne!(shorter, forward);
+    assert_ne!(forward, shorter);
+
+    assert_eq!(BiMap::<u32, u32>::new(), BiMap::with_capacity(100));
+}
 
-    assert!(map.current_capacity() >= 500 && map.current_capacity() < 2000);
+#[test]
+fn test_equality_with_hashmap() {
+    use std::collections::HashMap;
+
+    let map: BiMap<u32, u32> = (0..50).map(|i| (i, i * 2)).collect();
+    let mut forward: HashMap<u32, u32> = (0..50).rev().map(|i| (i, i * 2)).collect();
+    assert_eq!(map, forward);
+    assert_eq!(forward, map);
+
+    // a different right value
+    forward.insert(7, 1000);
+    assert_ne!(map, forward);
+    assert_ne!(forward, map);
+    forward.insert(7, 14);
+
+    // an extra entry, and a missing entry
+    forward.insert(50, 100);
+    assert_ne!(map, forward);
+    assert_ne!(forward, map);
+    forward.remove(&50);
+    forward.remove(&0);
+    assert_ne!(map, forward);
+    forward.insert(0, 0);
+    assert_eq!(map, forward);
+
+    // a HashMap with a duplicate value never equals a map
+    let duplicate = HashMap::from([(1, 1), (2, 1)]);
+    assert_ne!(crate::bimap![1 => 1, 2 => 2], duplicate);
+    assert_ne!(crate::bimap![1 => 1], duplicate);
+}
 
-    map.shrink_to(10);
+#[test]
+fn test_hash_ignores_order() {
+    fn hash_of<T: Hash>(value: &T) -> u64 {
+        let mut hasher = std::hash::DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
 
-    for i in 0..100 {
-        assert_eq!(map.get_right(&i), Some(&i));
-        assert_eq!(map.get_left(&i), Some(&i));
+    let forward: BiMap<u32, u32> = (0..100).map(|i| (i, i * 2)).collect();
+    let mut cycled: BiMap<u32, u32> = (0..100).rev().map(|i| (i, i * 2)).collect();
+    for i in (0..100).step_by(7) {
+        cycled.remove_right(&(i * 2));
+    }
+    for i in (0..100).step_by(7) {
+        cycled.insert(i, i * 2);
     }
+    assert_eq!(cycled, forward);
+    assert_eq!(hash_of(&cycled), hash_of(&forward));
+
+    // maps can be used as keys of other maps, the hooks behind their mutexes are not hashed
+    #[allow(clippy::mutable_key_type)]
+    let mut memo = std::collections::HashMap::new();
+    memo.insert(forward.clone(), "forward");
+    assert_eq!(memo.get(&cycled), Some(&"forward"));
+
+    cycled.insert(3, 1000);
+    assert_ne!(hash_of(&cycled), hash_of(&forward));
+    assert_eq!(memo.get(&cycled), None);
+    assert_ne!(hash_of(&BiMap::<u32, u32>::new()), hash_of(&crate::bimap![0 => 0]));
+}
 
-    assert!(map.current_capacity() < 500);
+#[test]
+fn test_index_by_side() {
+    let mut map = crate::bimap![1 => 2, 2 => 3, 3 => 1];
+    assert_eq!(map[Left(&1)], 2);
+    assert_eq!(map[Right(&1)], 3);
+    assert_eq!(map[Right(&map[Left(&2)])], 2);
+
+    map.insert(1, 4);
+    assert_eq!(map[Left(&1)], 4);
+    assert_eq!(map[Right(&4)], 1);
+
+    let strings = crate::bimap!["three" => 3, "foo" => 4];
+    assert_eq!(strings[Right(&4)], "foo");
+    assert_eq!(strings[Left(&"three")], 3);
 }
 
 #[test]
-fn test_iter() {
-    // test that the iterator returns all elements
-    let mut map = BiMap::default();
+#[should_panic(expected = "the left value is not in the map")]
+fn test_index_missing_left() {
+    let map = crate::bimap![1 => 2];
+    let _ = map[Left(&2)];
+}
 
-    map.insert(1, 2);
-    map.insert(3, 4);
-    map.insert(5, 6);
-    map.insert(7, 8);
-    map.insert(1, 8);
+#[test]
+#[should_panic(expected = "the right value is not in the map")]
+fn test_index_missing_right() {
+    let map = crate::bimap![1 => 2];
+    let _ = map[Right(&1)];
+}
 
-    let from_iter = map.iter().collect::<Vec<_>>();
+#[test]
+fn test_resize_hook() {
+    // test that the hook fires for growing, reserving and shrinking, but not for other insertions
+    let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorded = events.clone();
+    let mut map = BiMap::new();
+    map.set_resize_hook(Some(Box::new(move |event| recorded.lock().unwrap().push(event))));
+
+    for i in 0..SMALL_MAP_CAPACITY {
+        map.insert(i, i);
+    }
+    // shrinking a small map keeps it without indices
+    map.shrink_to_fit();
+    map.shrink_to(0);
+    assert_invariants(&map);
+    assert!(events.lock().unwrap().is_empty());
+
+    map.insert(SMALL_MAP_CAPACITY, SMALL_MAP_CAPACITY);
+    assert_invariants(&map);
+    let grown = map.current_capacity();
+    assert_eq!(events.lock().unwrap().as_slice(), &[
+        ResizeEvent { old_capacity: 0, new_capacity: grown, len: SMALL_MAP_CAPACITY, reason: ResizeReason::Growth },
+    ]);
+
+    // inserting and updating within the capacity does not resize
+    map.insert(0, 100);
+    map.insert(100, 1);
+    assert_invariants(&map);
+    assert_eq!(events.lock().unwrap().len(), 1);
 
-    assert_eq!(from_iter.len(), 3);
-    assert!(from_iter.contains(&(&1, &8)));
-    assert!(from_iter.contains(&(&3, &4)));
-    assert!(from_iter.contains(&(&5, &6)));
+    map.reserve(1000);
+    assert_invariants(&map);
+    let reserved = map.current_capacity();
+    map.shrink_to_fit();
+    assert_invariants(&map);
+    assert_eq!(events.lock().unwrap()[1..], [
+        ResizeEvent { old_capacity: grown, new_capacity: reserved, len: map.len(), reason: ResizeReason::Reserve },
+        ResizeEvent { old_capacity: reserved, new_capacity: map.current_capacity(), len: map.len(), reason: ResizeReason::Shrink },
+    ]);
 
-    let from_iter = map.left_values().collect::<Vec<_>>();
+    // shrinking again keeps the capacity
+    map.shrink_to_fit();
+    assert_eq!(events.lock().unwrap().len(), 3);
 
-    assert_eq!(from_iter.len(), 3);
-    assert!(from_iter.contains(&&1));
-    assert!(from_iter.contains(&&3));
-    assert!(from_iter.contains(&&5));
+    // clones do not inherit the hook, and removing it stops the notifications
+    map.clone().reserve(2000);
+    map.set_resize_hook(None);
+    map.reserve(2000);
+    assert_invariants(&map);
+    assert_eq!(events.lock().unwrap().len(), 3);
 
-    let from_iter = map.right_values().collect::<Vec<_>>();
+    fn assert_sync<M: Sync>(_: &M) {}
+    assert_sync(&map);
+}
 
-    assert_eq!(from_iter.len(), 3);
-    assert!(from_iter.contains(&&4));
-    assert!(from_iter.contains(&&6));
-    assert!(from_iter.contains(&&8));
+/// A notification of the insert or remove hook of a map.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Notification {
+    Insert(usize, usize),
+    Remove(usize, usize),
+}
+
+/// Set hooks that record the notifications of a map.
+fn record_notifications(map: &mut BiMap<usize, usize>) -> std::sync::Arc<std::sync::Mutex<Vec<Notification>>> {
+    let notifications = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let inserted = notifications.clone();
+    let removed = notifications.clone();
+    map.set_on_insert(Some(Box::new(move |&left, &right| inserted.lock().unwrap().push(Notification::Insert(left, right)))));
+    map.set_on_remove(Some(Box::new(move |&left, &right| removed.lock().unwrap().push(Notification::Remove(left, right)))));
+    notifications
+}
+
+/// Assert that replaying notifications into a set of pairs reconstructs the pairs of the map.
+fn assert_reconstructs(notifications: &[Notification], map: &BiMap<usize, usize>) {
+    let mut mirror = std::collections::HashSet::new();
+    for &notification in notifications {
+        match notification {
+            Notification::Insert(left, right) => assert!(mirror.insert((left, right)), "{:?} is in the mirror already", notification),
+            Notification::Remove(left, right) => assert!(mirror.remove(&(left, right)), "{:?} is not in the mirror", notification),
+        }
+    }
+    assert_eq!(mirror, map.iter().map(|(&left, &right)| (left, right)).collect());
 }
 
 #[test]
-pub fn test_drain() {
-    // test that the drain iterator returns all elements
-    let mut map = BiMap::default();
+fn test_observer_hooks() {
+    // test that a scripted sequence of operations produces the documented notifications, in both representations
+    use Notification::{Insert, Remove};
+    for len in [4, 100] {
+        let mut map = BiMap::new();
+        for i in 0..len {
+            map.insert(i, i + 1000);
+        }
+        let notifications = record_notifications(&mut map);
+
+        map.insert(5000, 6000);
+        map.insert(1, 7000);
+        map.insert(8000, 1002);
+        map.insert(3, 3 + 1000);
+        map.insert(0, 1003);
+        map.remove_left(&1);
+        map.remove_right(&6000);
+        map.remove_left(&1);
+        let _ = map.try_insert(9000, 9001);
+        let _ = map.try_insert(9000, 9002);
+        map.insert_unique_unchecked(9100, 9101);
+        assert_eq!(notifications.lock().unwrap().as_slice(), &[
+            Insert(5000, 6000),
+            Remove(1, 1001), Insert(1, 7000),
+            Remove(2, 1002), Insert(8000, 1002),
+            Remove(0, 1000), Remove(3, 1003), Insert(0, 1003),
+            Remove(1, 7000),
+            Remove(5000, 6000),
+            Insert(9000, 9001),
+            Insert(9100, 9101),
+        ]);
+
+        let initial = (0..len).map(|i| Insert(i, i + 1000));
+        let stream = initial.chain(notifications.lock().unwrap().iter().copied()).collect::<Vec<_>>();
+        assert_reconstructs(&stream, &map);
+    }
+}
+
+#[test]
+fn test_observer_hooks_implicit_changes() {
+    // test that the mutable accessors, cursors, transactions, clear and drain keep a mirror in sync
+    let mut map = BiMap::new();
+    let notifications = record_notifications(&mut map);
+    for i in 0..100 {
+        map.insert(i, i + 1000);
+    }
+
+    *map.get_right_mut(&5).unwrap() = 5000;
+    assert_eq!(*map.get_right_mut(&6).unwrap(), 1006);
+    let mut left = map.get_left_mut(&1007).unwrap();
+    *left = 1;
+    assert!(left.commit().is_err());
+    let mut cursor = map.cursor_mut();
+    while let Some((&left, _)) = cursor.next() {
+        if left % 3 == 0 {
+            cursor.remove_current();
+        } else if left % 3 == 1 {
+            cursor.replace_right_current(left + 2000);
+        }
+    }
+    let _: Result<(), ()> = map.transaction(|txn| {
+        txn.insert(10_000, 10_001);
+        txn.insert(2, 2001);
+        txn.insert(4, 1008);
+        txn.remove_left(&11);
+        Err(())
+    });
+    assert_invariants(&map);
+    assert_reconstructs(&notifications.lock().unwrap(), &map);
+
+    let drained = map.drain().take(10).count();
+    assert_eq!(drained, 10);
+    assert_reconstructs(&notifications.lock().unwrap(), &map);
+
+    for i in 0..20 {
+        map.insert(i, i);
+    }
+    map.clear();
+    assert_reconstructs(&notifications.lock().unwrap(), &map);
+
+    // clones do not inherit the hooks, and removing them stops the notifications
+    let count = notifications.lock().unwrap().len();
+    map.clone().insert(1, 1);
+    map.set_on_insert(None);
+    map.set_on_remove(None);
+    map.insert(1, 1);
+    map.remove_left(&1);
+    assert_eq!(notifications.lock().unwrap().len(), count);
+}
+
+#[cfg(not(feature = "hashbrown-backend"))]
+#[test]
+fn test_check_invariants() {
+    // test that corrupted maps are reported with the slot or bucket involved
+    let build = || {
+        let mut map = BiMap::with_test_hashers(DEFAULT_CAPACITY, DEFAULT_CAPACITY);
+        map.insert(1, 1);
+        map.insert(DEFAULT_CAPACITY + 1, 2);
+        map.insert(3, 3);
+        map
+    };
+    assert_eq!(build().check_invariants(), Ok(()));
+
+    let mut map = build();
+    map.left.distances[2] = 0;
+    assert_eq!(map.check_invariants(), Err(InvariantViolation::ProbeDistance { side: Side::Left, slot: 2, stored: 0, expected: 1 }));
+
+    let mut map = build();
+    map.right.slots[3] = SlotIndex::new(7);
+    assert_eq!(map.check_invariants(), Err(InvariantViolation::DanglingSlot { side: Side::Right, slot: 3, bucket: 7 }));
+
+    let mut map = build();
+    map.left.slots[1] = SlotIndex::EMPTY;
+    assert_eq!(map.check_invariants(), Err(InvariantViolation::OccupiedSlots { side: Side::Left, occupied: 2, len: 3 }));
+
+    // an entry behind an empty slot cannot be reached by probing
+    let mut map = build();
+    map.left.slots.swap(1, 5);
+    map.left.distances[5] = 4;
+    map.left.back_references[0] = 5;
+    assert_eq!(map.check_invariants(), Err(InvariantViolation::RobinHoodOrder { side: Side::Left, slot: 2 }));
+
+    let mut map = build();
+    map.right.back_references[2] = 5;
+    assert_eq!(map.check_invariants(), Err(InvariantViolation::BackReference { side: Side::Right, bucket: 2, slot: 5 }));
+
+    let mut map = build();
+    map.left.back_references.pop();
+    assert_eq!(map.check_invariants(), Err(InvariantViolation::BackReferenceCount { back_references: 2, len: 3 }));
+
+    let error = map.check_invariants().unwrap_err();
+    assert_eq!(error.to_string(), "map keeps 2 back-references for 3 pairs");
+}
+
+#[test]
+fn test_clear() {
+    // test whether the map is cleared correctly
+    let mut map: BiMap<_, _> = BiMap::default();
 
     map.insert(1, 2);
     map.insert(3, 4);
-    map.insert(5, 6);
-    map.insert(7, 8);
-    map.insert(1, 8);
+    assert_invariants(&map);
 
-    let from_iter = map.drain().collect::<Vec<_>>();
-
-    assert_eq!(from_iter.len(), 3);
-    assert!(from_iter.contains(&(1, 8)));
-    assert!(from_iter.contains(&(3, 4)));
-    assert!(from_iter.contains(&(5, 6)));
+    map.clear();
+    assert_invariants(&map);
 
     assert!(map.is_empty());
+    assert_eq!(map.len(), 0);
+    assert_eq!(map.get_right(&1), None);
+    assert_eq!(map.get_left(&2), None);
+    assert_eq!(map.get_right(&3), None);
+    assert_eq!(map.get_left(&4), None);
+}
 
-    assert_eq!(map.get_left(&1), None);
-    assert_eq!(map.get_right(&8), None);
-    assert_eq!(map.get_left(&3), None);
-    assert_eq!(map.get_right(&4), None);
-    assert_eq!(map.get_left(&5), None);
-    assert_eq!(map.get_right(&6), None);
-}
\ No newline at end of file
+#[test]
+fn test_growing() {
+    // test whether the map grows correctly
+    let mut map = BiMap::with_capacity(10);
+
+    let initial_capacity = map.current_capacity();
+
+    // insert more than capacity elements
+    for i in 0..40 {
+        map.insert(i, i + 1);
+    }
+    assert_invariants(&map);
+
+    for i in 0..40 {
+        assert_eq!(map.get_right(&i), Some(&(i + 1)));
+        assert_eq!(map.get_left(&(i + 1)), Some(&i));
+    }
+
+    assert!(map.current_capacity() > initial_capacity);
+}
+
+#[test]
+fn test_capacity() {
+    // test that the load factor is not exceeded when map is allocated with capacity
+
+    for i in 0..1000 {
+        let map = BiMap::<u8, u8>::with_capacity(i);
+        assert!(map.can_fit(i), "Failed for capacity {}", i);
+    }
+}
+
+#[test]
+fn test_reserve() {
+    // test that the map grows correctly when reserving space
+    let mut map = BiMap::<u8, u8>::with_capacity(100);
+    map.reserve(1000);
+    assert_invariants(&map);
+    assert!(map.can_fit(1000));
+
+    let mut map = BiMap::<u8, u8>::with_capacity(200);
+    map.reserve(5000);
+    assert_invariants(&map);
+    assert!(map.can_fit(5000));
+
+    let mut map = BiMap::<u8, u8>::with_capacity(10);
+    map.reserve(11);
+    assert_invariants(&map);
+    assert!(map.can_fit(11));
+
+    let mut map = BiMap::<u8, u8>::with_capacity(10);
+    map.reserve(0);
+    assert_invariants(&map);
+    assert!(map.can_fit(10));
+
+    let mut map = BiMap::<u8, u8>::with_capacity(1000);
+    for i in 0..200 {
+        map.insert(i, i);
+    }
+    map.reserve(2000);
+    assert_invariants(&map);
+    assert!(map.can_fit(2000));
+    for i in 0..200 {
+        assert_eq!(map.get_right(&i), Some(&i));
+        assert_eq!(map.get_left(&i), Some(&i));
+    }
+}
+
+#[test]
+fn test_growth_threshold() {
+    // test that the growth threshold follows the capacity of the indices
+
+    let mut map = BiMap::with_capacity(1000);
+    assert_eq!(map.growth_threshold, BiMap::<usize, usize>::growth_threshold_for(map.current_capacity()));
+    assert!(map.growth_threshold >= 1000);
+
+    for i in 0..100 {
+        map.insert(i, i);
+    }
+    assert_invariants(&map);
+
+    map.shrink_to_fit();
+    assert_invariants(&map);
+    assert_eq!(map.growth_threshold, BiMap::<usize, usize>::growth_threshold_for(map.current_capacity()));
+    assert!(map.growth_threshold >= 100 && map.growth_threshold < 1000);
+    assert!(map.can_fit(0));
+    assert!(!map.can_fit(map.growth_threshold - 100 + 1));
+
+    // clearing keeps the allocation and therefore the threshold
+    let threshold = map.growth_threshold;
+    map.clear();
+    assert_invariants(&map);
+    assert_eq!(map.growth_threshold, threshold);
+    assert_eq!(map.growth_threshold, BiMap::<usize, usize>::growth_threshold_for(map.current_capacity()));
+    assert!(map.can_fit(threshold));
+    assert!(!map.can_fit(threshold + 1));
+
+    // shrinking an empty map
+    map.shrink_to_fit();
+    assert_invariants(&map);
+    assert_eq!(map.growth_threshold, BiMap::<usize, usize>::growth_threshold_for(map.current_capacity()));
+    assert!(map.can_fit(0));
+
+    // the threshold must always be reachable without exceeding the capacity of the indices.
+    // Indices of capacity zero belong to small maps, which scan their pairs instead.
+    assert_eq!(BiMap::<u8, u8>::growth_threshold_for(0), SMALL_MAP_CAPACITY);
+    for capacity in 1..1000 {
+        assert!(BiMap::<u8, u8>::growth_threshold_for(capacity) < capacity.max(1));
+        assert!(BiMap::<u8, u8>::growth_threshold_for(BiMap::<u8, u8>::apply_load_factor(capacity)) >= capacity);
+    }
+}
+
+#[test]
+fn test_replacement_does_not_grow() {
+    // test that inserts which only replace mappings never grow the map, even at the threshold
+    let mut map = BiMap::with_capacity(100);
+    let capacity = map.current_capacity();
+    for i in 0..map.growth_threshold {
+        map.insert(i, i);
+    }
+    assert_eq!(map.current_capacity(), capacity);
+    assert!(!map.can_fit(1));
+
+    for round in 1..10 {
+        // replace the right value of existing left values
+        for i in 0..50 {
+            map.insert(i, i + 1000 * round);
+        }
+        assert_eq!(map.current_capacity(), capacity);
+
+        // replace the left value of existing right values
+        for i in 50..100 {
+            map.insert(i + 1000 * round, i);
+        }
+        assert_eq!(map.current_capacity(), capacity);
+
+        // reinsert an existing pair
+        map.insert(0, 1000 * round);
+        assert_eq!(map.current_capacity(), capacity);
+    }
+    assert_invariants(&map);
+
+    // collapsing two mappings shrinks the map, so a new pair fits again afterwards
+    let len = map.len();
+    map.insert(0, 50);
+    assert_eq!(map.len(), len - 1);
+    map.insert(1_000_000, 1_000_000);
+    assert_eq!(map.current_capacity(), capacity);
+    assert_invariants(&map);
+
+    // a new pair still grows the full map
+    map.insert(1_000_001, 1_000_001);
+    assert!(map.current_capacity() > capacity);
+    assert_invariants(&map);
+}
+
+#[test]
+fn test_shrink_to_fit() {
+    let mut map = BiMap::with_capacity(1000);
+    assert!(map.current_capacity() >= 1000);
+
+    for i in 0..100 {
+        map.insert(i, i);
+    }
+    assert_invariants(&map);
+
+    map.shrink_to_fit();
+    assert_invariants(&map);
+
+    for i in 0..100 {
+        assert_eq!(map.get_right(&i), Some(&i));
+        assert_eq!(map.get_left(&i), Some(&i));
+    }
+
+    assert!(map.current_capacity() < 1000);
+}
+
+#[test]
+fn test_shrink_to() {
+    let mut map = BiMap::with_capacity(2000);
+    assert!(map.current_capacity() >= 2000);
+
+    for i in 0..100 {
+        map.insert(i, i);
+    }
+    assert_invariants(&map);
+
+    map.shrink_to(500);
+    assert_invariants(&map);
+
+    for i in 0..100 {
+        assert_eq!(map.get_right(&i), Some(&i));
+        assert_eq!(map.get_left(&i), Some(&i));
+    }
+
+    assert!(map.current_capacity() >= 500 && map.current_capacity() < 2000);
+
+    map.shrink_to(10);
+    assert_invariants(&map);
+
+    for i in 0..100 {
+        assert_eq!(map.get_right(&i), Some(&i));
+        assert_eq!(map.get_left(&i), Some(&i));
+    }
+
+    assert!(map.current_capacity() < 500);
+}
+
+#[test]
+fn test_iter() {
+    // test that the iterator returns all elements
+    let mut map: BiMap<_, _> = BiMap::default();
+
+    map.insert(1, 2);
+    map.insert(3, 4);
+    map.insert(5, 6);
+    map.insert(7, 8);
+    map.insert(1, 8);
+    assert_invariants(&map);
+
+    let from_iter = map.iter().collect::<Vec<_>>();
+
+    assert_eq!(from_iter.len(), 3);
+    assert!(from_iter.contains(&(&1, &8)));
+    assert!(from_iter.contains(&(&3, &4)));
+    assert!(from_iter.contains(&(&5, &6)));
+
+    let from_iter = map.left_values().collect::<Vec<_>>();
+
+    assert_eq!(from_iter.len(), 3);
+    assert!(from_iter.contains(&&1));
+    assert!(from_iter.contains(&&3));
+    assert!(from_iter.contains(&&5));
+
+    let from_iter = map.right_values().collect::<Vec<_>>();
+
+    assert_eq!(from_iter.len(), 3);
+    assert!(from_iter.contains(&&4));
+    assert!(from_iter.contains(&&6));
+    assert!(from_iter.contains(&&8));
+}
+
+#[test]
+pub fn test_drain() {
+    // test that the drain iterator returns all elements
+    let mut map: BiMap<_, _> = BiMap::default();
+
+    map.insert(1, 2);
+    map.insert(3, 4);
+    map.insert(5, 6);
+    map.insert(7, 8);
+    map.insert(1, 8);
+
+    let from_iter = map.drain().collect::<Vec<_>>();
+
+    assert_eq!(from_iter.len(), 3);
+    assert!(from_iter.contains(&(1, 8)));
+    assert!(from_iter.contains(&(3, 4)));
+    assert!(from_iter.contains(&(5, 6)));
+
+    assert!(map.is_empty());
+
+    assert_eq!(map.get_left(&1), None);
+    assert_eq!(map.get_right(&8), None);
+    assert_eq!(map.get_left(&3), None);
+    assert_eq!(map.get_right(&4), None);
+    assert_eq!(map.get_left(&5), None);
+    assert_eq!(map.get_right(&6), None);
+}
+
+#[test]
+fn test_drain_dropped_early() {
+    let mut map = BiMap::new();
+    for i in 0..100 {
+        map.insert(i, i + 1000);
+    }
+    let capacity = map.current_capacity();
+    let data_capacity = map.data.capacity();
+
+    let mut drain = map.drain();
+    assert_eq!(drain.len(), 100);
+    let taken = drain.by_ref().take(50).count();
+    assert_eq!(taken, 50);
+    assert_eq!(drain.len(), 50);
+    drop(drain);
+
+    assert!(map.is_empty());
+    assert_eq!(map.iter().count(), 0);
+    assert_eq!(map.current_capacity(), capacity);
+    assert_eq!(map.data.capacity(), data_capacity);
+    assert_invariants(&map);
+
+    map.insert(1, 2);
+    assert_eq!(map.get_right(&1), Some(&2));
+}
+
+#[test]
+fn test_into_iter() {
+    for len in [5, 100] {
+        let mut map = BiMap::new();
+        for i in 0..len {
+            map.insert(i, i + 1000);
+        }
+        let order = map.iter().map(|(&left, &right)| (left, right)).collect::<Vec<_>>();
+
+        let into_iter = map.into_iter();
+        assert_eq!(into_iter.size_hint(), (len, Some(len)));
+        assert_eq!(into_iter.collect::<Vec<_>>(), order);
+    }
+
+    // a partially consumed iterator drops the remaining pairs exactly once
+    let value = std::rc::Rc::new(0);
+    let mut map = BiMap::new();
+    for i in 0..100 {
+        map.insert(i, (i, value.clone()));
+    }
+    let mut into_iter = map.into_iter();
+    assert_eq!(into_iter.len(), 100);
+    let taken = into_iter.by_ref().take(30).collect::<Vec<_>>();
+    assert_eq!(into_iter.next_back().map(|(left, _)| left), Some(99));
+    assert_eq!(into_iter.len(), 69);
+    assert_eq!(std::rc::Rc::strong_count(&value), 100);
+    drop(into_iter);
+    assert_eq!(std::rc::Rc::strong_count(&value), 31);
+    drop(taken);
+    assert_eq!(std::rc::Rc::strong_count(&value), 1);
+}
+
+#[test]
+fn test_into_iter_ref() {
+    fn sum_rights<'a>(pairs: impl IntoIterator<Item=(&'a u32, &'a u32)>) -> u32 {
+        pairs.into_iter().map(|(_, right)| right).sum()
+    }
+
+    let mut map = BiMap::new();
+    for i in 0..20 {
+        map.insert(i, i * 2);
+    }
+
+    let mut pairs = Vec::new();
+    for (left, right) in &map {
+        pairs.push((*left, *right));
+    }
+    assert_eq!(pairs, map.iter().map(|(&left, &right)| (left, right)).collect::<Vec<_>>());
+    assert_eq!(sum_rights(&map), 380);
+
+    let mut iter = (&map).into_iter();
+    assert_eq!(iter.len(), 20);
+    let first = iter.next();
+    let last = iter.next_back();
+    assert_eq!(first.map(|(&left, &right)| (left, right)), pairs.first().copied());
+    assert_eq!(last.map(|(&left, &right)| (left, right)), pairs.last().copied());
+    assert_eq!(iter.clone().count(), 18);
+    assert_eq!(format!("{:?}", BiMap::<u32, u32>::new().iter()), "[]");
+}
+
+#[test]
+fn test_named_iterators() {
+    // the iterators can be stored in fields, since their types can be named
+    struct Cursor<'a> {
+        pairs: Iter<'a, u32, char>,
+        lefts: LeftValues<'a, u32, char>,
+        rights: RightValues<'a, u32, char>,
+    }
+
+    let mut map = BiMap::new();
+    for (i, c) in ('a'..='z').enumerate() {
+        map.insert(i as u32, c);
+    }
+    let mut cursor = Cursor { pairs: map.iter(), lefts: map.left_values(), rights: map.right_values() };
+    for _ in 0..map.len() {
+        let (left, right) = cursor.pairs.next().unwrap();
+        assert_eq!(cursor.lefts.next(), Some(left));
+        assert_eq!(cursor.rights.next(), Some(right));
+    }
+    assert_eq!(cursor.pairs.next(), None);
+    assert_eq!(cursor.lefts.len(), 0);
+
+    let lefts = map.left_values();
+    assert_eq!(lefts.clone().rev().collect::<Vec<_>>(), lefts.collect::<Vec<_>>().into_iter().rev().collect::<Vec<_>>());
+    let mut small = BiMap::new();
+    small.insert(1, 'x');
+    assert_eq!(format!("{:?}", small.iter()), "[(1, 'x')]");
+    assert_eq!(format!("{:?}", small.left_values()), "[1]");
+    assert_eq!(format!("{:?}", small.right_values()), "['x']");
+
+    let into_iter: IntoIter<u32, char> = small.clone().into_iter();
+    assert_eq!(into_iter.clone().collect::<Vec<_>>(), [(1, 'x')]);
+    assert_eq!(format!("{:?}", into_iter), "IntoIter { remaining: 1 }");
+    let drain: Drain<'_, u32, char> = small.drain();
+    assert_eq!(format!("{:?}", drain), "Drain { remaining: 1 }");
+}
+
+#[test]
+fn test_iterator_traits() {
+    fn assert_exact_and_reversible<I>(iter: I)
+        where I: DoubleEndedIterator + ExactSizeIterator + std::iter::FusedIterator + Clone, I::Item: PartialEq + fmt::Debug
+    {
+        let forward = iter.clone().collect::<Vec<_>>();
+        let mut backward = iter.clone().rev().collect::<Vec<_>>();
+        backward.reverse();
+        assert_eq!(forward, backward);
+
+        // the length shrinks from both ends, and the iterator stays exhausted
+        let mut iter = iter;
+        for remaining in (0..forward.len()).rev() {
+            if remaining % 2 == 0 {
+                iter.next().unwrap();
+            } else {
+                iter.next_back().unwrap();
+            }
+            assert_eq!(iter.len(), remaining);
+            assert_eq!(iter.size_hint(), (remaining, Some(remaining)));
+        }
+        assert!(iter.next().is_none());
+        assert!(iter.next_back().is_none());
+        assert!(iter.next().is_none());
+    }
+
+    for len in [0, 5, 100] {
+        let mut map = BiMap::new();
+        for i in 0..len {
+            map.insert(i, i * 3);
+        }
+        assert_exact_and_reversible(map.iter());
+        assert_exact_and_reversible(map.left_values());
+        assert_exact_and_reversible(map.right_values());
+        assert_exact_and_reversible(map.inverse().iter());
+        assert_exact_and_reversible(map.inverse().left_values());
+        assert_exact_and_reversible(map.left_keys().iter());
+        assert_exact_and_reversible(map.clone().into_iter());
+        assert_eq!(map.iter().rev().map(|(&left, _)| left).collect::<Vec<_>>(), map.left_values().rev().copied().collect::<Vec<_>>());
+
+        let mut drain = map.drain();
+        assert_eq!(drain.len(), len);
+        while drain.next_back().is_some() {}
+        assert_eq!(drain.len(), 0);
+        assert!(drain.next().is_none());
+        drop(drain);
+        assert!(map.is_empty());
+        assert_invariants(&map);
+    }
+}
+
+#[test]
+fn test_retain() {
+    for len in [6, 100] {
+        let mut map = BiMap::new();
+        let notifications = record_notifications(&mut map);
+        for i in 0..len {
+            map.insert(i, i + 1000);
+        }
+        let capacity = map.current_capacity();
+        map.enable_journal();
+
+        map.retain(|&left, &right| left % 3 != 0 && right != 1001);
+        let expected = (0..len).filter(|&i| i % 3 != 0 && i != 1).map(|i| (i, i + 1000)).collect::<Vec<_>>();
+        assert_eq!(map.iter().map(|(&left, &right)| (left, right)).collect::<Vec<_>>(), expected);
+        assert_eq!(map.current_capacity(), capacity);
+        assert_invariants(&map);
+        assert!(!map.contains_left(&3));
+        assert!(!map.contains_right(&1001));
+        assert_eq!(map.get_right(&2), Some(&1002));
+        assert_reconstructs(&notifications.lock().unwrap(), &map);
+
+        // the removals are undone as one entry
+        assert!(map.undo());
+        assert_eq!(map.len(), len);
+        assert_invariants(&map);
+    }
+
+    // a panicking predicate leaves the map unchanged
+    let mut map = BiMap::new();
+    for i in 0..50 {
+        map.insert(i, i);
+    }
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| map.retain(|&left, _| left < 40 || panic!("rejected"))));
+    assert!(result.is_err());
+    assert_eq!(map.len(), 50);
+    assert_invariants(&map);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_par_retain_matches_retain() {
+    use rand::{Rng, SeedableRng};
+    let mut rng = rand::rngs::StdRng::seed_from_u64(183);
+    for len in [0, 5, 8, 9, 100, 5000] {
+        let mut map = BiMap::new();
+        while map.len() < len {
+            map.insert(rng.gen_range(0..len as u64 * 4), rng.gen::<u64>());
+        }
+
+        for _ in 0..5 {
+            let modulus = rng.gen_range(1..5u64);
+            let salt = rng.gen::<u64>();
+            let predicate = |&left: &u64, &right: &u64| (left ^ right ^ salt) % modulus == 0;
+
+            let mut sequential = map.clone();
+            sequential.retain(predicate);
+            let mut parallel = map.clone();
+            parallel.par_retain(predicate);
+            assert_invariants(&parallel);
+            assert_eq!(parallel.iter().collect::<Vec<_>>(), sequential.iter().collect::<Vec<_>>());
+
+            let mut drained = map.clone();
+            let removed = drained.par_drain_filter(|left, right| !predicate(left, right));
+            assert_invariants(&drained);
+            assert_eq!(drained.iter().collect::<Vec<_>>(), sequential.iter().collect::<Vec<_>>());
+            let expected = map.iter().filter(|(left, right)| !predicate(left, right)).map(|(&left, &right)| (left, right)).collect::<Vec<_>>();
+            assert_eq!(removed, expected);
+        }
+    }
+}
+
+#[test]
+fn test_sorted_positions() {
+    fn pairs<'a>(range: impl Iterator<Item=(&'a u32, &'a u32)>) -> Vec<(u32, u32)> {
+        range.map(|(&left, &right)| (left, right)).collect()
+    }
+
+    for len in [6u32, 100] {
+        // a shuffled insertion order, with the left values spaced out so that searches can miss
+        let mut map = BiMap::new();
+        for i in 0..len {
+            let left = (i * 37 % len) * 2;
+            map.insert(left, 1000 - left);
+        }
+
+        map.sort_by_left();
+        assert_invariants(&map);
+        let sorted = (0..len).map(|i| (i * 2, 1000 - i * 2)).collect::<Vec<_>>();
+        assert_eq!(pairs(map.iter()), sorted);
+        assert_eq!(map.get_left(&1000), Some(&0));
+
+        // hits return the position, misses the position to insert at
+        for (position, &(left, _)) in sorted.iter().enumerate() {
+            assert_eq!(map.binary_search_by_left(&left), Ok(position));
+            assert_eq!(map.binary_search_by_left(&(left + 1)), Err(position + 1));
+        }
+        assert_eq!(map.binary_search_by_left(&(len * 2 + 10)), Err(len as usize));
+
+        // ranges at the boundaries
+        let len = len as usize;
+        assert_eq!(pairs(map.get_range(0..0).unwrap()), []);
+        assert_eq!(pairs(map.get_range(0..len).unwrap()), sorted);
+        assert_eq!(pairs(map.get_range(len..len).unwrap()), []);
+        assert_eq!(pairs(map.get_range(len - 2..len).unwrap()), sorted[len - 2..]);
+        assert_eq!(map.get_range(2..5).unwrap().len(), 3);
+        assert_eq!(pairs(map.get_range(2..5).unwrap().rev()), sorted[2..5].iter().rev().copied().collect::<Vec<_>>());
+        assert!(map.get_range(0..len + 1).is_none());
+        let (start, end) = (3, 2);
+        assert!(map.get_range(start..end).is_none());
+
+        // a run of pairs from a search
+        let start = map.binary_search_by_left(&4).unwrap();
+        let end = map.binary_search_by_left(&9).unwrap_err();
+        assert_eq!(pairs(map.get_range(start..end).unwrap()), [(4, 996), (6, 994), (8, 992)]);
+
+        map.sort_by_right();
+        assert_invariants(&map);
+        assert_eq!(pairs(map.iter()), sorted.iter().rev().copied().collect::<Vec<_>>());
+        map.sort_by(|left_a, _, left_b, _| (left_a % 4).cmp(&(left_b % 4)).then(left_a.cmp(left_b)));
+        assert_invariants(&map);
+        assert!(map.left_values().is_sorted_by_key(|&left| (left % 4, left)));
+
+        // an unsorted insert is appended at the end, so the search no longer finds smaller values
+        map.sort_by_left();
+        map.insert(1, 2000);
+        assert_eq!(map.binary_search_by_left(&1), Err(1));
+        assert_eq!(map.get_right(&1), Some(&2000));
+        map.sort_by_left();
+        assert_eq!(map.binary_search_by_left(&1), Ok(1));
+    }
+
+    // a panicking comparator leaves the map unchanged
+    let mut map = BiMap::new();
+    for i in (0..50).rev() {
+        map.insert(i, i);
+    }
+    let before = pairs(map.iter());
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| map.sort_by(|_, _, _, _| panic!("no order"))));
+    assert!(result.is_err());
+    assert_eq!(pairs(map.iter()), before);
+    assert_invariants(&map);
+}
+
+#[test]
+fn test_reverse_and_swap_indices() {
+    fn assert_order(map: &BiMap<u32, u32>, expected: &[u32]) {
+        assert_eq!(map.left_values().copied().collect::<Vec<_>>(), expected);
+        for &left in expected {
+            assert_eq!(map.get_right(&left), Some(&(left + 100)));
+            assert_eq!(map.get_left(&(left + 100)), Some(&left));
+        }
+        assert_invariants(map);
+    }
+
+    // empty and one-element maps are unchanged
+    let mut map = BiMap::new();
+    map.reverse();
+    assert_order(&map, &[]);
+    map.insert(0, 100);
+    map.reverse();
+    map.swap_indices(0, 0);
+    assert_order(&map, &[0]);
+
+    for len in [6u32, 100] {
+        let mut map = BiMap::new();
+        for i in 0..len {
+            map.insert(i, i + 100);
+        }
+        let mut expected = (0..len).collect::<Vec<_>>();
+
+        map.reverse();
+        expected.reverse();
+        assert_order(&map, &expected);
+
+        map.swap_indices(0, len as usize - 1);
+        expected.swap(0, len as usize - 1);
+        assert_order(&map, &expected);
+
+        map.swap_indices(3, 1);
+        expected.swap(3, 1);
+        assert_order(&map, &expected);
+
+        map.swap_indices(2, 2);
+        assert_order(&map, &expected);
+    }
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| map.swap_indices(0, 1)));
+    assert!(result.is_err());
+    assert_order(&map, &[0]);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_par_collect() {
+    use rayon::prelude::*;
+
+    // the paranoid checks scan all pairs for every insertion
+    let len: u64 = if cfg!(feature = "paranoid") { 10_000 } else { 1_000_000 };
+    let map: BiMap<u64, u64> = (0..len).into_par_iter().map(|i| (i, i.wrapping_mul(0x9e37_79b9_7f4a_7c15))).collect();
+    assert_eq!(map.len(), len as usize);
+    assert_eq!(map.current_capacity(), BiMap::<u64, u64>::with_capacity(len as usize).current_capacity());
+    assert!((0..len).step_by(997).all(|i| map.get_right(&i) == Some(&i.wrapping_mul(0x9e37_79b9_7f4a_7c15))));
+    assert_eq!(map.get_left(&0), Some(&0));
+    assert!(!map.contains_left(&len));
+
+    // duplicates are resolved like with a sequential iterator, whatever the threads did
+    let pairs = (0..10_000u32).map(|i| (i % 3000, i % 7000)).collect::<Vec<_>>();
+    let parallel: BiMap<u32, u32> = pairs.par_iter().copied().collect();
+    let sequential: BiMap<u32, u32> = pairs.iter().copied().collect();
+    assert_invariants(&parallel);
+    assert_eq!(parallel, sequential);
+
+    let mut extended = BiMap::new();
+    extended.insert(5, 5);
+    extended.par_extend(pairs.par_iter().copied());
+    let mut expected = crate::bimap![5 => 5];
+    expected.extend(pairs.iter().copied());
+    assert_eq!(extended, expected);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_par_sort_matches_sort() {
+    use rand::{Rng, SeedableRng};
+    let mut rng = rand::rngs::StdRng::seed_from_u64(187);
+    for len in [0, 5, 9, 100, 5000] {
+        let mut map = BiMap::new();
+        while map.len() < len {
+            map.insert(rng.gen::<u64>(), rng.gen_range(0..len as u64 * 4));
+        }
+
+        let mut sequential = map.clone();
+        sequential.sort_by_left();
+        let mut parallel = map.clone();
+        parallel.par_sort_by_left();
+        assert_invariants(&parallel);
+        assert_eq!(parallel.iter().collect::<Vec<_>>(), sequential.iter().collect::<Vec<_>>());
+
+        sequential.sort_by_right();
+        parallel.par_sort_by_right();
+        assert_invariants(&parallel);
+        assert_eq!(parallel.iter().collect::<Vec<_>>(), sequential.iter().collect::<Vec<_>>());
+
+        // a comparator with many ties, so that the stability of the sort shows
+        let compare = |left_a: &u64, _: &u64, left_b: &u64, _: &u64| (left_a % 7).cmp(&(left_b % 7));
+        sequential.sort_by(compare);
+        parallel.par_sort_by(compare);
+        assert_invariants(&parallel);
+        assert_eq!(parallel.iter().collect::<Vec<_>>(), sequential.iter().collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn test_bulk_insert() {
+    // bulk insertion rejects the same pairs as trying to insert them one by one
+    fn assert_like_try_insert(map: &BiMap<u32, u32>, pairs: &[(u32, u32)]) {
+        let mut bulk = map.clone();
+        let rejected = bulk.bulk_insert(pairs.iter().copied());
+        let mut sequential = map.clone();
+        let expected = pairs.iter()
+            .filter(|&&(left, right)| sequential.try_insert(left, right).is_err())
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(rejected, expected);
+        assert_same_pairs(&bulk, &sequential);
+        assert_invariants(&bulk);
+    }
+
+    for existing in [0, 3, 40] {
+        let mut map = BiMap::new();
+        for i in 0..existing {
+            map.insert(i, i + 1000);
+        }
+
+        assert_like_try_insert(&map, &[]);
+        assert_like_try_insert(&map, &[(100, 100), (101, 101)]);
+        // duplicate lefts and rights within the input, and values of the map
+        assert_like_try_insert(&map, &[(100, 100), (100, 101), (102, 100), (1, 103), (104, 1001), (105, 105)]);
+        // a pair that is rejected does not keep its values from later pairs
+        assert_like_try_insert(&map, &[(100, 100), (100, 200), (300, 200), (300, 400)]);
+        // enough pairs to grow the map, with about every third pair rejected
+        let pairs = (0..200).map(|i| (i * 7 % 150 + 20, i * 5 % 130 + 1000)).collect::<Vec<_>>();
+        assert_like_try_insert(&map, &pairs);
+    }
+
+    // random pairs on a map with a colliding hasher, so that the runs of equal ideal indices are long
+    let mut map = BiMap::with_hashers(0, CollidingBuildHasher::new(16), CollidingBuildHasher::new(16));
+    let mut state = 0x9e37_79b9_u64;
+    let mut pairs = Vec::new();
+    for _ in 0..500 {
+        state = state.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+        pairs.push(((state >> 33) as u32 % 400, (state >> 45) as u32 % 400));
+    }
+    let rejected = map.bulk_insert(pairs.iter().copied());
+    let mut sequential = BiMap::with_hashers(0, CollidingBuildHasher::new(16), CollidingBuildHasher::new(16));
+    let expected = pairs.into_iter().filter(|&(left, right)| sequential.try_insert(left, right).is_err()).collect::<Vec<_>>();
+    assert_eq!(rejected, expected);
+    assert_same_pairs(&map, &sequential);
+    assert_invariants(&map);
+
+    // the inserted pairs notify the hook in their order in the input
+    let mut map = BiMap::new();
+    let notifications = record_notifications(&mut map);
+    map.insert(0, 0);
+    map.bulk_insert((1..30).map(|i| (i, i)).chain([(0, 30)]));
+    assert_eq!(map.len(), 30);
+    assert_reconstructs(&notifications.lock().unwrap(), &map);
+}
+
+#[test]
+fn test_drain_from_back() {
+    let mut map = BiMap::new();
+    for i in 0..20 {
+        map.insert(i, i + 100);
+    }
+    let order = map.iter().map(|(&left, &right)| (left, right)).collect::<Vec<_>>();
+
+    let mut drain = map.drain();
+    assert_eq!(drain.next_back(), order.last().copied());
+    assert_eq!(drain.next(), order.first().copied());
+    let rest = drain.rev().collect::<Vec<_>>();
+    assert_eq!(rest, order[1..19].iter().rev().copied().collect::<Vec<_>>());
+    assert!(map.is_empty());
+}
+/// Check every method of an inverse view against the map it was created from.
+fn assert_inverse_matches<T, U, H, RH>(map: &BiMap<T, U, H, RH>, probes: &[(T, U)])
+    where T: Hash + Eq + fmt::Debug, U: Hash + Eq + fmt::Debug, H: BuildHasher, RH: BuildHasher
+{
+    let inverse = map.inverse();
+    let copy = inverse;
+    assert_eq!(inverse.len(), map.len());
+    assert_eq!(copy.is_empty(), map.is_empty());
+    assert!(inverse.iter().eq(map.iter().map(|(left, right)| (right, left))));
+    assert!(copy.into_iter().eq(map.iter().map(|(left, right)| (right, left))));
+    assert_eq!(inverse.iter().len(), map.len());
+    assert!(inverse.left_values().eq(map.right_values()));
+    assert!(inverse.right_values().eq(map.left_values()));
+    for (left, right) in probes {
+        assert_eq!(inverse.get_right(right), map.get_left(right));
+        assert_eq!(inverse.get_left(left), map.get_right(left));
+        assert_eq!(inverse.contains_left(right), map.contains_right(right));
+        assert_eq!(inverse.contains_right(left), map.contains_left(left));
+    }
+}
+
+#[test]
+fn test_inverse_view() {
+    let mut map = BiMap::new();
+    let probes = (0..40).map(|i| (i, format!("{}", i * 2))).collect::<Vec<_>>();
+    assert_inverse_matches(&map, &probes);
+
+    for i in 0..30 {
+        map.insert(i, format!("{}", i * 2));
+    }
+    assert_inverse_matches(&map, &probes);
+    assert_eq!(map.inverse().get_right(&"10".to_string()), Some(&5));
+    assert_eq!(map.inverse().get_left(&5), Some(&"10".to_string()));
+    assert!(!map.inverse().contains_left(&"11".to_string()));
+
+    // the view is a borrow of the map, so views created after a mutation see it
+    map.remove_left(&5);
+    map.insert(100, "10".to_string());
+    assert_inverse_matches(&map, &probes);
+    assert_eq!(map.inverse().get_right(&"10".to_string()), Some(&100));
+    assert!(!map.inverse().contains_right(&5));
+
+    // when both sides have the same type, the view must not mix them up
+    let mut same = BiMap::new();
+    for i in 0..20u32 {
+        same.insert(i, i + 100);
+    }
+    assert_inverse_matches(&same, &(0..120).map(|i| (i, i)).collect::<Vec<_>>());
+    assert_eq!(same.inverse().get_right(&105), Some(&5));
+    assert_eq!(same.inverse().get_right(&5), None);
+    assert_eq!(format!("{:?}", BiMap::<u8, u8>::new().inverse()), "{}");
+}
+
+/// The power of a map computed by applying it repeatedly to every value, for comparison with
+/// [`BiMap::pow`].
+fn naive_pow(map: &BiMap<u32, u32>, n: isize) -> HashSet<(u32, u32)> {
+    let values = map.left_values().chain(map.right_values()).copied().collect::<HashSet<_>>();
+    let mut pairs = HashSet::new();
+    for &value in &values {
+        let mut current = Some(value);
+        for _ in 0..n.unsigned_abs() {
+            current = current.and_then(|current| if n > 0 { map.get_right(&current) } else { map.get_left(&current) }.copied());
+        }
+        if let Some(power) = current {
+            if n != 0 || map.contains_left(&value) {
+                pairs.insert((value, power));
+            }
+        }
+    }
+    pairs
+}
+
+#[test]
+fn test_pow() {
+    // a 3-cycle, a 5-cycle, a fixed point, and two paths, of 4 and 1 pairs
+    let mut map = BiMap::new();
+    for (left, right) in [(1, 2), (2, 3), (3, 1), (10, 11), (11, 12), (12, 13), (13, 14), (14, 10), (20, 20),
+                          (30, 31), (31, 32), (32, 33), (33, 34), (40, 41)] {
+        map.insert(left, right);
+    }
+
+    for n in -17..=17 {
+        let power = map.pow(n);
+        assert_invariants(&power);
+        assert_eq!(power.iter().map(|(&left, &right)| (left, right)).collect::<HashSet<_>>(), naive_pow(&map, n), "power {}", n);
+    }
+
+    let identity = map.pow(0);
+    assert_eq!(identity.len(), map.len());
+    assert!(map.left_values().all(|value| identity.get_right(value) == Some(value)));
+    assert!(!identity.contains_left(&34));
+
+    // exponents larger than the cycles wrap around, paths run out
+    assert_eq!(map.pow(1).iter().map(|(&left, &right)| (left, right)).collect::<HashSet<_>>(), map.iter().map(|(&left, &right)| (left, right)).collect());
+    assert_eq!(map.pow(-1).get_right(&1), Some(&3));
+    let power = map.pow(1_000_000_007);
+    assert_eq!(power.get_right(&1), map.pow(1_000_000_007 % 3).get_right(&1));
+    assert_eq!(power.get_right(&10), map.pow(1_000_000_007 % 5).get_right(&10));
+    assert_eq!(power.get_right(&20), Some(&20));
+    assert!(!power.contains_left(&30));
+    // only the values on cycles remain
+    assert_eq!(map.pow(isize::MIN).len(), 9);
+    assert_eq!(map.pow(4).get_right(&30), Some(&34));
+    assert_eq!(map.pow(-4).get_right(&34), Some(&30));
+    assert_eq!(BiMap::<u32, u32>::new().pow(3).len(), 0);
+}
+
+#[test]
+fn test_key_sets() {
+    // the views are side-specific, so left and right values of the same type are not confused
+    let mut map: BiMap<_, _> = BiMap::default();
+    for i in 0..20usize {
+        map.insert(i, i + 10);
+    }
+
+    let lefts = map.left_keys();
+    let rights = map.right_keys();
+    assert_eq!(lefts.len(), 20);
+    assert!(lefts.contains(&0));
+    assert!(!lefts.contains(&25));
+    assert!(rights.contains(&25));
+    assert!(!rights.contains(&0));
+    assert_eq!(lefts.iter().copied().collect::<HashSet<_>>(), (0..20).collect());
+    assert_eq!(rights.iter().copied().collect::<HashSet<_>>(), (10..30).collect());
+
+    assert_eq!(lefts, (0..20).collect::<HashSet<_>>());
+    assert_ne!(lefts, (10..30).collect::<HashSet<_>>());
+    assert_ne!(lefts, (0..19).collect::<HashSet<_>>());
+    assert_ne!(lefts, rights);
+    assert_eq!(lefts, lefts);
+
+    assert!(!lefts.is_disjoint(&rights));
+    assert!(lefts.is_disjoint(&(20..100).collect::<HashSet<_>>()));
+    assert!(!lefts.is_disjoint(&(19..100).collect::<HashSet<_>>()));
+    assert!(lefts.is_disjoint(&HashSet::new()));
+    assert!(lefts.is_subset(&(0..30).collect::<HashSet<_>>()));
+    assert!(!lefts.is_subset(&(1..30).collect::<HashSet<_>>()));
+    assert!(!lefts.is_subset(&rights));
+    assert!(lefts.is_superset(&(5..15).collect::<HashSet<_>>()));
+    assert!(!lefts.is_superset(&(15..25).collect::<HashSet<_>>()));
+
+    // views of another map with the same values
+    let mut other: BiMap<_, _> = BiMap::default();
+    for i in 0..20usize {
+        other.insert(i + 10, i);
+    }
+    assert_eq!(lefts, other.right_keys());
+    assert_eq!(rights, other.left_keys());
+    assert!(lefts.is_subset(&other.right_keys()));
+    assert!(lefts.is_disjoint(&BiMap::<usize, usize>::default().left_keys()));
+}
+
+#[test]
+fn test_key_sets_collisions() {
+    // the values collide in the map and in the foreign sets, so membership has to compare values
+    let mut map = BiMap::with_test_hashers(DEFAULT_CAPACITY, DEFAULT_CAPACITY);
+    for i in 0..5 {
+        map.insert(i * DEFAULT_CAPACITY + 1, i * DEFAULT_CAPACITY + 2);
+    }
+    assert_invariants(&map);
+    let lefts = map.left_keys();
+
+    let mut colliding = HashSet::with_hasher(CollidingBuildHasher::new(DEFAULT_CAPACITY));
+    colliding.extend((5..10).map(|i| i * DEFAULT_CAPACITY + 1));
+    assert!(lefts.is_disjoint(&colliding));
+    assert!(!lefts.is_subset(&colliding));
+    assert_ne!(lefts, colliding);
+
+    colliding.extend((0..5).map(|i| i * DEFAULT_CAPACITY + 1));
+    assert!(!lefts.is_disjoint(&colliding));
+    assert!(lefts.is_subset(&colliding));
+    assert!(!lefts.is_superset(&colliding));
+
+    colliding.retain(|&value| value < 5 * DEFAULT_CAPACITY);
+    assert_eq!(lefts, colliding);
+    assert!(lefts.is_superset(&colliding));
+    assert!(!map.right_keys().contains(&(5 * DEFAULT_CAPACITY + 2)));
+    assert!(map.right_keys().contains(&(4 * DEFAULT_CAPACITY + 2)));
+}
+
+#[test]
+fn test_cursor_remove_every_other() {
+    // test that removing pairs visits the pairs that are moved into their positions, in both representations
+    for len in [7, 100] {
+        let mut map = BiMap::new();
+        for i in 0..len {
+            map.insert(i, i + 1000);
+        }
+
+        let mut visited = vec![0; len];
+        let mut removed = Vec::new();
+        let mut cursor = map.cursor_mut();
+        let mut visits = 0;
+        while let Some((&left, &right)) = cursor.next() {
+            assert_eq!(right, left + 1000);
+            visited[left] += 1;
+            if visits % 2 == 0 {
+                assert_eq!(cursor.remove_current(), Some((left, right)));
+                assert_eq!(cursor.remove_current(), None);
+                removed.push(left);
+            }
+            visits += 1;
+        }
+        assert!(cursor.next().is_none());
+
+        assert!(visited.iter().all(|&count| count == 1));
+        assert_eq!(removed.len(), len.div_ceil(2));
+        assert_eq!(map.len(), len - removed.len());
+        assert_invariants(&map);
+        assert_back_references(&map);
+        for i in 0..len {
+            let expected = if removed.contains(&i) { None } else { Some(&(i + 1000)) };
+            assert_eq!(map.get_right(&i), expected);
+        }
+    }
+}
+
+#[test]
+fn test_cursor_remove_colliding_chains() {
+    // test that removals during the walk keep colliding chains reachable
+    let mut map = BiMap::with_test_hashers(DEFAULT_CAPACITY, DEFAULT_CAPACITY);
+    for i in 0..24 {
+        map.insert(i * DEFAULT_CAPACITY + 30 + i % 2, i * DEFAULT_CAPACITY + 1);
+    }
+
+    let mut cursor = map.cursor_mut();
+    let mut visits = 0;
+    while let Some((&left, _)) = cursor.next() {
+        visits += 1;
+        if (left / DEFAULT_CAPACITY).is_multiple_of(2) {
+            cursor.remove_current();
+        }
+    }
+    assert_eq!(visits, 24);
+    assert_invariants(&map);
+    assert_probe_distances(&map);
+    assert_back_references(&map);
+
+    for i in 0..24 {
+        let expected = if i % 2 == 0 { None } else { Some(&(i * DEFAULT_CAPACITY + 1)) };
+        assert_eq!(map.get_right(&(i * DEFAULT_CAPACITY + 30 + i % 2)), expected);
+    }
+}
+
+#[test]
+fn test_cursor_replace_colliding() {
+    // test that replaced right values that collide with the other right values are indexed correctly
+    let mut map = BiMap::with_test_hashers(DEFAULT_CAPACITY, DEFAULT_CAPACITY);
+    for i in 0..20 {
+        map.insert(i, i * DEFAULT_CAPACITY + 5);
+    }
+
+    let mut cursor = map.cursor_mut();
+    let mut visits = 0;
+    while let Some((&left, &right)) = cursor.next() {
+        visits += 1;
+        if left % 2 == 0 {
+            // the new value hashes to the same ideal index as all other right values
+            assert_eq!(cursor.replace_right_current((left + 20) * DEFAULT_CAPACITY + 5), right);
+        }
+    }
+    assert_eq!(visits, 20);
+    assert_invariants(&map);
+    assert_probe_distances(&map);
+    assert_back_references(&map);
+
+    for i in 0..20 {
+        let right = if i % 2 == 0 { (i + 20) * DEFAULT_CAPACITY + 5 } else { i * DEFAULT_CAPACITY + 5 };
+        assert_eq!(map.get_right(&i), Some(&right));
+        assert_eq!(map.get_left(&right), Some(&i));
+    }
+    assert_eq!(map.get_left(&5), None);
+
+    // a right value of another pair is rejected without modifying the map
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let mut cursor = map.cursor_mut();
+        cursor.next();
+        cursor.replace_right_current(DEFAULT_CAPACITY + 5);
+    }));
+    assert!(result.is_err());
+    assert_invariants(&map);
+    assert_eq!(map.len(), 20);
+    assert_eq!(map.get_left(&(DEFAULT_CAPACITY + 5)), Some(&1));
+}
+
+/// Assert that a map holds the same pairs in the same order as another map, and that all of them
+/// can be looked up in both directions.
+fn assert_same_pairs<T, U, H, RH>(map: &BiMap<T, U, H, RH>, expected: &BiMap<T, U, H, RH>)
+    where T: Hash + Eq + fmt::Debug, U: Hash + Eq + fmt::Debug, H: BuildHasher, RH: BuildHasher
+{
+    assert_eq!(map.len(), expected.len());
+    assert_eq!(map.iter().collect::<Vec<_>>(), expected.iter().collect::<Vec<_>>());
+    for (left, right) in expected.iter() {
+        assert_eq!(map.get_right(left), Some(right));
+        assert_eq!(map.get_left(right), Some(left));
+    }
+}
+
+#[test]
+fn test_transaction_rollback() {
+    // test that a failed transaction restores the pairs after a mix of operations, in both representations
+    for len in [7, 100] {
+        let mut map = BiMap::new();
+        for i in 0..len {
+            map.insert(i, i + 1000);
+        }
+        let before = map.clone();
+
+        let result: Result<(), &str> = map.transaction(|txn| {
+            // a new pair, a new right value, a new left value, and two pairs that collapse into one
+            assert_eq!(txn.insert(5000, 6000), (None, None));
+            assert_eq!(txn.insert(1, 7000), (Some(&1001), None));
+            assert_eq!(txn.insert(8000, 1002), (None, Some(&2)));
+            assert_eq!(txn.insert(3, 1004), (Some(&1003), Some(&4)));
+            assert_eq!(txn.insert(0, len - 1 + 1000), (Some(&1000), Some(&(len - 1))));
+            assert_eq!(txn.remove_left(&5), Some(&1005));
+            assert_eq!(txn.remove_right(&6000), Some(&5000));
+            assert_eq!(txn.remove_left(&5), None);
+            assert_eq!(txn.insert(9000, 1005), (None, None));
+            assert_eq!(txn.get_right(&3), Some(&1004));
+            assert_ne!(txn.len(), len);
+            Err("invalid")
+        });
+        assert_eq!(result, Err("invalid"));
+
+        assert_invariants(&map);
+        assert_back_references(&map);
+        assert_same_pairs(&map, &before);
+        for absent in [5000, 8000, 9000] {
+            assert_eq!(map.get_right(&absent), None);
+        }
+        for absent in [6000, 7000] {
+            assert_eq!(map.get_left(&absent), None);
+        }
+    }
+}
+
+#[test]
+fn test_transaction_replace_rollback() {
+    // test that a failed transaction restores both pairs that a replace displaced, in both representations
+    for len in [7, 100] {
+        let mut map = BiMap::new();
+        for i in 0..len {
+            map.insert(i, i + 1000);
+        }
+        let before = map.clone();
+
+        let result: Result<(), &str> = map.transaction(|txn| {
+            assert_eq!(txn.replace(1, 1001), (Some((&1, &1001)), Some((&1, &1001))));
+            assert_eq!(txn.replace(2, 7000), (Some((&2, &1002)), None));
+            assert_eq!(txn.replace(8000, 1004), (None, Some((&4, &1004))));
+            assert_eq!(txn.replace(3, 1005), (Some((&3, &1003)), Some((&5, &1005))));
+            assert_eq!(txn.replace(9000, 9001), (None, None));
+            assert_eq!(txn.get_right(&3), Some(&1005));
+            assert_eq!(txn.get_left(&1003), None);
+            assert_eq!(txn.get_right(&5), None);
+            Err("invalid")
+        });
+        assert_eq!(result, Err("invalid"));
+
+        assert_invariants(&map);
+        assert_back_references(&map);
+        assert_same_pairs(&map, &before);
+        assert_eq!(map.get_right(&3), Some(&1003));
+        assert_eq!(map.get_left(&1005), Some(&5));
+    }
+}
+
+#[test]
+fn test_transaction_commit() {
+    // test that a successful transaction keeps its operations
+    let mut map = BiMap::new();
+    for i in 0..20 {
+        map.insert(i, i + 1000);
+    }
+
+    let result = map.transaction(|txn| {
+        txn.insert(1, 7000);
+        txn.remove_left(&2);
+        Ok::<_, ()>(txn.len())
+    });
+    assert_eq!(result, Ok(19));
+    assert_invariants(&map);
+    assert_eq!(map.get_right(&1), Some(&7000));
+    assert_eq!(map.get_right(&2), None);
+}
+
+#[test]
+fn test_transaction_rollback_after_growth() {
+    // test that the capacity grown by a failed transaction is kept, while the pairs are restored
+    let mut map = BiMap::new();
+    for i in 0..6 {
+        map.insert(i, i + 1000);
+    }
+    let before = map.clone();
+
+    let result: Result<(), ()> = map.transaction(|txn| {
+        for i in 0..200 {
+            txn.insert(i + 100, i + 2000);
+        }
+        txn.remove_left(&3);
+        Err(())
+    });
+    assert_eq!(result, Err(()));
+
+    assert!(map.current_capacity() > 0);
+    assert_invariants(&map);
+    assert_back_references(&map);
+    assert_same_pairs(&map, &before);
+}
+
+#[test]
+fn test_transaction_panic_rollback() {
+    // test that a panicking transaction is undone as well
+    let mut map = BiMap::new();
+    for i in 0..50 {
+        map.insert(i, i + 1000);
+    }
+    let before = map.clone();
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        map.transaction(|txn| -> Result<(), ()> {
+            txn.insert(1, 2000);
+            txn.remove_left(&7);
+            panic!("validation panicked");
+        })
+    }));
+    assert!(result.is_err());
+    assert_invariants(&map);
+    assert_same_pairs(&map, &before);
+}
+
+#[test]
+fn test_iterator_ext() {
+    let map = (0..20u32).filter(|i| i % 2 == 0).map(|i| (i, i.to_string())).into_bimap();
+    assert_eq!(map.len(), 10);
+    assert_invariants(&map);
+    for i in (0..20u32).step_by(2) {
+        assert_eq!(map.get_right(&i), Some(&i.to_string()));
+    }
+
+    // later pairs replace earlier ones
+    let map = [(1, 'a'), (2, 'b'), (1, 'c'), (3, 'b')].into_iter().into_bimap();
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get_right(&1), Some(&'c'));
+    assert_eq!(map.get_right(&3), Some(&'b'));
+    assert!(!map.contains_left(&2));
+
+    let inverse = (0..20u32).map(|i| (i, i * 3)).into_inverse_bimap();
+    assert_eq!(inverse.len(), 20);
+    assert_invariants(&inverse);
+    for i in 0..20u32 {
+        assert_eq!(inverse.get_right(&(i * 3)), Some(&i));
+    }
+
+    let map = (0..20u32).map(|i| (i, i + 100)).try_into_bimap().unwrap();
+    assert_eq!(map.len(), 20);
+    assert_invariants(&map);
+    assert!((0..20u32).all(|i| map.get_right(&i) == Some(&(i + 100))));
+
+    let result = (0..20u32).map(|i| (i % 15, i)).try_into_bimap();
+    assert_eq!(result.unwrap_err(), FromPairsError::DuplicateLeft { first: 0, duplicate: 15 });
+    let result = (0..20u32).map(|i| (i, i / 2)).try_into_bimap();
+    assert_eq!(result.unwrap_err(), FromPairsError::DuplicateRight { first: 0, duplicate: 1 });
+    let result = std::iter::empty::<(u32, u32)>().try_into_bimap();
+    assert!(result.unwrap().is_empty());
+}
+
+#[test]
+fn test_try_from_hashmap() {
+    use std::collections::HashMap;
+    use crate::errors::{NonInjectiveError, ValueCollision};
+
+    let forward: HashMap<u32, u32> = (0..100).map(|i| (i, i * 3)).collect();
+    let map = BiMap::<u32, u32>::try_from(forward.clone()).unwrap();
+    assert_invariants(&map);
+    assert_eq!(map, forward);
+
+    // a single collision returns the right value with both left values
+    let forward = HashMap::from([(1, "a"), (2, "b"), (3, "a"), (4, "c")]);
+    let mut error = BiMap::<u32, &str>::try_from(forward).unwrap_err();
+    error.collisions[0].lefts.sort_unstable();
+    assert_eq!(error, NonInjectiveError { collisions: vec![ValueCollision { right: "a", lefts: vec![1, 3] }] });
+    assert_eq!(error.to_string(), r#"the right value "a" belongs to the left values [1, 3]"#);
+
+    // several collisions, one of them with three left values
+    let forward: HashMap<u32, u32> = (0..20).map(|i| (i, if i < 6 { i % 2 } else if i < 9 { 100 } else { i })).collect();
+    let error = BiMap::<u32, u32>::try_from(forward).unwrap_err();
+    let mut collisions = error.collisions.iter()
+        .map(|collision| {
+            let mut lefts = collision.lefts.clone();
+            lefts.sort_unstable();
+            (collision.right, lefts)
+        })
+        .collect::<Vec<_>>();
+    collisions.sort_unstable();
+    assert_eq!(collisions, vec![(0, vec![0, 2, 4]), (1, vec![1, 3, 5]), (100, vec![6, 7, 8])]);
+    assert!(error.to_string().ends_with(", and 2 more right values belong to several left values"));
+
+    // collecting the entries keeps one left value of each right value instead
+    let forward = HashMap::from([(1, "a"), (2, "b"), (3, "a")]);
+    let map: BiMap<u32, &str> = forward.into_iter().collect();
+    assert_eq!(map.len(), 2);
+    assert!(map.get_left(&"a") == Some(&1) || map.get_left(&"a") == Some(&3));
+}
+
+#[test]
+fn test_into_pairs() {
+    // the values are neither Clone nor Copy
+    #[derive(Debug, PartialEq, Eq, Hash)]
+    struct Unique(u32);
+
+    let mut map = BiMap::new();
+    for i in 0..100 {
+        map.insert(Unique(i), Unique(i + 1000));
+    }
+    map.remove_left(&Unique(10));
+    let order = map.iter().map(|(left, right)| (left.0, right.0)).collect::<Vec<_>>();
+    let pairs = map.into_pairs();
+    assert_eq!(pairs.iter().map(|(left, right)| (left.0, right.0)).collect::<Vec<_>>(), order);
+    assert_eq!(pairs.len(), 99);
+
+    let map = crate::bimap![1 => 'a', 2 => 'b'];
+    let mut pairs: Vec<(u32, char)> = map.into();
+    pairs.sort_unstable();
+    assert_eq!(pairs, vec![(1, 'a'), (2, 'b')]);
+    assert_eq!(Vec::from(BiMap::<u32, u32>::new()), vec![]);
+}
+
+#[test]
+fn test_into_hashmaps() {
+    use std::collections::HashMap;
+
+    // the values are neither Clone nor Copy
+    #[derive(Debug, PartialEq, Eq, Hash)]
+    struct Unique(u32);
+
+    let build = || {
+        let mut map = BiMap::new();
+        for i in 0..100 {
+            map.insert(Unique(i), Unique(i * 2));
+        }
+        map
+    };
+
+    let forward = build().into_left_hashmap();
+    assert_eq!(forward.len(), 100);
+    assert!((0..100).all(|i| forward.get(&Unique(i)) == Some(&Unique(i * 2))));
+
+    let backward = build().into_right_hashmap();
+    assert_eq!(backward.len(), 100);
+    assert!((0..100).all(|i| backward.get(&Unique(i * 2)) == Some(&Unique(i))));
+
+    // the pairs round-trip through both directions
+    let map: BiMap<u32, u32> = (0..100).map(|i| (i, i * 3)).collect();
+    assert_eq!(BiMap::<u32, u32>::try_from(map.clone().into_left_hashmap()).unwrap(), map);
+    let inverse = BiMap::<u32, u32>::try_from(map.clone().into_right_hashmap()).unwrap();
+    assert!(map.iter().all(|(left, right)| inverse.get_right(right) == Some(left)));
+
+    let seeded: HashMap<u32, u32, SeededBuildHasher> = map.clone().into_left_hashmap_with_hasher(SeededBuildHasher::new(5));
+    assert_eq!(seeded.hasher(), &SeededBuildHasher::new(5));
+    assert_eq!(map, seeded);
+    let seeded = map.clone().into_right_hashmap_with_hasher(SeededBuildHasher::new(5));
+    assert_eq!(seeded.len(), 100);
+    assert_eq!(seeded.get(&30), Some(&10));
+}
+
+#[test]
+fn test_default_with_other_hashers() {
+    use std::hash::{BuildHasherDefault, DefaultHasher};
+
+    type Hashers = BuildHasherDefault<DefaultHasher>;
+
+    #[derive(Default)]
+    struct Registry {
+        names: BiMap<String, u32, Hashers, Hashers>,
+        seeded: BiMap<u32, u32, SeededBuildHasher, Hashers>,
+    }
+
+    let mut registry = Registry::default();
+    assert!(registry.names.is_empty());
+    assert_eq!(registry.names.current_capacity(), BiMap::<String, u32>::new().current_capacity());
+    assert_eq!(registry.seeded.hasher_left(), &SeededBuildHasher::default());
+
+    for i in 0..20 {
+        registry.names.insert(i.to_string(), i);
+        registry.seeded.insert(i, i * 2);
+    }
+    assert_invariants(&registry.names);
+    assert_eq!(registry.names.get_left(&7).map(String::as_str), Some("7"));
+    assert_eq!(registry.seeded.get_right(&7), Some(&14));
+
+    let map: BiMap<u32, u32> = Default::default();
+    assert!(map.is_empty());
+}
+
+#[test]
+fn test_clone_from() {
+    // the hooks of the target map are dropped, like when it is assigned a clone
+    let notified = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = notified.clone();
+    let mut scratch = BiMap::new();
+    scratch.set_on_insert(Some(Box::new(move |_: &u32, _: &String| {
+        counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    })));
+
+    for round in 0..6u32 {
+        // alternate between small and large sources, with more and fewer pairs than the scratch map
+        let len = if round % 2 == 0 { 5 } else { 200 + round * 50 };
+        let source: BiMap<u32, String> = (0..len).map(|i| (i + round, (i * 7).to_string())).collect();
+        scratch.clone_from(&source);
+        assert_invariants(&scratch);
+        assert_eq!(scratch, source);
+        assert_eq!(scratch.current_capacity(), source.current_capacity());
+        assert_eq!(scratch.iter().collect::<Vec<_>>(), source.iter().collect::<Vec<_>>());
+        scratch.insert(10_000, "new".to_string());
+        assert_eq!(notified.load(std::sync::atomic::Ordering::Relaxed), 0);
+        assert_eq!(scratch.get_left(&"new".to_string()), Some(&10_000));
+        assert!(!source.contains_left(&10_000));
+    }
+
+    // the indices are cloned into the existing allocation if the capacities are equal
+    #[cfg(not(feature = "hashbrown-backend"))]
+    {
+        let source: BiMap<u32, u32> = (0..1000).map(|i| (i, i)).collect();
+        let mut scratch = source.clone();
+        let slots = scratch.left.slots.as_ptr();
+        for i in 0..10 {
+            let mut changed = source.clone();
+            changed.remove_left(&i);
+            scratch.clone_from(&changed);
+            assert_eq!(scratch.left.slots.as_ptr(), slots);
+            assert_eq!(scratch, changed);
+        }
+    }
+}
+
+#[test]
+fn test_with_single_hasher() {
+    let map = BiMap::<u32, u32, _, _>::with_hasher(SeededBuildHasher::new(9));
+    assert_eq!(map.hasher_left(), &SeededBuildHasher::new(9));
+    assert_eq!(map.hasher_right(), &SeededBuildHasher::new(9));
+    assert_eq!(map.current_capacity(), BiMap::<u32, u32>::new().current_capacity());
+
+    // the capacity has the same headroom for the load factor as with_capacity
+    for capacity in [0, 1, SMALL_MAP_CAPACITY, SMALL_MAP_CAPACITY + 1, 100, 1000, 4096] {
+        let mut map = BiMap::with_capacity_and_hasher(capacity, SeededBuildHasher::new(9));
+        assert_eq!(map.current_capacity(), BiMap::<u32, u32>::with_capacity(capacity).current_capacity());
+
+        let initial = map.current_capacity();
+        for i in 0..capacity as u32 {
+            map.insert(i, i);
+        }
+        assert_eq!(map.current_capacity(), initial);
+        assert_invariants(&map);
+    }
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_deserialize_reserves_size_hint() {
+    // the binary format announces the number of pairs, so the map is allocated once for all of them
+    let pairs = (0..1000u32).map(|i| (i, i * 2)).collect::<Vec<_>>();
+    let mut bytes = Vec::new();
+    ciborium::into_writer(&pairs, &mut bytes).unwrap();
+    let map: BiMap<u32, u32> = ciborium::from_reader(bytes.as_slice()).unwrap();
+    assert_eq!(map.current_capacity(), BiMap::<u32, u32>::with_capacity(1000).current_capacity());
+    assert_invariants(&map);
+}
+
+/// The longest and the mean probe distance of the entries of both indices.
+#[cfg(all(any(feature = "fxhash", feature = "ahash"), not(feature = "hashbrown-backend")))]
+fn probe_distances<T, U, H, RH>(map: &BiMap<T, U, H, RH>) -> (u8, f64) {
+    let occupied = map.left.slots.iter().zip(&map.left.distances)
+        .chain(map.right.slots.iter().zip(&map.right.distances))
+        .filter(|(slot, _)| slot.bucket().is_some())
+        .map(|(_, &distance)| distance)
+        .collect::<Vec<_>>();
+    let mean = occupied.iter().map(|&distance| distance as f64).sum::<f64>() / occupied.len() as f64;
+    (occupied.iter().copied().max().unwrap_or(0), mean)
+}
+
+#[test]
+#[cfg(all(any(feature = "fxhash", feature = "ahash"), not(feature = "hashbrown-backend")))]
+fn test_sequential_keys_with_fast_hashers() {
+    // sequential integers, and integers that only differ in their upper or their lower bits, which
+    // weak hashers spread poorly
+    fn check<H: BuildHasher, RH: BuildHasher>(mut map: BiMap<u64, u64, H, RH>, name: &str) {
+        for i in 0..100_000u64 {
+            map.insert(i, i << 20);
+        }
+        let (longest, mean) = probe_distances(&map);
+        assert!(longest <= 32 && mean < 2.0, "{}: longest probe {}, mean probe {}", name, longest, mean);
+        assert!((0..100_000u64).all(|i| map.get_right(&i) == Some(&(i << 20)) && map.get_left(&(i << 20)) == Some(&i)));
+    }
+
+    #[cfg(feature = "fxhash")]
+    check(crate::FxBiMap::with_default_hashers(0), "fxhash");
+    #[cfg(feature = "fxhash")]
+    check(crate::FxBiMap::with_default_hashers(100_000), "fxhash reserved");
+    #[cfg(feature = "ahash")]
+    check(crate::ABiMap::with_default_hashers(0), "ahash");
+}
+
+#[test]
+fn test_from_iterator() {
+    let map: BiMap<u32, String> = (0..20).map(|i| (i, i.to_string())).collect();
+    assert_eq!(map.len(), 20);
+    assert_invariants(&map);
+    assert!((0..20).all(|i| map.get_left(&i.to_string()) == Some(&i)));
+
+    // the map is allocated once for the size hint
+    let map: BiMap<u32, u32> = (0..1000).map(|i| (i, i)).collect();
+    assert_eq!(map.current_capacity(), BiMap::<u32, u32>::with_capacity(1000).current_capacity());
+
+    // a duplicate left, a duplicate right, and a pair that hits an existing left and an existing
+    // right, which collapses both of their mappings
+    let pairs = [(1, 'a'), (2, 'b'), (1, 'c'), (3, 'b'), (4, 'd'), (5, 'e'), (4, 'e')];
+    let map: BiMap<u32, char> = pairs.into_iter().collect();
+    let mut expected = BiMap::new();
+    for (left, right) in pairs {
+        expected.insert(left, right);
+    }
+    assert_same_pairs(&map, &expected);
+    assert_eq!(map.len(), 3);
+    assert_eq!(map.get_right(&1), Some(&'c'));
+    assert_eq!(map.get_right(&3), Some(&'b'));
+    assert_eq!(map.get_right(&4), Some(&'e'));
+    assert!(!map.contains_left(&2));
+    assert!(!map.contains_left(&5));
+    assert!(!map.contains_right(&'a'));
+    assert!(!map.contains_right(&'d'));
+
+    // maps with other hashers are collected with default-constructed hashers
+    let map: BiMap<u32, u32, SeededBuildHasher, SeededBuildHasher> = (0..50).map(|i| (i, i * 2)).collect();
+    assert_eq!(map.len(), 50);
+    assert_invariants(&map);
+    assert_eq!(map.get_left(&98), Some(&49));
+}
+
+#[test]
+fn test_extend() {
+    let mut map = BiMap::new();
+    for i in 0..100u32 {
+        map.insert(i * 2, i * 2 + 1000);
+    }
+    let resizes = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorded = resizes.clone();
+    map.set_resize_hook(Some(Box::new(move |event| recorded.lock().unwrap().push(event.reason))));
+
+    // half of the incoming left values are in the map, and their pairs are replaced; every tenth
+    // pair also takes the right value of another pair of the map
+    let pairs = (100..300u32).map(|i| (i, if i % 10 == 0 { i + 950 } else { i + 5000 })).collect::<Vec<_>>();
+    let mut expected = map.clone();
+    for &(left, right) in &pairs {
+        expected.insert(left, right);
+    }
+    map.extend(pairs.iter().copied());
+    assert_same_pairs(&map, &expected);
+    assert_invariants(&map);
+    assert_eq!(map.get_right(&100), Some(&1050));
+    assert_eq!(map.get_right(&101), Some(&5101));
+    assert!(!map.contains_left(&50));
+
+    // extending past the capacity resizes once for the size hint
+    assert_eq!(*resizes.lock().unwrap(), [ResizeReason::Reserve]);
+    resizes.lock().unwrap().clear();
+    let threshold = map.growth_threshold;
+    map.extend((0..threshold as u32 * 2).map(|i| (i + 10_000, i + 10_000)));
+    assert_eq!(*resizes.lock().unwrap(), [ResizeReason::Reserve]);
+    assert_invariants(&map);
+
+    // an iterator without a size hint grows the map as it goes
+    let mut map = BiMap::new();
+    map.extend((0..1000u32).filter(|i| i % 3 != 0).map(|i| (i, i)));
+    assert_eq!(map.len(), 666);
+    assert_invariants(&map);
+}
+
+#[test]
+fn test_extend_from_references() {
+    let mut map = BiMap::new();
+    for i in 0..30u32 {
+        map.insert(i, u64::from(i) + 100);
+    }
+    let mut other = BiMap::new();
+    for i in 20..60u32 {
+        other.insert(i, u64::from(i) * 1000);
+    }
+
+    let mut expected = map.clone();
+    for (&left, &right) in other.iter() {
+        expected.insert(left, right);
+    }
+    map.extend(other.iter());
+    assert_same_pairs(&map, &expected);
+    assert_invariants(&map);
+    assert_eq!(map.len(), 60);
+    assert_eq!(map.get_right(&25), Some(&25_000));
+    assert_eq!(map.get_right(&5), Some(&105));
+
+    map.extend([(&1, &7u64)]);
+    assert_eq!(map.get_left(&7), Some(&1));
+}
+
+#[test]
+fn test_from_indexed() {
+    let words = "the quick brown fox jumps over the lazy dog and the fox sleeps".split(' ');
+    let map = BiMap::from_indexed(words.clone());
+    assert_invariants(&map);
+    assert_eq!(map.len(), 10);
+
+    // indices are dense and follow the order of first occurrence
+    let mut first_occurrences = Vec::new();
+    for word in words {
+        if !first_occurrences.contains(&word) {
+            first_occurrences.push(word);
+        }
+    }
+    for (index, word) in first_occurrences.iter().enumerate() {
+        assert_eq!(map.get_right(word), Some(&index));
+        assert_eq!(map.get_left(&index), Some(word));
+    }
+    assert_eq!(map.get_right(&"the"), Some(&0));
+    assert_eq!(map.get_right(&"fox"), Some(&3));
+    assert_eq!(map.get_left(&10), None);
+
+    let map = BiMap::from_indexed((0..100u32).map(|i| i % 30));
+    assert_invariants(&map);
+    assert_eq!(map.len(), 30);
+    assert!((0..30u32).all(|i| map.get_right(&i) == Some(&(i as usize))));
+
+    let map = BiMap::from_indexed(Vec::<u32>::new());
+    assert!(map.is_empty());
+}
+
+/// The left and right values of the exhaustive tests. Under the `CollidingBuildHasher` and indices with
+/// eight slots, the left values collide in pairs at the slots 1 and 7, and the right values form
+/// one cluster starting at slot 6, so probe chains and backward shifts wrap around the end.
+const EXHAUSTIVE_LEFTS: [u8; 4] = [1, 9, 7, 15];
+const EXHAUSTIVE_RIGHTS: [u8; 4] = [6, 14, 7, 0];
+
+#[derive(Clone, Copy, Debug)]
+enum ExhaustiveOperation {
+    Insert(u8, u8),
+    RemoveLeft(u8),
+    RemoveRight(u8),
+}
+
+fn exhaustive_operations() -> Vec<ExhaustiveOperation> {
+    let mut operations = Vec::new();
+    for left in EXHAUSTIVE_LEFTS {
+        for right in EXHAUSTIVE_RIGHTS {
+            operations.push(ExhaustiveOperation::Insert(left, right));
+        }
+    }
+    operations.extend(EXHAUSTIVE_LEFTS.map(ExhaustiveOperation::RemoveLeft));
+    operations.extend(EXHAUSTIVE_RIGHTS.map(ExhaustiveOperation::RemoveRight));
+    operations
+}
+
+/// Apply all sequences of the operations up to the given length to the map, comparing the map with
+/// a pair of std maps and checking its invariants after every step. Sequences share their prefixes,
+/// so every map and oracle state is computed once by cloning it before each operation.
+fn run_exhaustive(
+    map: &BiMap<u8, u8, CollidingBuildHasher, CollidingBuildHasher>,
+    right_of: &std::collections::HashMap<u8, u8>,
+    left_of: &std::collections::HashMap<u8, u8>,
+    operations: &[ExhaustiveOperation],
+    sequence: &mut Vec<ExhaustiveOperation>,
+    max_length: usize,
+) {
+    if sequence.len() == max_length {
+        return;
+    }
+
+    for &operation in operations {
+        let mut map = map.clone();
+        let mut right_of = right_of.clone();
+        let mut left_of = left_of.clone();
+        sequence.push(operation);
+        match operation {
+            ExhaustiveOperation::Insert(left, right) => {
+                let result = map.insert(left, right);
+                let old_right = right_of.remove(&left);
+                let old_left = left_of.remove(&right);
+                if let Some(old_right) = old_right {
+                    left_of.remove(&old_right);
+                }
+                if let Some(old_left) = old_left {
+                    right_of.remove(&old_left);
+                }
+                right_of.insert(left, right);
+                left_of.insert(right, left);
+                assert_eq!(result, (old_right, old_left), "insert returned wrong values after {:?}", sequence);
+            }
+            ExhaustiveOperation::RemoveLeft(left) => {
+                let expected = right_of.remove(&left);
+                if let Some(right) = expected {
+                    left_of.remove(&right);
+                }
+                assert_eq!(map.remove_left(&left), expected, "remove_left returned wrong value after {:?}", sequence);
+            }
+            ExhaustiveOperation::RemoveRight(right) => {
+                let expected = left_of.remove(&right);
+                if let Some(left) = expected {
+                    right_of.remove(&left);
+                }
+                assert_eq!(map.remove_right(&right), expected, "remove_right returned wrong value after {:?}", sequence);
+            }
+        }
+
+        assert!(!map.is_small(), "map lost its indices after {:?}", sequence);
+        if let Err(violation) = map.check_invariants() {
+            panic!("invariant violated after {:?}: {}", sequence, violation);
+        }
+        assert_eq!(map.len(), right_of.len(), "wrong length after {:?}", sequence);
+        for value in EXHAUSTIVE_LEFTS {
+            assert_eq!(map.get_right(&value), right_of.get(&value), "wrong lookup of left {} after {:?}", value, sequence);
+        }
+        for value in EXHAUSTIVE_RIGHTS {
+            assert_eq!(map.get_left(&value), left_of.get(&value), "wrong lookup of right {} after {:?}", value, sequence);
+        }
+
+        run_exhaustive(&map, &right_of, &left_of, operations, sequence, max_length);
+        sequence.pop();
+    }
+}
+
+/// Run all operation sequences up to the given length on a map with indices of eight slots. Such
+/// a map would otherwise be a small map without indices, so the indices are allocated manually.
+fn test_exhaustive_up_to(max_length: usize) {
+    let mut map = BiMap::with_test_hashers(0, DEFAULT_CAPACITY);
+    map.left = IndexSide::with_capacity_in(8, 0, map.left.hasher, Global);
+    map.right = IndexSide::with_capacity_in(8, 0, map.right.hasher, Global);
+    map.growth_threshold = BiMap::<u8, u8, CollidingBuildHasher, CollidingBuildHasher>::growth_threshold_for(8);
+    assert_invariants(&map);
+
+    let empty = std::collections::HashMap::new();
+    run_exhaustive(&map, &empty, &empty, &exhaustive_operations(), &mut Vec::new(), max_length);
+}
+
+#[test]
+fn test_exhaustive_short_sequences() {
+    test_exhaustive_up_to(4);
+}
+
+#[test]
+#[ignore = "slow, enumerates hundreds of millions of operation sequences"]
+fn test_exhaustive_long_sequences() {
+    test_exhaustive_up_to(6);
+}
+
+#[cfg(feature = "ttl")]
+mod expiring_clock {
+    use std::cell::Cell;
+    use std::time::{Duration, Instant};
+
+    thread_local! {
+        static START: Instant = Instant::now();
+        static ELAPSED: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+    }
+
+    /// A clock that only advances when the test advances it.
+    pub(super) fn now() -> Instant {
+        START.with(|start| *start + ELAPSED.get())
+    }
+
+    pub(super) fn advance(duration: Duration) {
+        ELAPSED.set(ELAPSED.get() + duration);
+    }
+}
+
+#[test]
+#[cfg(feature = "ttl")]
+fn test_expiring_lookup_and_reinsertion() {
+    use std::time::Duration;
+    use crate::expiring::ExpiringBiMap;
+    use expiring_clock::{advance, now};
+
+    let mut map = ExpiringBiMap::new();
+    map.set_clock(now);
+    assert_eq!(map.insert_with_ttl("token-a", 1, Duration::from_secs(10)), (None, None));
+    assert_eq!(map.insert_with_ttl("token-b", 2, Duration::from_secs(20)), (None, None));
+    assert_eq!(map.insert("token-c", 3), (None, None));
+    assert_eq!(map.deadline_left(&"token-a"), Some(now() + Duration::from_secs(10)));
+    assert_eq!(map.deadline_right(&3), None);
+
+    advance(Duration::from_secs(10));
+    assert_eq!(map.get_right(&"token-a"), None);
+    assert_eq!(map.get_left(&1), None);
+    assert!(!map.contains_left(&"token-a"));
+    assert_eq!(map.get_right(&"token-b"), Some(&2));
+    assert_eq!(map.iter().count(), 2);
+    // the expired pair stays in the map until it is purged or replaced
+    assert_eq!(map.len(), 3);
+
+    // reinserting an expired value does not report the expired pair as replaced
+    assert_eq!(map.insert_with_ttl("token-a", 4, Duration::from_secs(5)), (None, None));
+    assert_eq!(map.len(), 3);
+    assert_eq!(map.get_right(&"token-a"), Some(&4));
+    assert_eq!(map.insert_with_ttl("token-d", 2, Duration::from_secs(5)), (None, Some("token-b")));
+    assert_eq!(map.get_left(&2), Some(&"token-d"));
+    assert_eq!(map.remove_left(&"token-c"), Some(3));
+
+    advance(Duration::from_secs(5));
+    assert_eq!(map.remove_right(&4), None);
+    assert_eq!(map.len(), 1);
+    assert!(map.iter().next().is_none());
+    assert_invariants(map.as_bimap());
+}
+
+#[test]
+#[cfg(feature = "ttl")]
+fn test_expiring_purge() {
+    use std::time::Duration;
+    use crate::expiring::ExpiringBiMap;
+
+    // a hashed map, where purging moves buckets into the holes of the purged pairs
+    let start = std::time::Instant::now();
+    let mut map = ExpiringBiMap::new();
+    for i in 0..200u32 {
+        let deadline = (i % 4 != 0).then(|| start + Duration::from_secs(i as u64 % 3 + 1));
+        map.insert_with_deadline(i, i + 1000, deadline);
+    }
+
+    assert_eq!(map.purge_expired(start), 0);
+    // pairs with i % 3 == 0 expire after one second, unless they never expire
+    let expected = (0..200).filter(|i| i % 4 != 0 && i % 3 == 0).count();
+    assert_eq!(map.purge_expired(start + Duration::from_secs(1)), expected);
+    assert_eq!(map.len(), 200 - expected);
+    assert_invariants(map.as_bimap());
+    for i in 0..200u32 {
+        let purged = i % 4 != 0 && i % 3 == 0;
+        assert_eq!(map.as_bimap().get_right(&i).is_some(), !purged);
+        assert_eq!(map.as_bimap().get_left(&(i + 1000)).is_some(), !purged);
+    }
+
+    assert_eq!(map.purge_expired(start + Duration::from_secs(3)), 150 - expected);
+    assert_eq!(map.len(), 50);
+    assert!(map.as_bimap().iter().all(|(left, _)| left % 4 == 0));
+    assert_invariants(map.as_bimap());
+}
+
+#[test]
+fn test_id_allocator() {
+    let mut ids = IdAllocator::new();
+    // without releases, the ids are dense and follow the order of allocation
+    for (i, name) in ["a", "b", "c", "d", "e"].into_iter().enumerate() {
+        assert_eq!(ids.allocate(name.to_string()), i as u32);
+    }
+    assert_eq!(ids.allocate("c".to_string()), 2);
+    assert_eq!(ids.id_bound(), 5);
+
+    // released ids are reused in last-in, first-out order
+    assert_eq!(ids.release_left(&"b".to_string()), Some(1));
+    assert_eq!(ids.release_id(3), Some("d".to_string()));
+    assert_eq!(ids.release_left(&"b".to_string()), None);
+    assert_eq!(ids.release_id(3), None);
+    assert_eq!(ids.allocate("f".to_string()), 3);
+    assert_eq!(ids.allocate("g".to_string()), 1);
+    assert_eq!(ids.allocate("h".to_string()), 5);
+    assert_eq!(ids.get_left(1), Some(&"g".to_string()));
+    assert_eq!(ids.get_id(&"d".to_string()), None);
+
+    ids.release_all();
+    assert!(ids.is_empty());
+    assert_eq!(ids.id_bound(), 6);
+    assert_eq!(ids.allocate("x".to_string()), 0);
+    assert_eq!(ids.allocate("y".to_string()), 1);
+    ids.clear();
+    assert_eq!(ids.id_bound(), 0);
+    assert_eq!(ids.allocate("z".to_string()), 0);
+
+    // random allocations and releases never assign an id twice and stay below the peak length
+    let mut ids = IdAllocator::new();
+    let mut peak = 0;
+    let mut state = 0x2545_f491_u64;
+    for _ in 0..5000 {
+        state = state.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+        let value = (state >> 40) as u32 % 300;
+        if (state >> 60) < 9 {
+            ids.allocate(value);
+        } else {
+            ids.release_left(&value);
+        }
+        peak = peak.max(ids.len());
+        assert_eq!(ids.id_bound() as usize, peak);
+    }
+    let assigned = ids.iter().map(|(_, id)| id).collect::<HashSet<_>>();
+    assert_eq!(assigned.len(), ids.len());
+    assert!(assigned.iter().all(|&id| id < ids.id_bound()));
+    assert_invariants(ids.as_bimap());
+}
+
+#[test]
+#[cfg(feature = "annotated")]
+fn test_annotated_bimap() {
+    use crate::annotated::AnnotatedBiMap;
+
+    // the payload of every pair is its right value, so it can be checked after every change
+    let mut map = AnnotatedBiMap::new();
+    for i in 0..40u32 {
+        assert_eq!(map.insert_with(i, i + 100, i + 100), (None, None));
+    }
+    assert_eq!(map.get_meta_by_left(&3), Some(&103));
+    assert_eq!(map.get_meta_by_right(&104), Some(&104));
+    assert_eq!(map.get_meta_by_left(&40), None);
+
+    // payloads are mutated in place, through either side and while iterating
+    *map.get_meta_mut_by_left(&5).unwrap() += 1000;
+    *map.get_meta_mut_by_right(&106).unwrap() += 1000;
+    assert_eq!(map.get_meta_mut_by_right(&140), None);
+    assert_eq!(map.get_meta_by_right(&105), Some(&1105));
+    assert_eq!(map.get_meta_by_left(&6), Some(&1106));
+    for (_, _, meta) in map.iter_mut() {
+        *meta %= 1000;
+    }
+
+    // removals move the last bucket into the gap, and its payload moves along
+    assert_eq!(map.remove_left(&0), Some((100, 100)));
+    assert_eq!(map.remove_right(&110), Some((10, 110)));
+    assert_eq!(map.remove_left(&0), None);
+    assert!(map.iter().all(|(_, &right, &meta)| right == meta));
+
+    // a replacement returns the payloads of both replaced pairs
+    assert_eq!(map.insert_with(1, 102, 7), (Some((101, 101)), Some((2, 102))));
+    assert_eq!(map.get_meta_by_left(&1), Some(&7));
+    assert_eq!(map.get_meta_by_right(&101), None);
+    assert_eq!(map.len(), 37);
+    assert!(map.iter().filter(|&(&left, _, _)| left != 1).all(|(_, &right, &meta)| right == meta));
+    assert_invariants(map.as_bimap());
+
+    map.clear();
+    assert!(map.is_empty());
+    assert_eq!(map.iter().count(), 0);
+}
+
+#[test]
+fn test_journal_undo_redo() {
+    fn sorted_pairs(map: &BiMap<u32, u32>) -> Vec<(u32, u32)> {
+        let mut pairs = map.iter().map(|(&left, &right)| (left, right)).collect::<Vec<_>>();
+        pairs.sort_unstable();
+        pairs
+    }
+
+    // a hashed map, so that removals move buckets around
+    let mut map = BiMap::new();
+    for i in 0..20 {
+        map.insert(i, i + 100);
+    }
+    map.enable_journal();
+    let initial = sorted_pairs(&map);
+
+    let mut snapshots = vec![initial.clone()];
+    let mut edit = |map: &mut BiMap<u32, u32>, edit: &dyn Fn(&mut BiMap<u32, u32>)| {
+        edit(map);
+        assert_invariants(map);
+        snapshots.push(sorted_pairs(map));
+    };
+    edit(&mut map, &|map| { map.insert(50, 150); });
+    // replaces the right value of 3, then the left value of 104
+    edit(&mut map, &|map| { map.insert(3, 200); });
+    edit(&mut map, &|map| { map.insert(60, 104); });
+    // collapses the pairs of 5 and 106 into one
+    edit(&mut map, &|map| { map.insert(5, 106); });
+    edit(&mut map, &|map| { map.remove_left(&7); });
+    edit(&mut map, &|map| { map.remove_right(&150); });
+    edit(&mut map, &|map| { *map.get_right_mut(&8).unwrap() = 300; });
+    edit(&mut map, &|map| { map.try_insert(70, 170).unwrap(); });
+    edit(&mut map, &|map| map.clear());
+    edit(&mut map, &|map| { map.insert(1, 1); });
+    let last = snapshots.len() - 1;
+
+    // walking a cursor does not change the map, so it records no entry
+    map.cursor_mut().next();
+
+    for expected in snapshots[..last].iter().rev() {
+        assert!(map.undo());
+        assert_invariants(&map);
+        assert_eq!(&sorted_pairs(&map), expected);
+    }
+    assert!(!map.undo());
+    assert_eq!(sorted_pairs(&map), initial);
+
+    for expected in &snapshots[1..] {
+        assert!(map.redo());
+        assert_invariants(&map);
+        assert_eq!(&sorted_pairs(&map), expected);
+    }
+    assert!(!map.redo());
+
+    // undoing steps back through the snapshots, and a new change discards the undone entries
+    assert!(map.undo());
+    assert!(map.undo());
+    assert_eq!(sorted_pairs(&map), snapshots[last - 2]);
+    map.insert(80, 180);
+    assert!(!map.redo());
+    assert!(map.undo());
+    assert_eq!(sorted_pairs(&map), snapshots[last - 2]);
+
+    // the limit keeps the most recent entries
+    map.set_journal_limit(2);
+    map.insert(81, 181);
+    map.insert(82, 182);
+    map.insert(83, 183);
+    assert!(map.undo());
+    assert!(map.undo());
+    assert!(!map.undo());
+    assert_eq!(sorted_pairs(&map).last(), Some(&(81, 181)));
+
+    map.truncate_journal();
+    assert!(!map.undo());
+    assert!(!map.redo());
+    map.disable_journal();
+    map.insert(84, 184);
+    assert!(!map.undo());
+}
+
+#[test]
+fn test_clone_starts_without_journal() {
+    let mut map: BiMap<u32, u32> = (0..20).map(|i| (i, i + 100)).collect();
+    map.enable_journal();
+    map.insert(50, 150);
+
+    let mut clone = map.clone();
+    assert!(!clone.undo());
+    clone.insert(60, 160);
+    assert!(!clone.undo());
+    assert!(clone.contains_left(&60));
+
+    // clone_from discards the journal of the target map
+    let mut scratch: BiMap<u32, u32> = BiMap::new();
+    scratch.enable_journal();
+    scratch.insert(1, 1);
+    scratch.clone_from(&map);
+    assert!(!scratch.undo());
+    assert_eq!(scratch, map);
+
+    // the journal of the source is untouched
+    assert!(map.undo());
+    assert!(!map.contains_left(&50));
+}
+
+#[test]
+#[cfg(feature = "stats")]
+fn test_op_counters() {
+    // values hash to themselves modulo 16 and the indices have exactly 16 slots, so the probe steps
+    // are known. Tables do not count probe steps.
+    let step = if cfg!(feature = "hashbrown-backend") { 0 } else { 1 };
+    let mut map = BiMap::with_test_hashers(16, 16);
+    map.insert(1, 1);
+    map.insert(17, 2);
+    assert_eq!(map.op_counters(), OpCounters { inserts: 2, removals: 0, replacements: 0, rehashes: 0, probe_steps: step });
+
+    // both insertions of 17 find it one slot past its ideal index again, but only the first one
+    // replaces a pair
+    assert_eq!(map.insert(17, 3), (Some(2), None));
+    assert_eq!(map.insert(17, 3), (Some(3), Some(17)));
+    assert_eq!(map.remove_left(&1), Some(1));
+    assert_eq!(map.remove_right(&3), Some(17));
+    assert_eq!(map.remove_left(&5), None);
+    assert_eq!(map.op_counters(), OpCounters { inserts: 3, removals: 3, replacements: 1, rehashes: 0, probe_steps: 3 * step });
+
+    // clones and equality ignore the counters
+    assert_eq!(map.clone().op_counters(), OpCounters::default());
+    assert_eq!(map.clone(), map);
+
+    map.reset_op_counters();
+    assert_eq!(map.op_counters(), OpCounters::default());
+
+    // 14 pairs fit into 16 slots, so the 15th pair grows the indices once. None of them collide
+    for value in 0..15 {
+        map.insert(value, value + 100);
+    }
+    assert_eq!(map.current_capacity(), 32);
+    assert_eq!(map.op_counters(), OpCounters { inserts: 15, removals: 0, replacements: 0, rehashes: 1, probe_steps: 0 });
+
+    map.shrink_to_fit();
+    map.clear();
+    assert_eq!(map.op_counters(), OpCounters { inserts: 15, removals: 15, replacements: 0, rehashes: 2, probe_steps: 0 });
+}
+
+#[cfg(feature = "quickcheck")]
+#[test]
+fn test_quickcheck_round_trip() {
+    fn round_trip(map: BiMap<u8, u16>) -> bool {
+        map.iter().all(|(left, right)| {
+            let mut map = map.clone();
+            map.get_left(right) == Some(left)
+                && map.remove_left(left) == Some(*right)
+                && !map.contains_right(right)
+        })
+    }
+
+    quickcheck::quickcheck(round_trip as fn(BiMap<u8, u16>) -> bool);
+}
+
+#[cfg(feature = "quickcheck")]
+#[test]
+fn test_quickcheck_shrinks_to_subsets() {
+    use quickcheck::Arbitrary;
+
+    let map: BiMap<u32, u32> = (0..10).map(|i| (i, i * 10)).collect();
+    let shrunk: Vec<_> = map.shrink().collect();
+    assert!(shrunk[0].is_empty());
+    for smaller in &shrunk {
+        assert_invariants(smaller);
+        assert!(smaller.len() < map.len());
+        assert!(smaller.iter().all(|(left, right)| map.get_right(left) == Some(right)));
+    }
+    // every single pair can be removed
+    assert_eq!(shrunk.iter().filter(|smaller| smaller.len() == 9).count(), 10);
+
+    // a property that fails for maps with three or more pairs shrinks to a map with three pairs
+    let result = std::panic::catch_unwind(|| {
+        quickcheck::QuickCheck::new().quickcheck((|map: BiMap<u8, u8>| map.len() < 3) as fn(BiMap<u8, u8>) -> bool);
+    });
+    let message = result.unwrap_err();
+    let message = message.downcast_ref::<String>().unwrap();
+    assert_eq!(message.matches("<->").count(), 3, "{message}");
+}
+
+#[cfg(not(feature = "hashbrown-backend"))]
+#[test]
+fn test_same_seed_same_layout() {
+    fn build(seed: u64) -> BiMap<u64, String, FixedState, FixedState> {
+        let mut map = BiMap::with_seed(0, seed);
+        for i in 0..2000u64 {
+            map.insert(i.wrapping_mul(0x9e37_79b9_7f4a_7c15), format!("value {}", i % 1500));
+            if i % 7 == 0 {
+                map.remove_left(&(i / 2).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+            }
+        }
+        map
+    }
+
+    let (first, second) = (build(286), build(286));
+    assert!(first.iter().eq(second.iter()));
+    assert_eq!(first.debug_table(), second.debug_table());
+    assert_eq!(first.left, second.left);
+    assert_eq!(first.right, second.right);
+    assert_eq!(first.longest_probe, second.longest_probe);
+    #[cfg(feature = "stats")]
+    assert_eq!(first.op_counters(), second.op_counters());
+
+    // another seed hashes the values to other slots
+    let other = build(287);
+    assert_eq!(other, first);
+    assert_ne!(other.left.slots, first.left.slots);
+    assert_eq!(*first.hasher_left(), FixedState::new(286));
+}
@@ -0,0 +1,200 @@
+//! Transactions, which apply a series of operations to a map and undo all of them if one fails.
+
+use std::hash::{BuildHasher, Hash, RandomState};
+use std::ops::Deref;
+use allocator_api2::alloc::{Allocator, Global};
+use crate::BiMap;
+use crate::side::{LeftSide, RightSide};
+use crate::storage::Bucket;
+
+/// The pairs displaced by [`Txn::replace`]: the pair of the left value and the pair of the right
+/// value, if any.
+pub type DisplacedPairs<'a, T, U> = (Option<(&'a T, &'a U)>, Option<(&'a T, &'a U)>);
+
+/// An operation of a transaction, recorded with everything that is required to undo it. Undoing
+/// the operations in reverse order restores the positions of the buckets as well, so the recorded
+/// bucket positions are valid when the operation is undone.
+#[derive(Debug)]
+enum Undo<T, U> {
+    /// A new pair was pushed to the tail of the data array.
+    Pushed,
+    /// The left value of the pair at `bucket_index` replaced `left`.
+    ReplacedLeft { bucket_index: usize, left: T },
+    /// The right value of the pair at `bucket_index` replaced `right`.
+    ReplacedRight { bucket_index: usize, right: U },
+    /// An insertion mapped the left value of one pair to the right value of another. The pair
+    /// ended up at `position`, and its old right value was `right`. The other pair was removed from
+    /// `right_bucket`, and its left value was `left`.
+    Collapsed { position: usize, right_bucket: usize, left: T, right: U },
+    /// The pair was removed from `bucket_index`.
+    Removed { bucket_index: usize, pair: Bucket<T, U> },
+}
+
+/// The operations of a transaction, see [`BiMap::transaction`].
+///
+/// The transaction dereferences to the map, so lookups see the operations applied so far. The
+/// values that operations replace or remove are kept until the transaction ends, in case it has to
+/// be undone, so the operations return references to them instead of the values themselves.
+#[derive(Debug)]
+pub struct Txn<'a, T, U, H = RandomState, RH = RandomState, A: Allocator + Clone = Global>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    map: &'a mut BiMap<T, U, H, RH, A>,
+    log: Vec<Undo<T, U>>,
+}
+
+impl<'a, T, U, H, RH, A: Allocator + Clone> Txn<'a, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    pub(crate) fn new(map: &'a mut BiMap<T, U, H, RH, A>) -> Self {
+        Txn { map, log: Vec::new() }
+    }
+
+    /// Inserts a value pair into the map, like [`BiMap::insert`]. Returns the right value that was
+    /// mapped to the left value and the left value that was mapped to the right value, if any.
+    pub fn insert(&mut self, left: T, right: U) -> (Option<&U>, Option<&T>) {
+        let (bucket_index, changed) = self.apply_insert(left, right);
+        if !changed {
+            return (Some(self.map.data.right(bucket_index)), Some(self.map.data.left(bucket_index)));
+        }
+
+        match self.log.last() {
+            Some(Undo::ReplacedLeft { left, .. }) => (None, Some(left)),
+            Some(Undo::ReplacedRight { right, .. }) => (Some(right), None),
+            Some(Undo::Collapsed { left, right, .. }) => (Some(right), Some(left)),
+            _ => (None, None),
+        }
+    }
+
+    /// Inserts a value pair into the map, like [`insert`], but returns the pairs it displaced: the
+    /// pair of the left value and the pair of the right value, if any. If the map contained the
+    /// pair already, it is returned as both.
+    ///
+    /// [`insert`]: #method.insert
+    pub fn replace(&mut self, left: T, right: U) -> DisplacedPairs<'_, T, U> {
+        let (bucket_index, changed) = self.apply_insert(left, right);
+        let (left, right) = (self.map.data.left(bucket_index), self.map.data.right(bucket_index));
+        if !changed {
+            return (Some((left, right)), Some((left, right)));
+        }
+
+        match self.log.last() {
+            Some(Undo::ReplacedLeft { left: old_left, .. }) => (None, Some((old_left, right))),
+            Some(Undo::ReplacedRight { right: old_right, .. }) => (Some((left, old_right)), None),
+            Some(Undo::Collapsed { left: old_left, right: old_right, .. }) => {
+                (Some((left, old_right)), Some((old_left, right)))
+            }
+            _ => (None, None),
+        }
+    }
+
+    /// Insert a value pair with [`BiMap::insert`] and record how to undo it. Returns the position
+    /// of the pair, and false if the map contained the pair already, in which case nothing is
+    /// recorded.
+    fn apply_insert(&mut self, left: T, right: U) -> (usize, bool) {
+        let tail = self.map.len().wrapping_sub(1);
+        let left_bucket = self.map.find_bucket_left(&left);
+        let right_bucket = self.map.find_bucket_right(&right);
+        let (replaced_right, replaced_left) = self.map.insert(left, right);
+
+        let (bucket_index, undo) = match (left_bucket, right_bucket) {
+            (None, None) => (self.map.len() - 1, Undo::Pushed),
+            (Some(bucket_index), None) => (bucket_index, Undo::ReplacedRight {
+                bucket_index,
+                right: replaced_right.expect("the left value was mapped to a right value"),
+            }),
+            (None, Some(bucket_index)) => (bucket_index, Undo::ReplacedLeft {
+                bucket_index,
+                left: replaced_left.expect("the right value was mapped to a left value"),
+            }),
+            (Some(left_bucket), Some(right_bucket)) if left_bucket == right_bucket => {
+                // the pair was in the map already, so nothing changed
+                return (left_bucket, false);
+            }
+            (Some(left_bucket), Some(right_bucket)) => {
+                // removing the right bucket moves the tail into its position
+                let position = if left_bucket == tail { right_bucket } else { left_bucket };
+                (position, Undo::Collapsed {
+                    position,
+                    right_bucket,
+                    left: replaced_left.expect("the right value was mapped to a left value"),
+                    right: replaced_right.expect("the left value was mapped to a right value"),
+                })
+            }
+        };
+        self.log.push(undo);
+        (bucket_index, true)
+    }
+
+    /// Deletes the mappings for the given left value, like [`BiMap::remove_left`], and returns the
+    /// right value that was mapped to it. If the left value is not in the map, None is returned.
+    pub fn remove_left(&mut self, left: &T) -> Option<&U> {
+        let bucket_index = self.map.find_bucket_left(left)?;
+        self.remove(bucket_index).map(|pair| &pair.right)
+    }
+
+    /// Deletes the mappings for the given right value, like [`BiMap::remove_right`], and returns the
+    /// left value that was mapped to it. If the right value is not in the map, None is returned.
+    pub fn remove_right(&mut self, right: &U) -> Option<&T> {
+        let bucket_index = self.map.find_bucket_right(right)?;
+        self.remove(bucket_index).map(|pair| &pair.left)
+    }
+
+    fn remove(&mut self, bucket_index: usize) -> Option<&Bucket<T, U>> {
+        let pair = self.map.remove_bucket(bucket_index);
+        self.map.rebuild_filter_if_stale();
+        self.log.push(Undo::Removed { bucket_index, pair });
+
+        match self.log.last() {
+            Some(Undo::Removed { pair, .. }) => Some(pair),
+            _ => None,
+        }
+    }
+
+    /// Keep the operations applied so far.
+    pub(crate) fn commit(&mut self) {
+        self.log.clear();
+    }
+
+    /// Undo the operations applied so far, in reverse order.
+    fn rollback(&mut self) {
+        while let Some(undo) = self.log.pop() {
+            match undo {
+                Undo::Pushed => {
+                    self.map.remove_bucket(self.map.len() - 1);
+                }
+                Undo::ReplacedLeft { bucket_index, left } => {
+                    self.map.replace_at::<LeftSide>(bucket_index, left);
+                }
+                Undo::ReplacedRight { bucket_index, right } => {
+                    self.map.replace_at::<RightSide>(bucket_index, right);
+                }
+                Undo::Collapsed { position, right_bucket, left, right } => {
+                    let moved_right = self.map.replace_at::<RightSide>(position, right);
+                    self.map.restore_bucket(right_bucket, Bucket { left, right: moved_right });
+                }
+                Undo::Removed { bucket_index, pair } => self.map.restore_bucket(bucket_index, pair),
+            }
+        }
+        self.map.rebuild_filter_if_stale();
+    }
+}
+
+impl<T, U, H, RH, A: Allocator + Clone> Deref for Txn<'_, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    type Target = BiMap<T, U, H, RH, A>;
+
+    fn deref(&self) -> &Self::Target {
+        self.map
+    }
+}
+
+impl<T, U, H, RH, A: Allocator + Clone> Drop for Txn<'_, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    /// Undo the operations of a transaction that was not committed, because it failed or panicked.
+    fn drop(&mut self) {
+        self.rollback();
+    }
+}
@@ -0,0 +1,222 @@
+//! Guards that give mutable access to the values of a map, and keep the hash indices consistent
+//! with the mutated values.
+
+use std::fmt;
+use std::hash::{BuildHasher, Hash, RandomState};
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::thread;
+use allocator_api2::alloc::{Allocator, Global};
+use crate::{BiMap, Side};
+use crate::side::{LeftSide, RightSide};
+
+/// Removes a bucket whose mutated value has no mapping in its index, if re-indexing the value
+/// panics. The guard is forgotten once the value is indexed again.
+struct RemoveOnUnwind<'m, T, U, H, RH, A: Allocator + Clone>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    map: &'m mut BiMap<T, U, H, RH, A>,
+    bucket_index: usize,
+    side: Side,
+}
+
+impl<T, U, H, RH, A: Allocator + Clone> Drop for RemoveOnUnwind<'_, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    fn drop(&mut self) {
+        match self.side {
+            Side::Left => self.map.remove_unindexed::<LeftSide>(self.bucket_index),
+            Side::Right => self.map.remove_unindexed::<RightSide>(self.bucket_index),
+        };
+    }
+}
+
+/// Mutable access to a left value of a map, returned by [`BiMap::get_left_mut`].
+///
+/// The guard dereferences to the left value. The first mutable dereference removes the value from
+/// the left index, and dropping the guard (or calling [`commit`](LeftMut::commit)) inserts the
+/// mutated value again, at the position of its new hash. Guards that are only read from leave the
+/// index untouched.
+///
+/// If the mutated value equals the left value of another pair, the mutated pair is removed from the
+/// map, since the map cannot hold both. Dropping the guard then panics, unless the thread is already
+/// panicking, while [`commit`](LeftMut::commit) returns the removed pair. If the `Hash` or `Eq`
+/// implementation panics while the value is indexed again, the mutated pair is removed as well.
+///
+/// Leaking the guard, e.g. with [`mem::forget`], after mutating the value leaves the value without
+/// its mapping, so lookups and removals of the pair fail until the map is cleared.
+pub struct LeftMut<'a, T, U, H = RandomState, RH = RandomState, A: Allocator + Clone = Global>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    map: &'a mut BiMap<T, U, H, RH, A>,
+    bucket_index: usize,
+    unindexed: bool,
+}
+
+impl<'a, T, U, H, RH, A: Allocator + Clone> LeftMut<'a, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    pub(crate) fn new(map: &'a mut BiMap<T, U, H, RH, A>, bucket_index: usize) -> Self {
+        LeftMut { map, bucket_index, unindexed: false }
+    }
+
+    /// Insert the mutated value into the left index again, and return the removed pair if the
+    /// mutated value equals the left value of another pair.
+    pub fn commit(mut self) -> Result<(), (T, U)> {
+        self.reindex()
+    }
+
+    fn reindex(&mut self) -> Result<(), (T, U)> {
+        if !mem::take(&mut self.unindexed) {
+            return Ok(());
+        }
+
+        let guard = RemoveOnUnwind { map: &mut *self.map, bucket_index: self.bucket_index, side: Side::Left };
+        let reindexed = guard.map.reindex::<LeftSide>(self.bucket_index);
+        mem::forget(guard);
+        if reindexed.is_ok() {
+            self.map.notify_insert(self.bucket_index);
+        }
+        // a mutated pair that was removed ends the journal entry without an insertion
+        self.map.seal_journal();
+
+        self.map.reseed_if_pathological();
+        self.map.rebuild_filter_if_stale();
+        reindexed.map_err(|bucket| (bucket.left, bucket.right))
+    }
+}
+
+impl<T, U, H, RH, A: Allocator + Clone> Deref for LeftMut<'_, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.map.data.left(self.bucket_index)
+    }
+}
+
+impl<T, U, H, RH, A: Allocator + Clone> DerefMut for LeftMut<'_, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    fn deref_mut(&mut self) -> &mut T {
+        if !self.unindexed {
+            self.map.unindex::<LeftSide>(self.bucket_index);
+            self.unindexed = true;
+        }
+        self.map.data.left_mut(self.bucket_index)
+    }
+}
+
+impl<T, U, H, RH, A: Allocator + Clone> Drop for LeftMut<'_, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    fn drop(&mut self) {
+        if self.reindex().is_err() && !thread::panicking() {
+            panic!("the mutated left value equals the left value of another pair, so its pair was removed");
+        }
+    }
+}
+
+impl<T, U, H, RH, A: Allocator + Clone> fmt::Debug for LeftMut<'_, T, U, H, RH, A>
+    where T: Hash + Eq + fmt::Debug, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("LeftMut").field(&**self).finish()
+    }
+}
+
+/// Mutable access to a right value of a map, returned by [`BiMap::get_right_mut`].
+///
+/// The guard dereferences to the right value. The first mutable dereference removes the value from
+/// the right index, and dropping the guard (or calling [`commit`](RightMut::commit)) inserts the
+/// mutated value again, at the position of its new hash. Guards that are only read from leave the
+/// index untouched.
+///
+/// If the mutated value equals the right value of another pair, the mutated pair is removed from
+/// the map, since the map cannot hold both. Dropping the guard then panics, unless the thread is
+/// already panicking, while [`commit`](RightMut::commit) returns the removed pair. If the `Hash` or
+/// `Eq` implementation panics while the value is indexed again, the mutated pair is removed as well.
+///
+/// Leaking the guard, e.g. with [`mem::forget`], after mutating the value leaves the value without
+/// its mapping, so lookups and removals of the pair fail until the map is cleared.
+pub struct RightMut<'a, T, U, H = RandomState, RH = RandomState, A: Allocator + Clone = Global>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    map: &'a mut BiMap<T, U, H, RH, A>,
+    bucket_index: usize,
+    unindexed: bool,
+}
+
+impl<'a, T, U, H, RH, A: Allocator + Clone> RightMut<'a, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    pub(crate) fn new(map: &'a mut BiMap<T, U, H, RH, A>, bucket_index: usize) -> Self {
+        RightMut { map, bucket_index, unindexed: false }
+    }
+
+    /// Insert the mutated value into the right index again, and return the removed pair if the
+    /// mutated value equals the right value of another pair.
+    pub fn commit(mut self) -> Result<(), (T, U)> {
+        self.reindex()
+    }
+
+    fn reindex(&mut self) -> Result<(), (T, U)> {
+        if !mem::take(&mut self.unindexed) {
+            return Ok(());
+        }
+
+        let guard = RemoveOnUnwind { map: &mut *self.map, bucket_index: self.bucket_index, side: Side::Right };
+        let reindexed = guard.map.reindex::<RightSide>(self.bucket_index);
+        mem::forget(guard);
+        if reindexed.is_ok() {
+            self.map.notify_insert(self.bucket_index);
+        }
+        // a mutated pair that was removed ends the journal entry without an insertion
+        self.map.seal_journal();
+
+        self.map.reseed_if_pathological();
+        self.map.rebuild_filter_if_stale();
+        reindexed.map_err(|bucket| (bucket.left, bucket.right))
+    }
+}
+
+impl<T, U, H, RH, A: Allocator + Clone> Deref for RightMut<'_, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    type Target = U;
+
+    fn deref(&self) -> &U {
+        self.map.data.right(self.bucket_index)
+    }
+}
+
+impl<T, U, H, RH, A: Allocator + Clone> DerefMut for RightMut<'_, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    fn deref_mut(&mut self) -> &mut U {
+        if !self.unindexed {
+            self.map.unindex::<RightSide>(self.bucket_index);
+            self.unindexed = true;
+        }
+        self.map.data.right_mut(self.bucket_index)
+    }
+}
+
+impl<T, U, H, RH, A: Allocator + Clone> Drop for RightMut<'_, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq, H: BuildHasher, RH: BuildHasher
+{
+    fn drop(&mut self) {
+        if self.reindex().is_err() && !thread::panicking() {
+            panic!("the mutated right value equals the right value of another pair, so its pair was removed");
+        }
+    }
+}
+
+impl<T, U, H, RH, A: Allocator + Clone> fmt::Debug for RightMut<'_, T, U, H, RH, A>
+    where T: Hash + Eq, U: Hash + Eq + fmt::Debug, H: BuildHasher, RH: BuildHasher
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RightMut").field(&**self).finish()
+    }
+}
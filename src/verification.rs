@@ -0,0 +1,113 @@
+//! Kani proof harnesses for the probing and backward-shift logic of the indices. The harnesses use
+//! a map of fixed index capacity and a modular hasher over a small universe of values, so that
+//! values collide and probe chains wrap around the end of the indices. Run them with `cargo kani`.
+
+use super::*;
+use std::hash::Hasher;
+
+/// The capacity of the indices of the verified maps. It is larger than the small map capacity, so
+/// the maps use their indices from the start.
+const CAPACITY: usize = SMALL_MAP_CAPACITY + 1;
+
+/// The values of the verified maps are drawn from `0..UNIVERSE`.
+const UNIVERSE: u8 = 2 * CAPACITY as u8;
+
+/// The number of pairs inserted before the operation under verification. It stays below the
+/// growth threshold of the indices, so the capacity does not change.
+const PREFILL: usize = 4;
+
+/// A hasher that hashes a value to itself modulo the index capacity. All values of the universe
+/// share their ideal index with another value.
+#[derive(Clone, Copy, Default)]
+struct ModularHasher {
+    state: u64,
+}
+
+impl Hasher for ModularHasher {
+    fn finish(&self) -> u64 {
+        self.state % CAPACITY as u64
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state = (self.state << 8) | byte as u64;
+        }
+    }
+}
+
+impl BuildHasher for ModularHasher {
+    type Hasher = ModularHasher;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        ModularHasher::default()
+    }
+}
+
+type VerifiedMap = BiMap<u8, u8, ModularHasher, ModularHasher>;
+
+/// A nondeterministic value of the universe.
+fn any_value() -> u8 {
+    kani::any_where(|value: &u8| *value < UNIVERSE)
+}
+
+/// A map of nondeterministic pairs, whose indices have not grown.
+fn any_map() -> VerifiedMap {
+    let mut map = BiMap::with_hashers(CAPACITY, ModularHasher::default(), ModularHasher::default());
+    for _ in 0..PREFILL {
+        map.insert(any_value(), any_value());
+    }
+    assert_eq!(map.current_capacity(), CAPACITY);
+    map
+}
+
+#[kani::proof]
+#[kani::unwind(12)]
+fn insert_then_get() {
+    let mut map = any_map();
+    let (left, right) = (any_value(), any_value());
+
+    map.insert(left, right);
+
+    assert_eq!(map.get_right(&left), Some(&right));
+    assert_eq!(map.get_left(&right), Some(&left));
+}
+
+#[kani::proof]
+#[kani::unwind(12)]
+fn remove_left_misses_both_directions() {
+    let mut map = any_map();
+    let (left, right) = (any_value(), any_value());
+    map.insert(left, right);
+
+    assert_eq!(map.remove_left(&left), Some(right));
+
+    assert!(map.get_right(&left).is_none());
+    assert!(map.get_left(&right).is_none());
+}
+
+/// The unwinding bound makes Kani prove that no probe loop runs longer than the capacity of the
+/// indices, and its bounds checks prove that no slot refers past the stored pairs.
+#[kani::proof]
+#[kani::unwind(12)]
+fn operations_keep_invariants() {
+    let mut map = any_map();
+
+    for _ in 0..2 {
+        match kani::any::<u8>() % 4 {
+            0 => {
+                map.insert(any_value(), any_value());
+            }
+            1 => {
+                map.remove_left(&any_value());
+            }
+            2 => {
+                map.remove_right(&any_value());
+            }
+            _ => {
+                let _ = map.try_insert(any_value(), any_value());
+            }
+        }
+    }
+
+    assert!(map.check_invariants().is_ok());
+}
\ No newline at end of file
@@ -0,0 +1,204 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::hash::RandomState;
+use std::ptr::NonNull;
+use std::rc::Rc;
+use allocator_api2::alloc::{AllocError, Allocator};
+use bijective_map::BiMap;
+
+/// A global allocator that counts the allocations made by the current thread, so that the test
+/// harness running on other threads does not interfere with the counts.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|allocations| allocations.set(allocations.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|allocations| allocations.set(allocations.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// The allocator of a single map, which counts its allocations and the bytes that are currently
+/// allocated in it. It allocates from the system allocator, so its allocations are not counted by
+/// the global allocator.
+#[derive(Clone, Default)]
+struct MapAllocator {
+    allocations: Rc<Cell<usize>>,
+    live_bytes: Rc<Cell<usize>>,
+}
+
+unsafe impl Allocator for MapAllocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let memory = System.allocate(layout)?;
+        self.allocations.set(self.allocations.get() + 1);
+        self.live_bytes.set(self.live_bytes.get() + layout.size());
+        Ok(memory)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.live_bytes.set(self.live_bytes.get() - layout.size());
+        System.deallocate(ptr, layout)
+    }
+}
+
+type CountedBiMap = BiMap<u64, u64, RandomState, RandomState, MapAllocator>;
+
+/// The number of allocations that hold the pairs themselves.
+const PAIR_ALLOCATIONS: usize = if cfg!(feature = "columnar") { 2 } else { 1 };
+
+/// The number of allocations that hold one index: its slots, probe distances and back-references,
+/// or its hashbrown table and hashes.
+const INDEX_ALLOCATIONS: usize = if cfg!(feature = "hashbrown-backend") { 2 } else { 3 };
+
+/// The number of allocations that resizing one index replaces: its slots and probe distances, or
+/// its hashbrown table.
+const RESIZE_ALLOCATIONS: usize = if cfg!(feature = "hashbrown-backend") { 1 } else { 2 };
+
+/// Count the allocations performed by `f` on the current thread.
+fn count_allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+#[test]
+fn test_allocation_counts() {
+    // small maps only allocate their pairs
+    let (_, allocations) = count_allocations(BiMap::<u64, u64>::new);
+    assert_eq!(allocations, PAIR_ALLOCATIONS);
+
+    // the pairs, and both indices
+    let (mut map, allocations) = count_allocations(|| BiMap::<u64, u64>::with_capacity(1000));
+    assert_eq!(allocations, PAIR_ALLOCATIONS + 2 * INDEX_ALLOCATIONS);
+
+    let (_, allocations) = count_allocations(|| {
+        for i in 0..1000 {
+            map.insert(i, i);
+        }
+    });
+    assert_eq!(allocations, 0);
+
+    // resizing allocates the slots and the probe distances, or the table, of each index once
+    let (_, allocations) = count_allocations(|| map.reserve(10_000));
+    assert_eq!(allocations, 2 * RESIZE_ALLOCATIONS);
+
+    let (_, allocations) = count_allocations(|| map.shrink_to_fit());
+    assert_eq!(allocations, 2 * RESIZE_ALLOCATIONS);
+
+    let (_, allocations) = count_allocations(|| map.clear());
+    assert_eq!(allocations, 0);
+}
+
+#[test]
+fn test_clone_from_reuses_allocations() {
+    let mut source = BiMap::<u64, u64>::with_capacity(1000);
+    for i in 0..1000 {
+        source.insert(i, i * 2);
+    }
+
+    // a plain clone allocates everything, like a new map
+    let (mut scratch, allocations) = count_allocations(|| source.clone());
+    assert_eq!(allocations, PAIR_ALLOCATIONS + 2 * INDEX_ALLOCATIONS);
+
+    for round in 0..5 {
+        source.remove_left(&round);
+        source.insert(round + 1000, round);
+        let (_, allocations) = count_allocations(|| scratch.clone_from(&source));
+        assert_eq!(allocations, 0);
+        assert_eq!(scratch, source);
+    }
+
+    // a source with a larger capacity needs larger indices and probe distances, but the pairs
+    // still fit
+    source.reserve(10_000);
+    let (_, allocations) = count_allocations(|| scratch.clone_from(&source));
+    assert_eq!(allocations, 2 * RESIZE_ALLOCATIONS);
+    assert_eq!(scratch, source);
+}
+
+#[test]
+fn test_custom_allocator() {
+    let alloc = MapAllocator::default();
+
+    // small maps only allocate their pairs
+    let (map, allocations) = count_allocations(|| CountedBiMap::new_in(alloc.clone()));
+    assert_eq!(allocations, 0);
+    assert_eq!(alloc.allocations.get(), PAIR_ALLOCATIONS);
+    assert_eq!(alloc.live_bytes.get(), map.allocated_bytes());
+    drop(map);
+    assert_eq!(alloc.live_bytes.get(), 0);
+
+    let (mut map, allocations) = count_allocations(|| CountedBiMap::with_capacity_in(1000, alloc.clone()));
+    assert_eq!(allocations, 0);
+    assert_eq!(alloc.allocations.get(), 2 * PAIR_ALLOCATIONS + 2 * INDEX_ALLOCATIONS);
+    assert!(Rc::ptr_eq(&map.allocator().live_bytes, &alloc.live_bytes));
+
+    // growing, shrinking and clearing the map allocate in its allocator only
+    let (_, allocations) = count_allocations(|| {
+        for i in 0..10_000 {
+            map.insert(i, i);
+        }
+    });
+    assert_eq!(allocations, 0);
+    assert_eq!(alloc.live_bytes.get(), map.allocated_bytes());
+
+    let (_, allocations) = count_allocations(|| map.reserve(10_000));
+    assert_eq!(allocations, 0);
+    assert_eq!(alloc.live_bytes.get(), map.allocated_bytes());
+
+    let (_, allocations) = count_allocations(|| {
+        for i in 0..9000 {
+            map.remove_left(&i);
+        }
+        map.shrink_to_fit();
+    });
+    assert_eq!(allocations, 0);
+    assert_eq!(alloc.live_bytes.get(), map.allocated_bytes());
+
+    let (_, allocations) = count_allocations(|| map.clear());
+    assert_eq!(allocations, 0);
+    assert_eq!(alloc.live_bytes.get(), map.allocated_bytes());
+
+    drop(map);
+    assert_eq!(alloc.live_bytes.get(), 0);
+}
+
+#[test]
+fn test_custom_allocator_clone_and_into_iter() {
+    let alloc = MapAllocator::default();
+    let mut map = CountedBiMap::with_capacity_in(1000, alloc.clone());
+    for i in 0..1000 {
+        map.insert(i, i * 2);
+    }
+    let live_bytes = alloc.live_bytes.get();
+
+    // clones share the allocator of the map
+    let (clone, allocations) = count_allocations(|| map.clone());
+    assert_eq!(allocations, 0);
+    assert_eq!(clone, map);
+    assert_eq!(alloc.live_bytes.get(), live_bytes + clone.allocated_bytes());
+    drop(clone);
+    assert_eq!(alloc.live_bytes.get(), live_bytes);
+
+    // the iterator owns the pairs, and releases them when it is dropped
+    let mut pairs = map.into_iter();
+    assert!(pairs.next().is_some());
+    assert!(alloc.live_bytes.get() > 0);
+    drop(pairs);
+    assert_eq!(alloc.live_bytes.get(), 0);
+}
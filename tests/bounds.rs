@@ -0,0 +1,33 @@
+use bijective_map::BiMap;
+
+/// A value type that can be neither hashed nor compared.
+#[derive(Clone, Debug, Default)]
+struct Opaque;
+
+/// A struct that embeds a map of values without `Hash` and `Eq`, which needs no bounds itself and
+/// can derive its trait implementations.
+#[derive(Clone, Debug, Default)]
+struct Registry<T> {
+    map: BiMap<T, u32>,
+    name: String,
+}
+
+impl<T> Registry<T> {
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+}
+
+#[test]
+fn test_map_of_unhashable_values() {
+    let registry = Registry::<Opaque>::default();
+    assert_eq!(registry.len(), 0);
+    assert!(registry.map.is_empty());
+    assert!(registry.name.is_empty());
+
+    let map: BiMap<Opaque, Opaque> = BiMap::with_capacity(64);
+    assert_eq!(map.iter().count(), 0);
+    assert_eq!(map.left_values().chain(map.right_values()).count(), 0);
+    let copied = map.clone();
+    assert_eq!(format!("{:?}", copied.iter().collect::<Vec<_>>()), "[]");
+}
@@ -0,0 +1,44 @@
+#![cfg(all(feature = "bytemuck", not(feature = "columnar")))]
+
+use bytemuck::PodCastError;
+use bijective_map::{BiMap, FromBytesError};
+
+#[test]
+fn test_bytes_round_trip() {
+    for len in [0, 5, 1000] {
+        let mut map = BiMap::new();
+        for i in 0..len {
+            map.insert(i, u64::MAX - i);
+        }
+        let bytes = map.as_bytes().to_vec();
+        assert_eq!(bytes.len(), len as usize * 16);
+
+        // copy into an aligned buffer, since the vector of bytes is only aligned by chance
+        let mut aligned = vec![0u64; bytes.len() / 8];
+        bytemuck::cast_slice_mut(&mut aligned).copy_from_slice(&bytes);
+        let read = BiMap::<u64, u64>::from_bytes(bytemuck::cast_slice(&aligned)).unwrap();
+        read.check_invariants().unwrap();
+        assert!(read.iter().eq(map.iter()));
+    }
+}
+
+#[test]
+fn test_bytes_rejects_malformed_input() {
+    let mut map = BiMap::new();
+    for i in 0..10u32 {
+        map.insert(i, i + 100);
+    }
+    let words = bytemuck::cast_slice::<u8, u32>(map.as_bytes()).to_vec();
+    let bytes = bytemuck::cast_slice::<u32, u8>(&words);
+
+    assert_eq!(BiMap::<u32, u32>::from_bytes(&bytes[1..9]).unwrap_err(), FromBytesError::Cast(PodCastError::TargetAlignmentGreaterAndInputNotAligned));
+    assert_eq!(BiMap::<u32, u32>::from_bytes(&bytes[..12]).unwrap_err(), FromBytesError::Cast(PodCastError::OutputSliceWouldHaveSlop));
+    assert_eq!(BiMap::<u32, u32>::from_bytes(&bytes[..7]).unwrap_err(), FromBytesError::Cast(PodCastError::OutputSliceWouldHaveSlop));
+
+    let mut duplicated = words.clone();
+    duplicated[6] = duplicated[2];
+    assert_eq!(BiMap::<u32, u32>::from_bytes(bytemuck::cast_slice(&duplicated)).unwrap_err(), FromBytesError::DuplicateLeft { first: 1, duplicate: 3 });
+    let mut duplicated = words;
+    duplicated[9] = duplicated[1];
+    assert_eq!(BiMap::<u32, u32>::from_bytes(bytemuck::cast_slice(&duplicated)).unwrap_err(), FromBytesError::DuplicateRight { first: 0, duplicate: 4 });
+}
@@ -0,0 +1,95 @@
+use proptest::prelude::*;
+use bijective_map::{BiMap, ChangeSet};
+
+/// The values of the maps are drawn from a small universe, so that the maps share many values.
+const UNIVERSE: u16 = 32;
+
+/// A map built by inserting the given pairs, which may replace each other.
+fn map_of(pairs: &[(u16, u16)]) -> BiMap<u16, u16> {
+    let mut map = BiMap::new();
+    for &(left, right) in pairs {
+        map.insert(left, right);
+    }
+    map
+}
+
+/// The pairs of a map in a canonical order.
+fn contents(map: &BiMap<u16, u16>) -> Vec<(u16, u16)> {
+    let mut contents = map.iter().map(|(&left, &right)| (left, right)).collect::<Vec<_>>();
+    contents.sort_unstable();
+    contents
+}
+
+/// Assert that applying the diff of two maps to the first one makes it hold the pairs of the second.
+fn assert_round_trip(old: &BiMap<u16, u16>, new: &BiMap<u16, u16>) {
+    let changes = old.diff(new);
+    let mut applied = old.clone();
+    applied.apply(&changes);
+    assert!(applied.check_invariants().is_ok());
+    assert_eq!(contents(&applied), contents(new));
+    assert!(applied.diff(new).is_empty());
+}
+
+#[test]
+fn test_diff() {
+    let old = map_of(&[(1, 10), (2, 20), (3, 30), (4, 40)]);
+    // 1 keeps its pair, the right value of 2 moves to 3, 4 is removed and 5 is added
+    let new = map_of(&[(1, 10), (3, 20), (2, 50), (5, 40)]);
+
+    let changes = old.diff(&new);
+    assert_eq!(changes, ChangeSet {
+        removed: vec![(4, 40)],
+        added: vec![(5, 40)],
+        changed: vec![(2, 50), (3, 20)],
+    });
+    assert!(old.diff(&old).is_empty());
+    assert_round_trip(&old, &new);
+    assert_round_trip(&new, &old);
+}
+
+#[test]
+fn test_diff_swapped_partners() {
+    // every right value moves to another left value, so each insertion of the changes removes the
+    // pair that the next one replaces
+    let old = map_of(&(0..100).map(|i| (i, i + 1000)).collect::<Vec<_>>());
+    let new = map_of(&(0..100).map(|i| (i, (i + 1) % 100 + 1000)).collect::<Vec<_>>());
+    assert_eq!(old.diff(&new).changed.len(), 100);
+    assert_round_trip(&old, &new);
+    assert_round_trip(&new, &old);
+    assert_round_trip(&old, &BiMap::new());
+    assert_round_trip(&BiMap::new(), &new);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serialize_changes() {
+    let old = map_of(&[(1, 10), (2, 20), (3, 30)]);
+    let new = map_of(&[(2, 30), (4, 40)]);
+    let changes = old.diff(&new);
+
+    let serialized = serde_json::to_string(&changes).unwrap();
+    let deserialized: ChangeSet<u16, u16> = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(deserialized, changes);
+
+    let mut applied = old.clone();
+    applied.apply(&deserialized);
+    assert_eq!(contents(&applied), contents(&new));
+}
+
+proptest! {
+    #[test]
+    fn test_diff_round_trip(
+        old in prop::collection::vec((0..UNIVERSE, 0..UNIVERSE), 0..48),
+        new in prop::collection::vec((0..UNIVERSE, 0..UNIVERSE), 0..48),
+    ) {
+        let (old, new) = (map_of(&old), map_of(&new));
+        let mut applied = old.clone();
+        applied.apply(&old.diff(&new));
+        prop_assert!(applied.check_invariants().is_ok());
+        prop_assert_eq!(contents(&applied), contents(&new));
+        for value in 0..UNIVERSE {
+            prop_assert_eq!(applied.get_right(&value), new.get_right(&value));
+            prop_assert_eq!(applied.get_left(&value), new.get_left(&value));
+        }
+    }
+}
@@ -0,0 +1,64 @@
+#![cfg(any(feature = "fxhash", feature = "ahash"))]
+
+use std::hash::{BuildHasher, Hash};
+use bijective_map::BiMap;
+
+/// Insert and look up pairs in both directions, across the switch from a small map to hashed indices.
+fn insert_and_lookup<H, RH>(mut map: BiMap<u64, String, H, RH>)
+    where H: BuildHasher, RH: BuildHasher
+{
+    for i in 0..100 {
+        assert_eq!(map.insert(i, i.to_string()), (None, None));
+    }
+    assert_eq!(map.len(), 100);
+    for i in 0..100 {
+        assert_eq!(map.get_right(&i), Some(&i.to_string()));
+        assert_eq!(map.get_left(&i.to_string()), Some(&i));
+    }
+    assert_eq!(map.remove_left(&42), Some("42".to_string()));
+    assert!(!map.contains_right(&"42".to_string()));
+    map.check_invariants().unwrap();
+}
+
+fn columns<T: Hash + Eq, U: Hash + Eq>(pairs: impl Iterator<Item=(T, U)>) -> (Vec<T>, Vec<U>) {
+    pairs.unzip()
+}
+
+#[cfg(feature = "fxhash")]
+#[test]
+fn test_fx_map() {
+    use bijective_map::FxBiMap;
+
+    insert_and_lookup(FxBiMap::with_default_hashers(0));
+    insert_and_lookup(FxBiMap::with_default_hashers(1000));
+    insert_and_lookup(FxBiMap::new_with_hashers(Default::default(), Default::default()));
+
+    let (lefts, rights) = columns((0..20u64).map(|i| (i, i.to_string())));
+    let map = FxBiMap::from_columns_with_hashers(lefts, rights, Default::default(), Default::default()).unwrap();
+    assert_eq!(map.get_left(&"7".to_string()), Some(&7));
+}
+
+#[cfg(feature = "fxhash")]
+#[test]
+fn test_fx_map_in_static() {
+    use std::sync::Mutex;
+    use bijective_map::FxBiMap;
+
+    static MAP: Mutex<FxBiMap<u32, u32>> = Mutex::new(FxBiMap::new_with_hashers(rustc_hash::FxBuildHasher, rustc_hash::FxBuildHasher));
+    MAP.lock().unwrap().insert(1, 2);
+    assert_eq!(MAP.lock().unwrap().get_left(&2), Some(&1));
+}
+
+#[cfg(feature = "ahash")]
+#[test]
+fn test_ahash_map() {
+    use bijective_map::ABiMap;
+
+    insert_and_lookup(ABiMap::with_default_hashers(0));
+    insert_and_lookup(ABiMap::with_default_hashers(1000));
+
+    let (lefts, rights) = columns((0..20u64).map(|i| (i, i.to_string())).chain([(3, "4".to_string())]));
+    let map = ABiMap::from_columns_last_wins_with_hashers(lefts, rights, Default::default(), Default::default()).unwrap();
+    assert_eq!(map.len(), 19);
+    assert_eq!(map.get_right(&3), Some(&"4".to_string()));
+}
@@ -0,0 +1,155 @@
+use std::cell::Cell;
+use std::hash::{Hash, Hasher};
+use bijective_map::{BiMap, InconsistencyReport, Side};
+
+/// A key whose `Eq` implementation compares the current value of the cell, and whose `Hash`
+/// implementation hashes it only if `hash_value` is set. Changing the cell of a stored key breaks
+/// the map in the ways that inconsistent implementations do.
+#[derive(Debug)]
+struct Mutable {
+    value: Cell<u32>,
+    hash_value: bool,
+}
+
+impl Mutable {
+    fn new(value: u32, hash_value: bool) -> Self {
+        Mutable { value: Cell::new(value), hash_value }
+    }
+}
+
+impl Hash for Mutable {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        if self.hash_value {
+            self.value.get().hash(state);
+        }
+    }
+}
+
+impl PartialEq for Mutable {
+    fn eq(&self, other: &Self) -> bool {
+        self.value.get() == other.value.get()
+    }
+}
+
+impl Eq for Mutable {}
+
+/// A key whose hash changes every time it is hashed.
+#[derive(Debug, PartialEq, Eq)]
+struct Unstable {
+    value: u32,
+    hashes: Cell<u64>,
+}
+
+impl Hash for Unstable {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hashes.set(self.hashes.get() + 1);
+        (self.value, self.hashes.get()).hash(state);
+    }
+}
+
+#[test]
+fn test_consistent_map_has_no_reports() {
+    let mut map = BiMap::new();
+    for i in 0..100u32 {
+        map.insert(i, i.to_string());
+    }
+    assert_eq!(map.find_inconsistencies(), vec![]);
+}
+
+#[test]
+#[allow(clippy::mutable_key_type)]
+fn test_report_shadowed_and_unreachable() {
+    for len in [4, 50] {
+        // all keys that do not hash their value collide, so equal keys find each other. The
+        // hashers are fixed, because with random hashers the changed right value could hash to a
+        // slot from which its probe still reaches it.
+        let mut map = BiMap::with_seed(0, 162);
+        for i in 0..len {
+            map.insert(Mutable::new(i, false), Mutable::new(i, true));
+        }
+        assert_eq!(map.find_inconsistencies(), vec![]);
+
+        map.left_values().nth(3).unwrap().value.set(1);
+        map.right_values().nth(2).unwrap().value.set(1000);
+        let mut expected = vec![InconsistencyReport::Shadowed { side: Side::Left, bucket: 3, other: 1 }];
+        // small maps scan their values instead of hashing them, so a changed hash goes unnoticed
+        if len > 8 {
+            expected.insert(0, InconsistencyReport::Unreachable { side: Side::Right, bucket: 2 });
+        }
+        assert_eq!(map.find_inconsistencies(), expected);
+    }
+}
+
+// the paranoid checks reject the values when they are inserted
+#[cfg(not(feature = "paranoid"))]
+#[test]
+fn test_report_not_reflexive() {
+    /// A key that is not equal to itself, like a floating point NaN.
+    #[derive(Debug)]
+    struct Unequal(u32);
+
+    impl Hash for Unequal {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.0.hash(state);
+        }
+    }
+
+    impl PartialEq for Unequal {
+        fn eq(&self, _: &Self) -> bool {
+            false
+        }
+    }
+
+    impl Eq for Unequal {}
+
+    let mut map = BiMap::new();
+    for i in 0..20 {
+        map.insert(Unequal(i), i);
+    }
+    let reports = map.find_inconsistencies();
+    assert_eq!(reports.len(), 20);
+    assert_eq!(reports[7], InconsistencyReport::NotReflexive { side: Side::Left, bucket: 7 });
+}
+
+#[test]
+fn test_report_unstable_hash() {
+    let mut map = BiMap::new();
+    map.insert(1u32, Unstable { value: 1, hashes: Cell::new(0) });
+    assert_eq!(map.find_inconsistencies(), vec![InconsistencyReport::UnstableHash { side: Side::Right, bucket: 0 }]);
+}
+
+#[cfg(feature = "paranoid")]
+#[test]
+#[should_panic(expected = "Hash and Eq implementations are inconsistent")]
+fn test_paranoid_insert_catches_duplicates() {
+    use std::hash::{BuildHasherDefault, DefaultHasher, RandomState};
+
+    /// A key whose `Hash` implementation includes a field that `Eq` ignores.
+    #[derive(Debug)]
+    struct Tagged {
+        id: u32,
+        tag: u32,
+    }
+
+    impl Hash for Tagged {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            (self.id, self.tag).hash(state);
+        }
+    }
+
+    impl PartialEq for Tagged {
+        fn eq(&self, other: &Self) -> bool {
+            self.id == other.id
+        }
+    }
+
+    impl Eq for Tagged {}
+
+    // with random hashers, the probe of the new key could reach the stored key by chance
+    let mut map = BiMap::with_hashers(0, BuildHasherDefault::<DefaultHasher>::default(), RandomState::new());
+    for i in 0..20 {
+        map.insert(Tagged { id: i, tag: 0 }, i);
+    }
+    // the key equals a stored key, but its hash leads elsewhere, so it would be stored twice
+    map.insert(Tagged { id: 5, tag: 1 }, 100);
+}
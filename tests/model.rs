@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher, RandomState};
+use proptest::prelude::*;
+use bijective_map::BiMap;
+use bijective_map::errors::OccupiedError;
+
+/// The values of the operations are drawn from a small universe, so that operations frequently hit
+/// values that are already in the map.
+const UNIVERSE: u16 = 48;
+
+#[derive(Clone, Debug)]
+enum Operation {
+    Insert(u16, u16),
+    TryInsert(u16, u16),
+    RemoveLeft(u16),
+    RemoveRight(u16),
+    Clear,
+    Reserve(usize),
+    ShrinkToFit,
+    ShrinkTo(usize),
+}
+
+fn operation() -> impl Strategy<Value=Operation> {
+    let value = || 0..UNIVERSE;
+    prop_oneof![
+        4 => (value(), value()).prop_map(|(left, right)| Operation::Insert(left, right)),
+        2 => (value(), value()).prop_map(|(left, right)| Operation::TryInsert(left, right)),
+        2 => value().prop_map(Operation::RemoveLeft),
+        2 => value().prop_map(Operation::RemoveRight),
+        1 => Just(Operation::Clear),
+        1 => (0..64usize).prop_map(Operation::Reserve),
+        1 => Just(Operation::ShrinkToFit),
+        1 => (0..64usize).prop_map(Operation::ShrinkTo),
+    ]
+}
+
+/// A hasher that hashes values to themselves modulo a small number, so that values collide in the
+/// indices and probe chains wrap around their end.
+#[derive(Clone, Copy, Default)]
+struct CollidingHasher {
+    state: u64,
+}
+
+impl Hasher for CollidingHasher {
+    fn finish(&self) -> u64 {
+        self.state % 7
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state = (self.state << 8) | byte as u64;
+        }
+    }
+}
+
+impl BuildHasher for CollidingHasher {
+    type Hasher = CollidingHasher;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        CollidingHasher::default()
+    }
+}
+
+/// The reference model of a bijection, kept consistent manually.
+#[derive(Default)]
+struct Model {
+    right_of: HashMap<u16, u16>,
+    left_of: HashMap<u16, u16>,
+}
+
+impl Model {
+    fn insert(&mut self, left: u16, right: u16) -> (Option<u16>, Option<u16>) {
+        // reinserting an existing pair reports both values as replaced
+        if self.right_of.get(&left) == Some(&right) {
+            return (Some(right), Some(left));
+        }
+
+        let old_right = self.right_of.remove(&left);
+        if let Some(old_right) = old_right {
+            self.left_of.remove(&old_right);
+        }
+        let old_left = self.left_of.remove(&right);
+        if let Some(old_left) = old_left {
+            self.right_of.remove(&old_left);
+        }
+        self.right_of.insert(left, right);
+        self.left_of.insert(right, left);
+        (old_right, old_left)
+    }
+
+    fn remove_left(&mut self, left: u16) -> Option<u16> {
+        let right = self.right_of.remove(&left)?;
+        self.left_of.remove(&right);
+        Some(right)
+    }
+
+    fn remove_right(&mut self, right: u16) -> Option<u16> {
+        let left = self.left_of.remove(&right)?;
+        self.right_of.remove(&left);
+        Some(left)
+    }
+}
+
+/// Apply the operations to a map with the given hashers and to a model of its initial pairs, and
+/// compare them after every step.
+fn run<H, RH>(mut map: BiMap<u16, u16, H, RH>, operations: &[Operation]) -> Result<(), TestCaseError>
+    where H: BuildHasher, RH: BuildHasher
+{
+    let mut model = Model::default();
+    for (&left, &right) in map.iter() {
+        model.insert(left, right);
+    }
+    for operation in operations {
+        match *operation {
+            Operation::Insert(left, right) => {
+                let expected = model.insert(left, right);
+                prop_assert_eq!(map.insert(left, right), expected);
+            }
+            Operation::TryInsert(left, right) => {
+                let left_occupied = model.right_of.contains_key(&left);
+                let right_occupied = model.left_of.contains_key(&right);
+                let result = map.try_insert(left, right);
+                if !left_occupied && !right_occupied {
+                    model.insert(left, right);
+                    prop_assert_eq!(result, Ok(()));
+                } else {
+                    prop_assert_eq!(result, Err(OccupiedError { left, right, left_occupied, right_occupied }));
+                }
+            }
+            Operation::RemoveLeft(left) => prop_assert_eq!(map.remove_left(&left), model.remove_left(left)),
+            Operation::RemoveRight(right) => prop_assert_eq!(map.remove_right(&right), model.remove_right(right)),
+            Operation::Clear => {
+                map.clear();
+                model = Model::default();
+            }
+            Operation::Reserve(additional) => map.reserve(additional),
+            Operation::ShrinkToFit => map.shrink_to_fit(),
+            Operation::ShrinkTo(min_capacity) => map.shrink_to(min_capacity),
+        }
+
+        prop_assert_eq!(map.check_invariants(), Ok(()));
+        prop_assert_eq!(map.len(), model.right_of.len());
+        let mut contents = map.iter().map(|(&left, &right)| (left, right)).collect::<Vec<_>>();
+        let mut expected = model.right_of.iter().map(|(&left, &right)| (left, right)).collect::<Vec<_>>();
+        contents.sort_unstable();
+        expected.sort_unstable();
+        prop_assert_eq!(contents, expected);
+        for value in 0..UNIVERSE {
+            prop_assert_eq!(map.get_right(&value), model.right_of.get(&value));
+            prop_assert_eq!(map.get_left(&value), model.left_of.get(&value));
+        }
+    }
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn test_model_default_hasher(operations in prop::collection::vec(operation(), 0..200)) {
+        run(BiMap::new(), &operations)?;
+    }
+
+    #[test]
+    fn test_model_colliding_hasher(operations in prop::collection::vec(operation(), 0..200)) {
+        run(BiMap::with_hashers(0, CollidingHasher::default(), CollidingHasher::default()), &operations)?;
+    }
+
+    #[test]
+    fn test_model_mixed_hashers(operations in prop::collection::vec(operation(), 0..200)) {
+        run(BiMap::with_hashers(0, RandomState::new(), CollidingHasher::default()), &operations)?;
+    }
+}
+
+#[cfg(feature = "proptest")]
+proptest! {
+    #[test]
+    fn test_model_generated_map(
+        map in bijective_map::strategy::bi_map(0..UNIVERSE, 0..UNIVERSE, 0..32),
+        operations in prop::collection::vec(operation(), 0..100),
+    ) {
+        run(map, &operations)?;
+    }
+
+    #[test]
+    fn test_generated_map_sizes(map in bijective_map::strategy::bi_map(0..UNIVERSE, 0..UNIVERSE, 10..20)) {
+        prop_assert!((10..20).contains(&map.len()));
+        prop_assert_eq!(map.check_invariants(), Ok(()));
+    }
+}
@@ -0,0 +1,218 @@
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher, RandomState};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+use bijective_map::BiMap;
+
+/// The payload of the panics raised by [`Fragile`] values.
+const POISONED: &str = "poisoned value";
+
+thread_local! {
+    /// The number of hashes and comparisons of [`Fragile`] values that succeed before the next one
+    /// panics, or None if they never panic.
+    static BUDGET: Cell<Option<usize>> = const { Cell::new(None) };
+
+    /// The number of hashes and comparisons of [`Fragile`] values so far.
+    static CALLS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Count a hash or comparison, and panic if the budget is exhausted.
+fn spend() {
+    CALLS.with(|calls| calls.set(calls.get() + 1));
+    BUDGET.with(|budget| match budget.get() {
+        Some(0) => {
+            budget.set(None);
+            panic!("{}", POISONED);
+        }
+        Some(remaining) => budget.set(Some(remaining - 1)),
+        None => {}
+    });
+}
+
+/// A value whose `Hash` and `Eq` implementations panic once the budget is exhausted. Only the upper
+/// half of the value is hashed, so all values below `1 << 16` collide.
+#[derive(Clone, Copy, Debug)]
+struct Fragile(u32);
+
+impl Hash for Fragile {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        spend();
+        (self.0 >> 16).hash(state);
+    }
+}
+
+impl PartialEq for Fragile {
+    fn eq(&self, other: &Self) -> bool {
+        spend();
+        self.0 == other.0
+    }
+}
+
+impl Eq for Fragile {}
+
+type FragileMap = BiMap<Fragile, Fragile>;
+
+/// An operation that is applied to copies of a map.
+type Operation = Box<dyn Fn(&mut FragileMap)>;
+
+/// Silence the expected panics, while still reporting failed assertions.
+fn silence_poisoned_panics() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if info.payload().downcast_ref::<String>().is_none_or(|message| message != POISONED) {
+                default_hook(info);
+            }
+        }));
+    });
+}
+
+/// The pairs of a map, collected without hashing or comparing them.
+fn pairs(map: &FragileMap) -> HashSet<(u32, u32)> {
+    map.iter().map(|(left, right)| (left.0, right.0)).collect()
+}
+
+/// Check that the map is consistent, and that every pair can be looked up from both sides.
+fn assert_consistent(map: &FragileMap) {
+    map.check_invariants().unwrap();
+    assert_eq!(map.iter().count(), map.len());
+    for (left, right) in map.iter() {
+        assert_eq!(map.get_right(left).map(|right| right.0), Some(right.0));
+        assert_eq!(map.get_left(right).map(|left| left.0), Some(left.0));
+    }
+}
+
+/// Build a map of `len` pairs from the given values.
+fn map_of(len: u32, value: fn(u32) -> u32) -> FragileMap {
+    let mut map = BiMap::new();
+    for i in 0..len {
+        map.insert(Fragile(value(i)), Fragile(value(i + 10_000)));
+    }
+    map
+}
+
+/// Apply an operation to copies of the map, letting the `n`th hash or comparison panic for a range
+/// of `n`. The surviving map must be consistent, and hold either the pairs from before or from after
+/// the operation.
+fn check_operation(base: &FragileMap, operation: &dyn Fn(&mut FragileMap)) {
+    silence_poisoned_panics();
+
+    let mut expected = base.clone();
+    let calls = CALLS.with(Cell::get);
+    operation(&mut expected);
+    let calls = CALLS.with(Cell::get) - calls;
+    let (before, after) = (pairs(base), pairs(&expected));
+
+    // panicking at every call is too slow for long probe chains, so later calls are sampled
+    let step = (calls / 32).max(1);
+    for n in (0..16.min(calls)).chain((16..calls).step_by(step)) {
+        let mut map = base.clone();
+        BUDGET.with(|budget| budget.set(Some(n)));
+        let result = panic::catch_unwind(AssertUnwindSafe(|| operation(&mut map)));
+        BUDGET.with(|budget| budget.set(None));
+
+        if let Err(payload) = result {
+            assert_eq!(payload.downcast_ref::<String>().map(String::as_str), Some(POISONED));
+        }
+        assert_consistent(&map);
+        let survivor = pairs(&map);
+        assert!(survivor == before || survivor == after, "operation left {} of {} pairs after {} calls", survivor.len(), before.len(), n);
+    }
+}
+
+/// The operations to check, with the values of the map they are applied to.
+fn operations(value: fn(u32) -> u32) -> Vec<Operation> {
+    vec![
+        // a new pair
+        Box::new(move |map| { map.insert(Fragile(value(1000)), Fragile(value(20_000))); }),
+        Box::new(move |map| { let _ = map.try_insert(Fragile(value(1000)), Fragile(value(20_000))); }),
+        // a new right value for an existing left value, and the other way around
+        Box::new(move |map| { map.insert(Fragile(value(5)), Fragile(value(20_000))); }),
+        Box::new(move |map| { map.insert(Fragile(value(1000)), Fragile(value(10_005))); }),
+        // two existing values, which collapse two pairs into one
+        Box::new(move |map| { map.insert(Fragile(value(5)), Fragile(value(10_007))); }),
+        Box::new(move |map| { map.remove_left(&Fragile(value(5))); }),
+        Box::new(move |map| { map.remove_right(&Fragile(value(10_007))); }),
+        Box::new(|map| map.reserve(1000)),
+        Box::new(|map| map.shrink_to(0)),
+    ]
+}
+
+#[test]
+fn test_panic_safety_small_map() {
+    let base = map_of(6, |i| i << 16);
+    for operation in operations(|i| i << 16) {
+        check_operation(&base, &operation);
+    }
+}
+
+#[test]
+fn test_panic_safety() {
+    let base = map_of(200, |i| i << 16);
+    for operation in operations(|i| i << 16) {
+        check_operation(&base, &operation);
+    }
+}
+
+#[test]
+fn test_panic_safety_saturated_distances() {
+    // all values collide, so the probe distances of most entries saturate and deletions have to
+    // hash the entries they shift back
+    let base = map_of(300, |i| i);
+    for operation in operations(|i| i) {
+        check_operation(&base, &operation);
+    }
+}
+
+#[test]
+fn test_panic_safety_negative_filter() {
+    let mut base = map_of(200, |i| i << 16);
+    base.enable_negative_filter();
+    for operation in operations(|i| i << 16) {
+        check_operation(&base, &operation);
+    }
+}
+
+#[test]
+fn test_panic_safety_reseeding() {
+    // the colliding values never get shorter chains, but every insertion that finds a long chain
+    // rebuilds the indices with fresh hashers until the map resizes
+    for operation in operations(|i| i) {
+        let mut base = map_of(300, |i| i);
+        base.enable_reseeding(64, || (RandomState::new(), RandomState::new()));
+        check_operation(&base, &operation);
+    }
+}
+#[test]
+fn test_panic_safety_get_mut() {
+    // a panic while the mutated value is indexed again removes its pair, so the survivor may also
+    // hold the pairs from before without the mutated one
+    silence_poisoned_panics();
+    for (len, value) in [(6, (|i| i << 16) as fn(u32) -> u32), (200, |i| i << 16), (300, |i| i)] {
+        let base = map_of(len, value);
+        let before = pairs(&base);
+        let mutated = (value(5), value(10_005));
+        let after = before.iter().map(|&pair| if pair == mutated { (value(5), value(20_000)) } else { pair }).collect::<HashSet<_>>();
+        let removed = before.iter().copied().filter(|&pair| pair != mutated).collect::<HashSet<_>>();
+
+        for n in 0..64 {
+            let mut map = base.clone();
+            BUDGET.with(|budget| budget.set(Some(n)));
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                if let Some(mut right) = map.get_right_mut(&Fragile(value(5))) {
+                    right.0 = value(20_000);
+                }
+            }));
+            BUDGET.with(|budget| budget.set(None));
+
+            if let Err(payload) = result {
+                assert_eq!(payload.downcast_ref::<String>().map(String::as_str), Some(POISONED));
+            }
+            assert_consistent(&map);
+            let survivor = pairs(&map);
+            assert!(survivor == before || survivor == after || survivor == removed, "mutation left {} of {} pairs after {} calls", survivor.len(), before.len(), n);
+        }
+    }
+}
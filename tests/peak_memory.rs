@@ -0,0 +1,92 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::mem;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use bijective_map::{BiMap, ResizeStrategy};
+
+/// A global allocator that tracks the bytes allocated by the current thread, and the peak of the
+/// allocated bytes, so that the test harness running on other threads does not interfere.
+struct PeakAllocator;
+
+thread_local! {
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+    static PEAK: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Record an allocation and a deallocation. Memory allocated by other threads may be freed on this
+/// thread, so the count saturates at zero.
+fn record(allocated: usize, freed: usize) {
+    ALLOCATED.with(|current| {
+        current.set((current.get() + allocated).saturating_sub(freed));
+        PEAK.with(|peak| peak.set(peak.get().max(current.get())));
+    });
+}
+
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(layout.size(), 0);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        record(0, layout.size());
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record(new_size, layout.size());
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAllocator = PeakAllocator;
+
+/// The peak of the bytes allocated by `f` on the current thread, above the bytes allocated before.
+fn peak_allocation(f: impl FnOnce()) -> usize {
+    let before = ALLOCATED.with(Cell::get);
+    PEAK.with(|peak| peak.set(before));
+    f();
+    PEAK.with(Cell::get) - before
+}
+
+/// The bytes of one index and its probe distances at the given capacity.
+#[cfg(not(feature = "hashbrown-backend"))]
+fn index_bytes(capacity: usize) -> usize {
+    capacity * (mem::size_of::<usize>() + mem::size_of::<u8>())
+}
+
+/// The bytes of the buckets and control bytes of one hashbrown table at the given capacity, which
+/// keeps an eighth of its buckets empty and rounds them up to a power of two.
+#[cfg(feature = "hashbrown-backend")]
+fn index_bytes(capacity: usize) -> usize {
+    (capacity * 8 / 7).next_power_of_two() * (mem::size_of::<usize>() + mem::size_of::<u8>())
+}
+
+#[test]
+fn test_in_place_resize_peak() {
+    for strategy in [ResizeStrategy::Rebuild, ResizeStrategy::InPlace] {
+        let mut map = BiMap::<u64, u64>::with_capacity(10_000);
+        map.set_resize_strategy(strategy);
+        for i in 0..10_000 {
+            map.insert(i, i);
+        }
+        let new_capacity = Arc::new(AtomicUsize::new(0));
+        let recorded = Arc::clone(&new_capacity);
+        map.set_resize_hook(Some(Box::new(move |event| recorded.store(event.new_capacity, Ordering::Relaxed))));
+
+        // reserving room for twice the pairs doubles the capacity of the indices
+        let peak = peak_allocation(|| map.reserve(10_000));
+        map.check_invariants().unwrap();
+        let new_capacity = new_capacity.load(Ordering::Relaxed);
+        assert!(new_capacity >= 20_000);
+
+        match strategy {
+            // both new indices are allocated next to the old ones
+            ResizeStrategy::Rebuild => assert!(peak >= 2 * index_bytes(new_capacity), "rebuilding peaked at {} bytes", peak),
+            // the allocations of the indices only grow by one index at the new capacity
+            _ => assert!(peak <= index_bytes(new_capacity), "resizing in place peaked at {} bytes", peak),
+        }
+    }
+}
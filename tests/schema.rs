@@ -0,0 +1,47 @@
+#![cfg(all(feature = "schemars", feature = "serde"))]
+
+use schemars::JsonSchema;
+use serde_json::json;
+use bijective_map::BiMap;
+
+#[derive(JsonSchema, serde::Serialize)]
+struct Config {
+    languages: BiMap<String, u32>,
+}
+
+#[test]
+fn test_schema_validates_serialized_map() {
+    let schema = schemars::schema_for!(Config);
+    let validator = jsonschema::validator_for(schema.as_value()).unwrap();
+
+    let mut languages = BiMap::new();
+    for (id, name) in ["en", "de", "fr", "es", "it", "nl", "pl", "pt", "sv", "fi"].into_iter().enumerate() {
+        languages.insert(name.to_string(), id as u32);
+    }
+    let config = serde_json::to_value(Config { languages }).unwrap();
+    assert!(validator.is_valid(&config), "{}", config);
+
+    // the pairs must be arrays of a string and an unsigned integer
+    assert!(!validator.is_valid(&json!({ "languages": [["en", -1]] })));
+    assert!(!validator.is_valid(&json!({ "languages": [["en", 1, 2]] })));
+    assert!(!validator.is_valid(&json!({ "languages": [[1, "en"]] })));
+    assert!(!validator.is_valid(&json!({ "languages": { "en": 1 } })));
+}
+
+#[test]
+fn test_serialize_round_trip() {
+    let mut map = BiMap::new();
+    for i in 0..100u32 {
+        map.insert(i, i.to_string());
+    }
+    let serialized = serde_json::to_string(&map).unwrap();
+    let deserialized: BiMap<u32, String> = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(deserialized.len(), map.len());
+    assert!(map.iter().all(|(left, right)| deserialized.get_right(left) == Some(right)));
+
+    // later pairs replace the mappings of earlier pairs
+    let deserialized: BiMap<u32, String> = serde_json::from_str(r#"[[1, "a"], [2, "b"], [1, "c"], [3, "b"]]"#).unwrap();
+    assert_eq!(deserialized.len(), 2);
+    assert_eq!(deserialized.get_right(&1).map(String::as_str), Some("c"));
+    assert_eq!(deserialized.get_left(&"b".to_string()), Some(&3));
+}
@@ -0,0 +1,118 @@
+#![cfg(feature = "serde")]
+
+use std::hash::{BuildHasherDefault, DefaultHasher};
+use serde::{Deserialize, Serialize};
+use bijective_map::BiMap;
+
+fn to_cbor<T: serde::Serialize>(value: &T) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(value, &mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn test_json_round_trip() {
+    let map: BiMap<u32, String> = (0..100).map(|i| (i, format!("value {}", i))).collect();
+    let json = serde_json::to_string(&map).unwrap();
+    let deserialized: BiMap<u32, String> = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, map);
+
+    // the pairs are serialized in iteration order
+    let map = bijective_map::bimap![1 => "a", 2 => "b"];
+    assert_eq!(serde_json::to_string(&map).unwrap(), r#"[[1,"a"],[2,"b"]]"#);
+    assert_eq!(serde_json::to_string(&BiMap::<u32, u32>::new()).unwrap(), "[]");
+}
+
+#[test]
+fn test_binary_round_trip() {
+    type Hashers = BuildHasherDefault<DefaultHasher>;
+
+    let map: BiMap<u64, (u8, bool), Hashers, Hashers> = (0..1000).map(|i| (i * 31, ((i % 256) as u8, i >= 256))).collect();
+    let bytes = to_cbor(&map);
+    let deserialized: BiMap<u64, (u8, bool), Hashers, Hashers> = ciborium::from_reader(bytes.as_slice()).unwrap();
+    assert_eq!(deserialized, map);
+    assert_eq!(deserialized.iter().collect::<Vec<_>>(), map.iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn test_deserialize_duplicates() {
+    // later pairs replace the mappings of earlier pairs, in both formats
+    let pairs = vec![(1, 'a'), (2, 'b'), (1, 'c'), (3, 'b'), (4, 'd'), (4, 'e')];
+    let expected: BiMap<u32, char> = pairs.iter().copied().collect();
+    assert_eq!(expected.len(), 3);
+
+    let from_json: BiMap<u32, char> = serde_json::from_str(&serde_json::to_string(&pairs).unwrap()).unwrap();
+    let from_cbor: BiMap<u32, char> = ciborium::from_reader(to_cbor(&pairs).as_slice()).unwrap();
+    assert_eq!(from_json, expected);
+    assert_eq!(from_cbor, expected);
+    assert_eq!(from_json.get_right(&1), Some(&'c'));
+    assert_eq!(from_json.get_left(&'b'), Some(&3));
+    assert_eq!(from_json.get_right(&4), Some(&'e'));
+
+    assert!(serde_json::from_str::<BiMap<u32, char>>(r#"[[1, "a"], [2]]"#).is_err());
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Tables {
+    #[serde(with = "bijective_map::serde::as_seq")]
+    seq: BiMap<u32, String>,
+    #[serde(with = "bijective_map::serde::as_seq_strict")]
+    seq_strict: BiMap<u32, String>,
+    #[serde(with = "bijective_map::serde::as_map")]
+    map: BiMap<String, u32>,
+    #[serde(with = "bijective_map::serde::as_map_strict")]
+    map_strict: BiMap<String, u32>,
+}
+
+#[test]
+fn test_representations() {
+    let numbers: BiMap<u32, String> = (0..3).map(|i| (i, i.to_string())).collect();
+    let names: BiMap<String, u32> = numbers.iter().map(|(left, right)| (right.clone(), *left)).collect();
+    let tables = Tables { seq: numbers.clone(), seq_strict: numbers, map: names.clone(), map_strict: names };
+
+    let json = serde_json::to_value(&tables).unwrap();
+    assert_eq!(json["seq"], serde_json::json!([[0, "0"], [1, "1"], [2, "2"]]));
+    assert_eq!(json["seq_strict"], json["seq"]);
+    assert_eq!(json["map"], serde_json::json!({"0": 0, "1": 1, "2": 2}));
+    assert_eq!(json["map_strict"], json["map"]);
+    assert_eq!(serde_json::from_value::<Tables>(json).unwrap(), tables);
+
+    // left values that are no strings are keys of binary formats
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Points(#[serde(with = "bijective_map::serde::as_map_strict")] BiMap<(i32, i32), u8>);
+
+    let points = Points(bijective_map::bimap![(0, 0) => 1, (-3, 7) => 2]);
+    let restored: Points = ciborium::from_reader(to_cbor(&points).as_slice()).unwrap();
+    assert_eq!(restored, points);
+}
+
+#[test]
+fn test_duplicate_right_value_policies() {
+    // the right value 7 occurs twice in each representation
+    let seq = r#"[[1, 7], [2, 8], [3, 7]]"#;
+    let map = r#"{"a": 7, "b": 8, "c": 7}"#;
+
+    #[derive(Deserialize)]
+    struct Seq(#[serde(with = "bijective_map::serde::as_seq")] BiMap<u32, u32>);
+    #[derive(Debug, Deserialize)]
+    struct SeqStrict(#[serde(with = "bijective_map::serde::as_seq_strict")] #[allow(dead_code)] BiMap<u32, u32>);
+    #[derive(Deserialize)]
+    struct Map(#[serde(with = "bijective_map::serde::as_map")] BiMap<String, u32>);
+    #[derive(Debug, Deserialize)]
+    struct MapStrict(#[serde(with = "bijective_map::serde::as_map_strict")] #[allow(dead_code)] BiMap<String, u32>);
+
+    // without the strict policy, the later pair replaces the earlier one
+    let Seq(collapsed) = serde_json::from_str(seq).unwrap();
+    assert_eq!(collapsed, bijective_map::bimap![2 => 8, 3 => 7]);
+    let Map(collapsed) = serde_json::from_str(map).unwrap();
+    assert_eq!(collapsed, bijective_map::bimap!["b".to_string() => 8, "c".to_string() => 7]);
+
+    let error = serde_json::from_str::<SeqStrict>(seq).unwrap_err();
+    assert!(error.to_string().starts_with("duplicate right value in pair 2"), "{}", error);
+    let error = serde_json::from_str::<MapStrict>(map).unwrap_err();
+    assert!(error.to_string().starts_with("duplicate right value in entry 2"), "{}", error);
+
+    // a duplicate left value is rejected as well, if the format allows it
+    let error = serde_json::from_str::<SeqStrict>(r#"[[1, 7], [1, 8]]"#).unwrap_err();
+    assert!(error.to_string().starts_with("duplicate left value in pair 1"), "{}", error);
+}
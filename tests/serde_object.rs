@@ -0,0 +1,70 @@
+#![cfg(feature = "serde")]
+
+use serde::{Deserialize, Serialize};
+use bijective_map::BiMap;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Config {
+    #[serde(with = "bijective_map::serde_object")]
+    languages: BiMap<String, u32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Language {
+    English,
+    German,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Codes {
+    #[serde(with = "bijective_map::serde_object")]
+    codes: BiMap<Language, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Numbered {
+    #[serde(with = "bijective_map::serde_object")]
+    numbers: BiMap<u32, String>,
+}
+
+#[test]
+fn test_object_round_trip() {
+    let mut languages = BiMap::new();
+    for (id, name) in ["en", "de", "fr", "es", "it", "nl", "pl", "pt", "sv", "fi"].into_iter().enumerate() {
+        languages.insert(name.to_string(), id as u32);
+    }
+    let json = serde_json::to_value(Config { languages: languages.clone() }).unwrap();
+    assert_eq!(json["languages"]["en"], 0);
+    assert_eq!(json["languages"]["fi"], 9);
+    assert_eq!(json["languages"].as_object().unwrap().len(), 10);
+
+    let config: Config = serde_json::from_value(json).unwrap();
+    assert_eq!(config.languages.len(), languages.len());
+    assert!(languages.iter().all(|(left, right)| config.languages.get_right(left) == Some(right)));
+
+    // unit variants serialize as strings as well
+    let mut codes = BiMap::new();
+    codes.insert(Language::English, "en".to_string());
+    codes.insert(Language::German, "de".to_string());
+    let json = serde_json::to_string(&Codes { codes }).unwrap();
+    let codes: Codes = serde_json::from_str(&json).unwrap();
+    assert_eq!(codes.codes.get_left(&"de".to_string()), Some(&Language::German));
+}
+
+#[test]
+fn test_object_duplicates() {
+    let error = serde_json::from_str::<Config>(r#"{"languages": {"en": 1, "de": 2, "en": 3}}"#).unwrap_err();
+    assert!(error.to_string().contains("duplicate left value in entry 2"), "{}", error);
+
+    let error = serde_json::from_str::<Config>(r#"{"languages": {"en": 1, "de": 1}}"#).unwrap_err();
+    assert!(error.to_string().contains("duplicate right value in entry 1"), "{}", error);
+}
+
+#[test]
+fn test_object_rejects_non_string_keys() {
+    let mut numbers = BiMap::new();
+    numbers.insert(1, "one".to_string());
+    let error = serde_json::to_string(&Numbered { numbers }).unwrap_err();
+    assert!(error.to_string().contains("must serialize as strings, found an integer"), "{}", error);
+}
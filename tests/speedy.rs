@@ -0,0 +1,70 @@
+#![cfg(feature = "speedy")]
+
+use std::borrow::Cow;
+use speedy::{Readable, Writable};
+use bijective_map::BiMap;
+
+#[test]
+fn test_speedy_round_trip() {
+    let mut map = BiMap::new();
+    for i in 0..100u64 {
+        map.insert(i.to_string(), i);
+    }
+    let bytes = map.write_to_vec().unwrap();
+    assert_eq!(bytes.len(), Writable::<speedy::LittleEndian>::bytes_needed(&map).unwrap());
+
+    let read: BiMap<String, u64> = BiMap::read_from_buffer(&bytes).unwrap();
+    assert_eq!(read.len(), map.len());
+    assert!(map.iter().all(|(left, right)| read.get_right(left) == Some(right)));
+}
+
+#[test]
+fn test_speedy_borrowed_keys() {
+    let mut map = BiMap::new();
+    for i in 0..20u32 {
+        map.insert(format!("key {}", i), i);
+    }
+    let bytes = map.write_to_vec().unwrap();
+
+    // the keys borrow from the buffer instead of being copied
+    let read: BiMap<Cow<'_, str>, u32> = BiMap::read_from_buffer(&bytes).unwrap();
+    assert_eq!(read.len(), map.len());
+    for (left, right) in read.iter() {
+        assert!(matches!(left, Cow::Borrowed(_)));
+        assert_eq!(map.get_right(&left.to_string()), Some(right));
+    }
+}
+
+#[test]
+fn test_speedy_corrupt_input() {
+    let mut map = BiMap::new();
+    for i in 0..10u32 {
+        map.insert(i, i + 100);
+    }
+    let mut bytes = map.write_to_vec().unwrap();
+
+    // a length that claims more pairs than the buffer holds
+    bytes[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+    let error = BiMap::<u32, u32>::read_from_buffer(&bytes).unwrap_err();
+    assert!(error.to_string().contains("too short"), "{}", error);
+
+    // a length that claims fewer pairs leaves bytes behind, which reading the whole buffer rejects
+    bytes[..4].copy_from_slice(&5u32.to_le_bytes());
+    let (read, consumed) = BiMap::<u32, u32>::read_with_length_from_buffer(&bytes);
+    assert_eq!(read.unwrap().len(), 5);
+    assert_eq!(consumed, 4 + 5 * 8);
+
+    // duplicates on either side
+    let encode = |pairs: &[(u32, u32)]| {
+        let mut bytes = (pairs.len() as u32).to_le_bytes().to_vec();
+        for (left, right) in pairs {
+            bytes.extend(left.to_le_bytes());
+            bytes.extend(right.to_le_bytes());
+        }
+        bytes
+    };
+    let error = BiMap::<u32, u32>::read_from_buffer(&encode(&[(1, 2), (3, 4), (1, 5)])).unwrap_err();
+    assert!(error.to_string().contains("duplicate left value in pair 2"), "{}", error);
+    let error = BiMap::<u32, u32>::read_from_buffer(&encode(&[(1, 2), (3, 4), (7, 4)])).unwrap_err();
+    assert!(error.to_string().contains("duplicate right value in pair 2"), "{}", error);
+}
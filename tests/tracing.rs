@@ -0,0 +1,130 @@
+#![cfg(feature = "tracing")]
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+use bijective_map::BiMap;
+
+/// An event that was emitted, with its level and its fields rendered as strings.
+#[derive(Debug)]
+struct RecordedEvent {
+    level: Level,
+    fields: HashMap<String, String>,
+}
+
+impl Visit for RecordedEvent {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.fields.insert(field.name().to_string(), format!("{:?}", value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.fields.insert(field.name().to_string(), value.to_string());
+    }
+}
+
+/// A subscriber that collects all events and ignores spans.
+#[derive(Clone, Default)]
+struct Collector {
+    events: Arc<Mutex<Vec<RecordedEvent>>>,
+}
+
+impl Subscriber for Collector {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut recorded = RecordedEvent { level: *event.metadata().level(), fields: HashMap::new() };
+        event.record(&mut recorded);
+        self.events.lock().unwrap().push(recorded);
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+impl Collector {
+    /// The events with the given message.
+    fn events_with_message(&self, message: &str) -> Vec<HashMap<String, String>> {
+        self.events.lock().unwrap().iter()
+            .filter(|event| event.fields.get("message").is_some_and(|m| m == message))
+            .map(|event| event.fields.clone())
+            .collect()
+    }
+}
+
+/// A hasher that maps all values to the same hash.
+#[derive(Clone, Copy, Default)]
+struct CollidingHasher;
+
+impl Hasher for CollidingHasher {
+    fn finish(&self) -> u64 {
+        0
+    }
+
+    fn write(&mut self, _: &[u8]) {}
+}
+
+impl BuildHasher for CollidingHasher {
+    type Hasher = CollidingHasher;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        CollidingHasher
+    }
+}
+
+#[test]
+fn test_resize_events() {
+    let collector = Collector::default();
+    tracing::subscriber::with_default(collector.clone(), || {
+        let mut map = BiMap::new();
+        map.reserve(1000);
+        for i in 0..8 {
+            map.insert(i, i);
+        }
+        map.shrink_to_fit();
+    });
+
+    let resizes = collector.events_with_message("resized the indices");
+    assert_eq!(resizes.len(), 2);
+    assert_eq!(resizes[0]["old_capacity"], "0");
+    assert_eq!(resizes[0]["new_capacity"], "1112");
+    assert_eq!(resizes[0]["len"], "0");
+    assert_eq!(resizes[1]["old_capacity"], "1112");
+    assert_eq!(resizes[1]["new_capacity"], "0");
+    assert_eq!(resizes[1]["len"], "8");
+}
+
+#[test]
+fn test_long_probe_warning() {
+    let collector = Collector::default();
+    tracing::subscriber::with_default(collector.clone(), || {
+        let mut map = BiMap::with_hashers(0, CollidingHasher, CollidingHasher);
+        for i in 0..200 {
+            map.insert(i, i);
+        }
+    });
+
+    // the warning is emitted at most once between two resizes
+    let warnings = collector.events_with_message("long probe chain in the indices");
+    let resizes = collector.events_with_message("resized the indices");
+    // tables do not track the probe distances of their entries, so they never warn
+    assert_eq!(warnings.is_empty(), cfg!(feature = "hashbrown-backend"));
+    assert!(warnings.len() <= resizes.len());
+    assert!(collector.events.lock().unwrap().iter()
+        .filter(|event| event.level == Level::WARN)
+        .all(|event| event.fields["probe_distance"].parse::<u8>().unwrap() > 64));
+}